@@ -0,0 +1,214 @@
+//! Compares keying the transposition table by a copy of the board with
+//! keying it by Zobrist hash, with and without a check word, on a board
+//! of 2KB: how much the keys take up and how long a probe takes.
+//!
+//! Run with `cargo bench --bench hash_keys`.
+
+extern crate minimax;
+
+use minimax::{Minimax, Score, Team};
+use minimax::board::{Board, ZobristBoard};
+use minimax::transposition_table::{TranspositionTable, Bound, PositionKey, ZobristKey, CheckedZobristKey, ReplacementPolicy};
+
+use std::mem;
+use std::num::NonZeroUsize;
+use std::time::Instant;
+
+const CELLS: usize = 2048;
+const BRANCHING: usize = 6;
+const PLIES: u32 = 5;
+const PROBES: usize = 100_000;
+
+fn xorshift(state: &mut u64) -> u64
+{
+    *state ^= *state << 13;
+    *state ^= *state >> 7;
+    *state ^= *state << 17;
+    *state
+}
+
+/* Random keys of a stone of either side on every cell, for the hash and
+ * for the check word
+ */
+struct Keys([[u64; 4]; CELLS]);
+
+impl Keys
+{
+    fn new() -> Keys
+    {
+        let mut state = 0x9e37_79b9_7f4a_7c15;
+        let mut keys = [[0; 4]; CELLS];
+        for cell in keys.iter_mut()
+        {
+            for key in cell.iter_mut()
+            {
+                *key = xorshift(&mut state);
+            }
+        }
+        Keys(keys)
+    }
+}
+
+/* Stones on 2048 cells, with two Zobrist hashes of them. Each side plays
+ * on the first few empty cells from its own end.
+ */
+#[derive(Clone,PartialEq,Eq,Hash)]
+struct Sheet
+{
+    cells: [u8; CELLS],
+    hash: u64,
+    check: u32,
+    placed: u32,
+}
+
+impl Sheet
+{
+    fn new() -> Sheet
+    {
+        Sheet {
+            cells: [0; CELLS],
+            hash: 0,
+            check: 0,
+            placed: 0,
+        }
+    }
+
+    fn empty_cells(&self, turn: Team) -> Vec<usize>
+    {
+        let cells: Box<dyn Iterator<Item = usize>> = match turn
+        {
+            Team::Ally => Box::new(0..CELLS),
+            Team::Enemy => Box::new((0..CELLS).rev()),
+        };
+        cells.filter(|&c| self.cells[c] == 0).take(BRANCHING).collect()
+    }
+
+    fn place(&mut self, keys: &Keys, cell: usize)
+    {
+        let side = (self.placed % 2) as usize;
+        self.cells[cell] = 1 + side as u8;
+        self.hash ^= keys.0[cell][side];
+        self.check ^= keys.0[cell][2 + side] as u32;
+        self.placed += 1;
+    }
+}
+
+thread_local!
+{
+    static KEYS: Keys = Keys::new();
+}
+
+impl Board for Sheet
+{
+    type Move = usize;
+
+    fn gen_ally_moves(&self) -> Vec<usize>
+    {
+        self.empty_cells(Team::Ally)
+    }
+
+    fn gen_enemy_moves(&self) -> Vec<usize>
+    {
+        self.empty_cells(Team::Enemy)
+    }
+
+    fn do_move(&mut self, mv: &usize)
+    {
+        KEYS.with(|keys| self.place(keys, *mv));
+    }
+
+    fn score(&self) -> Score
+    {
+        let worth = |stone| self.cells.iter().enumerate().filter(|&(_, &c)| c == stone).map(|(c, _)| c % 7).sum::<usize>();
+        Score::Heuristic(worth(1) as i32 - worth(2) as i32)
+    }
+
+    fn is_game_over(&self) -> bool
+    {
+        false
+    }
+}
+
+impl ZobristBoard for Sheet
+{
+    fn zobrist(&self) -> u64
+    {
+        self.hash
+    }
+
+    fn zobrist_check(&self) -> u32
+    {
+        self.check
+    }
+}
+
+/* Boards with a few random stones on them */
+fn boards(count: usize) -> Vec<Sheet>
+{
+    let mut state = 0x2545_f491_4f6c_dd1d;
+    KEYS.with(|keys| (0..count)
+        .map(|_|
+        {
+            let mut sheet = Sheet::new();
+            for _ in 0..4
+            {
+                let cell = (xorshift(&mut state) % CELLS as u64) as usize;
+                if sheet.cells[cell] == 0
+                {
+                    sheet.place(keys, cell);
+                }
+            }
+            sheet
+        })
+        .collect())
+}
+
+fn probes<K>(name: &str, boards: &[Sheet])
+    where K: PositionKey<Sheet, Score>
+{
+    let mut table = TranspositionTable::new(NonZeroUsize::new(boards.len()).unwrap());
+    for (idx, board) in boards.iter().enumerate()
+    {
+        table.insert(K::of(board, Team::Ally), idx, 0, Bound::Exact);
+    }
+
+    let start = Instant::now();
+    let mut found = 0;
+    for board in boards
+    {
+        if table.get(&K::of(board, Team::Ally), 0).is_some()
+        {
+            found += 1;
+        }
+    }
+    let elapsed = start.elapsed();
+
+    println!("{:>8}: {:>5} bytes a key, {:>6} KB of keys, {} probes in {:>10.3?} ({:.0} ns each), {} found",
+             name, mem::size_of::<K>(), mem::size_of::<K>() * boards.len() / 1024,
+             boards.len(), elapsed, elapsed.as_nanos() as f64 / boards.len() as f64, found);
+}
+
+fn search<K>(name: &str, mut minimax: Minimax<Sheet, Score, K>)
+    where K: PositionKey<Sheet, Score>
+{
+    let start = Instant::now();
+    let stats = minimax.minimax(&Sheet::new(), Team::Ally, PLIES);
+    let elapsed = start.elapsed();
+
+    println!("{:>8}: {:>9} nodes in {:>10.3?} ({:.0} nodes/s)",
+             name, stats.nodes_visited, elapsed,
+             stats.nodes_visited as f64 / elapsed.as_secs_f64());
+}
+
+fn main()
+{
+    let boards = boards(PROBES);
+    probes::<(Sheet, Team)>("board", &boards);
+    probes::<ZobristKey>("zobrist", &boards);
+    probes::<CheckedZobristKey>("checked", &boards);
+
+    let size = NonZeroUsize::new(1 << 16).unwrap();
+    search("board", Minimax::new(size));
+    search("zobrist", Minimax::with_zobrist_tables(size, ReplacementPolicy::Lru));
+    search("checked", Minimax::with_checked_zobrist_tables(size, ReplacementPolicy::Lru));
+}
@@ -0,0 +1,144 @@
+//! Compares a board that allocates a `Vec` of moves at every node against
+//! one that hands them out lazily through `Board::moves`.
+//!
+//! Run with `cargo bench --bench move_generation`.
+
+extern crate minimax;
+
+use minimax::{Minimax, Score, Team};
+use minimax::board::Board;
+
+use std::num::NonZeroUsize;
+use std::time::Instant;
+
+const BRANCHING: u64 = 30;
+const PLIES: u32 = 4;
+
+/* Synthetic game tree. Every position is identified by the path taken to
+ * reach it, and leaves get a pseudo-random score from that.
+ */
+#[derive(Clone,PartialEq,Eq,Hash)]
+struct Position
+{
+    path: u64,
+    depth: u32,
+}
+
+impl Position
+{
+    fn child(&self, mv: u64) -> Position
+    {
+        Position {
+            path: self.path.wrapping_mul(BRANCHING + 1).wrapping_add(mv + 1),
+            depth: self.depth + 1,
+        }
+    }
+
+    fn heuristic(&self) -> Score
+    {
+        let mut x = self.path ^ 0x9e37_79b9_7f4a_7c15;
+        x ^= x >> 33;
+        x = x.wrapping_mul(0xff51_afd7_ed55_8ccd);
+        x ^= x >> 33;
+        Score::Heuristic((x % 201) as i32 - 100)
+    }
+}
+
+#[derive(Clone,PartialEq,Eq,Hash)]
+struct VecBoard(Position);
+
+impl Board for VecBoard
+{
+    type Move = u64;
+
+    fn gen_ally_moves(&self) -> Vec<u64>
+    {
+        (0..BRANCHING).collect()
+    }
+
+    fn gen_enemy_moves(&self) -> Vec<u64>
+    {
+        (0..BRANCHING).collect()
+    }
+
+    fn do_move(&mut self, mv: &u64)
+    {
+        self.0 = self.0.child(*mv);
+    }
+
+    fn score(&self) -> Score
+    {
+        self.0.heuristic()
+    }
+
+    fn is_game_over(&self) -> bool
+    {
+        false
+    }
+}
+
+#[derive(Clone,PartialEq,Eq,Hash)]
+struct IterBoard(Position);
+
+impl Board for IterBoard
+{
+    type Move = u64;
+
+    fn gen_ally_moves(&self) -> Vec<u64>
+    {
+        self.moves(Team::Ally).collect()
+    }
+
+    fn gen_enemy_moves(&self) -> Vec<u64>
+    {
+        self.moves(Team::Enemy).collect()
+    }
+
+    fn moves(&self, _turn: Team) -> impl Iterator<Item = u64>
+    {
+        0..BRANCHING
+    }
+
+    fn do_move(&mut self, mv: &u64)
+    {
+        self.0 = self.0.child(*mv);
+    }
+
+    fn score(&self) -> Score
+    {
+        self.0.heuristic()
+    }
+
+    fn is_game_over(&self) -> bool
+    {
+        false
+    }
+}
+
+fn root() -> Position
+{
+    Position {
+        path: 0,
+        depth: 0,
+    }
+}
+
+fn bench<B>(name: &str, board: &B)
+    where B: Board + Eq + std::hash::Hash
+{
+    let mut minimax = Minimax::new(NonZeroUsize::new(1 << 20).unwrap());
+
+    let start = Instant::now();
+    let stats = minimax.minimax(board, Team::Ally, PLIES);
+    let elapsed = start.elapsed();
+
+    println!("{:>6}: {:>9} nodes in {:>10.3?} ({:.0} nodes/s)",
+             name, stats.nodes_visited, elapsed,
+             stats.nodes_visited as f64 / elapsed.as_secs_f64());
+}
+
+fn main()
+{
+    bench("vec", &VecBoard(root()));
+    bench("iter", &IterBoard(root()));
+}
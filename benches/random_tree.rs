@@ -0,0 +1,33 @@
+//! Times the search on a random tree with eight moves at every node, at
+//! a few depths, so a change to the search shows up as a change in nodes
+//! or time.
+//!
+//! Run with `cargo bench --bench random_tree --features testing`.
+
+extern crate minimax;
+
+use minimax::{Minimax, Team};
+use minimax::testing::RandomTree;
+
+use std::num::NonZeroUsize;
+use std::time::Instant;
+
+const BRANCHING: u32 = 8;
+const SEED: u64 = 41;
+
+fn main()
+{
+    for &plies in [4, 6, 8].iter()
+    {
+        let mut minimax = Minimax::new(NonZeroUsize::new(1 << 20).unwrap());
+        let board = RandomTree::new(SEED, plies, BRANCHING);
+
+        let start = Instant::now();
+        let stats = minimax.minimax(&board, Team::Ally, plies);
+        let elapsed = start.elapsed();
+
+        println!("depth {}: {:>9} nodes in {:>10.3?} ({:.0} nodes/s)",
+                 plies, stats.nodes_visited, elapsed,
+                 stats.nodes_visited as f64 / elapsed.as_secs_f64());
+    }
+}
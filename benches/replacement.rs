@@ -0,0 +1,54 @@
+//! Plays a few moves down a random tree with a table far too small for
+//! it, searching as deep from every position as from the first, under
+//! each replacement policy. Each search finds the line the one before it
+//! expected in the table, and the nodes it gets to skip show how much of
+//! the earlier searches each policy kept.
+//!
+//! Run with `cargo bench --bench replacement --features testing`.
+
+extern crate minimax;
+
+use minimax::{Minimax, Team};
+use minimax::board::Board;
+use minimax::testing::RandomTree;
+use minimax::transposition_table::ReplacementPolicy;
+
+use std::num::NonZeroUsize;
+use std::time::Instant;
+
+const BRANCHING: u32 = 8;
+const PLIES: u32 = 8;
+/* Moves by each side after the first search */
+const MOVES: u32 = 4;
+const SEED: u64 = 41;
+
+fn main()
+{
+    for &entries in [1 << 10, 1 << 13].iter()
+    {
+        for &policy in [ReplacementPolicy::Lru, ReplacementPolicy::DepthPreferred, ReplacementPolicy::TwoTier].iter()
+        {
+            let mut minimax = Minimax::with_policy(NonZeroUsize::new(entries).unwrap(), policy);
+            let mut board = RandomTree::new(SEED, PLIES + 2 * MOVES, BRANCHING);
+            let start = Instant::now();
+            let mut stats = minimax.minimax(&board, Team::Ally, PLIES);
+            let first = stats.nodes_visited;
+
+            let mut rest = 0;
+            for _ in 0..MOVES
+            {
+                /* Ally's move and the reply the search expected */
+                for mv in stats.mvs.iter().take(2)
+                {
+                    board.do_move(mv);
+                }
+                stats = minimax.minimax(&board, Team::Ally, PLIES);
+                rest += stats.nodes_visited;
+            }
+            let elapsed = start.elapsed();
+
+            println!("{:>6} entries, {:<14}: {:>9} nodes, then {:>9} in {:>10.3?}",
+                     entries, format!("{:?}", policy), first, rest, elapsed);
+        }
+    }
+}
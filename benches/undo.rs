@@ -0,0 +1,183 @@
+//! Compares cloning the board for every move against playing moves in
+//! place and undoing them, on a Go-sized board that is costly to clone.
+//!
+//! Run with `cargo bench --bench undo`.
+
+extern crate minimax;
+
+use minimax::{Minimax, Score, Team};
+use minimax::board::{Board, UndoBoard};
+
+use std::hash::{Hash, Hasher};
+use std::num::NonZeroUsize;
+use std::time::Instant;
+
+const CELLS: usize = 19 * 19;
+const BRANCHING: usize = 8;
+const PLIES: u32 = 5;
+
+/* Stones on a 19x19 board, plus the history of every move played. Each side
+ * may play on the first few empty points it finds from its own corner.
+ */
+#[derive(Clone)]
+struct Goban
+{
+    cells: [u8; CELLS],
+    history: Vec<usize>,
+}
+
+impl Goban
+{
+    fn new() -> Goban
+    {
+        Goban {
+            cells: [0; CELLS],
+            history: Vec::new(),
+        }
+    }
+
+    fn empty_points(&self, turn: Team) -> Vec<usize>
+    {
+        let points: Box<dyn Iterator<Item = usize>> = match turn
+        {
+            Team::Ally => Box::new(0..CELLS),
+            Team::Enemy => Box::new((0..CELLS).rev()),
+        };
+
+        points.filter(|&p| self.cells[p] == 0).take(BRANCHING).collect()
+    }
+}
+
+impl PartialEq for Goban
+{
+    fn eq(&self, other: &Goban) -> bool
+    {
+        self.cells[..] == other.cells[..]
+    }
+}
+
+impl Eq for Goban {}
+
+impl Hash for Goban
+{
+    fn hash<H: Hasher>(&self, state: &mut H)
+    {
+        self.cells[..].hash(state);
+    }
+}
+
+fn score(goban: &Goban) -> Score
+{
+    let ally = goban.cells.iter().enumerate().filter(|&(_, &c)| c == 1).map(|(p, _)| p % 19).sum::<usize>();
+    let enemy = goban.cells.iter().enumerate().filter(|&(_, &c)| c == 2).map(|(p, _)| p % 19).sum::<usize>();
+    Score::Heuristic(ally as i32 - enemy as i32)
+}
+
+fn play(goban: &mut Goban, point: usize)
+{
+    goban.cells[point] = 1 + (goban.history.len() % 2) as u8;
+    goban.history.push(point);
+}
+
+#[derive(Clone,PartialEq,Eq,Hash)]
+struct CloneBoard(Goban);
+
+impl Board for CloneBoard
+{
+    type Move = usize;
+
+    fn gen_ally_moves(&self) -> Vec<usize>
+    {
+        self.0.empty_points(Team::Ally)
+    }
+
+    fn gen_enemy_moves(&self) -> Vec<usize>
+    {
+        self.0.empty_points(Team::Enemy)
+    }
+
+    fn do_move(&mut self, mv: &usize)
+    {
+        play(&mut self.0, *mv);
+    }
+
+    fn score(&self) -> Score
+    {
+        score(&self.0)
+    }
+
+    fn is_game_over(&self) -> bool
+    {
+        false
+    }
+}
+
+#[derive(Clone,PartialEq,Eq,Hash)]
+struct InPlaceBoard(Goban);
+
+impl Board for InPlaceBoard
+{
+    type Move = usize;
+
+    const UNDO: Option<fn(&mut Self, &usize)> = Some(UndoBoard::undo_move);
+
+    fn gen_ally_moves(&self) -> Vec<usize>
+    {
+        self.0.empty_points(Team::Ally)
+    }
+
+    fn gen_enemy_moves(&self) -> Vec<usize>
+    {
+        self.0.empty_points(Team::Enemy)
+    }
+
+    fn do_move(&mut self, mv: &usize)
+    {
+        play(&mut self.0, *mv);
+    }
+
+    fn score(&self) -> Score
+    {
+        score(&self.0)
+    }
+
+    fn is_game_over(&self) -> bool
+    {
+        false
+    }
+}
+
+impl UndoBoard for InPlaceBoard
+{
+    fn undo_move(&mut self, mv: &usize)
+    {
+        self.0.cells[*mv] = 0;
+        self.0.history.pop();
+    }
+}
+
+fn bench<B>(name: &str, board: &B)
+    where B: Board + Eq + Hash
+{
+    let mut minimax = Minimax::new(NonZeroUsize::new(1 << 16).unwrap());
+
+    let start = Instant::now();
+    let stats = minimax.minimax(board, Team::Ally, PLIES);
+    let elapsed = start.elapsed();
+
+    println!("{:>6}: {:>9} nodes in {:>10.3?} ({:.0} nodes/s)",
+             name, stats.nodes_visited, elapsed,
+             stats.nodes_visited as f64 / elapsed.as_secs_f64());
+}
+
+fn main()
+{
+    let mut goban = Goban::new();
+    for _ in 0..100
+    {
+        goban.history.push(0);
+    }
+
+    bench("clone", &CloneBoard(goban.clone()));
+    bench("undo", &InPlaceBoard(goban));
+}
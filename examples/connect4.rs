@@ -0,0 +1,255 @@
+//! Connect four against the engine.
+//!
+//! Run with `cargo run --example connect4` and type the number of a
+//! column, 1 to 7, to drop a disc in it. The engine is the ally and plays
+//! X, you are the enemy and play O.
+
+extern crate minimax;
+
+use minimax::{Minimax, Score, Team};
+use minimax::board::Board;
+
+use std::io::{self, BufRead, Write};
+use std::num::NonZeroUsize;
+
+pub const COLUMNS: usize = 7;
+pub const ROWS: usize = 6;
+
+/* How far the engine looks ahead in the demo */
+const PLIES: u32 = 8;
+
+/// A disc for `team` dropped in `column`, numbered 0 to 6 from the left.
+#[derive(Copy,Clone,Debug,PartialEq,Eq,Hash)]
+pub struct Drop
+{
+    pub column: usize,
+    pub team: Team,
+}
+
+/// Discs by column, bottom up.
+#[derive(Copy,Clone,Debug,Default,PartialEq,Eq,Hash)]
+pub struct ConnectFour
+{
+    cells: [[Option<Team>; ROWS]; COLUMNS],
+    heights: [usize; COLUMNS],
+}
+
+impl ConnectFour
+{
+    pub fn new() -> ConnectFour
+    {
+        ConnectFour {
+            cells: [[None; ROWS]; COLUMNS],
+            heights: [0; COLUMNS],
+        }
+    }
+
+    /// Plays discs in `columns` (numbered from 1, like in the demo) taking
+    /// turns, `first` going first.
+    pub fn from_columns(columns: &str, first: Team) -> ConnectFour
+    {
+        let mut board = ConnectFour::new();
+        let mut team = first;
+        for column in columns.bytes()
+        {
+            board.do_move(&Drop { column: (column - b'1') as usize, team });
+            team = team.other_team();
+        }
+        board
+    }
+
+    fn at(&self, column: isize, row: isize) -> Option<Team>
+    {
+        if column < 0 || row < 0 || column >= COLUMNS as isize || row >= ROWS as isize
+        {
+            return None;
+        }
+        self.cells[column as usize][row as usize]
+    }
+
+    pub fn winner(&self) -> Option<Team>
+    {
+        for column in 0..COLUMNS as isize
+        {
+            for row in 0..ROWS as isize
+            {
+                let team = match self.at(column, row)
+                {
+                    Some(team) => team,
+                    None => continue,
+                };
+                for &(dc, dr) in [(1, 0), (0, 1), (1, 1), (1, -1)].iter()
+                {
+                    if (1..4).all(|step| self.at(column + dc * step, row + dr * step) == Some(team))
+                    {
+                        return Some(team);
+                    }
+                }
+            }
+        }
+        None
+    }
+
+    fn is_full(&self) -> bool
+    {
+        self.heights.iter().all(|&height| height == ROWS)
+    }
+
+    fn drops(&self, team: Team) -> Vec<Drop>
+    {
+        (0..COLUMNS).filter(|&column| self.heights[column] < ROWS)
+            .map(|column| Drop { column, team })
+            .collect()
+    }
+
+    /* Windows of four that one side could still fill, worth more the more
+     * of its discs are already in
+     */
+    fn threats(&self) -> i32
+    {
+        let mut total = 0;
+        for column in 0..COLUMNS as isize
+        {
+            for row in 0..ROWS as isize
+            {
+                for &(dc, dr) in [(1, 0), (0, 1), (1, 1), (1, -1)].iter()
+                {
+                    let cells: Vec<_> = (0..4).map(|step| (column + dc * step, row + dr * step)).collect();
+                    if cells.iter().any(|&(c, r)| c < 0 || r < 0 || c >= COLUMNS as isize || r >= ROWS as isize)
+                    {
+                        continue;
+                    }
+                    let allies = cells.iter().filter(|&&(c, r)| self.at(c, r) == Some(Team::Ally)).count();
+                    let enemies = cells.iter().filter(|&&(c, r)| self.at(c, r) == Some(Team::Enemy)).count();
+                    match (allies, enemies)
+                    {
+                        (n, 0) => total += [0, 1, 4, 16][n],
+                        (0, n) => total -= [0, 1, 4, 16][n],
+                        _ => (),
+                    }
+                }
+            }
+        }
+        total
+    }
+
+    pub fn draw(&self) -> String
+    {
+        let mut out = String::new();
+        for row in (0..ROWS).rev()
+        {
+            for column in 0..COLUMNS
+            {
+                out.push(match self.cells[column][row]
+                {
+                    Some(Team::Ally) => 'X',
+                    Some(Team::Enemy) => 'O',
+                    None => '.',
+                });
+            }
+            out.push('\n');
+        }
+        out.push_str("1234567\n");
+        out
+    }
+}
+
+impl Board for ConnectFour
+{
+    type Move = Drop;
+
+    fn gen_ally_moves(&self) -> Vec<Drop>
+    {
+        self.drops(Team::Ally)
+    }
+
+    fn gen_enemy_moves(&self) -> Vec<Drop>
+    {
+        self.drops(Team::Enemy)
+    }
+
+    fn do_move(&mut self, mv: &Drop)
+    {
+        self.cells[mv.column][self.heights[mv.column]] = Some(mv.team);
+        self.heights[mv.column] += 1;
+    }
+
+    /* Mid-game scores are only a guess, from the ally's point of view
+     * whoever is to move, and must stay clear of Win and Lose
+     */
+    fn score(&self) -> Score
+    {
+        match self.winner()
+        {
+            Some(Team::Ally) => Score::Win,
+            Some(Team::Enemy) => Score::Lose,
+            None if self.is_full() => Score::Draw,
+            None => Score::Heuristic(self.threats()),
+        }
+    }
+
+    fn is_game_over(&self) -> bool
+    {
+        self.winner().is_some() || self.is_full()
+    }
+
+    /* The middle columns take part in the most lines */
+    fn order_moves(&self, moves: &mut Vec<Drop>, _turn: Team)
+    {
+        moves.sort_by_key(|mv| (mv.column as isize - COLUMNS as isize / 2).abs());
+    }
+}
+
+fn main()
+{
+    let mut minimax = Minimax::new(NonZeroUsize::new(1_000_000).unwrap());
+    let mut board = ConnectFour::new();
+    let stdin = io::stdin();
+    let mut lines = stdin.lock().lines();
+
+    let mut turn = Team::Enemy;
+    while !board.is_game_over()
+    {
+        match turn
+        {
+            Team::Ally => {
+                let stats = minimax.minimax(&board, Team::Ally, PLIES);
+                let mv = stats.mv.expect("the engine has a move while the game is on");
+                println!("The engine plays {}", mv.column + 1);
+                board.do_move(&mv);
+            }
+            Team::Enemy => {
+                print!("{}Your move: ", board.draw());
+                io::stdout().flush().unwrap();
+                let line = match lines.next()
+                {
+                    Some(line) => line.unwrap(),
+                    None => return,
+                };
+                let mv = match line.trim().parse::<usize>()
+                {
+                    Ok(column) if column >= 1 => Drop { column: column - 1, team: Team::Enemy },
+                    _ => {
+                        println!("Type a column from 1 to 7");
+                        continue;
+                    }
+                };
+                if !board.gen_enemy_moves().contains(&mv)
+                {
+                    println!("That column is full");
+                    continue;
+                }
+                board.do_move(&mv);
+            }
+        }
+        turn = turn.other_team();
+    }
+
+    print!("{}", board.draw());
+    match board.winner()
+    {
+        Some(Team::Ally) => println!("The engine wins"),
+        Some(Team::Enemy) => println!("You win"),
+        None => println!("Draw"),
+    }
+}
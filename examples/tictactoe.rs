@@ -0,0 +1,200 @@
+//! Tic-tac-toe against the engine, which plays perfectly.
+//!
+//! Run with `cargo run --example tictactoe` and type the number of a cell,
+//! 1 to 9, to play it. The engine is the ally and plays X, you are the
+//! enemy and play O.
+
+extern crate minimax;
+
+use minimax::{Minimax, Score, Team};
+use minimax::board::Board;
+
+use std::io::{self, BufRead, Write};
+use std::num::NonZeroUsize;
+
+const LINES: [[usize; 3]; 8] = [
+    [0, 1, 2], [3, 4, 5], [6, 7, 8],
+    [0, 3, 6], [1, 4, 7], [2, 5, 8],
+    [0, 4, 8], [2, 4, 6],
+];
+
+/// A mark for `team` in cell `cell`, numbered 0 to 8 row by row.
+///
+/// Moves say who plays them, since the board itself doesn't track whose
+/// turn it is. The search only ever asks for the moves of one side and
+/// then plays them.
+#[derive(Copy,Clone,Debug,PartialEq,Eq,Hash)]
+pub struct Mark
+{
+    pub cell: usize,
+    pub team: Team,
+}
+
+#[derive(Copy,Clone,Debug,Default,PartialEq,Eq,Hash)]
+pub struct TicTacToe
+{
+    cells: [Option<Team>; 9],
+}
+
+impl TicTacToe
+{
+    pub fn new() -> TicTacToe
+    {
+        TicTacToe {
+            cells: [None; 9],
+        }
+    }
+
+    /// Parses a board drawn as nine of `X` (ally), `O` (enemy) and `.`,
+    /// with any whitespace in between.
+    pub fn parse(rows: &str) -> TicTacToe
+    {
+        let mut board = TicTacToe::new();
+        let marks = rows.chars().filter(|c| !c.is_whitespace());
+        for (cell, mark) in marks.enumerate()
+        {
+            board.cells[cell] = match mark
+            {
+                'X' => Some(Team::Ally),
+                'O' => Some(Team::Enemy),
+                _ => None,
+            };
+        }
+        board
+    }
+
+    pub fn winner(&self) -> Option<Team>
+    {
+        LINES.iter()
+            .find(|line| self.cells[line[0]].is_some() && line.iter().all(|&cell| self.cells[cell] == self.cells[line[0]]))
+            .and_then(|line| self.cells[line[0]])
+    }
+
+    fn is_full(&self) -> bool
+    {
+        self.cells.iter().all(|cell| cell.is_some())
+    }
+
+    fn marks(&self, team: Team) -> Vec<Mark>
+    {
+        (0..9).filter(|&cell| self.cells[cell].is_none())
+            .map(|cell| Mark { cell, team })
+            .collect()
+    }
+
+    pub fn draw(&self) -> String
+    {
+        let mut out = String::new();
+        for row in 0..3
+        {
+            for col in 0..3
+            {
+                let cell = row * 3 + col;
+                out.push(match self.cells[cell]
+                {
+                    Some(Team::Ally) => 'X',
+                    Some(Team::Enemy) => 'O',
+                    None => (b'1' + cell as u8) as char,
+                });
+            }
+            out.push('\n');
+        }
+        out
+    }
+}
+
+impl Board for TicTacToe
+{
+    type Move = Mark;
+
+    fn gen_ally_moves(&self) -> Vec<Mark>
+    {
+        self.marks(Team::Ally)
+    }
+
+    fn gen_enemy_moves(&self) -> Vec<Mark>
+    {
+        self.marks(Team::Enemy)
+    }
+
+    fn do_move(&mut self, mv: &Mark)
+    {
+        self.cells[mv.cell] = Some(mv.team);
+    }
+
+    /* Only finished games say anything. Everything else is even, and the
+     * search tells the positions apart by looking ahead.
+     */
+    fn score(&self) -> Score
+    {
+        match self.winner()
+        {
+            Some(Team::Ally) => Score::Win,
+            Some(Team::Enemy) => Score::Lose,
+            None if self.is_full() => Score::Draw,
+            None => Score::Heuristic(0),
+        }
+    }
+
+    /* Has to hold for a full board too, or the side to move would have no
+     * moves and be scored as having lost.
+     */
+    fn is_game_over(&self) -> bool
+    {
+        self.winner().is_some() || self.is_full()
+    }
+}
+
+fn main()
+{
+    let mut minimax = Minimax::new(NonZeroUsize::new(100_000).unwrap());
+    let mut board = TicTacToe::new();
+    let stdin = io::stdin();
+    let mut lines = stdin.lock().lines();
+
+    let mut turn = Team::Enemy;
+    while !board.is_game_over()
+    {
+        match turn
+        {
+            Team::Ally => {
+                let stats = minimax.minimax(&board, Team::Ally, 9);
+                let mv = stats.mv.expect("the engine has a move while the game is on");
+                println!("The engine plays {}", mv.cell + 1);
+                board.do_move(&mv);
+            }
+            Team::Enemy => {
+                print!("{}Your move: ", board.draw());
+                io::stdout().flush().unwrap();
+                let line = match lines.next()
+                {
+                    Some(line) => line.unwrap(),
+                    None => return,
+                };
+                let mv = match line.trim().parse::<usize>()
+                {
+                    Ok(cell) if cell >= 1 => Mark { cell: cell - 1, team: Team::Enemy },
+                    _ => {
+                        println!("Type a cell from 1 to 9");
+                        continue;
+                    }
+                };
+                if !board.gen_enemy_moves().contains(&mv)
+                {
+                    println!("That cell isn't free");
+                    continue;
+                }
+                board.do_move(&mv);
+            }
+        }
+        turn = turn.other_team();
+    }
+
+    print!("{}", board.draw());
+    match board.winner()
+    {
+        Some(Team::Ally) => println!("The engine wins"),
+        Some(Team::Enemy) => println!("You win"),
+        None => println!("Draw"),
+    }
+}
@@ -1,11 +1,220 @@
-use ::Score;
+use ::{Score, Team};
+use depth::Depth;
 
-pub trait Board: Clone
+use std::collections::hash_map::DefaultHasher;
+use std::hash::{Hash, Hasher};
+
+/// What a side with no legal moves does.
+#[derive(Copy,Clone,Debug,PartialEq,Eq,Hash)]
+pub enum NoMovePolicy
+{
+    /// It has lost.
+    Lose,
+    /// It passes, and the other side moves next from the same position.
+    Pass,
+    /// The position is scored as it stands.
+    ScoreBoard,
+}
+
+/// A game position the search can explore.
+///
+/// Positions are scored as `S`, which is `Score` unless the board picks
+/// another `GameScore`.
+pub trait Board<S = Score>: Clone
 {
     type Move: Clone + Eq;
     fn gen_ally_moves(&self) -> Vec<Self::Move>;
     fn gen_enemy_moves(&self) -> Vec<Self::Move>;
     fn do_move(&mut self, mv: &Self::Move);
-    fn score(&self) -> Score;
+
+    /// Scores the position from the ally's point of view.
+    ///
+    /// Boards implement this one. Callers should ask `score_for`, which
+    /// also tells the board the side to move.
+    #[deprecated(note = "call `score_for`, which is told the side to move")]
+    fn score(&self) -> S;
+
+    /// Scores the position from the ally's point of view, with `to_move`
+    /// about to move.
+    ///
+    /// The score is the ally's whichever side is to move: an enemy win is
+    /// `Lose` even when the enemy is the one to move. `to_move` is there
+    /// for evaluations that aren't symmetric, like a bonus for having the
+    /// move. Defaults to `score`, so only boards whose evaluation depends
+    /// on the side to move override it.
+    fn score_for(&self, _to_move: Team) -> S
+    {
+        #[allow(deprecated)]
+        self.score()
+    }
+
     fn is_game_over(&self) -> bool;
+
+    /// Moves available to `turn`.
+    ///
+    /// Defaults to `gen_ally_moves`/`gen_enemy_moves`. Boards that can
+    /// produce their moves lazily should override this: the search only
+    /// pulls the first move at leaf positions and otherwise drains the
+    /// iterator into a buffer it reuses between nodes, so nothing gets
+    /// allocated per node.
+    fn moves(&self, turn: Team) -> impl Iterator<Item = Self::Move>
+    {
+        match turn
+        {
+            Team::Ally => self.gen_ally_moves(),
+            Team::Enemy => self.gen_enemy_moves(),
+        }.into_iter()
+    }
+
+    /// Noisy moves for `turn`, such as captures, that could swing the score
+    /// of an otherwise static position.
+    ///
+    /// Once the search runs out of plies it keeps following these moves
+    /// until the position is quiet, so it doesn't score a position in the
+    /// middle of an exchange. None by default.
+    fn gen_quiescence_moves(&self, _turn: Team) -> Vec<Self::Move>
+    {
+        Vec::new()
+    }
+
+    /// The most a noisy move can raise the static score of the side
+    /// playing it, for delta pruning: quiescence search skips the move
+    /// once even that much wouldn't lift the score above alpha. Moves that
+    /// could end the game have no bound. `None`, unknown, by default, which
+    /// searches every noisy move.
+    fn move_gain_upper_bound(&self, _mv: &Self::Move) -> Option<i32>
+    {
+        None
+    }
+
+    /// How the search may take back a move it played on the board in
+    /// place, rather than cloning the board for every move it tries. Worth
+    /// setting for boards that are costly to clone: a board implementing
+    /// `UndoBoard` sets it to `Some(UndoBoard::undo_move)`. `None`, clone
+    /// every time, by default.
+    const UNDO: Option<fn(&mut Self, &Self::Move)> = None;
+
+    /// Reorders `moves` so the most promising ones come first.
+    ///
+    /// Called by the search before it walks the moves for `turn`. Good
+    /// ordering lets alpha-beta prune far more of the tree. Does nothing
+    /// by default.
+    fn order_moves(&self, _moves: &mut Vec<Self::Move>, _turn: Team)
+    {
+    }
+
+    /// Extra plies to search below `mv`, played by `turn`, such as one for a
+    /// check or a capture that has to be answered, so the search doesn't
+    /// stop in the middle of a forcing line.
+    ///
+    /// Added to the plies left below the move, up to what is left of the
+    /// cap set by `Minimax::set_max_extensions` for the line. None by
+    /// default.
+    fn move_extension(&self, _mv: &Self::Move, _turn: Team) -> u32
+    {
+        0
+    }
+
+    /// Like `move_extension`, in `Depth` units, for extending mildly
+    /// forcing moves by less than a ply. The search asks this one, which
+    /// defaults to the plies `move_extension` gives.
+    fn fractional_extension(&self, mv: &Self::Move, turn: Team) -> Depth
+    {
+        Depth::from_plies(self.move_extension(mv, turn))
+    }
+
+    /// Plies to search below `mv`, played by `turn`, beyond the rest or,
+    /// if negative, short of them, such as more for a move that makes a
+    /// threat and less for one that shuffles back and forth.
+    ///
+    /// It comes on top of any extension. The adjustments along a line add
+    /// up to no more than `Minimax::set_max_depth_adjustment` either way,
+    /// and a move adjusted past the horizon only gets quiescence search.
+    /// None by default.
+    fn depth_adjustment(&self, _mv: &Self::Move, _turn: Team) -> i32
+    {
+        0
+    }
+
+    /// Whether `mv`, played by `turn`, is a forcing move such as a capture
+    /// or a check, which late move reductions must search to full depth.
+    /// No move is by default.
+    fn is_tactical(&self, _mv: &Self::Move, _turn: Team) -> bool
+    {
+        false
+    }
+
+    /// Whether the position, `turn` to move, is too sharp for its static
+    /// score to say much, such as with `turn` in check, so reverse
+    /// futility pruning leaves it alone. No position is by default.
+    fn is_tactical_position(&self, _turn: Team) -> bool
+    {
+        false
+    }
+
+    /// Passes the turn to the other side without moving, for null-move
+    /// pruning. Returns false if passing isn't allowed here, or could give
+    /// a misleading result, as in positions where having to move hurts.
+    ///
+    /// The search calls this on a copy of the board, and only once per
+    /// position. Never passes by default, which turns null-move pruning off.
+    fn do_null_move(&mut self) -> bool
+    {
+        false
+    }
+
+    /// What happens when `turn` has no moves but the game isn't over.
+    /// Defaults to `NoMovePolicy::Lose`.
+    fn on_no_moves(&self, _turn: Team) -> NoMovePolicy
+    {
+        NoMovePolicy::Lose
+    }
+
+    /// About how many bytes the board keeps on the heap, on top of its own
+    /// size, for `Minimax::with_table_bytes_for` to size tables by. None by
+    /// default.
+    fn approx_heap_size(&self) -> usize
+    {
+        0
+    }
+}
+
+/// A board that can take back the moves played on it, for
+/// `Board::UNDO`.
+///
+/// `do_move` returns nothing for `undo_move` to go on, so that boards
+/// that clone keep their signature. Boards that need more than the move
+/// itself to restore the old position should push whatever they need onto
+/// a stack of their own in `do_move`. The search still clones a board
+/// whenever it looks one up in the transposition table, unless the table
+/// is keyed by `ZobristBoard` hashes.
+pub trait UndoBoard<S = Score>: Board<S>
+{
+    /// Takes back `mv`, which is always the last move played on the board.
+    fn undo_move(&mut self, mv: &Self::Move);
+}
+
+/// A board that keeps a Zobrist hash of itself, for transposition tables
+/// keyed by the hash instead of the whole board.
+///
+/// The hash should be updated in `do_move` (and `UndoBoard::undo_move`)
+/// by xoring out the random keys of what the move changed and xoring in
+/// the new ones, rather than computed from scratch. It doesn't need to
+/// cover the side to move, the search mixes that in.
+pub trait ZobristBoard<S = Score>: Board<S>
+{
+    fn zobrist(&self) -> u64;
+
+    /// A second hash of the position, independent of `zobrist`, that
+    /// `CheckedZobristKey` keeps next to it to tell apart boards whose
+    /// hashes collide. Defaults to hashing the whole board, which costs
+    /// about what the board's own `Hash` does; a second Zobrist hash kept
+    /// up to date in `do_move` is cheaper.
+    fn zobrist_check(&self) -> u32
+        where Self: Hash
+    {
+        let mut hasher = DefaultHasher::new();
+        self.hash(&mut hasher);
+        (hasher.finish() >> 32) as u32
+    }
 }
@@ -0,0 +1,61 @@
+use std::time::Duration;
+#[cfg(feature = "std-clock")]
+use std::time::Instant;
+
+/// Where a time limited search gets the time from.
+///
+/// Only differences between readings matter, so the clock may count from
+/// whenever it likes. Functions returning a `Duration` are clocks too,
+/// which is handy for wrapping something like `performance.now()`.
+pub trait Clock
+{
+    /// Time since the clock started.
+    fn elapsed(&self) -> Duration;
+}
+
+impl<F> Clock for F
+    where F: Fn() -> Duration
+{
+    fn elapsed(&self) -> Duration
+    {
+        self()
+    }
+}
+
+/// Clock on `std::time::Instant`, started when it is made. Not available
+/// on wasm32-unknown-unknown, where `Instant` panics.
+#[cfg(feature = "std-clock")]
+#[derive(Copy,Clone,Debug)]
+pub struct StdClock
+{
+    start: Instant,
+}
+
+#[cfg(feature = "std-clock")]
+impl StdClock
+{
+    pub fn new() -> StdClock
+    {
+        StdClock {
+            start: Instant::now(),
+        }
+    }
+}
+
+#[cfg(feature = "std-clock")]
+impl Default for StdClock
+{
+    fn default() -> StdClock
+    {
+        StdClock::new()
+    }
+}
+
+#[cfg(feature = "std-clock")]
+impl Clock for StdClock
+{
+    fn elapsed(&self) -> Duration
+    {
+        self.start.elapsed()
+    }
+}
@@ -0,0 +1,383 @@
+use ::{Team, Score, OrderedF32, TimedScore, MoveStats};
+use transposition_table::{Bound, ZobristKey, CheckedZobristKey};
+
+use std::io::{self, Read, Write};
+
+/// Types that can be written out, for saving transposition tables.
+///
+/// Numbers are written little endian at a fixed width.
+pub trait Encode
+{
+    fn encode<W: Write>(&self, w: &mut W) -> io::Result<()>;
+}
+
+/// Types that can be read back from what their `Encode` impl wrote.
+pub trait Decode: Sized
+{
+    fn decode<R: Read>(r: &mut R) -> io::Result<Self>;
+}
+
+fn invalid(what: &str) -> io::Error
+{
+    io::Error::new(io::ErrorKind::InvalidData, format!("invalid {}", what))
+}
+
+macro_rules! int_codec
+{
+    ($($ty:ty),*) => {$(
+        impl Encode for $ty
+        {
+            fn encode<W: Write>(&self, w: &mut W) -> io::Result<()>
+            {
+                w.write_all(&self.to_le_bytes())
+            }
+        }
+
+        impl Decode for $ty
+        {
+            fn decode<R: Read>(r: &mut R) -> io::Result<$ty>
+            {
+                let mut bytes = [0; ::std::mem::size_of::<$ty>()];
+                r.read_exact(&mut bytes)?;
+                Ok(<$ty>::from_le_bytes(bytes))
+            }
+        }
+    )*}
+}
+
+int_codec!(u8, u32, u64, i32);
+
+/* usize goes out as a u64 so tables move between platforms */
+impl Encode for usize
+{
+    fn encode<W: Write>(&self, w: &mut W) -> io::Result<()>
+    {
+        (*self as u64).encode(w)
+    }
+}
+
+impl Decode for usize
+{
+    fn decode<R: Read>(r: &mut R) -> io::Result<usize>
+    {
+        let value = u64::decode(r)?;
+        if value > usize::MAX as u64
+        {
+            return Err(invalid("length"));
+        }
+        Ok(value as usize)
+    }
+}
+
+impl Encode for bool
+{
+    fn encode<W: Write>(&self, w: &mut W) -> io::Result<()>
+    {
+        (*self as u8).encode(w)
+    }
+}
+
+impl Decode for bool
+{
+    fn decode<R: Read>(r: &mut R) -> io::Result<bool>
+    {
+        match u8::decode(r)?
+        {
+            0 => Ok(false),
+            1 => Ok(true),
+            _ => Err(invalid("bool")),
+        }
+    }
+}
+
+impl<T> Encode for Option<T>
+    where T: Encode
+{
+    fn encode<W: Write>(&self, w: &mut W) -> io::Result<()>
+    {
+        match *self
+        {
+            None => false.encode(w),
+            Some(ref value) => {
+                true.encode(w)?;
+                value.encode(w)
+            }
+        }
+    }
+}
+
+impl<T> Decode for Option<T>
+    where T: Decode
+{
+    fn decode<R: Read>(r: &mut R) -> io::Result<Option<T>>
+    {
+        if bool::decode(r)?
+        {
+            Ok(Some(T::decode(r)?))
+        }
+        else
+        {
+            Ok(None)
+        }
+    }
+}
+
+impl<T> Encode for Vec<T>
+    where T: Encode
+{
+    fn encode<W: Write>(&self, w: &mut W) -> io::Result<()>
+    {
+        self.len().encode(w)?;
+        for value in self
+        {
+            value.encode(w)?;
+        }
+        Ok(())
+    }
+}
+
+impl<T> Decode for Vec<T>
+    where T: Decode
+{
+    fn decode<R: Read>(r: &mut R) -> io::Result<Vec<T>>
+    {
+        let len = usize::decode(r)?;
+        /* Don't trust the length with the allocation up front */
+        let mut values = Vec::new();
+        for _ in 0..len
+        {
+            values.push(T::decode(r)?);
+        }
+        Ok(values)
+    }
+}
+
+macro_rules! tuple_codec
+{
+    ($($name:ident),*) => {
+        impl<$($name),*> Encode for ($($name,)*)
+            where $($name: Encode),*
+        {
+            #[allow(non_snake_case)]
+            fn encode<W: Write>(&self, w: &mut W) -> io::Result<()>
+            {
+                let ($(ref $name,)*) = *self;
+                $($name.encode(w)?;)*
+                Ok(())
+            }
+        }
+
+        impl<$($name),*> Decode for ($($name,)*)
+            where $($name: Decode),*
+        {
+            fn decode<R: Read>(r: &mut R) -> io::Result<($($name,)*)>
+            {
+                Ok(($($name::decode(r)?,)*))
+            }
+        }
+    }
+}
+
+tuple_codec!(A, B);
+tuple_codec!(A, B, C);
+tuple_codec!(A, B, C, D);
+
+impl Encode for Score
+{
+    fn encode<W: Write>(&self, w: &mut W) -> io::Result<()>
+    {
+        match *self
+        {
+            Score::Lose => 0u8.encode(w),
+            Score::Heuristic(value) => {
+                1u8.encode(w)?;
+                value.encode(w)
+            }
+            Score::Draw => 2u8.encode(w),
+            Score::Win => 3u8.encode(w),
+        }
+    }
+}
+
+impl Decode for Score
+{
+    fn decode<R: Read>(r: &mut R) -> io::Result<Score>
+    {
+        match u8::decode(r)?
+        {
+            0 => Ok(Score::Lose),
+            1 => Ok(Score::Heuristic(i32::decode(r)?)),
+            2 => Ok(Score::Draw),
+            3 => Ok(Score::Win),
+            _ => Err(invalid("score")),
+        }
+    }
+}
+
+impl Encode for OrderedF32
+{
+    fn encode<W: Write>(&self, w: &mut W) -> io::Result<()>
+    {
+        self.0.to_bits().encode(w)
+    }
+}
+
+impl Decode for OrderedF32
+{
+    fn decode<R: Read>(r: &mut R) -> io::Result<OrderedF32>
+    {
+        u32::decode(r).map(|bits| OrderedF32(f32::from_bits(bits)))
+    }
+}
+
+impl<S> Encode for TimedScore<S>
+    where S: Encode
+{
+    fn encode<W: Write>(&self, w: &mut W) -> io::Result<()>
+    {
+        self.score.encode(w)?;
+        self.turns.encode(w)
+    }
+}
+
+impl<S> Decode for TimedScore<S>
+    where S: Decode
+{
+    fn decode<R: Read>(r: &mut R) -> io::Result<TimedScore<S>>
+    {
+        Ok(TimedScore {
+            score: S::decode(r)?,
+            turns: u32::decode(r)?,
+        })
+    }
+}
+
+impl Encode for Team
+{
+    fn encode<W: Write>(&self, w: &mut W) -> io::Result<()>
+    {
+        let tag: u8 = match *self
+        {
+            Team::Enemy => 0,
+            Team::Ally => 1,
+        };
+        tag.encode(w)
+    }
+}
+
+impl Decode for Team
+{
+    fn decode<R: Read>(r: &mut R) -> io::Result<Team>
+    {
+        match u8::decode(r)?
+        {
+            0 => Ok(Team::Enemy),
+            1 => Ok(Team::Ally),
+            _ => Err(invalid("team")),
+        }
+    }
+}
+
+impl Encode for ZobristKey
+{
+    fn encode<W: Write>(&self, w: &mut W) -> io::Result<()>
+    {
+        self.0.encode(w)
+    }
+}
+
+impl Decode for ZobristKey
+{
+    fn decode<R: Read>(r: &mut R) -> io::Result<ZobristKey>
+    {
+        u64::decode(r).map(ZobristKey)
+    }
+}
+
+impl Encode for CheckedZobristKey
+{
+    fn encode<W: Write>(&self, w: &mut W) -> io::Result<()>
+    {
+        self.hash.encode(w)?;
+        self.check.encode(w)
+    }
+}
+
+impl Decode for CheckedZobristKey
+{
+    fn decode<R: Read>(r: &mut R) -> io::Result<CheckedZobristKey>
+    {
+        Ok(CheckedZobristKey {
+            hash: u64::decode(r)?,
+            check: u32::decode(r)?,
+        })
+    }
+}
+
+impl Encode for Bound
+{
+    fn encode<W: Write>(&self, w: &mut W) -> io::Result<()>
+    {
+        let tag: u8 = match *self
+        {
+            Bound::Exact => 0,
+            Bound::LowerBound => 1,
+            Bound::UpperBound => 2,
+        };
+        tag.encode(w)
+    }
+}
+
+impl Decode for Bound
+{
+    fn decode<R: Read>(r: &mut R) -> io::Result<Bound>
+    {
+        match u8::decode(r)?
+        {
+            0 => Ok(Bound::Exact),
+            1 => Ok(Bound::LowerBound),
+            2 => Ok(Bound::UpperBound),
+            _ => Err(invalid("bound")),
+        }
+    }
+}
+
+impl<M, S> Encode for MoveStats<M, S>
+    where M: Encode,
+          S: Encode
+{
+    fn encode<W: Write>(&self, w: &mut W) -> io::Result<()>
+    {
+        self.mv.encode(w)?;
+        self.score.encode(w)?;
+        self.nodes_visited.encode(w)?;
+        self.mvs.encode(w)?;
+        self.depth_reached.encode(w)?;
+        self.extended_depth.encode(w)?;
+        self.seldepth.encode(w)?;
+        self.exhaustive.encode(w)?;
+        self.completed.encode(w)?;
+        self.solved.encode(w)
+    }
+}
+
+impl<M, S> Decode for MoveStats<M, S>
+    where M: Decode,
+          S: Decode
+{
+    fn decode<R: Read>(r: &mut R) -> io::Result<MoveStats<M, S>>
+    {
+        Ok(MoveStats {
+            mv: Option::decode(r)?,
+            score: TimedScore::decode(r)?,
+            nodes_visited: u64::decode(r)?,
+            mvs: Vec::decode(r)?,
+            depth_reached: u32::decode(r)?,
+            extended_depth: u32::decode(r)?,
+            seldepth: u32::decode(r)?,
+            exhaustive: bool::decode(r)?,
+            completed: bool::decode(r)?,
+            solved: bool::decode(r)?,
+        })
+    }
+}
@@ -0,0 +1,107 @@
+use std::ops::{Add, AddAssign, Sub, SubAssign};
+
+/// Parts a ply is split into by `Depth`.
+pub const UNITS_PER_PLY: u32 = 4;
+
+/// How deep the search still has to go below a node, in quarters of a
+/// ply, so extensions and reductions can be less than a whole ply.
+///
+/// The search reaches its horizon once less than a ply is left. Depths
+/// in the transposition table are kept in these units too.
+#[derive(Copy,Clone,Debug,Default,PartialEq,Eq,PartialOrd,Ord,Hash)]
+pub struct Depth(u32);
+
+impl Depth
+{
+    pub const ZERO: Depth = Depth(0);
+    pub const ONE_PLY: Depth = Depth(UNITS_PER_PLY);
+    pub const HALF_PLY: Depth = Depth(UNITS_PER_PLY / 2);
+
+    pub fn from_plies(plies: u32) -> Depth
+    {
+        Depth(plies.saturating_mul(UNITS_PER_PLY))
+    }
+
+    pub fn from_units(units: u32) -> Depth
+    {
+        Depth(units)
+    }
+
+    /// Whole plies, leaving out any fraction.
+    pub fn plies(self) -> u32
+    {
+        self.0 / UNITS_PER_PLY
+    }
+
+    pub fn units(self) -> u32
+    {
+        self.0
+    }
+
+    pub fn checked_sub(self, other: Depth) -> Option<Depth>
+    {
+        self.0.checked_sub(other.0).map(Depth)
+    }
+
+    pub fn saturating_sub(self, other: Depth) -> Depth
+    {
+        Depth(self.0.saturating_sub(other.0))
+    }
+
+    /// `plies` deeper, or shallower if negative, but never below zero.
+    pub fn add_plies(self, plies: i32) -> Depth
+    {
+        let units = plies.unsigned_abs().saturating_mul(UNITS_PER_PLY);
+        if plies < 0
+        {
+            Depth(self.0.saturating_sub(units))
+        }
+        else
+        {
+            Depth(self.0.saturating_add(units))
+        }
+    }
+
+    /// Half as deep, rounded down to a unit.
+    pub fn half(self) -> Depth
+    {
+        Depth(self.0 / 2)
+    }
+}
+
+/* Depths never get anywhere near overflowing, but a deep enough request
+ * stays deep rather than wrapping around
+ */
+impl Add for Depth
+{
+    type Output = Depth;
+    fn add(self, other: Depth) -> Depth
+    {
+        Depth(self.0.saturating_add(other.0))
+    }
+}
+
+impl Sub for Depth
+{
+    type Output = Depth;
+    fn sub(self, other: Depth) -> Depth
+    {
+        Depth(self.0 - other.0)
+    }
+}
+
+impl AddAssign for Depth
+{
+    fn add_assign(&mut self, other: Depth)
+    {
+        *self = *self + other;
+    }
+}
+
+impl SubAssign for Depth
+{
+    fn sub_assign(&mut self, other: Depth)
+    {
+        *self = *self - other;
+    }
+}
@@ -0,0 +1,140 @@
+//! Expectimax, for games with dice or other chance in them, where plain
+//! minimax would have the opponent pick how the dice fall.
+//!
+//! A position where chance decides what comes next is worth the average
+//! of the positions it can lead to, weighted by how likely each is. The
+//! sides pick their best moves everywhere else.
+//!
+//! Averages need numbers, so scores are turned into `f64`s by
+//! `score_value`: a heuristic score is its value, a draw is zero, and a
+//! win or a loss is `WIN_VALUE` either way, beyond any heuristic score.
+//! A one in four chance of winning is worth a quarter of a win.
+
+use board::{Board, NoMovePolicy};
+use ::{Score, Team};
+
+/// What a win is worth to `score_value`, and a loss the other way.
+pub const WIN_VALUE: f64 = 1e10;
+
+/// A board where chance sometimes decides what happens next.
+pub trait ChanceBoard: Board
+{
+    /// The positions chance can turn this one into, each with how likely
+    /// it is, adding up to one. `None` if a side moves here instead.
+    ///
+    /// The side to move stays the same, so a move that rolls dice leaves
+    /// the board waiting for the roll, with the other side to move once
+    /// it has been made.
+    fn chance_outcomes(&self) -> Option<Vec<(Self, f64)>>;
+}
+
+/// Result of an expectimax search.
+#[derive(Clone,Debug,PartialEq)]
+pub struct ExpectimaxStats<M>
+{
+    pub mv: Option<M>,
+    /// Expected value of the position for the ally, see `score_value`.
+    pub value: f64,
+    /// Nodes searched, counting the root and the positions chance led to.
+    pub nodes_visited: u64,
+}
+
+/// `score` as a number that can be averaged, from the ally's point of
+/// view.
+pub fn score_value(score: Score) -> f64
+{
+    match score
+    {
+        Score::Win => WIN_VALUE,
+        Score::Lose => -WIN_VALUE,
+        Score::Draw => 0.0,
+        Score::Heuristic(val) => f64::from(val),
+    }
+}
+
+/// Searches `board` `plies` plies deep for `turn`, averaging over the
+/// outcomes of chance. Rolls of the dice don't count as plies.
+///
+/// Moves are pruned with alpha-beta, but the outcomes of chance are all
+/// searched with a window of their own, since the average needs each of
+/// them exactly. Ties go to the first move. Sides that pass when out of
+/// moves are scored as they stand there, like `NoMovePolicy::ScoreBoard`.
+pub fn expectimax<B>(board: &B, turn: Team, plies: u32) -> ExpectimaxStats<B::Move>
+    where B: ChanceBoard
+{
+    let mut nodes = 0;
+    let (value, mv) = search(board, turn, plies, -f64::INFINITY, f64::INFINITY, &mut nodes);
+    ExpectimaxStats {
+        mv,
+        value,
+        nodes_visited: nodes,
+    }
+}
+
+/* The ally's value of `board` and the move that gets it, exact inside
+ * the window and a bound past it
+ */
+fn search<B>(board: &B, turn: Team, plies: u32, mut alpha: f64, mut beta: f64, nodes: &mut u64) -> (f64, Option<B::Move>)
+    where B: ChanceBoard
+{
+    *nodes += 1;
+    if board.is_game_over()
+    {
+        return (score_value(board.score_for(turn)), None);
+    }
+    if let Some(outcomes) = board.chance_outcomes()
+    {
+        let value = outcomes.iter()
+            .map(|(outcome, chance)| chance * search(outcome, turn, plies, -f64::INFINITY, f64::INFINITY, nodes).0)
+            .sum();
+        return (value, None);
+    }
+    if plies == 0
+    {
+        return (score_value(board.score_for(turn)), None);
+    }
+
+    let moves: Vec<_> = board.moves(turn).collect();
+    if moves.is_empty()
+    {
+        let value = match board.on_no_moves(turn)
+        {
+            NoMovePolicy::Lose if turn == Team::Ally => -WIN_VALUE,
+            NoMovePolicy::Lose => WIN_VALUE,
+            _ => score_value(board.score_for(turn)),
+        };
+        return (value, None);
+    }
+
+    let mut best: Option<(f64, B::Move)> = None;
+    for mv in moves
+    {
+        let mut child = board.clone();
+        child.do_move(&mv);
+        let (value, _) = search(&child, turn.other_team(), plies - 1, alpha, beta, nodes);
+
+        let better = match (turn, &best)
+        {
+            (_, None) => true,
+            (Team::Ally, Some((best, _))) => value > *best,
+            (Team::Enemy, Some((best, _))) => value < *best,
+        };
+        if better
+        {
+            best = Some((value, mv));
+        }
+
+        match turn
+        {
+            Team::Ally => alpha = alpha.max(value),
+            Team::Enemy => beta = beta.min(value),
+        }
+        if alpha >= beta
+        {
+            break;
+        }
+    }
+
+    let (value, mv) = best.unwrap();
+    (value, Some(mv))
+}
@@ -0,0 +1,302 @@
+use ::Team;
+
+use std::cmp::Reverse;
+use std::collections::{HashMap, VecDeque};
+use std::hash::Hash;
+
+/// The last two moves that caused a beta cutoff at each ply.
+pub struct Killers<M>
+{
+    slots: Vec<[Option<M>; 2]>,
+}
+
+impl<M> Killers<M>
+    where M: Clone + Eq
+{
+    pub fn new() -> Killers<M>
+    {
+        Killers {
+            slots: Vec::new(),
+        }
+    }
+
+    /// Remembers `mv` as the newest killer at `ply`, pushing out the older
+    /// of the two.
+    pub fn record(&mut self, ply: u32, mv: &M)
+    {
+        let ply = ply as usize;
+        while self.slots.len() <= ply
+        {
+            self.slots.push([None, None]);
+        }
+
+        let slots = &mut self.slots[ply];
+        if slots[0].as_ref() != Some(mv)
+        {
+            slots[1] = slots[0].take();
+            slots[0] = Some(mv.clone());
+        }
+    }
+
+    /// 0 for the newest killer at `ply`, 1 for the older one and 2 for
+    /// everything else.
+    pub fn rank(&self, ply: u32, mv: &M) -> u8
+    {
+        match self.slots.get(ply as usize)
+        {
+            Some(slots) if slots[0].as_ref() == Some(mv) => 0,
+            Some(slots) if slots[1].as_ref() == Some(mv) => 1,
+            _ => 2,
+        }
+    }
+
+    pub fn clear(&mut self)
+    {
+        self.slots.clear();
+    }
+
+    /// Moves the killers `plies` plies closer to the root, for when the
+    /// game has moved on that far and the next search starts there.
+    pub fn advance(&mut self, plies: u32)
+    {
+        let plies = (plies as usize).min(self.slots.len());
+        self.slots.drain(..plies);
+    }
+}
+
+/// How often each move caused a beta cutoff, weighted by how deep the
+/// search below it went.
+///
+/// Looking moves up takes `Hash`, which moves in general don't have, so
+/// the lookups are picked when the table is made and kept as functions.
+pub struct History<M>
+{
+    counts: HashMap<M, u64>,
+    get: fn(&HashMap<M, u64>, &M) -> u64,
+    add: fn(&mut HashMap<M, u64>, &M, u64),
+}
+
+impl<M> History<M>
+{
+    pub fn new() -> History<M>
+        where M: Clone + Eq + Hash
+    {
+        History {
+            counts: HashMap::new(),
+            get: |counts, mv| counts.get(mv).cloned().unwrap_or(0),
+            add: |counts, mv, bonus|
+            {
+                let count = counts.entry(mv.clone()).or_insert(0);
+                *count = count.saturating_add(bonus);
+            },
+        }
+    }
+
+    /// An empty table of the same kind.
+    pub fn emptied(&self) -> History<M>
+    {
+        History {
+            counts: HashMap::new(),
+            get: self.get,
+            add: self.add,
+        }
+    }
+
+    /// Credits `mv` with a cutoff `plies` plies above the horizon.
+    pub fn record(&mut self, plies: u32, mv: &M)
+    {
+        let plies = plies as u64;
+        (self.add)(&mut self.counts, mv, plies * plies);
+    }
+
+    pub fn count(&self, mv: &M) -> u64
+    {
+        (self.get)(&self.counts, mv)
+    }
+
+    /// Halves every count, forgetting the moves that drop to nothing.
+    pub fn decay(&mut self)
+    {
+        self.counts.retain(|_, count|
+        {
+            *count /= 2;
+            *count > 0
+        });
+    }
+
+    pub fn clear(&mut self)
+    {
+        self.counts.clear();
+    }
+}
+
+/// The move of each side that last refuted each move of the other, found
+/// by causing a beta cutoff right after it.
+///
+/// Like `History`, the moves only need `Hash` to make one.
+pub struct Countermoves<M>
+{
+    ally: HashMap<M, M>,
+    enemy: HashMap<M, M>,
+    get: for<'a> fn(&'a HashMap<M, M>, &M) -> Option<&'a M>,
+    set: fn(&mut HashMap<M, M>, &M, &M),
+}
+
+impl<M> Countermoves<M>
+{
+    pub fn new() -> Countermoves<M>
+        where M: Clone + Eq + Hash
+    {
+        Countermoves {
+            ally: HashMap::new(),
+            enemy: HashMap::new(),
+            get: |counters, previous| counters.get(previous),
+            set: |counters, previous, mv|
+            {
+                counters.insert(previous.clone(), mv.clone());
+            },
+        }
+    }
+
+    /// An empty table of the same kind.
+    pub fn emptied(&self) -> Countermoves<M>
+    {
+        Countermoves {
+            ally: HashMap::new(),
+            enemy: HashMap::new(),
+            get: self.get,
+            set: self.set,
+        }
+    }
+
+    fn side(&self, turn: Team) -> &HashMap<M, M>
+    {
+        match turn
+        {
+            Team::Ally => &self.ally,
+            Team::Enemy => &self.enemy,
+        }
+    }
+
+    /// Remembers that `mv` of `turn` refuted `previous`.
+    pub fn record(&mut self, turn: Team, previous: &M, mv: &M)
+    {
+        let counters = match turn
+        {
+            Team::Ally => &mut self.ally,
+            Team::Enemy => &mut self.enemy,
+        };
+        (self.set)(counters, previous, mv);
+    }
+
+    /// What `turn` last refuted `previous` with.
+    pub fn get(&self, turn: Team, previous: &M) -> Option<&M>
+    {
+        (self.get)(self.side(turn), previous)
+    }
+
+    pub fn clear(&mut self)
+    {
+        self.ally.clear();
+        self.enemy.clear();
+    }
+}
+
+/* Replies filed under the side that played the refuted move and that
+ * move, with the keys oldest first
+ */
+type Replies<M> = HashMap<(Team, M), M>;
+
+/// The reply that last refuted each move, found by the move failing low
+/// because of it, wherever in the tree that was.
+///
+/// It holds up to a fixed number of moves, forgetting the one filed the
+/// longest ago to make room. Like `History`, the moves only need `Hash` to
+/// make one.
+pub struct RefutationTable<M>
+{
+    replies: Replies<M>,
+    filed: VecDeque<(Team, M)>,
+    capacity: usize,
+    get: for<'a> fn(&'a Replies<M>, Team, &M) -> Option<&'a M>,
+    /* Whether the move is new to the table */
+    set: fn(&mut Replies<M>, Team, &M, &M) -> bool,
+    remove: fn(&mut Replies<M>, Team, &M),
+}
+
+impl<M> RefutationTable<M>
+    where M: Clone
+{
+    pub fn new(capacity: usize) -> RefutationTable<M>
+        where M: Eq + Hash
+    {
+        RefutationTable {
+            replies: HashMap::new(),
+            filed: VecDeque::new(),
+            capacity: capacity.max(1),
+            get: |replies, turn, mv| replies.get(&(turn, mv.clone())),
+            set: |replies, turn, mv, reply| replies.insert((turn, mv.clone()), reply.clone()).is_none(),
+            remove: |replies, turn, mv|
+            {
+                replies.remove(&(turn, mv.clone()));
+            },
+        }
+    }
+
+    /// An empty table of the same kind and size.
+    pub fn emptied(&self) -> RefutationTable<M>
+    {
+        RefutationTable {
+            replies: HashMap::new(),
+            filed: VecDeque::new(),
+            capacity: self.capacity,
+            get: self.get,
+            set: self.set,
+            remove: self.remove,
+        }
+    }
+
+    /// Remembers that `reply` refuted `mv`, played by `turn`.
+    pub fn record(&mut self, turn: Team, mv: &M, reply: &M)
+    {
+        if (self.set)(&mut self.replies, turn, mv, reply)
+        {
+            self.filed.push_back((turn, mv.clone()));
+            if self.filed.len() > self.capacity
+            {
+                let (turn, oldest) = self.filed.pop_front().unwrap();
+                (self.remove)(&mut self.replies, turn, &oldest);
+            }
+        }
+    }
+
+    /// What last refuted `mv`, played by `turn`.
+    pub fn get(&self, turn: Team, mv: &M) -> Option<&M>
+    {
+        (self.get)(&self.replies, turn, mv)
+    }
+
+    pub fn clear(&mut self)
+    {
+        self.replies.clear();
+        self.filed.clear();
+    }
+}
+
+/// Puts the killers at `ply` first, then the countermove, then the rest by
+/// their history count. Moves none of them tell apart keep their order.
+pub fn order<M>(moves: &mut [M], ply: u32, killers: Option<&Killers<M>>, countermove: Option<&M>, history: Option<&History<M>>)
+    where M: Clone + Eq
+{
+    moves.sort_by_cached_key(|mv|
+    {
+        let rank = match killers.map_or(2, |killers| killers.rank(ply, mv))
+        {
+            2 if countermove == Some(mv) => 2,
+            2 => 3,
+            rank => rank,
+        };
+        let count = history.map_or(0, |history| history.count(mv));
+        (rank, Reverse(count))
+    });
+}
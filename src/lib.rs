@@ -2,18 +2,55 @@ extern crate lru;
 
 pub mod board;
 pub mod transposition_table;
+pub mod observer;
+pub mod codec;
+pub mod options;
+pub mod clock;
+pub mod trace;
+pub mod ordering;
+pub mod depth;
+pub mod expectimax;
+pub mod maxn;
+pub mod mcts;
+pub mod pns;
+pub mod reference;
+#[cfg(any(test, feature = "testing"))]
+pub mod testing;
+mod rng;
+mod heuristics;
 
-use board::Board;
-use transposition_table::TranspositionTable;
+use board::{Board, NoMovePolicy, ZobristBoard};
+use transposition_table::{TranspositionTable, TableStats, Bound, ReplacementPolicy, PositionKey, ZobristKey, CheckedZobristKey};
+use observer::{SearchObserver, NoObserver, NODES_PER_POLL};
+use codec::{Encode, Decode};
+use rng::XorShift;
+use mcts::RandomSource;
+use heuristics::{Killers, Countermoves, History, RefutationTable};
+use options::{NODES_PER_CLOCK_READING, SearchOptions, LateMoveReductions, SingularExtensions, InternalDeepening, ReverseFutility, OpponentError};
+use clock::Clock;
+#[cfg(feature = "std-clock")]
+use clock::StdClock;
+use trace::{SearchTrace, TraceNode};
+use ordering::{MoveOrderer, TableMoveFirst, SharedOrderer};
+use depth::Depth;
+use reference::{Reference, ReferenceResult};
 
-use std::i32;
-use std::fmt;
 use std::ops::Neg;
-use std::hash::Hash;
-use std::cmp::Ordering;
+use std::hash::{Hash, Hasher};
+use std::collections::hash_map::DefaultHasher;
+use std::cmp::{Ordering, Reverse};
 use std::num::NonZeroUsize;
+use std::time::Duration;
+#[cfg(feature = "std-clock")]
+use std::time::Instant;
+use std::io::{self, Read, Write};
+use std::sync::Arc;
+use std::sync::atomic::{self, AtomicBool, AtomicU32, AtomicU64};
+use std::thread;
+use std::panic;
+use std::mem;
 
-#[derive(Copy,Clone,Debug)]
+#[derive(Copy,Clone,Debug,PartialEq,Eq,Hash)]
 pub enum Team
 {
     Enemy,
@@ -32,551 +69,10470 @@ impl Team
     }
 }
 
-#[derive(PartialEq,Eq,PartialOrd,Ord,Copy,Clone,Debug,Hash)]
+/// Value of a position from the ally's point of view.
+///
+/// A `Draw` ranks level with `Heuristic(0)` and compares equal to it. That
+/// keeps negation order-reversing, which the search relies on, while still
+/// letting boards report a settled draw. Both sit above every loss and
+/// negative heuristic and below every win and positive heuristic.
+///
+/// Heuristics should stay within `-i32::MAX..=i32::MAX`, where every value
+/// has an exact negation. `Score::heuristic` clamps to that range, and
+/// negating `Heuristic(i32::MIN)` saturates to `Heuristic(i32::MAX)`.
+#[derive(Copy,Clone,Debug)]
 pub enum Score
 {
     Lose,
     Heuristic(i32),
+    Draw,
     Win,
 }
 
-impl Neg for Score
+impl Score
 {
-    type Output = Self;
-    fn neg(self) -> Self
+    /// A heuristic score of `val`, clamped to the range that negates
+    /// exactly.
+    pub fn heuristic(val: i32) -> Score
+    {
+        Score::Heuristic(val.max(-i32::MAX))
+    }
+
+    fn rank(self) -> (u8, i32)
     {
         match self
         {
-            Score::Win => Score::Lose,
-            Score::Lose => Score::Win,
-            Score::Heuristic(val) => Score::Heuristic(-val),
+            Score::Lose => (0, 0),
+            Score::Heuristic(val) => (1, val),
+            Score::Draw => (1, 0),
+            Score::Win => (2, 0),
         }
     }
 }
 
-#[derive(PartialEq,Eq,Copy,Clone,Debug,Hash)]
-pub struct TimedScore
+impl PartialEq for Score
 {
-    pub score: Score,
-    pub turns: u32,
+    fn eq(&self, other: &Self) -> bool
+    {
+        self.rank() == other.rank()
+    }
 }
 
-impl PartialOrd for TimedScore {
-    fn partial_cmp(&self, other: &Self) -> Option<Ordering> {
+impl Eq for Score {}
+
+impl PartialOrd for Score
+{
+    fn partial_cmp(&self, other: &Self) -> Option<Ordering>
+    {
         Some(self.cmp(other))
     }
 }
 
-impl Ord for TimedScore {
-    fn cmp(&self, other: &Self) -> Ordering {
-        match self.score.cmp(&other.score) {
-            Ordering::Less => Ordering::Less,
-            Ordering::Equal => {
-                match self.score.cmp(&Score::Heuristic(0)) {
-                    Ordering::Less => self.turns.cmp(&other.turns),
-                    Ordering::Equal => Ordering::Equal,
-                    Ordering::Greater => other.turns.cmp(&self.turns)
-                }
-            }
-            Ordering::Greater => Ordering::Greater,
-        }
+impl Ord for Score
+{
+    fn cmp(&self, other: &Self) -> Ordering
+    {
+        self.rank().cmp(&other.rank())
     }
 }
 
-#[derive(Clone,Debug,PartialEq,Eq)]
-pub struct MoveStats<M>
+impl Hash for Score
 {
-    pub mv: Option<M>,
-    pub score: TimedScore,
-    pub nodes_visited: u64,
-    pub mvs: Vec<M>,
+    fn hash<H: Hasher>(&self, state: &mut H)
+    {
+        self.rank().hash(state);
+    }
 }
 
-pub struct Minimax<B>
-    where B: Board + Eq + Hash
+/// What a search can score positions with.
+///
+/// Negating a score has to give the opponent's view of it and reverse the
+/// order, since the search flips scores between the two sides.
+pub trait GameScore: Ord + Neg<Output = Self> + Copy
 {
-    ally_ttable: TranspositionTable<B, MoveStats<B::Move>>,
-    enemy_ttable: TranspositionTable<B, MoveStats<B::Move>>,
-}
+    /// A loss. Nothing scores lower.
+    const MIN: Self;
+    /// A win. Nothing scores higher.
+    const MAX: Self;
+    /// An even position. Above it getting there sooner is better, below it
+    /// putting it off is.
+    const ZERO: Self;
 
-impl<B> Minimax<B>
-    where B: Board + Eq + Hash
-{
-    pub fn new(ttable_size: NonZeroUsize) -> Minimax<B>
+    /// What a draw is worth to a side holding `contempt` against draws, as
+    /// set by `SearchOptionsBuilder::contempt`. Positive contempt makes a
+    /// draw look like a small loss. Defaults to `ZERO`, which ignores it.
+    fn draw_with_contempt(_contempt: i32) -> Self
     {
-        Minimax
-        {
-            ally_ttable: TranspositionTable::new(ttable_size),
-            enemy_ttable: TranspositionTable::new(ttable_size),
-        }
+        Self::ZERO
     }
 
-    /// Minimax driver function.
-    ///
-    /// `turn` is the current player.
-    pub fn minimax(&mut self, board: &B, turn: Team, plies: u32) -> MoveStats<B::Move>
-    where <B as Board>::Move: fmt::Display
+    /// The lowest score above this one, if there is one and it's known.
+    /// Principal variation search makes its zero-width windows out of it.
+    /// Defaults to `None`, which leaves the windows full.
+    fn above(self) -> Option<Self>
     {
-        let lose = TimedScore {
-            score: Score::Lose,
-            turns: 0,
-        };
-        let win = TimedScore {
-            score: Score::Win,
-            turns: 0,
-        };
+        None
+    }
 
-        let mut optimal_move = match turn
-        {
-            Team::Ally =>
-                self.max(board, plies, lose, win),
-            Team::Enemy =>
-                self.min(board, plies, lose, win),
-        };
+    /// The score `margin` higher, or lower for a negative margin, if it
+    /// isn't a win or a loss. Razoring, reverse futility pruning and delta
+    /// pruning compare static scores to the window through it. Defaults to
+    /// `None`, which keeps them off.
+    fn add_margin(self, _margin: i32) -> Option<Self>
+    {
+        None
+    }
 
-        optimal_move.nodes_visited += 1;
-        optimal_move
+    /// The average of `weighted`, each score counted by its weight, for
+    /// `SearchOptionsBuilder::opponent_error`. The weights add up to one.
+    /// Defaults to `None`, which leaves the best score as it is.
+    fn blend(_weighted: &[(Self, f64)]) -> Option<Self>
+    {
+        None
     }
+}
+
+impl GameScore for Score
+{
+    const MIN: Score = Score::Lose;
+    const MAX: Score = Score::Win;
+    const ZERO: Score = Score::Heuristic(0);
 
-    /// Generates best move for ally
-    fn max(&mut self, board: &B, plies: u32, mut alpha: TimedScore, beta: TimedScore) -> MoveStats<B::Move>
-    where <B as Board>::Move: fmt::Display
+    fn draw_with_contempt(contempt: i32) -> Score
     {
-        let moves = board.gen_ally_moves();
+        Score::Heuristic(contempt.saturating_neg())
+    }
 
-        /* Fail state if you can't move */
-        if moves.len() == 0
+    fn above(self) -> Option<Score>
+    {
+        match self
         {
-            return MoveStats
-            {
-                mv: None,
-                score: TimedScore {
-                    score: Score::Lose,
-                    turns: 0,
-                },
-                nodes_visited: 0,
-                mvs: Vec::new(),
-            };
+            Score::Lose => Some(Score::Heuristic(i32::MIN)),
+            Score::Heuristic(i32::MAX) => Some(Score::Win),
+            Score::Heuristic(val) => Some(Score::Heuristic(val + 1)),
+            Score::Draw => Some(Score::Heuristic(1)),
+            Score::Win => None,
         }
+    }
 
-        /* If you cannot proceed further */
-        if plies == 0 || board.is_game_over()
+    fn add_margin(self, margin: i32) -> Option<Score>
+    {
+        match self
         {
-            return MoveStats
-            {
-                mv: None,
-                score: TimedScore {
-                    score: board.score(),
-                    turns: 0,
-                },
-                nodes_visited: 0,
-                mvs: Vec::new(),
-            }
+            Score::Heuristic(val) => Some(Score::heuristic(val.saturating_add(margin))),
+            Score::Draw => Some(Score::heuristic(margin)),
+            Score::Win | Score::Lose => None,
         }
+    }
 
-        let mut best = MoveStats{
-            mv: None,
-            score: TimedScore {
-                score: Score::Lose,
-                turns: 0,
-            },
-            nodes_visited: 0,
-            mvs: Vec::new(),
-        };
-
-        if let Some(mut precomputed_move) = self.ally_ttable.get(board, plies)
+    /* Wins and losses count as the furthest a heuristic score goes */
+    fn blend(weighted: &[(Score, f64)]) -> Option<Score>
+    {
+        let first = weighted.first()?.0;
+        if weighted.iter().all(|&(score, weight)| score == first || weight == 0.0)
         {
-            precomputed_move.mvs = Vec::new();
-            return precomputed_move;
+            return Some(first);
         }
 
-        for mv in moves
-        {
-            /* Make a clone of the board so we don't break this one */
-            let mut board_clone = board.clone();
-            board_clone.do_move(&mv);
-    
-            /* Find enemy's best move */
-            let enemy_move = self.min(&board_clone, plies - 1, alpha, beta);
+        let mean: f64 = weighted.iter()
+            .map(|&(score, weight)| weight * match score
+            {
+                Score::Win => f64::from(i32::MAX),
+                Score::Lose => -f64::from(i32::MAX),
+                Score::Heuristic(val) => f64::from(val),
+                Score::Draw => 0.0,
+            })
+            .sum();
+        Some(Score::heuristic(mean.round() as i32))
+    }
+}
 
-//            if plies == 5 {
-//                println!("  my move: {}", mv);
-//                println!("  score: {:?}", enemy_move.score);
-//                println!("  a: {:?} b {:?}", alpha, beta);
-//            }
+impl Neg for Score
+{
+    type Output = Self;
+    fn neg(self) -> Self
+    {
+        match self
+        {
+            Score::Win => Score::Lose,
+            Score::Lose => Score::Win,
+            Score::Heuristic(val) => Score::Heuristic(val.saturating_neg()),
+            Score::Draw => Score::Draw,
+        }
+    }
+}
 
-            if best.mv.is_none() || enemy_move.score > best.score
-            {
-                best.mv = Some(mv);
-                best.score = enemy_move.score;
-                best.score.turns += 1;
-                best.mvs = enemy_move.mvs.clone();
-            }
+/// Floating point score, ordered so it can be used as a `GameScore`.
+///
+/// Losses and wins are the infinities. Scores must not be NaN, comparing
+/// one panics.
+#[derive(Copy,Clone,Debug)]
+pub struct OrderedF32(pub f32);
 
-            best.nodes_visited += enemy_move.nodes_visited + 1;
+impl PartialEq for OrderedF32
+{
+    fn eq(&self, other: &Self) -> bool
+    {
+        self.cmp(other) == Ordering::Equal
+    }
+}
 
-            /* Set α and break on β ≤ α */
-            if best.score > alpha
-            {
-                alpha = best.score;
-            }
-            if alpha >= beta
-            {
-                if plies == 5 {
-//                    println!("  PRUNED");
-                }
-                break;
-            }
-        }
+impl Eq for OrderedF32 {}
 
-        best.mvs.push(best.mv.clone().unwrap());
-        self.ally_ttable.insert(board.clone(), best.clone(), plies);
+impl PartialOrd for OrderedF32
+{
+    fn partial_cmp(&self, other: &Self) -> Option<Ordering>
+    {
+        Some(self.cmp(other))
+    }
+}
 
-        best
+impl Ord for OrderedF32
+{
+    fn cmp(&self, other: &Self) -> Ordering
+    {
+        self.0.partial_cmp(&other.0).expect("NaN score")
     }
+}
 
-    /// Generates best move for enemy
-    fn min(&mut self, board: &B, plies: u32, alpha: TimedScore, mut beta: TimedScore) -> MoveStats<B::Move>
-    where <B as Board>::Move: fmt::Display
+impl Neg for OrderedF32
+{
+    type Output = Self;
+    fn neg(self) -> Self
     {
-        let moves = board.gen_enemy_moves();
+        OrderedF32(-self.0)
+    }
+}
 
-        /* Fail state if you can't move */
-        if moves.len() == 0
-        {
-            return MoveStats
-            {
-                mv: None,
-                /* If enemy can't move, we win. */
-                score: TimedScore {
-                    score: Score::Win,
-                    turns: 0,
-                },
-                nodes_visited: 0,
-                mvs: Vec::new(),
-            };
-        }
+impl GameScore for OrderedF32
+{
+    const MIN: OrderedF32 = OrderedF32(f32::NEG_INFINITY);
+    const MAX: OrderedF32 = OrderedF32(f32::INFINITY);
+    const ZERO: OrderedF32 = OrderedF32(0.0);
 
-        /* If you cannot proceed further */
-        if plies == 0 || board.is_game_over()
-        {
-            return MoveStats
-            {
-                mv: None,
-                score: TimedScore {
-                    score: board.score(),
-                    turns: 0,
-                },
-                nodes_visited: 0,
-                mvs: Vec::new(),
-            }
-        }
+    fn draw_with_contempt(contempt: i32) -> OrderedF32
+    {
+        OrderedF32(-(contempt as f32))
+    }
 
-        let mut best = MoveStats {
-            mv: None,
-            /* Technically doesn't matter, but for consistancy's sake */
-            score: TimedScore {
-                score: Score::Win,
-                turns: 0,
-            },
-            nodes_visited: 0,
-            mvs: Vec::new(),
-        };
+    fn above(self) -> Option<OrderedF32>
+    {
+        if self.0 == f32::INFINITY { None } else { Some(OrderedF32(self.0.next_up())) }
+    }
 
-        if let Some(precomputed_move) = self.enemy_ttable.get(board, plies)
-        {
-            return precomputed_move;
-        }
+    fn add_margin(self, margin: i32) -> Option<OrderedF32>
+    {
+        if self.0.is_infinite() { None } else { Some(OrderedF32(self.0 + margin as f32)) }
+    }
 
-        for mv in moves
+    fn blend(weighted: &[(OrderedF32, f64)]) -> Option<OrderedF32>
+    {
+        let first = weighted.first()?.0;
+        if weighted.iter().all(|&(score, weight)| score == first || weight == 0.0)
         {
-            /* Make a clone of the board so we don't break this one */
-            let mut board_clone = board.clone();
-            board_clone.do_move(&mv);
-
-            if plies == 6 {
-//                println!("ENEMY {}", mv);
-            }
+            return Some(first);
+        }
 
-            /* Find ally's best move */
-            let ally_move = self.max(&board_clone, plies - 1, alpha, beta);
+        let mean: f64 = weighted.iter()
+            .map(|&(score, weight)| weight * f64::from(score.0.clamp(f32::MIN, f32::MAX)))
+            .sum();
+        Some(OrderedF32(mean as f32))
+    }
+}
 
-            if best.mv.is_none() || ally_move.score < best.score
-            {
-                best.mv = Some(mv);
-                best.score = ally_move.score;
-                best.score.turns += 1;
-                best.mvs = ally_move.mvs.clone();
-            }
+#[derive(PartialEq,Eq,Copy,Clone,Debug,Hash)]
+pub struct TimedScore<S = Score>
+{
+    pub score: S,
+    pub turns: u32,
+}
 
-            best.nodes_visited += ally_move.nodes_visited + 1;
+impl<S> PartialOrd for TimedScore<S>
+    where S: GameScore
+{
+    fn partial_cmp(&self, other: &Self) -> Option<Ordering> {
+        Some(self.cmp(other))
+    }
+}
 
-            /* Set β and break on β ≤ α */
-            if best.score < beta
-            {
-                beta = best.score;
-            }
-            if beta <= alpha
-            {
-                break;
+impl<S> Ord for TimedScore<S>
+    where S: GameScore
+{
+    fn cmp(&self, other: &Self) -> Ordering {
+        match self.score.cmp(&other.score) {
+            Ordering::Less => Ordering::Less,
+            Ordering::Equal => {
+                match self.score.cmp(&S::ZERO) {
+                    Ordering::Less => self.turns.cmp(&other.turns),
+                    Ordering::Equal => Ordering::Equal,
+                    Ordering::Greater => other.turns.cmp(&self.turns)
+                }
             }
+            Ordering::Greater => Ordering::Greater,
         }
+    }
+}
 
-        best.mvs.push(best.mv.clone().unwrap());
-        self.enemy_ttable.insert(board.clone(), best.clone(), plies);
+impl<S> TimedScore<S>
+    where S: GameScore
+{
+    /// The lowest timed score above this one, for a zero-width window.
+    fn just_above(self) -> Option<TimedScore<S>>
+    {
+        /* Below even putting it off is better, above it getting there sooner */
+        if self.score < S::ZERO && self.turns < u32::MAX
+        {
+            return Some(self.later());
+        }
+        if self.score > S::ZERO && self.turns > 0
+        {
+            return Some(TimedScore {
+                turns: self.turns - 1,
+                ..self
+            });
+        }
+        let score = self.score.above()?;
+        Some(TimedScore {
+            score,
+            turns: if score > S::ZERO { u32::MAX } else { 0 },
+        })
+    }
 
-        best
+    /// The highest timed score below this one.
+    fn just_below(self) -> Option<TimedScore<S>>
+    {
+        /* Negating reverses the order */
+        (-self).just_above().map(Neg::neg)
     }
 }
 
-#[cfg(test)]
-mod tests
+impl<S> TimedScore<S>
 {
-    use super::{Team, Score, Minimax, MoveStats};
-    use board::Board;
-    use std::num::NonZeroUsize;
-
-    #[derive(Clone,PartialEq,Eq,Debug)]
-    struct SimpleMove(usize);
-    #[derive(Clone,PartialEq,Eq,Hash,Debug)]
-    enum SimpleBoard
+    /// The same score, reached one turn later.
+    fn later(self) -> TimedScore<S>
     {
-        Node(Vec<SimpleBoard>),
-        Leaf(Score),
+        TimedScore {
+            turns: self.turns.saturating_add(1),
+            ..self
+        }
     }
 
-    /* These aren't really efficient, but they
-     * work for testing.
-     */
-    impl Board for SimpleBoard
+    /// The same score, seen from `ply` plies further up the tree.
+    ///
+    /// Windows are kept this way during the search, counting turns from the
+    /// root, so they can be handed down to a reply by just negating them.
+    /// Shifting them a turn closer instead would get stuck at zero turns
+    /// below the end of a short line, and leave the reply an empty window.
+    fn seen_from_root(self, ply: u32) -> TimedScore<S>
     {
-        type Move = SimpleMove;
+        TimedScore {
+            turns: self.turns.saturating_add(ply),
+            ..self
+        }
+    }
+}
 
-        fn gen_ally_moves(&self) -> Vec<Self::Move>
-        {
+impl<S> Neg for TimedScore<S>
+    where S: Neg<Output = S>
+{
+    type Output = Self;
+    fn neg(self) -> Self
+    {
+        TimedScore {
+            score: -self.score,
+            turns: self.turns,
+        }
+    }
+}
+
+#[derive(Clone,Debug,PartialEq,Eq)]
+pub struct MoveStats<M, S = Score>
+{
+    pub mv: Option<M>,
+    pub score: TimedScore<S>,
+    /// Nodes this search went through, counting the root. A position
+    /// answered from the transposition table is one node, whatever it took
+    /// to search it the first time.
+    pub nodes_visited: u64,
+    /// Principal variation, starting with `mv`. Cached positions bring
+    /// along the line they were stored with.
+    pub mvs: Vec<M>,
+    /// Number of plies the search that produced this result went to.
+    pub depth_reached: u32,
+    /// Plies below this position the deepest line of the search went,
+    /// counting extensions but not quiescence search. Beyond
+    /// `depth_reached` only if moves were extended.
+    pub extended_depth: u32,
+    /// Selective depth: the most plies from the root any node of the
+    /// search was at, counting extensions and quiescence search. Only set
+    /// on the result a search hands back, zero on the lines it stores.
+    pub seldepth: u32,
+    /// False if the search left moves out somewhere below this position,
+    /// as `SearchOptionsBuilder::max_branching` does, so the score only
+    /// covers the moves it kept.
+    pub exhaustive: bool,
+    /// False if an observer stopped the search early. `mv` is then the
+    /// best of the root moves that were searched to the end, if any.
+    pub completed: bool,
+    /// True if every line the score rests on was followed to the end of the
+    /// game, so it is what the position is worth rather than a guess.
+    /// Never set for searches that didn't complete.
+    pub solved: bool,
+}
+
+impl<M, S> MoveStats<M, S>
+{
+    /// The reply the opponent is expected to play after `mv`, the second
+    /// move of the principal variation. This is the reply to `ponder` on.
+    pub fn expected_reply(&self) -> Option<&M>
+    {
+        self.mvs.get(1)
+    }
+
+    /// `depth_reached` in `Depth` units.
+    pub fn reached_depth(&self) -> Depth
+    {
+        Depth::from_plies(self.depth_reached)
+    }
+}
+
+/// Result of a search with a caller supplied window.
+#[derive(Clone,Debug,PartialEq,Eq)]
+pub struct WindowedResult<M, S = Score>
+{
+    pub stats: MoveStats<M, S>,
+    /// How `stats.score` relates to the true score. Anything but `Exact`
+    /// means the search failed low or high and has to be redone with a
+    /// wider window to learn the true score.
+    pub bound: Bound,
+}
+
+impl<M, S> WindowedResult<M, S>
+{
+    /// The true score is at most `stats.score`, which is at most alpha.
+    pub fn failed_low(&self) -> bool
+    {
+        self.bound == Bound::UpperBound
+    }
+
+    /// The true score is at least `stats.score`, which is at least beta.
+    pub fn failed_high(&self) -> bool
+    {
+        self.bound == Bound::LowerBound
+    }
+
+    pub fn is_exact(&self) -> bool
+    {
+        self.bound == Bound::Exact
+    }
+}
+
+/// Answer of `Minimax::test_bound`.
+#[derive(Copy,Clone,Debug,PartialEq,Eq)]
+pub enum BoundResult
+{
+    /// The position is worth at least the threshold to the side to move.
+    AtLeast,
+    /// It is worth less.
+    Below,
+}
+
+/// A root move and what it is worth, from `Minimax::analyze_root`.
+#[derive(Clone,Debug,PartialEq,Eq)]
+pub struct RootScore<M, S = Score>
+{
+    pub mv: M,
+    /// From the ally's point of view, like `MoveStats::score`.
+    pub score: TimedScore<S>,
+    /// `Bound::Exact`, unless the move was too far behind the best for
+    /// `Minimax::analyze_root_within` to search it to its exact score.
+    /// From the ally's point of view too.
+    pub bound: Bound,
+}
+
+/// Which root move `Minimax::best_move_ranked` plays, for playing weaker
+/// on purpose.
+#[derive(Copy,Clone,Debug,PartialEq,Eq)]
+pub enum MovePick
+{
+    /// The move at this rank, the best being zero.
+    Rank(usize),
+    /// The lowest ranked move that scores at most this much below the
+    /// best, for scores with `GameScore::add_margin`. A win or a loss is
+    /// only that close to one just as good.
+    Margin(i32),
+}
+
+/// Result of `Minimax::best_move_ranked`.
+#[derive(Clone,Debug,PartialEq,Eq)]
+pub struct RankedMoves<M, S = Score>
+{
+    /// Every root move with its exact score and principal variation, best
+    /// first, as `Minimax::minimax_multi` finds them. Empty without moves.
+    pub ranked: Vec<MoveStats<M, S>>,
+    /// Where the move picked is in `ranked`.
+    pub rank: Option<usize>,
+}
+
+impl<M, S> RankedMoves<M, S>
+{
+    /// The move picked, with its score and line.
+    pub fn chosen(&self) -> Option<&MoveStats<M, S>>
+    {
+        self.rank.map(|rank| &self.ranked[rank])
+    }
+}
+
+/// Result of `Minimax::try_minimax`, which tells positions without a move
+/// to pick apart from searched ones.
+#[derive(Clone,Debug,PartialEq,Eq)]
+pub enum SearchResult<M, S = Score>
+{
+    /// The position was searched. `mv` is set unless a search that was
+    /// stopped early didn't get through a single root move.
+    BestMove(MoveStats<M, S>),
+    /// The side to move has no legal moves, though the game isn't over.
+    NoLegalMoves,
+    /// The game is over, with this score from the ally's point of view.
+    GameAlreadyOver(S),
+    /// The caller left none of the legal moves to search.
+    NoRootMoves,
+}
+
+/// What `Minimax::verify` found.
+#[derive(Clone,Debug,PartialEq,Eq)]
+pub struct Verification<M, S = Score>
+{
+    pub search: MoveStats<M, S>,
+    pub reference: ReferenceResult<M, S>,
+    /// True if the search scored the position differently from the
+    /// reference, or picked a move the reference doesn't rate best.
+    pub mismatch: bool,
+}
+
+/// What `Minimax::ponder_result` made of the move the opponent played.
+#[derive(Clone,Debug,PartialEq,Eq)]
+pub enum PonderResult<M, S = Score>
+{
+    /// It was the expected reply, and this is what pondering found about
+    /// the position after it, as deep as it got.
+    Hit(MoveStats<M, S>),
+    /// It wasn't, or nothing was pondered. The new position has to be
+    /// searched, which still starts off the table pondering filled.
+    Miss,
+}
+
+/* The reply the last ponder expected, and what it found after it */
+struct Pondered<M, S>
+{
+    reply: M,
+    stats: MoveStats<M, S>,
+}
+
+/// A ponder running on a thread of its own, see
+/// `Minimax::ponder_in_background`. It owns the search until the opponent
+/// has moved and it is told whether the move was the expected reply.
+pub struct PonderHandle<B, S = Score, K = (B, Team)>
+    where B: Board<S> + Eq + Hash,
+          K: Eq + Hash
+{
+    /* The search comes back with what it pondered */
+    thread: thread::JoinHandle<Minimax<B, S, K>>,
+    timer: Arc<PonderTimer>,
+    clock: Arc<dyn Clock + Send + Sync>,
+    stop: Arc<AtomicBool>,
+}
+
+/* Bounds a pondering search gets once the ponder hits, as clock readings
+ * in nanoseconds and `u64::MAX` until then: it starts no iteration past
+ * the soft one and stops at the hard one. And the deepest iteration it
+ * completed so far.
+ */
+struct PonderTimer
+{
+    soft: AtomicU64,
+    hard: AtomicU64,
+    depth: AtomicU32,
+}
+
+impl PonderTimer
+{
+    fn new() -> PonderTimer
+    {
+        PonderTimer {
+            soft: AtomicU64::new(u64::MAX),
+            hard: AtomicU64::new(u64::MAX),
+            depth: AtomicU32::new(0),
+        }
+    }
+
+    /* Half the time left for starting iterations, all of it for finishing
+     * them
+     */
+    fn start(&self, clock: &dyn Clock, remaining: Duration)
+    {
+        let now = clock.elapsed();
+        self.soft.store(nanos(now + remaining / 2), atomic::Ordering::Relaxed);
+        self.hard.store(nanos(now + remaining), atomic::Ordering::Relaxed);
+    }
+
+    fn past(bound: &AtomicU64, clock: &dyn Clock) -> bool
+    {
+        let bound = bound.load(atomic::Ordering::Relaxed);
+        bound != u64::MAX && nanos(clock.elapsed()) >= bound
+    }
+
+    fn past_soft(&self, clock: &dyn Clock) -> bool
+    {
+        PonderTimer::past(&self.soft, clock)
+    }
+
+    fn past_hard(&self, clock: &dyn Clock) -> bool
+    {
+        PonderTimer::past(&self.hard, clock)
+    }
+}
+
+fn nanos(time: Duration) -> u64
+{
+    time.as_nanos().min(u128::from(u64::MAX - 1)) as u64
+}
+
+impl<B, S, K> PonderHandle<B, S, K>
+    where B: Board<S> + Eq + Hash,
+          K: Eq + Hash
+{
+    /// The deepest iteration pondering has completed so far.
+    pub fn depth(&self) -> u32
+    {
+        self.timer.depth.load(atomic::Ordering::Relaxed)
+    }
+
+    /// The opponent played the expected reply, and `remaining_time` is
+    /// what is left to answer it. The running search goes on from where
+    /// it got to, now timed: it starts no new iteration once half of the
+    /// time has passed and stops the one it's in when all of it has.
+    ///
+    /// Returns the search and the deepest iteration it completed, which
+    /// is there at once if pondering already got as deep as it could.
+    pub fn ponderhit(self, remaining_time: Duration) -> (Minimax<B, S, K>, MoveStats<B::Move, S>)
+    {
+        self.timer.start(&*self.clock, remaining_time);
+        let mut minimax = self.thread.join().unwrap_or_else(|payload| panic::resume_unwind(payload));
+        let pondered = minimax.pondered.take().expect("the ponder thread always stores its result");
+        (minimax, pondered.stats)
+    }
+
+    /// The opponent played something else. Stops the search and returns
+    /// it, with the stop handle cleared and the transposition table
+    /// keeping what pondering found.
+    pub fn miss(self) -> Minimax<B, S, K>
+    {
+        self.stop.store(true, atomic::Ordering::Relaxed);
+        let mut minimax = self.thread.join().unwrap_or_else(|payload| panic::resume_unwind(payload));
+        self.stop.store(false, atomic::Ordering::Relaxed);
+        minimax.pondered = None;
+        minimax
+    }
+}
+
+/* Root moves of the last search in the order they were searched, with
+ * the ally's scores, and a hash of the root and the side to move there
+ */
+struct RootOrder<M, S>
+{
+    position: u64,
+    moves: Vec<(M, TimedScore<S>)>,
+}
+
+/* The side to move after the root of the last search and the moves
+ * `advance` was told about since, and the rest of its principal variation
+ * while the game still follows it
+ */
+struct GameState<M>
+{
+    turn: Team,
+    pv: Vec<M>,
+    advanced: bool,
+}
+
+/// Why `Minimax::solve` couldn't solve a position. Both hold what the
+/// search found anyway.
+#[derive(Clone,Debug,PartialEq,Eq)]
+pub enum SolveError<M, S = Score>
+{
+    /// Some line was still going `max_plies` in, as in games that can go
+    /// on forever.
+    TooDeep(MoveStats<M, S>),
+    /// The search was stopped by the stop handle.
+    Stopped(MoveStats<M, S>),
+}
+
+/// What the transposition table knows about a position, from
+/// `Minimax::probe`.
+#[derive(Clone,Debug,PartialEq,Eq)]
+pub struct ProbeResult<M, S = Score>
+{
+    pub mv: Option<M>,
+    /// From the ally's point of view, counting turns from the position.
+    pub score: TimedScore<S>,
+    /// Which bound `score` is, for entries of searches that failed low or
+    /// high.
+    pub bound: Bound,
+    /// Plies the position was searched to.
+    pub depth: u32,
+    /// Principal variation, starting with `mv`.
+    pub mvs: Vec<M>,
+}
+
+/// Moves `first` to the front of `moves`, if it is in there. The rest keep
+/// their order.
+fn try_first<M>(moves: &mut [M], first: &Option<M>)
+    where M: Eq
+{
+    if let Some(ref first) = *first
+    {
+        if let Some(pos) = moves.iter().position(|mv| mv == first)
+        {
+            moves[..pos + 1].rotate_right(1);
+        }
+    }
+}
+
+/// Makes sure a search that was stopped before any root move was searched
+/// to the end still has a move to give.
+fn first_root_move<B, S>(board: &B, turn: Team, stopped: MoveStats<B::Move, S>, draw: Option<S>) -> MoveStats<B::Move, S>
+    where B: Board<S>,
+          S: GameScore
+{
+    if stopped.mv.is_some()
+    {
+        return stopped;
+    }
+    let mut moves: Vec<_> = board.moves(turn).collect();
+    board.order_moves(&mut moves, turn);
+    match moves.into_iter().next()
+    {
+        Some(mv) => MoveStats {
+            mv: Some(mv.clone()),
+            mvs: vec![mv],
+            nodes_visited: stopped.nodes_visited,
+            completed: false,
+            solved: false,
+            ..static_stats(board, turn, draw)
+        },
+        None => stopped,
+    }
+}
+
+/// Result for a side that has no moves left.
+fn lost_stats<M, S>() -> MoveStats<M, S>
+    where S: GameScore
+{
+    MoveStats
+    {
+        mv: None,
+        score: TimedScore {
+            score: S::MIN,
+            turns: 0,
+        },
+        nodes_visited: 0,
+        mvs: Vec::new(),
+        depth_reached: 0,
+        extended_depth: 0,
+        seldepth: 0,
+        exhaustive: true,
+        completed: true,
+        solved: true,
+    }
+}
+
+/// Result for a position that is scored as it stands, from the point of
+/// view of `turn`. Even scores count as `draw`, if given, which is from the
+/// ally's point of view.
+fn static_stats<B, S>(board: &B, turn: Team, draw: Option<S>) -> MoveStats<B::Move, S>
+    where B: Board<S>,
+          S: GameScore
+{
+    let mut score = board.score_for(turn);
+    if let Some(draw) = draw
+    {
+        if score == S::ZERO
+        {
+            score = draw;
+        }
+    }
+    let score = match turn
+    {
+        Team::Ally => score,
+        Team::Enemy => -score,
+    };
+
+    MoveStats
+    {
+        mv: None,
+        score: TimedScore {
+            score,
+            turns: 0,
+        },
+        nodes_visited: 0,
+        mvs: Vec::new(),
+        depth_reached: 0,
+        extended_depth: 0,
+        seldepth: 0,
+        exhaustive: true,
+        completed: true,
+        solved: true,
+    }
+}
+
+/// What an opponent erring as `model` makes of a node, given the scores
+/// of all its replies.
+fn blend<S>(mut replies: Vec<TimedScore<S>>, model: OpponentError) -> S
+    where S: GameScore
+{
+    replies.sort_by(|a, b| b.cmp(a));
+    let best = replies[0].score;
+    let top = model.replies.min(replies.len());
+    let weighted: Vec<_> = replies[..top].iter()
+        .enumerate()
+        .map(|(idx, reply)|
+        {
+            let weight = model.probability / top as f64;
+            (reply.score, if idx == 0 { 1.0 - model.probability + weight } else { weight })
+        })
+        .collect();
+    S::blend(&weighted).unwrap_or(best)
+}
+
+/// Result for a position that repeats one further up the search path,
+/// with `score` given from the ally's point of view.
+fn repetition_stats<M, S>(score: S, turn: Team) -> MoveStats<M, S>
+    where S: GameScore
+{
+    let score = match turn
+    {
+        Team::Ally => score,
+        Team::Enemy => -score,
+    };
+
+    MoveStats {
+        score: TimedScore {
+            score,
+            turns: 0,
+        },
+        ..lost_stats()
+    }
+}
+
+/// Result of a search that was stopped before it finished.
+fn stopped_stats<M, S>() -> MoveStats<M, S>
+    where S: GameScore
+{
+    MoveStats {
+        completed: false,
+        solved: false,
+        ..lost_stats()
+    }
+}
+
+/// Counters for what alpha-beta did during a search.
+#[derive(Copy,Clone,Debug,Default,PartialEq,Eq)]
+pub struct SearchStats
+{
+    /// Nodes left early because a move was too good for the opponent to
+    /// allow.
+    pub beta_cutoffs: u64,
+    /// Times a move raised the best score a side was sure of.
+    pub alpha_improvements: u64,
+    /// Positions found in the transposition table searched deep enough.
+    pub tt_hits: u64,
+    /// Lookups in the transposition table, and those of them that found
+    /// their position searched less deep than needed.
+    pub tt_probes: u64,
+    pub tt_shallow_hits: u64,
+    /// Positions stored in the transposition table, and the entries that
+    /// made room for them or were replaced by them.
+    pub tt_stores: u64,
+    pub tt_evictions: u64,
+    pub tt_overwrites: u64,
+    /// Nodes left early because passing was already too good for the
+    /// opponent to allow.
+    pub null_move_cutoffs: u64,
+    /// Noisy moves played past the horizon by quiescence search. They're
+    /// also counted in `MoveStats::nodes_visited`.
+    pub quiescence_nodes: u64,
+    /// Moves principal variation search or late move reductions tried
+    /// with a zero-width window.
+    pub zero_window_searches: u64,
+    /// Zero-width window searches that beat alpha and were searched again
+    /// with the full window.
+    pub re_searches: u64,
+    /// Beta cutoffs made by the first move searched at a node, which move
+    /// ordering tries to make as many as possible of.
+    pub first_move_cutoffs: u64,
+    /// Late moves searched less deep by late move reductions.
+    pub reduced_searches: u64,
+    /// Reduced moves that beat alpha and were searched again at full
+    /// depth.
+    pub reduction_re_searches: u64,
+    /// Table moves searched a ply deeper by singular extensions.
+    pub singular_extensions: u64,
+    /// Shallower searches internal iterative deepening made to find a move
+    /// to try first.
+    pub internal_deepenings: u64,
+    /// Nodes left at once because no win or loss there could come soon
+    /// enough to fall inside the window.
+    pub mate_distance_cutoffs: u64,
+    /// Nodes near the horizon that razoring left after quiescence search
+    /// confirmed they fall short of alpha.
+    pub razored_nodes: u64,
+    /// Nodes near the horizon reverse futility pruning left failing high
+    /// on their static score.
+    pub reverse_futility_prunes: u64,
+    /// Noisy moves quiescence search skipped because even their largest
+    /// gain couldn't lift the score above alpha.
+    pub delta_prunes: u64,
+    /// Positions after a move that pre-ordering scored statically, to sort
+    /// the moves of a node before searching them. Not counted as nodes.
+    pub pre_ordering_nodes: u64,
+    /// Moves searched while the refutation table had a reply for them, and
+    /// how many of those that reply refuted again.
+    pub refutation_probes: u64,
+    pub refutation_hits: u64,
+    /// Moves beam search left out, past the first
+    /// `SearchOptionsBuilder::max_branching` of their node.
+    pub beam_discarded_moves: u64,
+    /// Plies from the root of the shallowest and the deepest node where a
+    /// line reached the horizon, after extensions, reductions and depth
+    /// adjustments.
+    /// Zero if none did, which only a search that ended before its
+    /// horizon everywhere does.
+    pub shallowest_horizon: u32,
+    pub deepest_horizon: u32,
+}
+
+impl SearchStats
+{
+    fn add(&mut self, other: SearchStats)
+    {
+        self.beta_cutoffs += other.beta_cutoffs;
+        self.alpha_improvements += other.alpha_improvements;
+        self.tt_hits += other.tt_hits;
+        self.tt_probes += other.tt_probes;
+        self.tt_shallow_hits += other.tt_shallow_hits;
+        self.tt_stores += other.tt_stores;
+        self.tt_evictions += other.tt_evictions;
+        self.tt_overwrites += other.tt_overwrites;
+        self.null_move_cutoffs += other.null_move_cutoffs;
+        self.quiescence_nodes += other.quiescence_nodes;
+        self.zero_window_searches += other.zero_window_searches;
+        self.re_searches += other.re_searches;
+        self.first_move_cutoffs += other.first_move_cutoffs;
+        self.reduced_searches += other.reduced_searches;
+        self.reduction_re_searches += other.reduction_re_searches;
+        self.razored_nodes += other.razored_nodes;
+        self.reverse_futility_prunes += other.reverse_futility_prunes;
+        self.delta_prunes += other.delta_prunes;
+        self.beam_discarded_moves += other.beam_discarded_moves;
+        self.refutation_probes += other.refutation_probes;
+        self.refutation_hits += other.refutation_hits;
+        self.pre_ordering_nodes += other.pre_ordering_nodes;
+        self.mate_distance_cutoffs += other.mate_distance_cutoffs;
+        self.singular_extensions += other.singular_extensions;
+        self.internal_deepenings += other.internal_deepenings;
+        self.reached_horizon(other.shallowest_horizon);
+        self.reached_horizon(other.deepest_horizon);
+    }
+
+    fn reached_horizon(&mut self, ply: u32)
+    {
+        if ply == 0
+        {
+            return;
+        }
+        if self.shallowest_horizon == 0 || ply < self.shallowest_horizon
+        {
+            self.shallowest_horizon = ply;
+        }
+        self.deepest_horizon = self.deepest_horizon.max(ply);
+    }
+
+    /// The share of beta cutoffs the first move made, from 0 to 1, or 0
+    /// without any cutoffs. Quiescence search cutoffs from standing pat
+    /// count, but never as the first move's.
+    pub fn first_move_cutoff_rate(&self) -> f64
+    {
+        if self.beta_cutoffs == 0
+        {
+            return 0.0;
+        }
+        self.first_move_cutoffs as f64 / self.beta_cutoffs as f64
+    }
+}
+
+/* The root moves a search was told to keep to or leave out */
+#[derive(Clone)]
+enum RootFilter<M>
+{
+    Only(Vec<M>),
+    Except(Vec<M>),
+}
+
+impl<M> RootFilter<M>
+    where M: PartialEq
+{
+    fn allows(&self, mv: &M) -> bool
+    {
+        match *self
+        {
+            RootFilter::Only(ref moves) => moves.contains(mv),
+            RootFilter::Except(ref moves) => !moves.contains(mv),
+        }
+    }
+}
+
+/// State shared by all nodes of one search.
+struct SearchContext<'a, B, S, K>
+    where B: Board<S> + 'a
+{
+    observer: &'a mut dyn SearchObserver<B, S>,
+    /* Nodes entered since the observer was last polled */
+    unpolled: u64,
+    /* How far the node being searched is from the root, and the furthest
+     * any node was
+     */
+    ply: u32,
+    seldepth: u32,
+    /* Nodes entered so far, and how many the search may enter */
+    nodes: u64,
+    max_nodes: u64,
+    /* Clock reading past which the search has to stop, and the nodes
+     * entered since the clock was last read
+     */
+    deadline: Option<(&'a dyn Clock, Duration)>,
+    /* Bounds of a ponder that hit, read along with the clock */
+    ponder_timer: Option<(&'a dyn Clock, &'a PonderTimer)>,
+    clock_interval: u64,
+    unclocked: u64,
+    stopped: bool,
+    /* Positions from the root down to the node being searched */
+    path: Vec<K>,
+    /* Times a position was scored as a repetition */
+    repetitions: u64,
+    stats: SearchStats,
+    trace: Option<Tracer<B::Move, S>>,
+    /* What even positions are worth to the ally, if contempt changes it */
+    draw: Option<S>,
+    /* Whether the node about to be searched was reached by a null move */
+    after_null: bool,
+    /* How much the moves from the root to the node were extended by, and
+     * adjusted by in plies
+     */
+    extensions: Depth,
+    adjustment: i32,
+    /* Principal variation of an earlier search, tried first while the
+     * path to the node about to be searched still follows it
+     */
+    pv: Vec<B::Move>,
+    on_pv: bool,
+    /* The move that led to the node about to be searched, if it wasn't a
+     * pass, for looking up its countermove
+     */
+    last_move: Option<B::Move>,
+    /* When late moves are searched less deep, if they are */
+    late_move_reductions: Option<LateMoveReductions>,
+    singular_extensions: Option<SingularExtensions>,
+    internal_deepening: Option<InternalDeepening>,
+    reverse_futility: Option<ReverseFutility>,
+    /* Plies a node without a table move needs left for its moves to be
+     * sorted by their static scores first
+     */
+    pre_ordering: Option<u32>,
+    /* How many moves the nodes below the root, and the root, may search */
+    branching: Option<usize>,
+    root_branching: Option<usize>,
+    /* A move the node about to be searched must leave out, while singular
+     * extensions check whether it's the only good one
+     */
+    excluded: Option<B::Move>,
+    /* The only moves the root may search, if the caller picked some */
+    root_moves: Option<RootFilter<B::Move>>,
+    /* Whether the node about to be searched is searched again inside
+     * itself, by internal deepening or the singular extension check, so
+     * that at the root the search around it keeps the root's books
+     */
+    nested: bool,
+    /* Root moves as good as the best one, if the caller wants them all */
+    tied: Option<Vec<B::Move>>,
+    /* Whether the score has to be proven, which rules out pruning on a
+     * pass or a static score
+     */
+    solving: bool,
+    /* How `opponent` is expected to err, if it is */
+    opponent_error: Option<OpponentError>,
+    opponent: Team,
+    /* How much less deep the opponent's nodes are searched */
+    enemy_depth_penalty: Depth,
+    /* Stop handle of the search, read whenever the observer is polled */
+    stop: Option<Arc<AtomicBool>>,
+    /* Scores the root moves got in the last search of the same position,
+     * from the side to move, and those they get in this one, the ally's
+     */
+    previous_root: Option<Vec<(B::Move, TimedScore<S>)>>,
+    root_scores: Vec<(B::Move, TimedScore<S>)>,
+    /* Whether the search has started its generation yet */
+    generation_started: bool,
+}
+
+impl<'a, B, S, K> SearchContext<'a, B, S, K>
+    where B: Board<S>
+{
+    fn new(observer: &'a mut dyn SearchObserver<B, S>) -> SearchContext<'a, B, S, K>
+    {
+        SearchContext {
+            observer,
+            unpolled: 0,
+            ply: 0,
+            seldepth: 0,
+            nodes: 0,
+            max_nodes: u64::MAX,
+            deadline: None,
+            ponder_timer: None,
+            clock_interval: u64::MAX,
+            unclocked: 0,
+            stopped: false,
+            path: Vec::new(),
+            repetitions: 0,
+            stats: SearchStats::default(),
+            trace: None,
+            draw: None,
+            after_null: false,
+            extensions: Depth::ZERO,
+            adjustment: 0,
+            pv: Vec::new(),
+            on_pv: false,
+            last_move: None,
+            late_move_reductions: None,
+            singular_extensions: None,
+            internal_deepening: None,
+            reverse_futility: None,
+            pre_ordering: None,
+            branching: None,
+            root_branching: None,
+            excluded: None,
+            root_moves: None,
+            nested: false,
+            tied: None,
+            solving: false,
+            opponent_error: None,
+            opponent: Team::Enemy,
+            enemy_depth_penalty: Depth::ZERO,
+            stop: None,
+            previous_root: None,
+            root_scores: Vec::new(),
+            generation_started: false,
+        }
+    }
+
+    /// Takes on the node and time limits, the contempt, the reductions,
+    /// extensions and pruning and the opponent model of `opts`.
+    fn limit(&mut self, opts: &SearchOptions<'a>)
+        where S: GameScore
+    {
+        self.late_move_reductions = opts.late_move_reductions();
+        self.singular_extensions = opts.singular_extensions();
+        self.internal_deepening = opts.internal_deepening();
+        self.reverse_futility = opts.reverse_futility();
+        self.pre_ordering = opts.pre_ordering();
+        self.branching = opts.max_branching();
+        self.root_branching = opts.max_root_branching();
+        self.opponent_error = opts.opponent_error().filter(|model| model.probability > 0.0);
+        self.enemy_depth_penalty = Depth::from_plies(opts.enemy_depth_penalty());
+        if opts.contempt() != 0
+        {
+            self.draw = Some(S::draw_with_contempt(opts.contempt()));
+        }
+        if let Some(max_nodes) = opts.node_limit()
+        {
+            self.max_nodes = max_nodes;
+        }
+        if let Some((limit, clock)) = opts.time_limit()
+        {
+            self.deadline = Some((clock, clock.elapsed() + limit));
+            self.clock_interval = opts.clock_interval();
+        }
+    }
+
+    /// Tries the principal variation of `previous` first, if there is one.
+    fn follow(&mut self, previous: Option<&MoveStats<B::Move, S>>)
+    {
+        if let Some(previous) = previous
+        {
+            self.pv = previous.mvs.clone();
+            self.on_pv = true;
+            /* Deeper iterations go on in the generation of the first */
+            self.generation_started = true;
+        }
+    }
+
+    /// Counts a node, polling the observer every `NODES_PER_POLL` of them
+    /// and reading the clock every `clock_interval`. Returns whether the
+    /// search has to stop.
+    fn enter(&mut self) -> bool
+    {
+        self.nodes += 1;
+        self.seldepth = self.seldepth.max(self.ply);
+        if self.nodes > self.max_nodes
+        {
+            self.stopped = true;
+        }
+
+        self.unclocked += 1;
+        if self.unclocked >= self.clock_interval
+        {
+            self.unclocked = 0;
+            if let Some((clock, deadline)) = self.deadline
+            {
+                if clock.elapsed() > deadline
+                {
+                    self.stopped = true;
+                }
+            }
+            if let Some((clock, timer)) = self.ponder_timer
+            {
+                if timer.past_hard(clock)
+                {
+                    self.stopped = true;
+                }
+            }
+        }
+
+        self.unpolled += 1;
+        if self.unpolled >= NODES_PER_POLL
+        {
+            self.unpolled = 0;
+            self.poll();
+        }
+        self.stopped
+    }
+
+    fn poll(&mut self)
+    {
+        if !self.stopped && (self.observer.should_stop() || self.stop.as_ref().is_some_and(|stop| stop.load(atomic::Ordering::Relaxed)))
+        {
+            self.stopped = true;
+        }
+    }
+}
+
+/// Builds a `SearchTrace` as the search goes.
+struct Tracer<M, S>
+{
+    /* Nodes deeper than this aren't recorded */
+    depth: u32,
+    /* Nodes being searched, from the root down */
+    open: Vec<TraceNode<M, S>>,
+    /* The move about to be played, for the node it leads to */
+    next_move: Option<M>,
+    root: Option<TraceNode<M, S>>,
+}
+
+impl<M, S> Tracer<M, S>
+    where M: Clone,
+          S: GameScore
+{
+    fn new(depth: u32) -> Tracer<M, S>
+    {
+        Tracer {
+            depth,
+            open: Vec::new(),
+            next_move: None,
+            root: None,
+        }
+    }
+
+    fn records(&self, ply: u32) -> bool
+    {
+        ply <= self.depth
+    }
+
+    /// Notes the move played to reach the node at `ply`.
+    fn moving(&mut self, ply: u32, mv: &M)
+    {
+        if self.records(ply)
+        {
+            self.next_move = Some(mv.clone());
+        }
+    }
+
+    /// Starts a node for `turn`, searched with the window `alpha`, `beta`.
+    fn open(&mut self, turn: Team, alpha: TimedScore<S>, beta: TimedScore<S>)
+    {
+        let window = match turn
+        {
+            Team::Ally => (alpha, beta),
+            Team::Enemy => (-beta, -alpha),
+        };
+        self.open.push(TraceNode {
+            mv: self.next_move.take(),
+            turn,
+            window: Some(window),
+            score: None,
+            tt_hit: false,
+            pruned: false,
+            nodes_visited: 0,
+            children: Vec::new(),
+        });
+    }
+
+    /// Marks the node at `ply` as settled by the transposition table.
+    fn hit(&mut self, ply: u32)
+    {
+        if self.records(ply)
+        {
+            if let Some(node) = self.open.last_mut()
+            {
+                node.tt_hit = true;
+            }
+        }
+    }
+
+    /// Adds the moves a cutoff skipped as pruned nodes at `ply`.
+    fn prune<I>(&mut self, ply: u32, turn: Team, moves: I)
+        where I: Iterator<Item = M>
+    {
+        if !self.records(ply)
+        {
+            return;
+        }
+
+        if let Some(parent) = self.open.last_mut()
+        {
+            parent.children.extend(moves.map(|mv| TraceNode {
+                mv: Some(mv),
+                turn,
+                window: None,
+                score: None,
+                tt_hit: false,
+                pruned: true,
+                nodes_visited: 0,
+                children: Vec::new(),
+            }));
+        }
+    }
+
+    /// Finishes the innermost node with what it returned.
+    fn close(&mut self, best: &MoveStats<M, S>)
+    {
+        let mut node = self.open.pop().unwrap();
+        node.score = Some(match node.turn
+        {
+            Team::Ally => best.score,
+            Team::Enemy => -best.score,
+        });
+        node.nodes_visited = best.nodes_visited + 1;
+
+        match self.open.last_mut()
+        {
+            Some(parent) => parent.children.push(node),
+            None => self.root = Some(node),
+        }
+    }
+}
+
+/// How the search picks among root moves with the same score.
+///
+/// Only the root is affected. Everywhere below it the first of equal moves
+/// is kept, so the table entries are the same whichever is picked.
+pub enum TieBreak<M>
+{
+    /// The first one in search order. The default.
+    First,
+    /// A random one. The same seed picks the same move for the same
+    /// position, so change it between games for variety.
+    Random(u64),
+    /// Whichever the function picks. It is handed the tied moves in search
+    /// order and returns the index of one of them.
+    Custom(fn(&[M]) -> usize),
+}
+
+impl<M> Clone for TieBreak<M>
+{
+    fn clone(&self) -> TieBreak<M>
+    {
+        *self
+    }
+}
+
+impl<M> Copy for TieBreak<M> {}
+
+/// Whether the search prunes.
+#[derive(Copy,Clone,Debug,PartialEq,Eq)]
+pub enum SearchMode
+{
+    /// Alpha-beta pruning. The default.
+    AlphaBeta,
+    /// Every move is searched with the full window, as plain minimax would.
+    /// Gives the same moves and scores as `AlphaBeta`, for more nodes.
+    PlainMinimax,
+    /// Principal variation search: alpha-beta that searches each move after
+    /// the first with a zero-width window, which only tells whether it
+    /// beats the best so far, and searches it again with the full window
+    /// if it does. Pays off when the best move tends to come first. Gives
+    /// the same moves and scores as `AlphaBeta`.
+    ///
+    /// Windows only get narrow for scores that implement
+    /// `GameScore::above`.
+    PrincipalVariation,
+}
+
+/* What scores depend on besides the position, when a search changes it */
+#[derive(Copy,Clone,Debug,PartialEq)]
+struct Scoring<S>
+{
+    draw: Option<S>,
+    opponent_error: Option<OpponentError>,
+    branching: Option<usize>,
+    root_branching: Option<usize>,
+    /* The side searched less deep, and by how many plies */
+    depth_penalty: Option<(Team, u32)>,
+    noise: Option<u32>,
+}
+
+/* Transposition table of a `Minimax` */
+type SearchTable<B, S, K> = TranspositionTable<K, MoveStats<<B as Board<S>>::Move, S>>;
+
+/* Moves a stored principal variation is taken to hold when sizing a table
+ * in bytes
+ */
+const PV_ESTIMATE: usize = 8;
+
+/// The search, along with what it keeps between searches.
+///
+/// Positions are stored in the transposition table under a `K`, a copy of
+/// the board by default. See `with_zobrist_tables` for keying by hash.
+pub struct Minimax<B, S = Score, K = (B, Team)>
+    where B: Board<S> + Eq + Hash,
+          K: Eq + Hash
+{
+    /* Positions of both sides, told apart by who is to move */
+    ttable: TranspositionTable<K, MoveStats<B::Move, S>>,
+    /* Spare move lists, handed out to one node at a time */
+    move_buffers: Vec<Vec<B::Move>>,
+    quiescence_depth: u32,
+    tie_break: TieBreak<B::Move>,
+    repetition_score: Option<S>,
+    search_mode: SearchMode,
+    null_move_reduction: u32,
+    /* Indexed by plies left, less one */
+    razoring_margins: Vec<i32>,
+    max_extensions: u32,
+    max_depth_adjustment: u32,
+    /* Move ordering learned from cutoffs, when turned on */
+    killers: Option<Killers<B::Move>>,
+    countermoves: Option<Countermoves<B::Move>>,
+    refutations: Option<RefutationTable<B::Move>>,
+    history: Option<History<B::Move>>,
+    orderer: Box<dyn SharedOrderer<B, S>>,
+    /* Positions searched with contempt or an opponent model, and which */
+    side_ttable: Option<(Scoring<S>, SearchTable<B, S, K>)>,
+    /* Most noise static scores get either way, and where it comes from */
+    noise: Option<(u32, Box<dyn RandomSource + Send>)>,
+    /* Counters of the last search */
+    search_stats: SearchStats,
+    /* Searches started so far, tagging the table entries they store */
+    generation: u32,
+    stop: Arc<AtomicBool>,
+    pondered: Option<Pondered<B::Move, S>>,
+    root_order: Option<RootOrder<B::Move, S>>,
+    reuse_root_order: bool,
+    /* Moves played in the game so far, and where it got to */
+    played: Vec<B::Move>,
+    game: Option<GameState<B::Move>>,
+}
+
+impl<B, S> Minimax<B, S>
+    where B: Board<S> + Eq + Hash,
+          S: GameScore
+{
+    /// Makes a search whose transposition table holds up to `ttable_size`
+    /// positions, counting both sides.
+    pub fn new(ttable_size: NonZeroUsize) -> Minimax<B, S>
+    {
+        Minimax::with_policy(ttable_size, ReplacementPolicy::Lru)
+    }
+
+    /// Like `new`, with a transposition table that replaces entries
+    /// according to `policy`.
+    pub fn with_policy(ttable_size: NonZeroUsize, policy: ReplacementPolicy) -> Minimax<B, S>
+    {
+        Minimax::with_table(TranspositionTable::with_policy(ttable_size, policy))
+    }
+
+    /// Like `with_policy`, with a transposition table of about `bytes`
+    /// bytes.
+    ///
+    /// Boards and moves are taken to keep nothing on the heap, and every
+    /// entry a principal variation of a few moves, so boards that do keep
+    /// something there make for a bigger table; see `with_table_bytes_for`.
+    /// Only an estimate either way, and only of the one table: searches
+    /// with contempt, noise or an opponent model keep a second table with
+    /// as many entries, which takes as much again.
+    pub fn with_table_bytes(bytes: usize, policy: ReplacementPolicy) -> Minimax<B, S>
+    {
+        Minimax::with_table(Self::table_of_bytes(bytes, policy, 0))
+    }
+
+    /// Like `with_table_bytes`, counting what `board`, a typical one, keeps
+    /// on the heap by `Board::approx_heap_size`.
+    pub fn with_table_bytes_for(bytes: usize, policy: ReplacementPolicy, board: &B) -> Minimax<B, S>
+    {
+        Minimax::with_table(Self::table_of_bytes(bytes, policy, board.approx_heap_size()))
+    }
+
+    /// Like `new`, with killer moves and the history heuristic turned on.
+    pub fn with_heuristics(ttable_size: NonZeroUsize) -> Minimax<B, S>
+        where B::Move: Hash
+    {
+        let mut minimax = Minimax::new(ttable_size);
+        minimax.set_killer_moves(true);
+        minimax.set_history_heuristic(true);
+        minimax
+    }
+
+    /// Like `new`, with `orderer` putting the moves of every node in order.
+    pub fn with_orderer<O>(ttable_size: NonZeroUsize, orderer: O) -> Minimax<B, S>
+        where O: MoveOrderer<B, S> + Clone + Send + 'static
+    {
+        let mut minimax = Minimax::new(ttable_size);
+        minimax.set_move_orderer(orderer);
+        minimax
+    }
+}
+
+impl<B, S> Minimax<B, S, ZobristKey>
+    where B: ZobristBoard<S> + Eq + Hash,
+          S: GameScore
+{
+    /// Like `with_policy`, with a table keyed by `ZobristBoard::zobrist`
+    /// instead of the board, which saves hashing and cloning big boards.
+    /// Two positions with the same hash are taken for the same one, which
+    /// `with_checked_zobrist_tables` guards against.
+    pub fn with_zobrist_tables(ttable_size: NonZeroUsize, policy: ReplacementPolicy) -> Minimax<B, S, ZobristKey>
+    {
+        Minimax::with_table(TranspositionTable::with_policy(ttable_size, policy))
+    }
+
+    /// Like `with_zobrist_tables`, with a table of about `bytes` bytes, as
+    /// `with_table_bytes` estimates it.
+    pub fn with_zobrist_table_bytes(bytes: usize, policy: ReplacementPolicy) -> Minimax<B, S, ZobristKey>
+    {
+        Minimax::with_table(Self::table_of_bytes(bytes, policy, 0))
+    }
+}
+
+impl<B, S> Minimax<B, S, CheckedZobristKey>
+    where B: ZobristBoard<S> + Eq + Hash,
+          S: GameScore
+{
+    /// Like `with_zobrist_tables`, with `ZobristBoard::zobrist_check` kept
+    /// in every key too. A board whose hash collides with another's then
+    /// finds nothing in the table, instead of the other's result.
+    pub fn with_checked_zobrist_tables(ttable_size: NonZeroUsize, policy: ReplacementPolicy) -> Minimax<B, S, CheckedZobristKey>
+    {
+        Minimax::with_table(TranspositionTable::with_policy(ttable_size, policy))
+    }
+
+    /// Like `with_checked_zobrist_tables`, with a table of about `bytes`
+    /// bytes, as `with_table_bytes` estimates it.
+    pub fn with_checked_zobrist_table_bytes(bytes: usize, policy: ReplacementPolicy) -> Minimax<B, S, CheckedZobristKey>
+    {
+        Minimax::with_table(Self::table_of_bytes(bytes, policy, 0))
+    }
+}
+
+impl<B, S, K> Minimax<B, S, K>
+    where B: Board<S> + Eq + Hash,
+          S: GameScore,
+          K: PositionKey<B, S>
+{
+    fn with_table(ttable: TranspositionTable<K, MoveStats<B::Move, S>>) -> Minimax<B, S, K>
+    {
+        Minimax
+        {
+            ttable,
+            move_buffers: Vec::new(),
+            quiescence_depth: 8,
+            tie_break: TieBreak::First,
+            repetition_score: None,
+            search_mode: SearchMode::AlphaBeta,
+            null_move_reduction: 2,
+            razoring_margins: Vec::new(),
+            max_extensions: 3,
+            max_depth_adjustment: 3,
+            killers: None,
+            countermoves: None,
+            refutations: None,
+            history: None,
+            orderer: Box::new(TableMoveFirst),
+            side_ttable: None,
+            noise: None,
+            search_stats: SearchStats::default(),
+            generation: 0,
+            stop: Arc::new(AtomicBool::new(false)),
+            pondered: None,
+            root_order: None,
+            reuse_root_order: false,
+            played: Vec::new(),
+            game: None,
+        }
+    }
+
+    /* A table of about `bytes` bytes, for keys keeping `key_heap` bytes on
+     * the heap and entries holding a principal variation of a few moves
+     */
+    fn table_of_bytes(bytes: usize, policy: ReplacementPolicy, key_heap: usize) -> SearchTable<B, S, K>
+    {
+        TranspositionTable::with_bytes(bytes, policy, key_heap, PV_ESTIMATE * mem::size_of::<B::Move>())
+    }
+
+    /// Sets how to pick among root moves with the same score.
+    ///
+    /// Anything but `TieBreak::First` needs the exact score of every root
+    /// move, so the root is searched with the full window and isn't
+    /// answered from the transposition table. Deeper nodes are searched as
+    /// usual.
+    pub fn set_tie_break(&mut self, tie_break: TieBreak<B::Move>)
+    {
+        self.tie_break = tie_break;
+    }
+
+    /// Caps how many plies of noisy moves the quiescence search may play
+    /// past the horizon. Defaults to 8. Zero turns quiescence search off.
+    ///
+    /// Only matters for boards that implement `gen_quiescence_moves`.
+    pub fn set_quiescence_depth(&mut self, depth: u32)
+    {
+        self.quiescence_depth = depth;
+    }
+
+    /// Turns on repetition handling: a position that already appears on the
+    /// path from the root, with the same side to move, is given `score`
+    /// (from the ally's point of view) instead of being searched again.
+    /// `Score::Draw` is the usual choice. `None`, the default, turns it
+    /// off.
+    ///
+    /// Only the current search path counts, so a position reached again by
+    /// another path is searched as usual. Results that depended on a
+    /// repetition aren't stored in the transposition table.
+    pub fn set_repetition_score(&mut self, score: Option<S>)
+    {
+        self.repetition_score = score;
+    }
+
+    /// Sets whether to prune. `SearchMode::PlainMinimax` is only useful for
+    /// checking or showing off what pruning saves.
+    pub fn set_search_mode(&mut self, mode: SearchMode)
+    {
+        self.search_mode = mode;
+    }
+
+    /// Sets how many plies shallower than the moves of a node its null move
+    /// is searched, on top of the ply the pass takes. Defaults to 2.
+    ///
+    /// Null moves are only tried by boards that implement `do_null_move`,
+    /// away from the root, with more than this many plies left to search,
+    /// and when the side to move is already doing well enough to cut off
+    /// by its static score. A pass straight after another one isn't tried.
+    pub fn set_null_move_reduction(&mut self, plies: u32)
+    {
+        self.null_move_reduction = plies;
+    }
+
+    /// Turns on razoring: a node `plies` above the horizon whose static
+    /// score is more than `margins[plies - 1]` below alpha is checked with
+    /// quiescence search first, and left as it is if that can't reach
+    /// alpha either. Empty margins, the default, turn razoring off.
+    ///
+    /// Nodes with more plies left than there are margins aren't razored,
+    /// and neither are the root and the principal variation. Nor are nodes
+    /// whose alpha is already a win or a loss, where a faster or slower
+    /// one below could still matter. Scores only razor if they implement
+    /// `GameScore::add_margin`.
+    pub fn set_razoring_margins(&mut self, margins: Vec<i32>)
+    {
+        self.razoring_margins = margins;
+    }
+
+    /// Caps how many plies `Board::move_extension` may add to any one line
+    /// from the root. Defaults to 3. Zero turns extensions off.
+    /// `MoveStats::extended_depth` tells how deep the extended lines went.
+    pub fn set_max_extensions(&mut self, plies: u32)
+    {
+        self.max_extensions = plies;
+    }
+
+    /// Caps how many plies `Board::depth_adjustment` may add to any one
+    /// line from the root, or take away from it. Defaults to 3. Zero turns
+    /// the adjustments off. `SearchStats::shallowest_horizon` and
+    /// `deepest_horizon` tell how far apart they moved the lines.
+    pub fn set_max_depth_adjustment(&mut self, plies: u32)
+    {
+        self.max_depth_adjustment = plies;
+    }
+
+    /// Sets whether to try killer moves early: the last two moves that
+    /// caused a cutoff at the same distance from the root are searched
+    /// right after the table move. Off by default.
+    ///
+    /// Root moves keep the board's order, so the chosen move and score are
+    /// the same either way, only found with fewer nodes.
+    pub fn set_killer_moves(&mut self, on: bool)
+    {
+        self.killers = if on { Some(Killers::new()) } else { None };
+    }
+
+    /// Sets whether a search of the same position as the last one, told
+    /// apart by its hash and the side to move, searches the root moves
+    /// best first by the scores they got last time, see `root_order`. Off
+    /// by default.
+    ///
+    /// Searching 2, 4 and then 6 plies deep this way gets some of the
+    /// benefit of `iterative_deepening_to`. Unlike the other move ordering
+    /// it changes the order of the root, so of moves with the same score a
+    /// different one may be picked. Searches that break ties some other
+    /// way than `TieBreak::First` keep the board's order.
+    pub fn set_root_ordering(&mut self, on: bool)
+    {
+        self.reuse_root_order = on;
+    }
+
+    /// Sets whether to try countermoves early: for each move, the reply
+    /// that last caused a cutoff right after it is searched after the
+    /// killer moves. Off by default.
+    ///
+    /// Like killer moves, it leaves the root alone. It is kept between
+    /// searches until `clear`.
+    pub fn set_countermoves(&mut self, on: bool)
+        where B::Move: Hash
+    {
+        self.countermoves = if on { Some(Countermoves::new()) } else { None };
+    }
+
+    /// Sets whether to keep a refutation table of up to `moves` moves, the
+    /// reply that last made each of them fail low, wherever it was. That
+    /// reply is then tried right after the table move whenever the move is
+    /// played again. Off by default, and `None` turns it off again.
+    ///
+    /// Like killer moves, it leaves the root alone. It is kept between
+    /// searches until `clear`. `SearchStats::refutation_probes` and
+    /// `refutation_hits` tell how often it was right.
+    pub fn set_refutation_table(&mut self, moves: Option<NonZeroUsize>)
+        where B::Move: Hash
+    {
+        self.refutations = moves.map(|moves| RefutationTable::new(moves.get()));
+    }
+
+    /// Sets whether to keep a history table, counting how often each move
+    /// caused a cutoff anywhere in the tree. Moves that aren't killers are
+    /// then tried most successful first. Off by default.
+    ///
+    /// Like killer moves, it leaves the root alone. It is kept between
+    /// searches until `clear`.
+    pub fn set_history_heuristic(&mut self, on: bool)
+        where B::Move: Hash
+    {
+        self.history = if on { Some(History::new()) } else { None };
+    }
+
+    /// Sets what orders the moves of every node, after the board's own
+    /// `order_moves` and the heuristics that are turned on. Defaults to
+    /// `ordering::TableMoveFirst`. `ordering::Identity` leaves out the
+    /// table move too.
+    ///
+    /// Each worker of `minimax_parallel` orders with its own clone. The
+    /// orderer is kept, and learns what it likes, until it is replaced.
+    pub fn set_move_orderer<O>(&mut self, orderer: O)
+        where O: MoveOrderer<B, S> + Clone + Send + 'static
+    {
+        self.orderer = Box::new(orderer);
+    }
+
+    /// Adds random noise of at most `noise` either way to the static
+    /// scores of the positions the search scores as they stand, drawing
+    /// from `rng`, for playing weaker on purpose. Zero, the default, turns
+    /// it off. Only scores `GameScore::add_margin` moves get noise, so wins
+    /// and losses stay what they are.
+    ///
+    /// Noisy results are kept apart from the normal search's like those of
+    /// `SearchOptionsBuilder::contempt`. The workers of `minimax_parallel`
+    /// search without noise.
+    pub fn set_evaluation_noise<R>(&mut self, noise: u32, rng: R)
+        where R: RandomSource + Send + 'static
+    {
+        let noise = noise.min(i32::MAX as u32);
+        self.noise = if noise == 0 { None } else { Some((noise, Box::new(rng))) };
+    }
+
+    /// Flag that stops every search of this `Minimax`, set from any thread.
+    ///
+    /// It is read every `NODES_PER_POLL` nodes and after every root move.
+    /// A stopped search unwinds like a cancelled one: it returns the best
+    /// root move it searched to the end with `completed` unset, and the
+    /// positions it didn't finish aren't stored. The flag stays set, and
+    /// stops the searches after it too, until it is cleared.
+    pub fn stop_handle(&self) -> Arc<AtomicBool>
+    {
+        self.stop.clone()
+    }
+
+    /// What the last search did, counting every worker of
+    /// `minimax_parallel` and every iteration of `minimax_multi`.
+    pub fn search_stats(&self) -> SearchStats
+    {
+        self.search_stats
+    }
+
+    /// How many searches have been started, a call like `minimax`
+    /// counting once however many iterations it runs. Table entries are
+    /// tagged with it, and depth preferred tables let the old ones go
+    /// first.
+    pub fn generation(&self) -> u32
+    {
+        self.generation
+    }
+
+    /// Usage counters of the transposition table.
+    pub fn table_stats(&self) -> TableStats
+    {
+        self.ttable.stats()
+    }
+
+    /// Zeroes the counters of the transposition table.
+    pub fn reset_stats(&mut self)
+    {
+        self.ttable.reset_stats();
+    }
+
+    /// Empties the transposition table and zeroes its counters, for
+    /// starting over with a new game. Killer moves, countermoves, the
+    /// refutation and history tables and the moves told to `advance` are
+    /// forgotten too, and the generation starts over.
+    /// Other settings are kept.
+    pub fn clear(&mut self)
+    {
+        self.ttable.clear();
+        self.side_ttable = None;
+        self.generation = 0;
+        self.played.clear();
+        self.game = None;
+        self.root_order = None;
+        if let Some(ref mut killers) = self.killers
+        {
+            killers.clear();
+        }
+        if let Some(ref mut countermoves) = self.countermoves
+        {
+            countermoves.clear();
+        }
+        if let Some(ref mut refutations) = self.refutations
+        {
+            refutations.clear();
+        }
+        if let Some(ref mut history) = self.history
+        {
+            history.clear();
+        }
+    }
+
+    /// `clear`, for flushing the tables between games. Nothing learned in
+    /// one game is left for the next.
+    pub fn clear_tables(&mut self)
+    {
+        self.clear();
+    }
+
+    /// Tells the search `mv` was played in the game, by either side, so the
+    /// next search can start off where this one left off.
+    ///
+    /// The tables, killer moves, countermoves and history are kept as they
+    /// are anyway. On top of that the killers are moved a ply closer to
+    /// the root, and the next search tries the rest of the last principal
+    /// variation first, as long as the game followed it. That next search
+    /// should be of the position the game is in, which isn't checked: any
+    /// other is searched as well, only in a worse order.
+    pub fn advance(&mut self, mv: &B::Move)
+    {
+        self.played.push(mv.clone());
+        if let Some(ref mut killers) = self.killers
+        {
+            killers.advance(1);
+        }
+        if let Some(ref mut game) = self.game
+        {
+            game.turn = game.turn.other_team();
+            if game.pv.first() == Some(mv)
+            {
+                game.pv.remove(0);
+            }
+            else
+            {
+                game.pv.clear();
+            }
+            game.advanced = true;
+        }
+    }
+
+    /// The moves told to `advance` since the `Minimax` was made or
+    /// cleared, in the order they were played.
+    pub fn played_moves(&self) -> &[B::Move]
+    {
+        &self.played
+    }
+
+    /// Root moves of the last search that searched any, in the order it
+    /// searched them, with their scores from the ally's point of view.
+    /// Moves not searched to the end are left out, and under alpha-beta
+    /// the scores of all but the best are usually only bounds. With
+    /// `set_root_ordering` on, the next search of the same position starts
+    /// from this.
+    pub fn root_order(&self) -> &[(B::Move, TimedScore<S>)]
+    {
+        self.root_order.as_ref().map_or(&[], |order| &order.moves)
+    }
+
+    /// Halves the history heuristic's counts, so the moves that did well
+    /// in earlier searches, or earlier games, weigh less against those that
+    /// do well now. `clear` forgets them outright.
+    pub fn decay_history(&mut self)
+    {
+        if let Some(ref mut history) = self.history
+        {
+            history.decay();
+        }
+    }
+
+    /// Changes how many positions the transposition table holds, counting
+    /// both sides. Entries are kept as far as they fit, and the
+    /// replacement policy picks the ones that don't.
+    pub fn set_capacity(&mut self, ttable_size: NonZeroUsize)
+    {
+        self.ttable.set_capacity(ttable_size);
+        self.side_ttable = None;
+    }
+
+    /// `set_capacity`, for resizing the tables at runtime. The deepest and
+    /// most recent entries are the ones kept, and `TableStats::bytes`
+    /// shows what the table takes after.
+    pub fn resize_tables(&mut self, ttable_size: NonZeroUsize)
+    {
+        self.set_capacity(ttable_size);
+    }
+
+    /// Writes the contents of the transposition table to `w`, with the
+    /// depths in `Depth` units.
+    pub fn save_tables<W>(&self, w: &mut W) -> io::Result<()>
+        where W: Write,
+              K: Encode,
+              B::Move: Encode,
+              S: Encode
+    {
+        self.ttable.to_entries().encode(w)
+    }
+
+    /// Replaces the contents of the transposition table with what
+    /// `save_tables` wrote. The capacity stays as it is; if the saved table
+    /// doesn't fit, its deepest entries are kept. On error the table is
+    /// left alone.
+    pub fn load_tables<R>(&mut self, r: &mut R) -> io::Result<()>
+        where R: Read,
+              K: Decode,
+              B::Move: Decode,
+              S: Decode
+    {
+        let entries = Vec::decode(r)?;
+
+        let capacity = NonZeroUsize::new(self.ttable.stats().capacity).unwrap();
+        self.ttable = TranspositionTable::from_entries_with_policy(capacity, self.ttable.policy(), entries);
+        Ok(())
+    }
+
+    /// Looks `board` up in the transposition table, with `turn` to move,
+    /// without searching. Doesn't count as a table lookup or make the
+    /// entry any less likely to be evicted.
+    pub fn probe(&self, board: &B, turn: Team) -> Option<ProbeResult<B::Move, S>>
+    {
+        self.ttable.peek_with_bound(&K::of(board, turn)).map(|(stats, depth, bound)|
+        {
+            /* Entries are kept from the point of view of the side to move */
+            let (score, bound) = match (turn, bound)
+            {
+                (Team::Ally, bound) => (stats.score, bound),
+                (Team::Enemy, Bound::LowerBound) => (-stats.score, Bound::UpperBound),
+                (Team::Enemy, Bound::UpperBound) => (-stats.score, Bound::LowerBound),
+                (Team::Enemy, Bound::Exact) => (-stats.score, Bound::Exact),
+            };
+            ProbeResult {
+                mv: stats.mv.clone(),
+                score,
+                bound,
+                depth: Depth::from_units(depth).plies(),
+                mvs: stats.mvs.clone(),
+            }
+        })
+    }
+
+    /// The best move the transposition table has for `turn` on `board`,
+    /// however shallow the search that stored it, which is what the search
+    /// tries first there. Like `probe`, it leaves the table untouched.
+    pub fn move_hint(&self, board: &B, turn: Team) -> Option<B::Move>
+    {
+        self.table_move(&K::of(board, turn))
+    }
+
+    /* Table moves are looked up without refreshing the entry, so hints for
+     * shallow positions don't keep them in the table at the expense of
+     * deep ones
+     */
+    fn table_move(&self, key: &K) -> Option<B::Move>
+    {
+        self.ttable.peek(key).and_then(|(stats, _)| stats.mv.clone())
+    }
+
+    /// Stores `mv` as the best move for `turn` on `board`, scoring `score`
+    /// from the ally's point of view after a search `depth` plies deep,
+    /// such as from an opening book.
+    ///
+    /// Searches at most `depth` plies deep take the entry as it is. Deeper
+    /// ones search the position, trying `mv` first.
+    pub fn seed(&mut self, board: B, turn: Team, mv: B::Move, score: TimedScore<S>, depth: u32)
+    {
+        let score = match turn
+        {
+            Team::Ally => score,
+            Team::Enemy => -score,
+        };
+        let stats = MoveStats {
+            mv: Some(mv.clone()),
+            score,
+            nodes_visited: 0,
+            mvs: vec![mv],
+            depth_reached: depth,
+            extended_depth: 0,
+            seldepth: 0,
+            exhaustive: true,
+            completed: true,
+            solved: false,
+        };
+        self.ttable.insert(K::of(&board, turn), stats, Depth::from_plies(depth).units(), Bound::Exact);
+    }
+
+    /// Minimax driver function.
+    ///
+    /// `turn` is the current player. At least one ply is always searched,
+    /// so `plies` of 0 is taken as 1 and `mv` is set whenever `turn` has a
+    /// move. The same goes for the other drivers.
+    pub fn minimax(&mut self, board: &B, turn: Team, plies: u32) -> MoveStats<B::Move, S>
+    {
+        self.minimax_with_observer(board, turn, plies, &mut NoObserver)
+    }
+
+    /// Minimax driver that reports to `observer` and lets it stop the
+    /// search.
+    ///
+    /// Once stopped, the search unwinds and returns the best root move
+    /// searched so far, with `completed` unset. Positions that weren't done
+    /// don't go in the transposition table.
+    pub fn minimax_with_observer(&mut self, board: &B, turn: Team, plies: u32, observer: &mut dyn SearchObserver<B, S>) -> MoveStats<B::Move, S>
+    {
+        self.search_full(board, turn, plies, &mut SearchContext::new(observer))
+    }
+
+    /// Like `minimax`, but says so instead of returning a result without a
+    /// move when the game is over or `turn` has no moves.
+    pub fn try_minimax(&mut self, board: &B, turn: Team, plies: u32) -> SearchResult<B::Move, S>
+    {
+        if board.is_game_over()
+        {
+            return SearchResult::GameAlreadyOver(board.score_for(turn));
+        }
+        if board.moves(turn).next().is_none()
+        {
+            return SearchResult::NoLegalMoves;
+        }
+        SearchResult::BestMove(self.minimax(board, turn, plies))
+    }
+
+    /// Searches until every line ends with the game, for games small enough
+    /// to be solved outright, and returns their true score. Lines that are
+    /// still going after `max_plies` plies give up with an error instead,
+    /// so a game that can loop forever doesn't use up the stack.
+    ///
+    /// The transposition table keeps it to one search of each position.
+    /// Repetitions count as `set_repetition_score` says, which ends the
+    /// lines going round in circles. Null-move pruning and razoring are
+    /// left out, since they only guess.
+    pub fn solve(&mut self, board: &B, turn: Team, max_plies: u32) -> Result<MoveStats<B::Move, S>, SolveError<B::Move, S>>
+    {
+        let mut observer = NoObserver;
+        let mut ctx = SearchContext::new(&mut observer);
+        ctx.solving = true;
+
+        let stats = self.search_full(board, turn, max_plies, &mut ctx);
+        if !stats.completed
+        {
+            Err(SolveError::Stopped(stats))
+        }
+        else if !stats.solved
+        {
+            Err(SolveError::TooDeep(stats))
+        }
+        else
+        {
+            Ok(stats)
+        }
+    }
+
+    /// Searches `plies` plies deep like `minimax`, and again with the plain
+    /// minimax of `reference`, to check the settings and tables of this
+    /// search against it. Slow, since the reference looks at every node.
+    ///
+    /// The reference plays the quiescence depth, extensions and repetition
+    /// score set here. Whatever else prunes only finds the same answer
+    /// faster, if it's right.
+    pub fn verify(&mut self, board: &B, turn: Team, plies: u32) -> Verification<B::Move, S>
+    {
+        let search = self.minimax(board, turn, plies);
+        let settings = Reference {
+            quiescence_depth: self.quiescence_depth,
+            max_extensions: self.max_extensions,
+            max_depth_adjustment: self.max_depth_adjustment,
+            repetition_score: self.repetition_score,
+        };
+        let reference = settings.search(board, turn, plies);
+
+        /* Even scores come in any number of turns */
+        let mismatch = search.score.cmp(&reference.score) != Ordering::Equal
+            || search.mv.is_some() != reference.mv.is_some()
+            || search.mv.as_ref().is_some_and(|mv| !reference.is_best(mv));
+        Verification {
+            search,
+            reference,
+            mismatch,
+        }
+    }
+
+    /// Searches with the settings in `opts`.
+    ///
+    /// If the node limit or the time limit is hit, the result is the best
+    /// root move that was searched to the end, with `completed` unset.
+    pub fn search(&mut self, board: &B, turn: Team, opts: &SearchOptions) -> MoveStats<B::Move, S>
+    {
+        let mut observer = NoObserver;
+        let mut ctx = SearchContext::new(&mut observer);
+        ctx.limit(opts);
+
+        self.search_full(board, turn, opts.plies(), &mut ctx)
+    }
+
+    /// Like `search`, but only searches the moves of `turn` that are in
+    /// `root_moves`, as if they were the only legal ones. Says so if none
+    /// of them are, or the game is over or `turn` has no moves at all.
+    ///
+    /// The root position isn't looked up in or stored to the transposition
+    /// table, where it would stand for the position with all its moves.
+    /// Positions below it are.
+    pub fn search_moves(&mut self, board: &B, turn: Team, opts: &SearchOptions, root_moves: &[B::Move]) -> SearchResult<B::Move, S>
+    {
+        self.search_filtered(board, turn, opts, RootFilter::Only(root_moves.to_vec()))
+    }
+
+    /// Like `search_moves`, but searches the moves of `turn` that aren't in
+    /// `excluded`, to find the best move besides those.
+    pub fn search_without(&mut self, board: &B, turn: Team, opts: &SearchOptions, excluded: &[B::Move]) -> SearchResult<B::Move, S>
+    {
+        self.search_filtered(board, turn, opts, RootFilter::Except(excluded.to_vec()))
+    }
+
+    fn search_filtered(&mut self, board: &B, turn: Team, opts: &SearchOptions, root_moves: RootFilter<B::Move>) -> SearchResult<B::Move, S>
+    {
+        if board.is_game_over()
+        {
+            return SearchResult::GameAlreadyOver(board.score_for(turn));
+        }
+        let mut legal = board.moves(turn).peekable();
+        if legal.peek().is_none()
+        {
+            return SearchResult::NoLegalMoves;
+        }
+        if !legal.any(|mv| root_moves.allows(&mv))
+        {
+            return SearchResult::NoRootMoves;
+        }
+
+        let mut observer = NoObserver;
+        let mut ctx = SearchContext::new(&mut observer);
+        ctx.limit(opts);
+        ctx.root_moves = Some(root_moves);
+
+        SearchResult::BestMove(self.search_full(board, turn, opts.plies(), &mut ctx))
+    }
+
+    /// Like `search`, also recording the top `trace_depth` plies of the
+    /// tree: every node that was searched there, the window it got and what
+    /// it returned, and the moves a cutoff skipped.
+    ///
+    /// Quiescence search isn't recorded. A trace depth of 0 only records
+    /// the root.
+    pub fn search_with_trace(&mut self, board: &B, turn: Team, opts: &SearchOptions, trace_depth: u32) -> (MoveStats<B::Move, S>, SearchTrace<B::Move, S>)
+    {
+        let mut observer = NoObserver;
+        let mut ctx = SearchContext::new(&mut observer);
+        ctx.limit(opts);
+        ctx.trace = Some(Tracer::new(trace_depth));
+
+        let stats = self.search_full(board, turn, opts.plies(), &mut ctx);
+        let trace = SearchTrace {
+            root: ctx.trace.unwrap().root.unwrap(),
+        };
+        (stats, trace)
+    }
+
+    /// Minimax driver with a caller supplied window, given from the ally's
+    /// point of view.
+    ///
+    /// A narrow window around a guessed score, such as the one from the
+    /// last iteration of a deepening loop, prunes more than the full one.
+    /// The result says whether the true score fell inside the window.
+    ///
+    /// What the search learns goes into the transposition table marked
+    /// with the bound it is, as `probe` shows, so later searches with
+    /// other windows only take it as their score when it settles them.
+    pub fn minimax_windowed(&mut self, board: &B, turn: Team, plies: u32, alpha: TimedScore<S>, beta: TimedScore<S>) -> WindowedResult<B::Move, S>
+    {
+        let plies = plies.max(1);
+        let mut observer = NoObserver;
+        let scoring = self.scoring();
+        let mut stats = self.with_scoring(scoring, |minimax| minimax.search_window(board, turn, plies, alpha, beta, &mut SearchContext::new(&mut observer)));
+
+        stats.nodes_visited += 1;
+        stats.depth_reached = plies;
+        WindowedResult {
+            bound: Bound::of(stats.score, alpha, beta),
+            stats,
+        }
+    }
+
+    /// Whether `board` is worth at least `threshold` to `turn`, seen from
+    /// `turn`, searched `plies` plies deep.
+    ///
+    /// A single zero-width window just below `threshold` answers it, which
+    /// is far cheaper than finding the score. The bounds it finds go into
+    /// the transposition table like those of `minimax_windowed`, for later
+    /// searches to use. A win `u32::MAX` turns away, the slowest there is,
+    /// asks whether `turn` can force a win at all.
+    pub fn test_bound(&mut self, board: &B, turn: Team, plies: u32, threshold: TimedScore<S>) -> BoundResult
+    {
+        /* Nothing is worth less than the lowest score */
+        let below = match threshold.just_below()
+        {
+            Some(below) => below,
+            None => return BoundResult::AtLeast,
+        };
+        let result = match turn
+        {
+            Team::Ally => self.minimax_windowed(board, turn, plies, below, threshold).stats.score,
+            Team::Enemy => -self.minimax_windowed(board, turn, plies, -threshold, -below).stats.score,
+        };
+        if result >= threshold
+        {
+            BoundResult::AtLeast
+        }
+        else
+        {
+            BoundResult::Below
+        }
+    }
+
+    /// MTD(f) driver: finds the score with zero-width windows alone,
+    /// starting from `first_guess`, seen from the ally.
+    ///
+    /// Each pass asks whether the true score is above some test value and
+    /// narrows down where it can be, until the bounds meet. The passes share
+    /// the transposition table, which keeps the bounds the others found.
+    /// The closer the guess, such as the score of the last iteration of a
+    /// deepening loop, the fewer the passes. `nodes_visited` counts them
+    /// all.
+    ///
+    /// Needs scores that implement `GameScore::above`, and searches with
+    /// the full window otherwise. With floating point scores it can take a
+    /// great many passes.
+    pub fn mtdf(&mut self, board: &B, turn: Team, plies: u32, first_guess: TimedScore<S>) -> MoveStats<B::Move, S>
+    {
+        let mut lower = TimedScore {
+            score: S::MIN,
+            turns: 0,
+        };
+        let mut upper = TimedScore {
+            score: S::MAX,
+            turns: 0,
+        };
+
+        let mut guess = first_guess;
+        let mut nodes = 0;
+        let mut last = None;
+        let mut proven: Option<MoveStats<B::Move, S>> = None;
+        while lower < upper
+        {
+            /* Test a value at least the lower bound, below the upper */
+            let test = if guess >= upper { upper.just_below() } else { Some(guess.max(lower)) };
+            let (alpha, beta) = match test.and_then(|test| test.just_above().map(|above| (test, above)))
+            {
+                Some(window) => window,
+                None => return self.minimax(board, turn, plies),
+            };
+
+            let mut result = self.minimax_windowed(board, turn, plies, alpha, beta);
+            nodes += result.stats.nodes_visited;
+            if !result.stats.completed
+            {
+                result.stats.nodes_visited = nodes;
+                return result.stats;
+            }
+            guess = result.stats.score;
+            if result.failed_low()
+            {
+                upper = guess;
+            }
+            else
+            {
+                lower = guess;
+            }
+
+            /* The move to play is the one that held the bound in the
+             * side to move's favour
+             */
+            let proving = match turn
+            {
+                Team::Ally => !result.failed_low(),
+                Team::Enemy => result.failed_low(),
+            };
+            if proving
+            {
+                proven = Some(result.stats);
+            }
+            else
+            {
+                last = Some(result.stats);
+            }
+        }
+
+        /* There was at least one pass, lower started below upper */
+        let mut best = proven.or(last).unwrap();
+        best.score = lower;
+        best.nodes_visited = nodes;
+        best
+    }
+
+    /// Minimax driver that spreads the root moves over `threads` workers.
+    ///
+    /// Each worker searches its share of the root moves with the full
+    /// window and a transposition table of its own, so less gets pruned
+    /// than in `minimax`. The results are combined in root move order, so
+    /// the chosen move doesn't depend on which worker finished first, and
+    /// the workers' tables are merged into this one afterwards.
+    /// `nodes_visited` counts the nodes of all workers.
+    pub fn minimax_parallel(&mut self, board: &B, turn: Team, plies: u32, threads: usize) -> MoveStats<B::Move, S>
+        where B: Send,
+              B::Move: Send,
+              S: Send,
+              K: Send
+    {
+        let mut moves: Vec<B::Move> = board.moves(turn).collect();
+
+        /* Nothing to split up */
+        if plies == 0 || board.is_game_over() || moves.is_empty() || threads <= 1
+        {
+            return self.minimax(board, turn, plies);
+        }
+
+        let key = K::of(board, turn);
+        board.order_moves(&mut moves, turn);
+        let tt_move = match self.tie_break
+        {
+            TieBreak::First => self.table_move(&key),
+            _ => None,
+        };
+        self.orderer.order(board, turn, &mut moves, plies, tt_move.as_ref());
+
+        let lose = TimedScore {
+            score: S::MIN,
+            turns: 0,
+        };
+        let win = TimedScore {
+            score: S::MAX,
+            turns: 0,
+        };
+
+        /* Deal the root moves out round robin, remembering their index */
+        let mut shares: Vec<Vec<(usize, B::Move)>> = (0..threads).map(|_| Vec::new()).collect();
+        for (idx, mv) in moves.into_iter().enumerate()
+        {
+            shares[idx % threads].push((idx, mv));
+        }
+
+        let capacity = NonZeroUsize::new(self.ttable.stats().capacity).unwrap();
+        let policy = self.ttable.policy();
+        let quiescence_depth = self.quiescence_depth;
+        let repetition_score = self.repetition_score;
+        let search_mode = self.search_mode;
+        let null_move_reduction = self.null_move_reduction;
+        let max_extensions = self.max_extensions;
+        let max_depth_adjustment = self.max_depth_adjustment;
+        let killers = self.killers.is_some();
+        let stop = &self.stop;
+        let workers: Vec<_> = thread::scope(|scope|
+        {
+            let handles: Vec<_> = shares.into_iter()
+                .map(|share|
+                {
+                    let board = board.clone();
+                    let countermoves = self.countermoves.as_ref().map(Countermoves::emptied);
+                    let refutations = self.refutations.as_ref().map(RefutationTable::emptied);
+                    let history = self.history.as_ref().map(History::emptied);
+                    let orderer = self.orderer.boxed_clone();
+                    let razoring_margins = self.razoring_margins.clone();
+                    let stop = stop.clone();
+                    scope.spawn(move ||
+                    {
+                        let mut worker = Minimax::with_table(TranspositionTable::with_policy(capacity, policy));
+                        worker.quiescence_depth = quiescence_depth;
+                        worker.repetition_score = repetition_score;
+                        worker.search_mode = search_mode;
+                        worker.null_move_reduction = null_move_reduction;
+                        worker.razoring_margins = razoring_margins;
+                        worker.max_extensions = max_extensions;
+                        worker.max_depth_adjustment = max_depth_adjustment;
+                        worker.killers = if killers { Some(Killers::new()) } else { None };
+                        worker.countermoves = countermoves;
+                        worker.refutations = refutations;
+                        worker.history = history;
+                        worker.orderer = orderer;
+                        let mut observer = NoObserver;
+                        let mut ctx = SearchContext::new(&mut observer);
+                        /* What the workers search are children of the root */
+                        ctx.ply = 1;
+                        ctx.path.push(K::of(&board, turn));
+                        ctx.stop = Some(stop);
+
+                        let replies: Vec<_> = share.into_iter()
+                            .map(|(idx, mv)|
+                            {
+                                let extension = worker.extension(&ctx, &board, &mv, turn);
+                                let adjustment = worker.adjustment(&ctx, &board, &mv, turn);
+                                let mut child = board.clone();
+                                child.do_move(&mv);
+                                ctx.extensions = extension;
+                                ctx.adjustment = adjustment;
+                                let child_depth = (Depth::from_plies(plies - 1) + extension).add_plies(adjustment);
+                                let reply = worker.negamax(&mut ctx, &mut child, turn.other_team(), child_depth, -win, -lose);
+                                (idx, mv, reply)
+                            })
+                            .collect();
+                        (worker, replies, ctx.repetitions > 0, ctx.stats, ctx.seldepth)
+                    })
+                })
+                .collect();
+
+            handles.into_iter()
+                .map(|handle| handle.join().unwrap_or_else(|payload| panic::resume_unwind(payload)))
+                .collect()
+        });
+
+        let mut replies = Vec::new();
+        let mut repeated = false;
+        let mut seldepth = 0;
+        self.search_stats = SearchStats::default();
+        for (worker, worker_replies, worker_repeated, worker_stats, worker_seldepth) in workers
+        {
+            self.ttable.merge(&worker.ttable);
+            replies.extend(worker_replies);
+            repeated |= worker_repeated;
+            self.search_stats.add(worker_stats);
+            seldepth = seldepth.max(worker_seldepth);
+        }
+        replies.sort_by_key(|&(idx, _, _)| idx);
+
+        /* Same selection as in negamax, in root move order */
+        let mut best = MoveStats {
+            mv: None,
+            score: lose,
+            nodes_visited: 0,
+            mvs: Vec::new(),
+            depth_reached: 0,
+            extended_depth: 0,
+            seldepth: 0,
+            exhaustive: true,
+            completed: true,
+            solved: true,
+        };
+        let mut ties = Vec::new();
+        for (_, mv, reply) in replies
+        {
+            best.nodes_visited += reply.nodes_visited + 1;
+            best.solved &= reply.solved;
+            best.exhaustive &= reply.exhaustive;
+            if !reply.completed
+            {
+                best.completed = false;
+                continue;
+            }
+
+            best.extended_depth = best.extended_depth.max(reply.extended_depth + 1);
+            let score = (-reply.score).later();
+            if best.mv.is_none() || score > best.score
+            {
+                ties.clear();
+                best.mv = Some(mv);
+                best.score = score;
+                best.mvs = reply.mvs;
+            }
+            else if score.cmp(&best.score) == Ordering::Equal
+            {
+                ties.push((mv, reply.mvs));
+            }
+        }
+        self.break_tie(&mut best, ties);
+
+        if let Some(ref mv) = best.mv
+        {
+            best.mvs.insert(0, mv.clone());
+            if best.completed && !repeated
+            {
+                self.ttable.insert(key, best.clone(), Depth::from_plies(plies).units(), Bound::of(best.score, lose, win));
+            }
+        }
+
+        /* Back to the ally's point of view, like `search` */
+        if let Team::Enemy = turn
+        {
+            best.score = -best.score;
+        }
+        best.nodes_visited += 1;
+        best.depth_reached = plies;
+        best.seldepth = seldepth;
+        best
+    }
+
+    /// Searches for the `k` best root moves, best first, each with its
+    /// exact score and principal variation.
+    ///
+    /// The first result is what `minimax` returns. The other root moves are
+    /// then searched again, each with a window that only lets it through if
+    /// it beats the last of the best found so far, so moves that can't make
+    /// the cut are still pruned. Equal scores keep search order. A search
+    /// stopped by the stop handle returns the moves it finished.
+    ///
+    /// Every root move is searched as itself, so two that lead to the same
+    /// position are both reported even when the transposition table
+    /// answers for the second. Fewer than `k` moves give fewer results.
+    pub fn minimax_multi(&mut self, board: &B, turn: Team, plies: u32, k: usize) -> Vec<MoveStats<B::Move, S>>
+    {
+        if k == 0
+        {
+            return Vec::new();
+        }
+        let plies = plies.max(1);
+
+        let best = self.minimax(board, turn, plies);
+        let best_mv = match best.mv
+        {
+            Some(ref mv) if k > 1 && best.completed => mv.clone(),
+            _ => return vec![best],
+        };
+
+        let mut moves: Vec<B::Move> = board.moves(turn).collect();
+        board.order_moves(&mut moves, turn);
+
+        let lose = TimedScore {
+            score: S::MIN,
+            turns: 0,
+        };
+        let win = TimedScore {
+            score: S::MAX,
+            turns: 0,
+        };
+
+        let mut observer = NoObserver;
+        let mut ctx = SearchContext::new(&mut observer);
+        ctx.ply = 1;
+        ctx.path.push(K::of(board, turn));
+        ctx.stop = Some(self.stop.clone());
+
+        /* Runners up from `turn`'s point of view, best first */
+        let mut others: Vec<MoveStats<B::Move, S>> = Vec::new();
+        let mut root = board.clone();
+        let scoring = self.scoring();
+        self.with_scoring(scoring, |minimax|
+        {
+            for mv in moves
+            {
+                if mv == best_mv
+                {
+                    continue;
+                }
+
+                let full = others.len() == k - 1;
+                let cut = if full { others[k - 2].score } else { lose };
+                let alpha = if minimax.search_mode == SearchMode::PlainMinimax { lose } else { cut };
+                let extension = minimax.extension(&ctx, &root, &mv, turn);
+                let adjustment = minimax.adjustment(&ctx, &root, &mv, turn);
+                ctx.extensions = extension;
+                ctx.adjustment = adjustment;
+                ctx.seldepth = 0;
+                let child_depth = (Depth::from_plies(plies - 1) + extension).add_plies(adjustment);
+                let reply = minimax.play(&mut root, &mv, |minimax, child|
+                    minimax.negamax(&mut ctx, child, turn.other_team(), child_depth, -win, -alpha));
+                ctx.adjustment = 0;
+                if !reply.completed
+                {
+                    break;
+                }
+
+                /* Failing low, it doesn't beat anything already in */
+                let score = (-reply.score).later();
+                if full && score <= cut
+                {
+                    continue;
+                }
+
+                let mut mvs = reply.mvs;
+                mvs.insert(0, mv.clone());
+                let idx = others.iter().position(|other| score > other.score).unwrap_or(others.len());
+                others.insert(idx, MoveStats {
+                    mv: Some(mv),
+                    score,
+                    nodes_visited: reply.nodes_visited + 1,
+                    mvs,
+                    depth_reached: plies,
+                    extended_depth: reply.extended_depth + 1,
+                    seldepth: ctx.seldepth,
+                    exhaustive: reply.exhaustive,
+                    completed: true,
+                    solved: reply.solved,
+                });
+                others.truncate(k - 1);
+            }
+        });
+
+        self.search_stats.add(ctx.stats);
+
+        /* Back to the ally's point of view, like `minimax` */
+        let mut results = vec![best];
+        for mut stats in others
+        {
+            if let Team::Enemy = turn
+            {
+                stats.score = -stats.score;
+            }
+            results.push(stats);
+        }
+        results
+    }
+
+    /// Searches for every root move that does as well as the best one, in
+    /// search order, along with the score they share from the ally's point
+    /// of view.
+    ///
+    /// No root move is pruned for merely equalling the best, so this
+    /// searches more than `minimax`. Scores are only equal if they take as
+    /// many turns, so a win in three doesn't tie a win in five. Without a
+    /// move the list is empty and the score is that of the board.
+    pub fn all_best_moves(&mut self, board: &B, turn: Team, plies: u32) -> (TimedScore<S>, Vec<B::Move>)
+    {
+        let mut observer = NoObserver;
+        let mut ctx = SearchContext::new(&mut observer);
+        ctx.tied = Some(Vec::new());
+
+        let best = self.search_full(board, turn, plies, &mut ctx);
+        (best.score, ctx.tied.unwrap())
+    }
+
+    /// Ranks every root move and picks one that isn't necessarily the
+    /// best, for adjustable difficulty.
+    ///
+    /// The root moves are all searched to their exact scores, like
+    /// `minimax_multi` asked for all of them, so this searches far more
+    /// than `minimax`; below the root the search still prunes. A move
+    /// that loses is never picked while one that doesn't is left, however
+    /// low the rank asked for, and a rank past the last move picks the
+    /// lowest one that is left. A loss can still be played on purpose by
+    /// taking it out of `ranked`.
+    pub fn best_move_ranked(&mut self, board: &B, turn: Team, plies: u32, pick: MovePick) -> RankedMoves<B::Move, S>
+    {
+        let ranked = self.minimax_multi(board, turn, plies, usize::MAX);
+        if ranked.first().is_none_or(|best| best.mv.is_none())
+        {
+            return RankedMoves { ranked: Vec::new(), rank: None };
+        }
+
+        /* Scores from `turn`'s point of view */
+        let mine = |stats: &MoveStats<B::Move, S>| match turn
+        {
+            Team::Ally => stats.score.score,
+            Team::Enemy => -stats.score.score,
+        };
+        let best = mine(&ranked[0]);
+        let rank = match pick
+        {
+            MovePick::Rank(rank) => rank.min(ranked.len() - 1),
+            MovePick::Margin(margin) => {
+                let within = |score: S| match best.add_margin(-margin.max(0))
+                {
+                    Some(least) => score >= least,
+                    None => score == best,
+                };
+                ranked.iter().rposition(|stats| within(mine(stats))).unwrap_or(0)
+            }
+        };
+        let rank = match ranked.iter().rposition(|stats| mine(stats) != S::MIN)
+        {
+            Some(last) => rank.min(last),
+            None => rank,
+        };
+        RankedMoves { ranked, rank: Some(rank) }
+    }
+
+    /// Scores every root move, best first, from the ally's point of view.
+    ///
+    /// The root moves don't prune each other: each is searched with the
+    /// full window to its exact score, though the search still prunes
+    /// below them, so this costs about as many nodes as `minimax` for every
+    /// root move. Equal scores keep search order. A search stopped by the
+    /// stop handle returns the moves it finished, and without moves the
+    /// list is empty.
+    pub fn analyze_root(&mut self, board: &B, turn: Team, plies: u32) -> Vec<RootScore<B::Move, S>>
+    {
+        self.root_scores(board, turn, plies, None)
+    }
+
+    /// `analyze_root` for less: moves scoring more than `margin` below the
+    /// best, for scores with `GameScore::add_margin`, are only searched
+    /// far enough to show it, and come back with a bound on their score
+    /// rather than the exact one.
+    pub fn analyze_root_within(&mut self, board: &B, turn: Team, plies: u32, margin: i32) -> Vec<RootScore<B::Move, S>>
+    {
+        self.root_scores(board, turn, plies, Some(margin))
+    }
+
+    fn root_scores(&mut self, board: &B, turn: Team, plies: u32, margin: Option<i32>) -> Vec<RootScore<B::Move, S>>
+    {
+        let plies = plies.max(1);
+        let best = self.minimax(board, turn, plies);
+        let best_mv = match best.mv
+        {
+            Some(ref mv) if best.completed => mv.clone(),
+            _ => return Vec::new(),
+        };
+
+        let mut moves: Vec<B::Move> = board.moves(turn).collect();
+        board.order_moves(&mut moves, turn);
+
+        let lose = TimedScore {
+            score: S::MIN,
+            turns: 0,
+        };
+        let win = TimedScore {
+            score: S::MAX,
+            turns: 0,
+        };
+
+        /* Moves that fail low against `alpha`, from `turn`'s point of
+         * view, are far enough behind the best
+         */
+        let mine = |score: TimedScore<S>| match turn
+        {
+            Team::Ally => score,
+            Team::Enemy => -score,
+        };
+        let alpha = match margin
+        {
+            Some(margin) if self.search_mode != SearchMode::PlainMinimax =>
+                mine(best.score).score.add_margin(-margin.max(0)).map(|score| TimedScore { score, turns: 0 }).unwrap_or(lose),
+            _ => lose,
+        };
+
+        let mut observer = NoObserver;
+        let mut ctx = SearchContext::new(&mut observer);
+        ctx.ply = 1;
+        ctx.path.push(K::of(board, turn));
+        ctx.stop = Some(self.stop.clone());
+
+        let mut scores = vec![(best_mv.clone(), mine(best.score), true)];
+        let mut root = board.clone();
+        let scoring = self.scoring();
+        self.with_scoring(scoring, |minimax|
+        {
+            for mv in moves
+            {
+                if mv == best_mv
+                {
+                    continue;
+                }
+
+                let extension = minimax.extension(&ctx, &root, &mv, turn);
+                let adjustment = minimax.adjustment(&ctx, &root, &mv, turn);
+                ctx.extensions = extension;
+                ctx.adjustment = adjustment;
+                let child_depth = (Depth::from_plies(plies - 1) + extension).add_plies(adjustment);
+                let reply = minimax.play(&mut root, &mv, |minimax, child|
+                    minimax.negamax(&mut ctx, child, turn.other_team(), child_depth, -win, -alpha));
+                ctx.adjustment = 0;
+                if !reply.completed
+                {
+                    break;
+                }
+                let score = (-reply.score).later();
+                scores.push((mv, score, alpha == lose || score > alpha));
+            }
+        });
+        self.search_stats.add(ctx.stats);
+
+        scores.sort_by_key(|&(_, score, _)| Reverse(score));
+        scores.into_iter()
+            .map(|(mv, score, exact)| RootScore {
+                mv,
+                score: mine(score),
+                bound: match (exact, turn)
+                {
+                    (true, _) => Bound::Exact,
+                    (false, Team::Ally) => Bound::UpperBound,
+                    (false, Team::Enemy) => Bound::LowerBound,
+                },
+            })
+            .collect()
+    }
+
+    /// Iterative deepening driver.
+    ///
+    /// Runs `minimax` at 1, 2, 3, ... plies and returns the deepest
+    /// completed result. A new iteration is only started if it looks like
+    /// it can finish within `deadline`, estimated from how much the last
+    /// iteration grew over the one before it. The transposition table is
+    /// kept between iterations. The depth 1 search is always completed, even
+    /// if it overruns the deadline.
+    ///
+    /// Needs the `std-clock` feature.
+    #[cfg(feature = "std-clock")]
+    pub fn iterative_deepening(&mut self, board: &B, turn: Team, deadline: Duration) -> MoveStats<B::Move, S>
+    {
+        let start = Instant::now();
+
+        let mut last_time = Duration::from_secs(0);
+        let mut last_nodes = 0;
+        let mut best = self.deepen(board, turn, 1, None, &mut NoObserver);
+
+        loop
+        {
+            let elapsed = start.elapsed();
+
+            /* A win or loss can't be improved on by looking deeper */
+            if best.score.score == S::MAX || best.score.score == S::MIN
+            {
+                break;
+            }
+
+            if best.depth_reached == u32::MAX
+            {
+                break;
+            }
+
+            /* Guess the cost of the next iteration by assuming it grows
+             * by the same factor the last one did.
+             */
+            let iteration_time = elapsed - last_time;
+            let growth = if last_nodes == 0
+            {
+                1.0
+            }
+            else
+            {
+                (best.nodes_visited as f64 / last_nodes as f64).max(1.0)
+            };
+            let estimate = iteration_time.mul_f64(growth);
+
+            if elapsed + estimate > deadline
+            {
+                break;
+            }
+
+            last_time = elapsed;
+            last_nodes = best.nodes_visited;
+            let deeper = self.deepen(board, turn, best.depth_reached + 1, Some(&best), &mut NoObserver);
+            if !deeper.completed
+            {
+                break;
+            }
+            best = deeper;
+        }
+
+        best
+    }
+
+    /// Runs `minimax` at 1, 2, 3, ... up to `max_plies` plies and returns
+    /// the result of every iteration, the deepest last.
+    ///
+    /// Each iteration tries the previous one's principal variation first,
+    /// on top of what the transposition table remembers, so the deeper
+    /// searches cut off sooner than they would from cold. Stops early once
+    /// an iteration finds a settled win or loss, or the stop handle is set.
+    /// A stopped iteration is left out unless it is the first. Zero plies
+    /// is taken as one, like `minimax` does.
+    pub fn iterative_deepening_to(&mut self, board: &B, turn: Team, max_plies: u32) -> Vec<MoveStats<B::Move, S>>
+    {
+        let mut results: Vec<MoveStats<B::Move, S>> = Vec::new();
+        for plies in 1..=max_plies.max(1)
+        {
+            let stats = self.deepen(board, turn, plies, results.last(), &mut NoObserver);
+            let settled = stats.score.score == S::MAX || stats.score.score == S::MIN;
+            if !stats.completed
+            {
+                if results.is_empty()
+                {
+                    results.push(stats);
+                }
+                break;
+            }
+            results.push(stats);
+            if settled
+            {
+                break;
+            }
+        }
+        results
+    }
+
+    /// Deepens up to `opts.plies()` plies like `iterative_deepening_to`,
+    /// within the node and time limits of `opts`, which hold for all the
+    /// iterations together.
+    ///
+    /// Returns the deepest iteration that completed. An iteration that
+    /// runs out of nodes or time is thrown away, unless it is the first:
+    /// then the best root move it searched to the end is returned, and if
+    /// there is none, the first root move with the root's own score. Either
+    /// way `completed` is unset.
+    pub fn iterative_deepening_with(&mut self, board: &B, turn: Team, opts: &SearchOptions) -> MoveStats<B::Move, S>
+    {
+        let deadline = opts.time_limit().map(|(limit, clock)| (clock, clock.elapsed() + limit));
+        let mut nodes = 0;
+        let mut best: Option<MoveStats<B::Move, S>> = None;
+
+        for plies in 1..=opts.plies().max(1)
+        {
+            let mut observer = NoObserver;
+            let mut ctx = SearchContext::new(&mut observer);
+            ctx.limit(opts);
+            ctx.max_nodes = ctx.max_nodes.saturating_sub(nodes);
+            ctx.deadline = deadline;
+            ctx.follow(best.as_ref());
+
+            let stats = self.search_full(board, turn, plies, &mut ctx);
+            nodes += ctx.nodes;
+            if !stats.completed
+            {
+                if best.is_none()
+                {
+                    best = Some(first_root_move(board, turn, stats, ctx.draw));
+                }
+                break;
+            }
+
+            let settled = stats.score.score == S::MAX || stats.score.score == S::MIN;
+            best = Some(stats);
+            if settled
+            {
+                break;
+            }
+        }
+        best.unwrap()
+    }
+
+    /// Time limited `iterative_deepening_with`, measured on a `StdClock`
+    /// and read every `NODES_PER_CLOCK_READING` nodes.
+    ///
+    /// Needs the `std-clock` feature.
+    #[cfg(feature = "std-clock")]
+    pub fn minimax_timed(&mut self, board: &B, turn: Team, max_plies: u32, limit: Duration) -> MoveStats<B::Move, S>
+    {
+        let clock = StdClock::new();
+        let opts = SearchOptions::depth(max_plies).time_limit(limit, &clock).build();
+        self.iterative_deepening_with(board, turn, &opts)
+    }
+
+    /// Thinks on the opponent's time: `board` is the position after our
+    /// move, `turn` the opponent to move there, and `expected_reply` what
+    /// it is expected to play, usually `MoveStats::expected_reply`.
+    ///
+    /// Deepens on the position after the reply like
+    /// `iterative_deepening_with`, up to the limits of `opts`, until the
+    /// stop handle is set. Set it once the opponent has moved, and hand the
+    /// move to `ponder_result`. Either way the transposition table keeps
+    /// what pondering found.
+    pub fn ponder(&mut self, board: &B, turn: Team, expected_reply: B::Move, opts: &SearchOptions)
+    {
+        let mut expected = board.clone();
+        expected.do_move(&expected_reply);
+        /* The game is still where `advance` left it */
+        let game = self.game.take();
+        let stats = self.iterative_deepening_with(&expected, turn.other_team(), opts);
+        self.game = game;
+        self.pondered = Some(Pondered { reply: expected_reply, stats });
+    }
+
+    /// The pondered result if the opponent played `actual_move`, the reply
+    /// `ponder` expected. It is the deepest iteration pondering completed,
+    /// which may be short of what `opts` asked for if it was stopped; a
+    /// search of the position to the full depth then starts off the table.
+    ///
+    /// Clears the stop handle, which was set to end the pondering, and
+    /// forgets what was pondered.
+    pub fn ponder_result(&mut self, actual_move: &B::Move) -> PonderResult<B::Move, S>
+    {
+        self.stop.store(false, atomic::Ordering::Relaxed);
+        match self.pondered.take()
+        {
+            Some(pondered) if pondered.reply == *actual_move => PonderResult::Hit(pondered.stats),
+            _ => PonderResult::Miss,
+        }
+    }
+
+    /// Ponders like `ponder`, but on a thread of its own that the search
+    /// moves to, so the caller is free while the opponent thinks.
+    ///
+    /// Deepens on the position after `expected_reply` up to `max_plies`
+    /// plies, with no time limit until the handle's `ponderhit` gives it
+    /// one, reading `clock` every `NODES_PER_CLOCK_READING` nodes from
+    /// then on. `miss` stops it instead, as does the stop handle.
+    pub fn ponder_in_background<C>(mut self, board: &B, turn: Team, expected_reply: B::Move, max_plies: u32, clock: C) -> PonderHandle<B, S, K>
+        where B: Send + 'static,
+              B::Move: Send,
+              S: Send + 'static,
+              K: Send + 'static,
+              C: Clock + Send + Sync + 'static
+    {
+        let mut expected = board.clone();
+        expected.do_move(&expected_reply);
+        let timer = Arc::new(PonderTimer::new());
+        let clock: Arc<dyn Clock + Send + Sync> = Arc::new(clock);
+        let stop = self.stop.clone();
+
+        let thread = {
+            let timer = timer.clone();
+            let clock = clock.clone();
+            thread::spawn(move ||
+            {
+                /* The game is still where `advance` left it */
+                let game = self.game.take();
+                let stats = self.ponder_deepening(&expected, turn.other_team(), max_plies, &*clock, &timer);
+                self.game = game;
+                self.pondered = Some(Pondered { reply: expected_reply, stats });
+                self
+            })
+        };
+
+        PonderHandle {
+            thread,
+            timer,
+            clock,
+            stop,
+        }
+    }
+
+    /* `iterative_deepening_with` for a background ponder, under the
+     * bounds `timer` gets if it hits
+     */
+    fn ponder_deepening(&mut self, board: &B, turn: Team, max_plies: u32, clock: &dyn Clock, timer: &PonderTimer) -> MoveStats<B::Move, S>
+    {
+        let mut best: Option<MoveStats<B::Move, S>> = None;
+        for plies in 1..=max_plies.max(1)
+        {
+            if best.is_some() && timer.past_soft(clock)
+            {
+                break;
+            }
+
+            let mut observer = NoObserver;
+            let mut ctx = SearchContext::new(&mut observer);
+            ctx.ponder_timer = Some((clock, timer));
+            ctx.clock_interval = NODES_PER_CLOCK_READING;
+            ctx.follow(best.as_ref());
+
+            let stats = self.search_full(board, turn, plies, &mut ctx);
+            if !stats.completed
+            {
+                if best.is_none()
+                {
+                    best = Some(first_root_move(board, turn, stats, ctx.draw));
+                }
+                break;
+            }
+
+            let settled = stats.score.score == S::MAX || stats.score.score == S::MIN;
+            timer.depth.store(plies, atomic::Ordering::Relaxed);
+            best = Some(stats);
+            if settled
+            {
+                break;
+            }
+        }
+        best.unwrap()
+    }
+
+    /// One iteration of a deepening loop, which follows the principal
+    /// variation of the iteration before it.
+    fn deepen(&mut self, board: &B, turn: Team, plies: u32, previous: Option<&MoveStats<B::Move, S>>, observer: &mut dyn SearchObserver<B, S>) -> MoveStats<B::Move, S>
+    {
+        let mut ctx = SearchContext::new(observer);
+        ctx.follow(previous);
+        self.search_full(board, turn, plies, &mut ctx)
+    }
+
+    /// Full window search, counting the root and recording the depth like
+    /// the drivers do.
+    fn search_full(&mut self, board: &B, turn: Team, plies: u32, ctx: &mut SearchContext<B, S, K>) -> MoveStats<B::Move, S>
+    {
+        /* The root moves are always looked at */
+        let plies = plies.max(1);
+        if let Some(ref game) = self.game
+        {
+            if game.advanced && ctx.pv.is_empty() && game.turn == turn
+            {
+                ctx.pv = game.pv.clone();
+                ctx.on_pv = !ctx.pv.is_empty();
+            }
+        }
+        let mut hasher = DefaultHasher::new();
+        (board, turn).hash(&mut hasher);
+        let position = hasher.finish();
+        if let Some(ref order) = self.root_order
+        {
+            if self.reuse_root_order && order.position == position
+            {
+                ctx.previous_root = Some(order.moves.iter()
+                    .map(|&(ref mv, score)| (mv.clone(), match turn
+                    {
+                        Team::Ally => score,
+                        Team::Enemy => -score,
+                    }))
+                    .collect());
+            }
+        }
+        let lose = TimedScore {
+            score: S::MIN,
+            turns: 0,
+        };
+        let win = TimedScore {
+            score: S::MAX,
+            turns: 0,
+        };
+
+        let scoring = Scoring {
+            draw: ctx.draw,
+            opponent_error: ctx.opponent_error,
+            branching: ctx.branching,
+            root_branching: ctx.root_branching,
+            depth_penalty: Some((turn.other_team(), ctx.enemy_depth_penalty.plies())).filter(|_| ctx.enemy_depth_penalty > Depth::ZERO),
+            ..self.scoring()
+        };
+        let mut optimal_move = self.with_scoring(scoring, |minimax| minimax.search_window(board, turn, plies, lose, win, ctx));
+
+        optimal_move.nodes_visited += 1;
+        optimal_move.depth_reached = plies;
+        self.game = Some(GameState {
+            turn,
+            pv: optimal_move.mvs.clone(),
+            advanced: false,
+        });
+        if !ctx.root_scores.is_empty()
+        {
+            self.root_order = Some(RootOrder {
+                position,
+                moves: mem::take(&mut ctx.root_scores),
+            });
+        }
+        optimal_move
+    }
+
+    /* `static_stats`, with noise if there is any */
+    fn evaluate(&mut self, board: &B, turn: Team, draw: Option<S>) -> MoveStats<B::Move, S>
+    {
+        let mut stats = static_stats(board, turn, draw);
+        if let Some((noise, ref mut rng)) = self.noise
+        {
+            let noise = i64::from(noise);
+            let offset = (rng.next_u64() % (2 * noise + 1) as u64) as i64 - noise;
+            if let Some(score) = stats.score.score.add_margin(offset as i32)
+            {
+                stats.score.score = score;
+            }
+        }
+        stats
+    }
+
+    /* How this search changes scores, before any options */
+    fn scoring(&self) -> Scoring<S>
+    {
+        Scoring {
+            draw: None,
+            opponent_error: None,
+            branching: None,
+            root_branching: None,
+            depth_penalty: None,
+            noise: self.noise.as_ref().map(|&(noise, _)| noise),
+        }
+    }
+
+    /* Scores with contempt, noise, the opponent erring or looking less far
+     * ahead, or moves left out aren't the true ones, so `search` runs on
+     * a table of their own instead of the one the normal search uses
+     */
+    fn with_scoring<T, F>(&mut self, scoring: Scoring<S>, search: F) -> T
+        where F: FnOnce(&mut Minimax<B, S, K>) -> T
+    {
+        if scoring == self.scoring() && scoring.noise.is_none()
+        {
+            return search(self);
+        }
+
+        let capacity = NonZeroUsize::new(self.ttable.stats().capacity).unwrap();
+        let mut table = match self.side_ttable.take()
+        {
+            Some((used, table)) if used == scoring => table,
+            _ => TranspositionTable::with_policy(capacity, self.ttable.policy()),
+        };
+        mem::swap(&mut self.ttable, &mut table);
+        self.ttable.set_generation(self.generation);
+        let result = search(self);
+        mem::swap(&mut self.ttable, &mut table);
+        self.side_ttable = Some((scoring, table));
+        result
+    }
+
+    /// Alpha-beta search over `board` with the window given from the
+    /// ally's point of view. The returned score is also the ally's.
+    fn search_window(&mut self, board: &B, turn: Team, plies: u32, alpha: TimedScore<S>, beta: TimedScore<S>, ctx: &mut SearchContext<B, S, K>) -> MoveStats<B::Move, S>
+    {
+        /* The search plays moves on this copy when the board can undo them */
+        let mut board = board.clone();
+        ctx.stop = Some(self.stop.clone());
+        ctx.opponent = turn.other_team();
+        /* Iterations of one search share its generation */
+        if !ctx.generation_started
+        {
+            self.generation = self.generation.wrapping_add(1);
+            ctx.generation_started = true;
+        }
+        self.ttable.set_generation(self.generation);
+        let table = self.ttable.stats();
+
+        let mut best = match turn
+        {
+            Team::Ally =>
+                self.negamax(ctx, &mut board, turn, Depth::from_plies(plies), alpha, beta),
+            Team::Enemy => {
+                let mut best = self.negamax(ctx, &mut board, turn, Depth::from_plies(plies), -beta, -alpha);
+                best.score = -best.score;
+                best
+            }
+        };
+        best.seldepth = ctx.seldepth;
+
+        /* What the table did meanwhile, as it counted it */
+        let after = self.ttable.stats();
+        ctx.stats.tt_probes += after.probes() - table.probes();
+        ctx.stats.tt_shallow_hits += after.shallow_hits - table.shallow_hits;
+        ctx.stats.tt_stores += after.insertions - table.insertions;
+        ctx.stats.tt_evictions += after.evictions - table.evictions;
+        ctx.stats.tt_overwrites += after.overwrites - table.overwrites;
+        self.search_stats = ctx.stats;
+        best
+    }
+
+    /// Generates the best move for `turn`.
+    ///
+    /// Scores and the window are from the point of view of `turn`, so the
+    /// enemy's scores are negated going in and out. The window counts turns
+    /// from the root, see `TimedScore::seen_from_root`. Table entries are kept
+    /// per side to move. `board` is left as it was found.
+    fn negamax(&mut self, ctx: &mut SearchContext<B, S, K>, board: &mut B, turn: Team, depth: Depth, alpha: TimedScore<S>, beta: TimedScore<S>) -> MoveStats<B::Move, S>
+    {
+        let traced = match ctx.trace
+        {
+            Some(ref mut trace) if trace.records(ctx.ply) => {
+                trace.open(turn, alpha, beta);
+                true
+            }
+            _ => false,
+        };
+
+        let best = self.negamax_node(ctx, board, turn, depth, alpha, beta);
+
+        if traced
+        {
+            ctx.trace.as_mut().unwrap().close(&best);
+        }
+        best
+    }
+
+    /// One node of `negamax`, which records it in the trace if there is one.
+    fn negamax_node(&mut self, ctx: &mut SearchContext<B, S, K>, board: &mut B, turn: Team, depth: Depth, mut alpha: TimedScore<S>, mut beta: TimedScore<S>) -> MoveStats<B::Move, S>
+    {
+        let after_null = mem::replace(&mut ctx.after_null, false);
+        let on_pv = mem::replace(&mut ctx.on_pv, false);
+        let last_move = ctx.last_move.take();
+        let excluded = ctx.excluded.take();
+        let root_moves = ctx.root_moves.take();
+        let nested = mem::replace(&mut ctx.nested, false);
+        if ctx.enter()
+        {
+            return stopped_stats();
+        }
+
+        /* If you cannot proceed further */
+        if board.is_game_over()
+        {
+            return self.evaluate(board, turn, ctx.draw);
+        }
+
+        let mut move_iter = board.moves(turn).peekable();
+
+        /* Stuck without a move, the board says what that means */
+        if move_iter.peek().is_none()
+        {
+            drop(move_iter);
+            return match board.on_no_moves(turn)
+            {
+                NoMovePolicy::Lose => lost_stats(),
+                NoMovePolicy::ScoreBoard => self.evaluate(board, turn, ctx.draw),
+                NoMovePolicy::Pass if depth < Depth::ONE_PLY => MoveStats { solved: false, ..self.evaluate(board, turn, ctx.draw) },
+                NoMovePolicy::Pass => self.pass(ctx, board, turn, depth, alpha, beta),
+            };
+        }
+
+        /* Past the horizon, only noisy moves get looked at */
+        if depth < Depth::ONE_PLY
+        {
+            drop(move_iter);
+            ctx.stats.reached_horizon(ctx.ply);
+            let depth = self.quiescence_depth;
+            return self.quiesce(ctx, board, turn, depth, alpha, beta);
+        }
+
+        let mut best = MoveStats{
+            mv: None,
+            score: TimedScore {
+                score: S::MIN,
+                turns: 0,
+            },
+            nodes_visited: 0,
+            mvs: Vec::new(),
+            depth_reached: 0,
+            extended_depth: 0,
+            seldepth: 0,
+            exhaustive: true,
+            completed: true,
+            solved: true,
+        };
+
+        /* Whole plies left, for what is set in plies */
+        let plies = depth.plies();
+        let ply = ctx.ply;
+        let root = ply == 0;
+        /* Only the search of the root itself tells anyone about its moves */
+        let books = root && !nested;
+        let pvs = self.search_mode == SearchMode::PrincipalVariation;
+        let break_ties = root && (ctx.tied.is_some() || !matches!(self.tie_break, TieBreak::First));
+
+        /* A modelled opponent's node is worth a blend of the exact scores
+         * of all its replies, so nothing is pruned there
+         */
+        let model = ctx.opponent_error.filter(|_| turn == ctx.opponent);
+        let pruning = self.search_mode != SearchMode::PlainMinimax && model.is_none();
+        let exact = break_ties || model.is_some();
+        if model.is_some()
+        {
+            alpha = TimedScore { score: S::MIN, turns: 0 };
+            beta = TimedScore { score: S::MAX, turns: 0 };
+        }
+
+        /* Bounds only settle the search if they fall outside the window */
+        let key = K::of(board, turn);
+        if let Some(score) = self.repetition_score
+        {
+            if ctx.path.contains(&key)
+            {
+                ctx.repetitions += 1;
+                return repetition_stats(score, turn);
+            }
+        }
+        /* Without some of its moves the node isn't the one stored */
+        let partial = excluded.is_some() || root_moves.is_some();
+        let stored = if partial { None } else { self.ttable.get(&key, depth.units()) };
+        if let Some((precomputed_move, bound)) = stored
+        {
+            ctx.stats.tt_hits += 1;
+            if !break_ties && bound.cuts(precomputed_move.score.seen_from_root(ply), alpha, beta)
+            {
+                if let Some(ref mut trace) = ctx.trace
+                {
+                    trace.hit(ply);
+                }
+                /* The nodes below were searched by whoever stored it */
+                return MoveStats {
+                    nodes_visited: 0,
+                    ..precomputed_move
+                };
+            }
+        }
+
+        /* Whatever the moves lead to takes at least one more turn, winning
+         * no sooner and losing no sooner than that. A window with nothing
+         * in between is left at once. The root always gets a move, and
+         * windows that were empty to begin with are searched as before.
+         */
+        if pruning && !root && alpha < beta
+        {
+            let fastest_win = TimedScore { score: S::MAX, turns: 1 };
+            let fastest_loss = TimedScore { score: S::MIN, turns: 1 };
+            if alpha >= fastest_win.seen_from_root(ply)
+            {
+                ctx.stats.mate_distance_cutoffs += 1;
+                return MoveStats { score: fastest_win, ..lost_stats() };
+            }
+            if beta <= fastest_loss.seen_from_root(ply)
+            {
+                ctx.stats.mate_distance_cutoffs += 1;
+                return MoveStats { score: fastest_loss, ..lost_stats() };
+            }
+            beta = beta.min(fastest_win.seen_from_root(ply));
+            alpha = alpha.max(fastest_loss.seen_from_root(ply));
+        }
+        let alpha_orig = alpha;
+
+        /* Far enough below alpha near the horizon, only noisy moves could
+         * save the node, and if they don't it is left failing low
+         */
+        if !root && !on_pv && pruning && !ctx.solving
+        {
+            if let Some(verified) = self.razor(ctx, board, turn, plies, alpha)
+            {
+                if verified.completed && verified.score.seen_from_root(ply) < alpha
+                {
+                    ctx.stats.razored_nodes += 1;
+                    return verified;
+                }
+                best.nodes_visited += verified.nodes_visited;
+            }
+        }
+
+        /* Far enough above beta near the horizon, the node is taken to fail
+         * high on its static score alone
+         */
+        if !root && !on_pv && pruning && !ctx.solving
+        {
+            if let Some(pruned) = self.reverse_futility(ctx, board, turn, plies, beta)
+            {
+                ctx.stats.reverse_futility_prunes += 1;
+                return pruned;
+            }
+        }
+
+        /* If even passing is too good for the opponent to allow, so is the
+         * best move. Not if passing wins, that may only be down to the pass.
+         */
+        if !root && !after_null && pruning && !ctx.solving
+        {
+            if let Some(pass) = self.null_move(ctx, board, turn, depth, alpha, beta)
+            {
+                if pass.completed && pass.score.score != S::MAX && pass.score.seen_from_root(ply) >= beta
+                {
+                    ctx.stats.null_move_cutoffs += 1;
+                    return pass;
+                }
+                best.nodes_visited += pass.nodes_visited;
+            }
+        }
+
+        let mut moves = self.move_buffers.pop().unwrap_or_default();
+        moves.extend(move_iter);
+        if let Some(ref excluded) = excluded
+        {
+            moves.retain(|mv| mv != excluded);
+        }
+        if let Some(ref root_moves) = root_moves
+        {
+            moves.retain(|mv| root_moves.allows(mv));
+        }
+
+        /* Ties are broken by search order, which the table move mustn't
+         * change from one search to the next
+         */
+        board.order_moves(&mut moves, turn);
+        let countermove = match (self.countermoves.as_ref(), last_move.as_ref())
+        {
+            (Some(countermoves), Some(previous)) => countermoves.get(turn, previous).cloned(),
+            _ => None,
+        };
+        if !root && (self.killers.is_some() || self.countermoves.is_some() || self.history.is_some())
+        {
+            heuristics::order(&mut moves, ctx.ply, self.killers.as_ref(), countermove.as_ref(), self.history.as_ref());
+        }
+        /* What refuted the last move before, if it comes up again */
+        let refutation = match (self.refutations.as_ref(), last_move.as_ref())
+        {
+            (Some(refutations), Some(previous)) if !root => refutations.get(turn.other_team(), previous).cloned(),
+            _ => None,
+        };
+        try_first(&mut moves, &refutation);
+        /* A root move left out is no table move for this search */
+        let allowed = |mv: &B::Move| root_moves.as_ref().is_none_or(|root_moves| root_moves.allows(mv));
+        let mut tt_move = if break_ties { None } else { self.table_move(&key).filter(allowed) };
+        if tt_move.is_none() && !break_ties && excluded.is_none()
+        {
+            ctx.root_moves = root_moves.clone();
+            if let Some(shallow) = self.internal_deepening(ctx, board, turn, depth, alpha, beta)
+            {
+                best.nodes_visited += shallow.nodes_visited;
+                tt_move = shallow.mv;
+            }
+            ctx.root_moves = None;
+        }
+        if tt_move.is_none() && !break_ties
+        {
+            self.pre_order(ctx, board, turn, plies, &mut moves);
+        }
+        self.orderer.order(board, turn, &mut moves, plies, tt_move.as_ref());
+        let pv_move = if on_pv { ctx.pv.get(ply as usize).cloned() } else { None };
+        if !break_ties
+        {
+            try_first(&mut moves, &pv_move);
+        }
+        /* The root moves best first by the last search of the position,
+         * those it didn't get to last
+         */
+        if let Some(previous) = ctx.previous_root.as_ref().filter(|_| root && !break_ties)
+        {
+            let score = |mv: &B::Move| previous.iter().find(|(searched, _)| searched == mv).map(|&(_, score)| score);
+            moves.sort_by_cached_key(|mv| Reverse(score(mv)));
+        }
+        /* Beam search keeps the moves that come first */
+        if let Some(width) = if root { ctx.root_branching } else { ctx.branching }
+        {
+            if moves.len() > width
+            {
+                ctx.stats.beam_discarded_moves += (moves.len() - width) as u64;
+                moves.truncate(width);
+                best.exhaustive = false;
+                best.solved = false;
+            }
+        }
+        if books
+        {
+            ctx.root_scores.clear();
+        }
+        /* The check keeps to the root moves too */
+        ctx.root_moves = root_moves.clone();
+        let singular = pruning && !break_ties && excluded.is_none() && self.is_singular(ctx, board, turn, depth, &key, &mut best);
+        ctx.root_moves = None;
+
+        let mut ties = Vec::new();
+        let mut replies = Vec::new();
+        let repetitions = ctx.repetitions;
+        ctx.ply += 1;
+        ctx.path.push(key);
+        let mut pending = moves.drain(..);
+        let mut cut = false;
+        let mut searched = 0;
+        for mv in &mut pending
+        {
+            if ctx.stopped
+            {
+                best.completed = false;
+                best.solved = false;
+                break;
+            }
+
+            /* Telling ties apart takes exact scores, so the window stays wide */
+            let child_alpha = if exact { alpha_orig } else { alpha };
+
+            /* Find the other side's best reply */
+            let mut extension = self.extension(ctx, board, &mv, turn);
+            let left = Depth::from_plies(self.max_extensions).saturating_sub(ctx.extensions);
+            if singular && extension == Depth::ZERO && left > Depth::ZERO && tt_move.as_ref() == Some(&mv)
+            {
+                ctx.stats.singular_extensions += 1;
+                extension = Depth::ONE_PLY.min(left);
+            }
+            let adjustment = self.adjustment(ctx, board, &mv, turn);
+            let mut child_depth = (depth - Depth::ONE_PLY + extension).add_plies(adjustment);
+            if turn.other_team() == ctx.opponent
+            {
+                child_depth = child_depth.saturating_sub(ctx.enemy_depth_penalty);
+            }
+            let on_pv = pv_move.as_ref() == Some(&mv);
+
+            /* Late moves that nothing vouches for are searched less deep
+             * first, and again in full if they beat alpha after all
+             */
+            let mut reduction = match ctx.late_move_reductions
+            {
+                Some(lmr) if pruning && !root && plies >= lmr.min_depth && searched >= lmr.full_moves
+                    && extension == Depth::ZERO && !on_pv && tt_move.as_ref() != Some(&mv) && countermove.as_ref() != Some(&mv)
+                    && self.killers.as_ref().is_none_or(|killers| killers.rank(ply, &mv) == 2)
+                    && !board.is_tactical(&mv, turn) => Depth::from_plies(lmr.reduction).min(child_depth),
+                _ => Depth::ZERO,
+            };
+            if reduction > Depth::ZERO
+            {
+                ctx.stats.reduced_searches += 1;
+            }
+
+            /* After the first move, principal variation search only asks
+             * whether a move beats alpha, and searches it again with the
+             * full window if it does. So do reduced searches.
+             */
+            let mut scout = match best.mv
+            {
+                Some(_) if (pvs || reduction > Depth::ZERO) && !exact => alpha.just_above().filter(|&scout| scout < beta),
+                _ => None,
+            };
+            let mut window = match scout
+            {
+                Some(scout) => {
+                    ctx.stats.zero_window_searches += 1;
+                    (-scout, -alpha)
+                }
+                None => (-beta, -child_alpha),
+            };
+
+            ctx.extensions += extension;
+            ctx.adjustment += adjustment;
+            let reply = loop
+            {
+                if let Some(ref mut trace) = ctx.trace
+                {
+                    trace.moving(ply + 1, &mv);
+                }
+                ctx.on_pv = on_pv;
+                ctx.last_move = Some(mv.clone());
+                let (reply_alpha, reply_beta) = window;
+                let reply_depth = child_depth - reduction;
+                let reply = self.play(board, &mv, |minimax, child|
+                    minimax.negamax(ctx, child, turn.other_team(), reply_depth, reply_alpha, reply_beta));
+
+                /* Beating alpha less deep, it has to be searched in full */
+                let score = (-reply.score).later().seen_from_root(ply);
+                if reduction > Depth::ZERO && reply.completed && score > alpha
+                {
+                    best.nodes_visited += reply.nodes_visited + 1;
+                    ctx.stats.reduction_re_searches += 1;
+                    reduction = Depth::ZERO;
+                    match scout
+                    {
+                        Some(_) if pvs => ctx.stats.zero_window_searches += 1,
+                        _ => {
+                            scout = None;
+                            window = (-beta, -child_alpha);
+                        }
+                    }
+                    continue;
+                }
+
+                /* Failing high short of beta, it only gave a bound */
+                match scout.take()
+                {
+                    Some(scout) if reply.completed && score >= scout && score < beta => {
+                        best.nodes_visited += reply.nodes_visited + 1;
+                        ctx.stats.re_searches += 1;
+                        window = (-beta, -child_alpha);
+                    }
+                    _ => break reply,
+                }
+            };
+            ctx.extensions -= extension;
+            ctx.adjustment -= adjustment;
+            best.nodes_visited += reply.nodes_visited + 1;
+            best.solved &= reply.solved;
+            best.exhaustive &= reply.exhaustive;
+            searched += 1;
+
+            /* A reply that was cut short says nothing about this move */
+            if !reply.completed
+            {
+                best.completed = false;
+                break;
+            }
+            best.extended_depth = best.extended_depth.max(reply.extended_depth + 1);
+
+            let score = (-reply.score).later();
+            self.learn_refutation(ctx, turn, &mv, &reply, score.seen_from_root(ply) <= alpha);
+            if model.is_some()
+            {
+                replies.push(score);
+            }
+            if books
+            {
+                let ally_score = match turn
+                {
+                    Team::Ally => score,
+                    Team::Enemy => -score,
+                };
+                ctx.observer.on_root_move_searched(&mv, ally_score, reply.nodes_visited + 1);
+                ctx.root_scores.push((mv.clone(), ally_score));
+                ctx.poll();
+            }
+
+            if best.mv.is_none() || score > best.score
+            {
+                ties.clear();
+                best.mv = Some(mv);
+                best.score = score;
+                best.mvs = reply.mvs;
+            }
+            else if break_ties && score.cmp(&best.score) == Ordering::Equal
+            {
+                ties.push((mv, reply.mvs));
+            }
+
+            /* Set α and break on β ≤ α */
+            if pruning && best.score.seen_from_root(ply) > alpha
+            {
+                alpha = best.score.seen_from_root(ply);
+                ctx.stats.alpha_improvements += 1;
+            }
+            if pruning && alpha >= beta
+            {
+                ctx.stats.beta_cutoffs += 1;
+                if searched == 1
+                {
+                    ctx.stats.first_move_cutoffs += 1;
+                }
+                self.learn_cutoff(ply, plies, turn, last_move.as_ref(), &best);
+                cut = true;
+                break;
+            }
+        }
+        match ctx.trace
+        {
+            Some(ref mut trace) if cut => trace.prune(ply + 1, turn.other_team(), pending),
+            _ => drop(pending),
+        }
+
+        ctx.ply -= 1;
+        let key = ctx.path.pop().unwrap();
+        self.move_buffers.push(moves);
+        if let Some(ref mut tied) = ctx.tied
+        {
+            if books
+            {
+                tied.extend(best.mv.iter().cloned());
+                tied.extend(ties.iter().map(|tie| tie.0.clone()));
+            }
+        }
+        self.break_tie(&mut best, ties);
+        if let Some(model) = model
+        {
+            if best.completed
+            {
+                best.score.score = blend(replies, model);
+            }
+        }
+
+        /* Nothing worth remembering if no move was settled on, and nothing
+         * that only holds on this path
+         */
+        if let Some(ref mv) = best.mv
+        {
+            best.mvs.insert(0, mv.clone());
+            if best.completed && ctx.repetitions == repetitions && !partial
+            {
+                let bound = Bound::of(best.score.seen_from_root(ply), alpha_orig, beta);
+                self.ttable.insert(key, best.clone(), depth.units(), bound);
+            }
+        }
+
+        best
+    }
+
+    /// Searches a node the table has no move for less deep, to find one to
+    /// try first, keeping to the root moves it takes from `ctx`. Returns
+    /// `None` if internal iterative deepening is off, or the node isn't
+    /// deep enough or has a zero-width window.
+    fn internal_deepening(&mut self, ctx: &mut SearchContext<B, S, K>, board: &mut B, turn: Team, depth: Depth, alpha: TimedScore<S>, beta: TimedScore<S>) -> Option<MoveStats<B::Move, S>>
+    {
+        let root_moves = ctx.root_moves.take();
+        let plies = depth.plies();
+        let reduction = match ctx.internal_deepening
+        {
+            Some(iid) if plies >= iid.min_depth && plies > 1 => Depth::from_plies(iid.reduction.clamp(1, plies - 1)),
+            _ => return None,
+        };
+        if alpha.just_above().is_some_and(|above| above >= beta)
+        {
+            return None;
+        }
+
+        /* Like the singular extension check, it isn't part of the tree a
+         * trace shows, and it keeps to the moves the node keeps to
+         */
+        let trace = ctx.trace.take();
+        ctx.root_moves = root_moves;
+        ctx.nested = true;
+        let shallow = self.negamax_node(ctx, board, turn, depth - reduction, alpha, beta);
+        ctx.trace = trace;
+
+        ctx.stats.internal_deepenings += 1;
+        Some(shallow)
+    }
+
+    /// Sorts the moves of a node `plies` above the horizon by the static
+    /// score of the position after each, best for `turn` first, for
+    /// pre-ordering. Moves scoring the same keep their order. Does nothing
+    /// if pre-ordering is off or the node isn't deep enough.
+    fn pre_order(&mut self, ctx: &mut SearchContext<B, S, K>, board: &mut B, turn: Team, plies: u32, moves: &mut Vec<B::Move>)
+    {
+        match ctx.pre_ordering
+        {
+            Some(min_depth) if plies >= min_depth => {}
+            _ => return,
+        }
+
+        let draw = ctx.draw;
+        let mut scored: Vec<_> = moves.drain(..)
+            .map(|mv|
+            {
+                let after = self.play(board, &mv, |_, child| static_stats(child, turn.other_team(), draw));
+                (Reverse(-after.score), mv)
+            })
+            .collect();
+        ctx.stats.pre_ordering_nodes += scored.len() as u64;
+        scored.sort_by_key(|&(score, _)| score);
+        moves.extend(scored.into_iter().map(|(_, mv)| mv));
+    }
+
+    /// Whether the table move of a node `depth` above the horizon is the
+    /// only good one there, for singular extensions. The node is searched
+    /// again, half as deep and without the table move, with a zero-width
+    /// window below the stored score, and the move is singular if that
+    /// search fails low. Takes the root moves to keep to from `ctx`. The
+    /// nodes it takes are added to `best`.
+    fn is_singular(&mut self, ctx: &mut SearchContext<B, S, K>, board: &mut B, turn: Team, depth: Depth, key: &K, best: &mut MoveStats<B::Move, S>) -> bool
+    {
+        let root_moves = ctx.root_moves.take();
+        let margin = match ctx.singular_extensions
+        {
+            Some(se) if depth.plies() >= se.min_depth => se.margin,
+            _ => return false,
+        };
+        let (mv, below) = match self.ttable.peek_with_bound(key)
+        {
+            Some((stats, stored, bound)) if Depth::from_units(stored) + Depth::from_plies(3) >= depth && bound != Bound::UpperBound => {
+                let below = stats.score.score.add_margin(margin.saturating_neg()).map(|score| TimedScore {
+                    score,
+                    turns: stats.score.turns,
+                });
+                (stats.mv.clone(), below)
+            }
+            _ => return false,
+        };
+        let beta = match (mv.is_some(), below)
+        {
+            (true, Some(below)) => below.seen_from_root(ctx.ply),
+            _ => return false,
+        };
+        let alpha = match beta.just_below()
+        {
+            Some(alpha) => alpha,
+            None => return false,
+        };
+
+        /* The check isn't part of the tree a trace shows, and it keeps to
+         * the moves the node keeps to
+         */
+        let trace = ctx.trace.take();
+        ctx.excluded = mv;
+        ctx.root_moves = root_moves;
+        ctx.nested = true;
+        let verified = self.negamax_node(ctx, board, turn, depth.half(), alpha, beta);
+        ctx.trace = trace;
+
+        best.nodes_visited += verified.nodes_visited;
+        verified.completed && verified.score.seen_from_root(ctx.ply) < beta
+    }
+
+    /// Tells the killer moves, the countermoves and the history table that
+    /// the move of `best` caused a cutoff `ply` plies from the root and
+    /// `plies` above the horizon, `turn` replying to `previous`.
+    fn learn_cutoff(&mut self, ply: u32, plies: u32, turn: Team, previous: Option<&B::Move>, best: &MoveStats<B::Move, S>)
+    {
+        /* The root is never reordered, so it has nothing to learn */
+        if ply == 0
+        {
+            return;
+        }
+
+        if let Some(ref mv) = best.mv
+        {
+            if let Some(ref mut killers) = self.killers
+            {
+                killers.record(ply, mv);
+            }
+            if let (Some(countermoves), Some(previous)) = (self.countermoves.as_mut(), previous)
+            {
+                countermoves.record(turn, previous, mv);
+            }
+            if let Some(ref mut history) = self.history
+            {
+                history.record(plies, mv);
+            }
+        }
+    }
+
+    /// Tells the refutation table how `reply` went after `mv` of `turn`,
+    /// which failed low if `failed_low`.
+    fn learn_refutation(&mut self, ctx: &mut SearchContext<B, S, K>, turn: Team, mv: &B::Move, reply: &MoveStats<B::Move, S>, failed_low: bool)
+    {
+        let refutations = match self.refutations
+        {
+            Some(ref mut refutations) => refutations,
+            None => return,
+        };
+
+        if let Some(stored) = refutations.get(turn, mv)
+        {
+            ctx.stats.refutation_probes += 1;
+            if failed_low && reply.mv.as_ref() == Some(stored)
+            {
+                ctx.stats.refutation_hits += 1;
+            }
+        }
+        if let (true, Some(refuter)) = (failed_low, reply.mv.as_ref())
+        {
+            refutations.record(turn, mv, refuter);
+        }
+    }
+
+    /// Hands the move to the other side without changing the board. A pass
+    /// has no move to show for it, so the principal variation ends here.
+    fn pass(&mut self, ctx: &mut SearchContext<B, S, K>, board: &mut B, turn: Team, depth: Depth, alpha: TimedScore<S>, beta: TimedScore<S>) -> MoveStats<B::Move, S>
+    {
+        ctx.ply += 1;
+        ctx.path.push(K::of(board, turn));
+        let reply = self.negamax(ctx, board, turn.other_team(), depth - Depth::ONE_PLY, -beta, -alpha);
+        ctx.path.pop();
+        ctx.ply -= 1;
+
+        MoveStats {
+            mv: None,
+            score: (-reply.score).later(),
+            nodes_visited: reply.nodes_visited + 1,
+            mvs: Vec::new(),
+            depth_reached: 0,
+            extended_depth: reply.extended_depth + 1,
+            seldepth: 0,
+            exhaustive: reply.exhaustive,
+            completed: reply.completed,
+            solved: reply.solved,
+        }
+    }
+
+    /// How much to extend the search below `mv` by, within what is left
+    /// of `max_extensions` on the line in `ctx`.
+    fn extension(&self, ctx: &SearchContext<B, S, K>, board: &B, mv: &B::Move, turn: Team) -> Depth
+    {
+        match Depth::from_plies(self.max_extensions).saturating_sub(ctx.extensions)
+        {
+            Depth::ZERO => Depth::ZERO,
+            left => board.fractional_extension(mv, turn).min(left),
+        }
+    }
+
+    /// The static score of a node `plies` above the horizon if reverse
+    /// futility pruning leaves it failing high there, which takes the
+    /// score to reach `beta` even with the margin for `plies` taken off.
+    fn reverse_futility(&self, ctx: &SearchContext<B, S, K>, board: &B, turn: Team, plies: u32, beta: TimedScore<S>) -> Option<MoveStats<B::Move, S>>
+    {
+        let schedule = ctx.reverse_futility.filter(|schedule| plies <= schedule.max_depth)?;
+
+        /* Beating a win or a loss takes more than a static score */
+        beta.score.add_margin(0)?;
+        if board.is_tactical_position(turn)
+        {
+            return None;
+        }
+
+        let stand = static_stats::<B, S>(board, turn, ctx.draw);
+        let lowered = TimedScore {
+            score: stand.score.score.add_margin(schedule.margin.saturating_mul(plies.min(i32::MAX as u32) as i32).saturating_neg())?,
+            turns: stand.score.turns,
+        };
+        if lowered.seen_from_root(ctx.ply) >= beta
+        {
+            Some(MoveStats { solved: false, ..stand })
+        }
+        else
+        {
+            None
+        }
+    }
+
+    /// Whether delta pruning skips the noisy move `mv`: even its
+    /// `Board::move_gain_upper_bound` on top of `stand`, the static score
+    /// of the side to move, doesn't lift it above `alpha`. Never with a win
+    /// or a loss on either side of the comparison.
+    fn delta_prunes(&self, board: &B, mv: &B::Move, stand: TimedScore<S>, ply: u32, alpha: TimedScore<S>) -> bool
+    {
+        if alpha.score.add_margin(0).is_none()
+        {
+            return false;
+        }
+        let raised = board.move_gain_upper_bound(mv)
+            .and_then(|gain| stand.score.add_margin(gain))
+            .map(|score| TimedScore { score, turns: stand.turns });
+        raised.is_some_and(|raised| raised.seen_from_root(ply) <= alpha)
+    }
+
+    /// How many plies `Board::depth_adjustment` moves the line below `mv`
+    /// by, kept so the line's adjustments add up to no more than
+    /// `max_depth_adjustment` either way.
+    fn adjustment(&self, ctx: &SearchContext<B, S, K>, board: &B, mv: &B::Move, turn: Team) -> i32
+    {
+        let cap = self.max_depth_adjustment.min(i32::MAX as u32) as i32;
+        let total = ctx.adjustment.saturating_add(board.depth_adjustment(mv, turn)).clamp(-cap, cap);
+        total - ctx.adjustment
+    }
+
+    /// Quiescence search of a node `plies` above the horizon that razoring
+    /// might leave, asking only whether it reaches `alpha`. Returns `None`
+    /// if the node isn't worth checking: with no margin for `plies`, with
+    /// a static score close enough to alpha, or with a win or a loss on
+    /// either side of the comparison.
+    fn razor(&mut self, ctx: &mut SearchContext<B, S, K>, board: &B, turn: Team, plies: u32, alpha: TimedScore<S>) -> Option<MoveStats<B::Move, S>>
+    {
+        let margin = *self.razoring_margins.get(plies as usize - 1)?;
+
+        /* A win or a loss in hand could still be beaten by a faster one */
+        alpha.score.add_margin(0)?;
+
+        let stand = static_stats::<B, S>(board, turn, ctx.draw).score;
+        let raised = TimedScore {
+            score: stand.score.add_margin(margin)?,
+            turns: stand.turns,
+        };
+        if raised.seen_from_root(ctx.ply) >= alpha
+        {
+            return None;
+        }
+
+        let beta = alpha.just_above().unwrap_or(TimedScore { score: S::MAX, turns: 0 });
+        let depth = self.quiescence_depth;
+        Some(self.quiesce(ctx, &mut board.clone(), turn, depth, alpha, beta))
+    }
+
+    /// Searches `turn` passing with a null move, `null_move_reduction`
+    /// plies shallower than its moves would be. Returns `None` if the pass
+    /// isn't worth trying: with too few plies left, a static score below
+    /// beta, or a board that won't pass.
+    fn null_move(&mut self, ctx: &mut SearchContext<B, S, K>, board: &B, turn: Team, depth: Depth, alpha: TimedScore<S>, beta: TimedScore<S>) -> Option<MoveStats<B::Move, S>>
+    {
+        let reduced = match depth.checked_sub(Depth::from_plies(self.null_move_reduction + 1))
+        {
+            Some(reduced) if reduced >= Depth::ONE_PLY => reduced,
+            _ => return None,
+        };
+
+        let ply = ctx.ply;
+        if static_stats::<B, S>(board, turn, ctx.draw).score.seen_from_root(ply) < beta
+        {
+            return None;
+        }
+
+        let mut child = board.clone();
+        if !child.do_null_move()
+        {
+            return None;
+        }
+
+        ctx.ply += 1;
+        ctx.path.push(K::of(board, turn));
+        ctx.after_null = true;
+        let reply = self.negamax(ctx, &mut child, turn.other_team(), reduced, -beta, -alpha);
+        ctx.path.pop();
+        ctx.ply -= 1;
+
+        Some(MoveStats {
+            mv: None,
+            score: (-reply.score).later(),
+            nodes_visited: reply.nodes_visited + 1,
+            mvs: Vec::new(),
+            depth_reached: 0,
+            extended_depth: reply.extended_depth + 1,
+            seldepth: 0,
+            exhaustive: reply.exhaustive,
+            completed: reply.completed,
+            /* Passing isn't a move of the game */
+            solved: false,
+        })
+    }
+
+    /// Quiescence search below the horizon.
+    ///
+    /// `turn` can stand pat on the static score of `board` or try one of the
+    /// board's noisy moves, at most `depth` more plies down. `board` must
+    /// have moves and not be game over.
+    fn quiesce(&mut self, ctx: &mut SearchContext<B, S, K>, board: &mut B, turn: Team, depth: u32, mut alpha: TimedScore<S>, beta: TimedScore<S>) -> MoveStats<B::Move, S>
+    {
+        if ctx.enter()
+        {
+            return stopped_stats();
+        }
+
+        /* Standing pat, the side to move may decline every noisy move */
+        let ply = ctx.ply;
+        let pruning = self.search_mode != SearchMode::PlainMinimax;
+        /* Standing pat is a guess, whatever the noisy moves come to */
+        let mut best = MoveStats { solved: false, ..self.evaluate(board, turn, ctx.draw) };
+        if depth == 0
+        {
+            return best;
+        }
+        if pruning && best.score.seen_from_root(ply) >= beta
+        {
+            ctx.stats.beta_cutoffs += 1;
+            return best;
+        }
+
+        let moves = board.gen_quiescence_moves(turn);
+        let stand = best.score;
+        if pruning && best.score.seen_from_root(ply) > alpha
+        {
+            alpha = best.score.seen_from_root(ply);
+            ctx.stats.alpha_improvements += 1;
+        }
+
+        for (searched, mv) in moves.into_iter().enumerate()
+        {
+            if pruning && self.delta_prunes(board, &mv, stand, ply, alpha)
+            {
+                ctx.stats.delta_prunes += 1;
+                continue;
+            }
+
+            let other = turn.other_team();
+            let reply = self.play(board, &mv, |minimax, child|
+            {
+                if child.is_game_over()
+                {
+                    minimax.evaluate(child, other, ctx.draw)
+                }
+                else if child.moves(other).next().is_none()
+                {
+                    /* Passing wouldn't settle anything, so it stands pat */
+                    match child.on_no_moves(other)
+                    {
+                        NoMovePolicy::Lose => lost_stats(),
+                        NoMovePolicy::Pass | NoMovePolicy::ScoreBoard => minimax.evaluate(child, other, ctx.draw),
+                    }
+                }
+                else
+                {
+                    ctx.ply += 1;
+                    let reply = minimax.quiesce(ctx, child, other, depth - 1, -beta, -alpha);
+                    ctx.ply -= 1;
+                    reply
+                }
+            });
+            best.nodes_visited += reply.nodes_visited + 1;
+            ctx.stats.quiescence_nodes += 1;
+
+            if !reply.completed
+            {
+                best.completed = false;
+                break;
+            }
+
+            let score = (-reply.score).later();
+            if score > best.score
+            {
+                best.mv = Some(mv);
+                best.score = score;
+                best.mvs = reply.mvs;
+            }
+
+            if pruning && best.score.seen_from_root(ply) > alpha
+            {
+                alpha = best.score.seen_from_root(ply);
+                ctx.stats.alpha_improvements += 1;
+            }
+            if pruning && alpha >= beta
+            {
+                ctx.stats.beta_cutoffs += 1;
+                if searched == 0
+                {
+                    ctx.stats.first_move_cutoffs += 1;
+                }
+                break;
+            }
+        }
+
+        if let Some(ref mv) = best.mv
+        {
+            best.mvs.insert(0, mv.clone());
+        }
+
+        best
+    }
+
+    /// Swaps the move of `best` for one of `ties` if the tie break policy
+    /// says so. `ties` are the other moves scoring the same as `best`, in
+    /// search order, with their principal variations.
+    fn break_tie(&self, best: &mut MoveStats<B::Move, S>, mut ties: Vec<(B::Move, Vec<B::Move>)>)
+    {
+        let first = match best.mv.take()
+        {
+            Some(mv) if !ties.is_empty() => mv,
+            mv => {
+                best.mv = mv;
+                return;
+            }
+        };
+        ties.insert(0, (first, mem::take(&mut best.mvs)));
+
+        let pick = match self.tie_break
+        {
+            TieBreak::First => 0,
+            TieBreak::Random(seed) => XorShift::new(seed).below(ties.len() as u64) as usize,
+            TieBreak::Custom(pick) => {
+                let moves: Vec<B::Move> = ties.iter().map(|tie| tie.0.clone()).collect();
+                pick(&moves)
+            }
+        };
+
+        let (mv, mvs) = ties.swap_remove(pick);
+        best.mv = Some(mv);
+        best.mvs = mvs;
+    }
+
+    /// Plays `mv` on `board` and hands the resulting position to `f`.
+    ///
+    /// Boards that support it get the move undone afterwards. Others are
+    /// cloned, so `board` itself is never touched.
+    fn play<F>(&mut self, board: &mut B, mv: &B::Move, f: F) -> MoveStats<B::Move, S>
+        where F: FnOnce(&mut Self, &mut B) -> MoveStats<B::Move, S>
+    {
+        if let Some(undo_move) = B::UNDO
+        {
+            board.do_move(mv);
+            let reply = f(self, board);
+            undo_move(board, mv);
+            reply
+        }
+        else
+        {
+            /* Make a clone of the board so we don't break this one */
+            let mut board_clone = board.clone();
+            board_clone.do_move(mv);
+            f(self, &mut board_clone)
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests
+{
+    use super::{Team, Score, OrderedF32, GameScore, TimedScore, Minimax, MoveStats, SearchTable, PV_ESTIMATE, MovePick, RootScore, BoundResult, ProbeResult, PonderHandle, PonderResult, SearchResult, SolveError, Verification, TieBreak, SearchMode, SearchStats, SearchContext, try_first};
+    use heuristics::RefutationTable;
+    use options::{SearchOptions, LateMoveReductions, SingularExtensions, InternalDeepening, ReverseFutility};
+    use clock::Clock;
+    use trace::{SearchTrace, TraceNode, RootMove};
+    use testing::{self, GameTree, RandomTree, Branch};
+    use std::cmp::{Ordering, Reverse};
+    use std::cell::Cell;
+    use std::sync::Arc;
+    use std::sync::atomic::{self, AtomicU64, AtomicUsize};
+    use std::thread;
+    use std::mem;
+    use std::hash::{Hash, Hasher};
+    use board::{Board, NoMovePolicy, UndoBoard, ZobristBoard};
+    use transposition_table::{TranspositionTable, TableStats, Bound, ReplacementPolicy, PositionKey, ZobristKey, CheckedZobristKey, HashOnlyTable, STALE_AFTER};
+    use observer::{SearchObserver, NoObserver, NODES_PER_POLL};
+    use ordering::{MoveOrderer, Identity, TableMoveFirst};
+    use depth::Depth;
+    use maxn::{MaxN, MultiPlayerBoard};
+    use mcts::{Mcts, Budget};
+    use pns::{self, ProofSearch, ProofResult};
+    use reference;
+    use codec::{Encode, Decode};
+    use std::io::{self, Read, Write};
+    use std::num::NonZeroUsize;
+    use std::time::Duration;
+
+    #[derive(Clone,PartialEq,Eq,Debug)]
+    struct SimpleMove(usize);
+    #[derive(Clone,PartialEq,Eq,Hash,Debug)]
+    enum SimpleBoard
+    {
+        Node(Vec<SimpleBoard>),
+        Leaf(Score),
+    }
+
+    /* These aren't really efficient, but they
+     * work for testing.
+     */
+    impl Board for SimpleBoard
+    {
+        type Move = SimpleMove;
+
+        fn gen_ally_moves(&self) -> Vec<Self::Move>
+        {
+            match *self
+            {
+                SimpleBoard::Node(ref v) =>
+                    (0..v.len()).map(SimpleMove).collect(),
+                SimpleBoard::Leaf(_) =>
+                    /* Trick the algorithm into thinking
+                     * we still have moves left
+                     */
+                    vec![SimpleMove(1)],
+            }
+        }
+
+        fn gen_enemy_moves(&self) -> Vec<Self::Move>
+        {
+            match *self
+            {
+                SimpleBoard::Node(ref v) =>
+                    (0..v.len()).map(SimpleMove).collect(),
+                SimpleBoard::Leaf(_) =>
+                    /* Trick the algorithm into thinking
+                     * we still have moves left
+                     */
+                    vec![SimpleMove(1)],
+            }
+        }
+
+        fn do_move(&mut self, mv: &Self::Move)
+        {
+            let child = match *self
+            {
+                SimpleBoard::Node(ref v) =>
+                    Some(v[mv.0].clone()),
+                SimpleBoard::Leaf(_) =>
+                    None,
+            };
+
+            if let Some(c) = child
+            {
+                *self = c;
+            }
+        }
+
+        fn score(&self) -> Score
+        {
+            match *self
+            {
+                /* Only reached when searching shallower
+                 * than the tree goes.
+                 */
+                SimpleBoard::Node(_) =>
+                    Score::Heuristic(0),
+                SimpleBoard::Leaf(s) =>
+                    s,
+            }
+        }
+
+        fn is_game_over(&self) -> bool
+        {
+            match *self
+            {
+                SimpleBoard::Node(_) =>
+                    false,
+                SimpleBoard::Leaf(_) =>
+                    true,
+            }
+        }
+
+        fn approx_heap_size(&self) -> usize
+        {
+            match *self
+            {
+                SimpleBoard::Node(ref children) =>
+                    children.iter().map(|child| mem::size_of::<SimpleBoard>() + child.approx_heap_size()).sum(),
+                SimpleBoard::Leaf(_) =>
+                    0,
+            }
+        }
+    }
+
+    fn game1() -> SimpleBoard
+    {
+        SimpleBoard::Node(vec![
+            SimpleBoard::Node(vec![
+                SimpleBoard::Node(vec![
+                    SimpleBoard::Node(vec![
+                        SimpleBoard::Leaf(Score::Heuristic(5)),
+                        SimpleBoard::Leaf(Score::Heuristic(6)),
+                    ]),
+                    SimpleBoard::Node(vec![
+                        SimpleBoard::Leaf(Score::Heuristic(7)),
+                        SimpleBoard::Leaf(Score::Heuristic(4)),
+                        SimpleBoard::Leaf(Score::Heuristic(5)),
+                    ]),
+                ]),
+                SimpleBoard::Node(vec![
+                    SimpleBoard::Node(vec![
+                        SimpleBoard::Leaf(Score::Heuristic(3)),
+                    ]),
+                ]),
+            ]),
+            SimpleBoard::Node(vec![
+                SimpleBoard::Node(vec![
+                    SimpleBoard::Node(vec![
+                        SimpleBoard::Leaf(Score::Heuristic(6)),
+                    ]),
+                    SimpleBoard::Node(vec![
+                        SimpleBoard::Leaf(Score::Heuristic(6)),
+                        SimpleBoard::Leaf(Score::Heuristic(9)),
+                    ]),
+                ]),
+                SimpleBoard::Node(vec![
+                    SimpleBoard::Node(vec![
+                        SimpleBoard::Leaf(Score::Heuristic(7)),
+                    ]),
+                ]),
+            ]),
+            SimpleBoard::Node(vec![
+                SimpleBoard::Node(vec![
+                    SimpleBoard::Node(vec![
+                        SimpleBoard::Leaf(Score::Heuristic(5)),
+                    ]),
+                ]),
+                SimpleBoard::Node(vec![
+                    SimpleBoard::Node(vec![
+                        SimpleBoard::Leaf(Score::Heuristic(9)),
+                        SimpleBoard::Leaf(Score::Heuristic(8)),
+                    ]),
+                    SimpleBoard::Node(vec![
+                        SimpleBoard::Leaf(Score::Heuristic(6)),
+                    ]),
+                ]),
+            ]),
+        ])
+    }
+
+    fn game2() -> SimpleBoard
+    {
+        SimpleBoard::Node(vec![
+            SimpleBoard::Node(vec![
+                SimpleBoard::Node(vec![
+                    SimpleBoard::Node(vec![
+                        SimpleBoard::Leaf(Score::Heuristic(12)),
+                        SimpleBoard::Leaf(Score::Heuristic(10)),
+                    ]),
+                    SimpleBoard::Node(vec![
+                        SimpleBoard::Leaf(Score::Heuristic(-18)),
+                        SimpleBoard::Leaf(Score::Heuristic(-7)),
+                    ]),
+                ]),
+                SimpleBoard::Node(vec![
+                    SimpleBoard::Node(vec![
+                        SimpleBoard::Leaf(Score::Heuristic(6)),
+                        SimpleBoard::Leaf(Score::Heuristic(-17)),
+                    ]),
+                    SimpleBoard::Node(vec![
+                        SimpleBoard::Leaf(Score::Heuristic(-3)),
+                        SimpleBoard::Leaf(Score::Heuristic(6)),
+                    ]),
+                ]),
+            ]),
+            SimpleBoard::Node(vec![
+                SimpleBoard::Node(vec![
+                    SimpleBoard::Node(vec![
+                        SimpleBoard::Leaf(Score::Heuristic(-19)),
+                        SimpleBoard::Leaf(Score::Heuristic(-16)),
+                    ]),
+                    SimpleBoard::Node(vec![
+                        SimpleBoard::Leaf(Score::Heuristic(-4)),
+                        SimpleBoard::Leaf(Score::Heuristic(-6)),
+                    ]),
+                ]),
+                SimpleBoard::Node(vec![
+                    SimpleBoard::Node(vec![
+                        SimpleBoard::Leaf(Score::Heuristic(-1)),
+                        SimpleBoard::Leaf(Score::Heuristic(6)),
+                    ]),
+                    SimpleBoard::Node(vec![
+                        SimpleBoard::Leaf(Score::Heuristic(9)),
+                        SimpleBoard::Leaf(Score::Heuristic(15)),
+                    ]),
+                ]),
+            ]),
+        ])
+    }
+
+    #[test]
+    fn test_score_ord()
+    {
+        assert!(Score::Win > Score::Heuristic(0));
+        assert!(Score::Heuristic(0) > Score::Lose);
+        assert!(Score::Win > Score::Lose);
+        assert!(Score::Heuristic(100) > Score::Heuristic(0));
+        assert!(Score::Heuristic(0) > Score::Heuristic(-100));
+        assert!(Score::Win == Score::Win);
+        assert!(Score::Lose == Score::Lose);
+        assert!(Score::Heuristic(0) == Score::Heuristic(0));
+    }
+
+    #[test]
+    fn test_timed_score_ord()
+    {
+        let win_fast = TimedScore { score: Score::Win, turns: 1 };
+        let win_slow = TimedScore { score: Score::Win, turns: 5 };
+        let lose_fast = TimedScore { score: Score::Lose, turns: 1 };
+        let lose_slow = TimedScore { score: Score::Lose, turns: 5 };
+        let even = TimedScore { score: Score::Heuristic(0), turns: 1 };
+        let even_later = TimedScore { score: Score::Heuristic(0), turns: 5 };
+
+        assert!(win_fast > win_slow);
+        assert!(lose_slow > lose_fast);
+        assert!(win_slow > even);
+        assert!(even > lose_slow);
+        assert_eq!(even.cmp(&even_later), ::std::cmp::Ordering::Equal);
+    }
+
+    #[test]
+    fn test_minimax()
+    {
+        let mut minimax = Minimax::new(NonZeroUsize::new(100).unwrap());
+        let game1 = game1();
+        let game2 = game2();
+
+        println!();
+        println!("Game 1");
+        assert_eq!(game1.gen_ally_moves(), vec![SimpleMove(0), SimpleMove(1), SimpleMove(2)]);
+        let move_stats1 = minimax.minimax(&game1, Team::Ally, 4);
+        let optimal_move1 = MoveStats {
+            mv: Some(SimpleMove(1)),
+            score: TimedScore {
+                score: Score::Heuristic(6),
+                turns: 4,
+            },
+            nodes_visited: 25,
+            mvs: vec![SimpleMove(1), SimpleMove(0), SimpleMove(0), SimpleMove(0)],
+            depth_reached: 4,
+            extended_depth: 4,
+            seldepth: 4,
+            exhaustive: true,
+            completed: true,
+            solved: true,
+        };
+        assert_eq!(move_stats1, optimal_move1);
+
+        println!();
+        println!("Game 2");
+        assert_eq!(game2.gen_ally_moves(), vec![SimpleMove(0), SimpleMove(1)]);
+        let move_stats2 = minimax.minimax(&game2, Team::Ally, 4);
+        let optimal_move2 = MoveStats {
+            mv: Some(SimpleMove(0)),
+            score: TimedScore {
+                score: Score::Heuristic(-3),
+                turns: 4,
+            },
+            nodes_visited: 21,
+            mvs: vec![SimpleMove(0), SimpleMove(1), SimpleMove(1), SimpleMove(0)],
+            depth_reached: 4,
+            extended_depth: 4,
+            seldepth: 4,
+            exhaustive: true,
+            completed: true,
+            solved: true,
+        };
+        assert_eq!(move_stats2, optimal_move2);
+
+        println!();
+        println!("Testing caching");
+        assert_eq!(game2.gen_ally_moves(), vec![SimpleMove(0), SimpleMove(1)]);
+        let move_stats2 = minimax.minimax(&game2, Team::Ally, 4);
+        /* Only the root is visited, the table has the rest */
+        let optimal_move2 = MoveStats {
+            mv: Some(SimpleMove(0)),
+            score: TimedScore {
+                score: Score::Heuristic(-3),
+                turns: 4,
+            },
+            nodes_visited: 1,
+            mvs: vec![SimpleMove(0), SimpleMove(1), SimpleMove(1), SimpleMove(0)],
+            depth_reached: 4,
+            extended_depth: 4,
+            seldepth: 0,
+            exhaustive: true,
+            completed: true,
+            solved: true,
+        };
+        assert_eq!(move_stats2, optimal_move2);
+    }
+
+    #[test]
+    fn test_nodes_visited_with_table()
+    {
+        for &turn in [Team::Ally, Team::Enemy].iter()
+        {
+            let mut minimax = Minimax::new(NonZeroUsize::new(100).unwrap());
+            let cold = minimax.minimax(&game1(), turn, 4);
+
+            /* Asked again, the root is all there is to it */
+            for _ in 0..3
+            {
+                let again = minimax.minimax(&game1(), turn, 4);
+                assert_eq!((&again.mv, again.score, &again.mvs), (&cold.mv, cold.score, &cold.mvs));
+                assert_eq!(again.nodes_visited, 1);
+                assert_eq!(minimax.search_stats().tt_hits, 1);
+            }
+
+            /* With one root move already searched only the others count,
+             * just as a node limit sees them
+             */
+            let warm = ||
+            {
+                let mut child = game1();
+                child.do_move(&SimpleMove(1));
+                let mut minimax = Minimax::new(NonZeroUsize::new(100).unwrap());
+                minimax.minimax(&child, turn.other_team(), 3);
+                minimax
+            };
+            let deeper = warm().minimax(&game1(), turn, 4);
+            assert!(deeper.nodes_visited < cold.nodes_visited);
+            let limited = |nodes| warm().search(&game1(), turn, &SearchOptions::depth(4).max_nodes(nodes).build());
+            assert_eq!(limited(deeper.nodes_visited), deeper);
+            assert!(!limited(deeper.nodes_visited - 1).completed);
+        }
+    }
+
+    #[test]
+    fn test_zero_plies()
+    {
+        for &turn in [Team::Ally, Team::Enemy].iter()
+        {
+            let one_ply = Minimax::new(NonZeroUsize::new(100).unwrap()).minimax(&game1(), turn, 1);
+            assert!(one_ply.mv.is_some());
+            assert_eq!(Minimax::new(NonZeroUsize::new(100).unwrap()).minimax(&game1(), turn, 0), one_ply);
+            assert_eq!(Minimax::new(NonZeroUsize::new(100).unwrap()).try_minimax(&game1(), turn, 0), SearchResult::BestMove(one_ply.clone()));
+
+            let multi = Minimax::new(NonZeroUsize::new(100).unwrap()).minimax_multi(&game1(), turn, 0, 3);
+            assert_eq!(multi.len(), 3);
+            assert_eq!(multi[0], one_ply);
+            let lose = TimedScore { score: Score::Lose, turns: 0 };
+            let win = TimedScore { score: Score::Win, turns: 0 };
+            let windowed = Minimax::new(NonZeroUsize::new(100).unwrap()).minimax_windowed(&game1(), turn, 0, lose, win);
+            assert_eq!(windowed.stats, one_ply);
+        }
+    }
+
+    #[test]
+    fn test_try_minimax()
+    {
+        for &turn in [Team::Ally, Team::Enemy].iter()
+        {
+            let mut minimax = Minimax::new(NonZeroUsize::new(100).unwrap());
+            let stats = Minimax::new(NonZeroUsize::new(100).unwrap()).minimax(&game1(), turn, 4);
+            assert_eq!(minimax.try_minimax(&game1(), turn, 4), SearchResult::BestMove(stats));
+
+            /* Scores stay the ally's, whoever is to move */
+            assert_eq!(minimax.try_minimax(&SimpleBoard::Leaf(Score::Win), turn, 4), SearchResult::GameAlreadyOver(Score::Win));
+            assert_eq!(minimax.try_minimax(&SimpleBoard::Node(vec![]), turn, 4), SearchResult::NoLegalMoves);
+        }
+    }
+
+    #[test]
+    fn test_search_moves()
+    {
+        let opts = SearchOptions::depth(4).build();
+        let only = |found: SearchResult<SimpleMove>| match found
+        {
+            SearchResult::BestMove(stats) => stats,
+            other => panic!("{:?}", other),
+        };
+
+        /* Without move 1, the ally does best with move 2 */
+        let mut minimax = Minimax::new(NonZeroUsize::new(1000).unwrap());
+        let stats = only(minimax.search_moves(&game1(), Team::Ally, &opts, &[SimpleMove(0), SimpleMove(2), SimpleMove(9)]));
+        let runner_up = Minimax::new(NonZeroUsize::new(1000).unwrap()).minimax_multi(&game1(), Team::Ally, 4, 2).remove(1);
+        assert_eq!((&stats.mv, stats.score, &stats.mvs), (&runner_up.mv, runner_up.score, &runner_up.mvs));
+        assert!(stats.completed);
+
+        /* The root isn't stored as if that were all it had */
+        assert!(minimax.probe(&game1(), Team::Ally).is_none());
+        let full = Minimax::new(NonZeroUsize::new(1000).unwrap()).minimax(&game1(), Team::Ally, 4);
+        assert_eq!(minimax.minimax(&game1(), Team::Ally, 4).score, full.score);
+        let stats = only(minimax.search_moves(&game1(), Team::Ally, &opts, &[SimpleMove(0)]));
+        assert_eq!(stats.mv, Some(SimpleMove(0)));
+
+        assert_eq!(minimax.search_moves(&game1(), Team::Ally, &opts, &[SimpleMove(9)]), SearchResult::NoRootMoves);
+        assert_eq!(minimax.search_moves(&game1(), Team::Ally, &opts, &[]), SearchResult::NoRootMoves);
+        assert_eq!(minimax.search_moves(&SimpleBoard::Node(vec![]), Team::Ally, &opts, &[SimpleMove(0)]), SearchResult::NoLegalMoves);
+    }
+
+    #[test]
+    fn test_search_without()
+    {
+        let opts = SearchOptions::depth(4).build();
+        let all = [SimpleMove(0), SimpleMove(1), SimpleMove(2)];
+        for &turn in [Team::Ally, Team::Enemy].iter()
+        {
+            /* Leaving out the best move gets the runner up */
+            let top = Minimax::new(NonZeroUsize::new(1000).unwrap()).minimax_multi(&game1(), turn, 4, 2);
+            let mut minimax = Minimax::new(NonZeroUsize::new(1000).unwrap());
+            let stats = match minimax.search_without(&game1(), turn, &opts, &[top[0].mv.clone().unwrap()])
+            {
+                SearchResult::BestMove(stats) => stats,
+                other => panic!("{:?}", other),
+            };
+            assert_eq!((&stats.mv, stats.score, &stats.mvs), (&top[1].mv, top[1].score, &top[1].mvs));
+            assert!(minimax.probe(&game1(), turn).is_none());
+            assert_eq!(minimax.minimax(&game1(), turn, 4).score, top[0].score);
+
+            /* Leaving out everything isn't losing */
+            assert_eq!(minimax.search_without(&game1(), turn, &opts, &all), SearchResult::NoRootMoves);
+        }
+    }
+
+    #[test]
+    fn test_probe()
+    {
+        for &turn in [Team::Ally, Team::Enemy].iter()
+        {
+            let mut minimax = Minimax::new(NonZeroUsize::new(100).unwrap());
+            assert_eq!(minimax.probe(&game1(), turn), None);
+
+            let stats = minimax.minimax(&game1(), turn, 4);
+            let table_stats = minimax.table_stats();
+            assert_eq!(minimax.probe(&game1(), turn), Some(ProbeResult {
+                mv: stats.mv.clone(),
+                score: stats.score,
+                bound: Bound::Exact,
+                depth: 4,
+                mvs: stats.mvs.clone(),
+            }));
+            assert_eq!(minimax.probe(&game1(), turn.other_team()), None);
+            assert_eq!(minimax.table_stats(), table_stats);
+        }
+    }
+
+    #[test]
+    fn test_seed()
+    {
+        let opening = TimedScore { score: Score::Heuristic(1), turns: 0 };
+        for &turn in [Team::Ally, Team::Enemy].iter()
+        {
+            let expected = Minimax::new(NonZeroUsize::new(100).unwrap()).minimax(&game1(), turn, 4);
+
+            /* Too shallow to answer the search, the move still goes first */
+            let mut minimax = Minimax::new(NonZeroUsize::new(100).unwrap());
+            minimax.seed(game1(), turn, SimpleMove(2), opening, 0);
+            assert_eq!(minimax.probe(&game1(), turn).unwrap().score, opening);
+            let mut observer = stop_after(usize::MAX);
+            let stats = minimax.minimax_with_observer(&game1(), turn, 4, &mut observer);
+            assert_eq!(observer.searched[0].0, SimpleMove(2));
+            assert_eq!(stats.score, expected.score);
+
+            /* Deep enough, it is taken as it is */
+            let mut minimax = Minimax::new(NonZeroUsize::new(100).unwrap());
+            minimax.seed(game1(), turn, SimpleMove(2), opening, 4);
+            let stats = minimax.minimax(&game1(), turn, 4);
+            assert_eq!((stats.mv, stats.score, stats.mvs), (Some(SimpleMove(2)), opening, vec![SimpleMove(2)]));
+        }
+    }
+
+    #[test]
+    #[cfg(feature = "std-clock")]
+    fn test_iterative_deepening()
+    {
+        let mut minimax = Minimax::new(NonZeroUsize::new(100).unwrap());
+        let move_stats = minimax.iterative_deepening(&game1(), Team::Ally, Duration::from_millis(20));
+
+        assert_eq!(move_stats.mv, Some(SimpleMove(1)));
+        assert_eq!(move_stats.score.score, Score::Heuristic(6));
+        assert!(move_stats.depth_reached >= 4);
+    }
+
+    #[test]
+    #[cfg(feature = "std-clock")]
+    fn test_iterative_deepening_no_time()
+    {
+        let mut minimax = Minimax::new(NonZeroUsize::new(100).unwrap());
+        let move_stats = minimax.iterative_deepening(&game2(), Team::Ally, Duration::from_secs(0));
+
+        assert!(move_stats.mv.is_some());
+        assert_eq!(move_stats.depth_reached, 1);
+    }
+
+    /* A single-move game on a 2x2 grid. Its move type is
+     * a plain index pair with no `Display` impl.
+     */
+    #[derive(Clone,PartialEq,Eq,Hash,Debug)]
+    struct GridBoard
+    {
+        cells: [[i32; 2]; 2],
+        picked: Option<(usize, usize)>,
+    }
+
+    impl Board for GridBoard
+    {
+        type Move = (usize, usize);
+
+        fn gen_ally_moves(&self) -> Vec<Self::Move>
+        {
+            vec![(0, 0), (0, 1), (1, 0), (1, 1)]
+        }
+
+        fn gen_enemy_moves(&self) -> Vec<Self::Move>
+        {
+            self.gen_ally_moves()
+        }
+
+        fn do_move(&mut self, mv: &Self::Move)
+        {
+            self.picked = Some(*mv);
+        }
+
+        fn score(&self) -> Score
+        {
+            match self.picked
+            {
+                Some((row, col)) => Score::Heuristic(self.cells[row][col]),
+                None => Score::Heuristic(0),
+            }
+        }
+
+        fn is_game_over(&self) -> bool
+        {
+            self.picked.is_some()
+        }
+    }
+
+    #[test]
+    fn test_move_without_display()
+    {
+        let mut minimax = Minimax::new(NonZeroUsize::new(100).unwrap());
+        let board = GridBoard {
+            cells: [[1, 4], [9, -2]],
+            picked: None,
+        };
+
+        let ally_stats = minimax.minimax(&board, Team::Ally, 1);
+        assert_eq!(ally_stats.mv, Some((1, 0)));
+        assert_eq!(ally_stats.score.score, Score::Heuristic(9));
+
+        let enemy_stats = minimax.minimax(&board, Team::Enemy, 1);
+        assert_eq!(enemy_stats.mv, Some((1, 1)));
+        assert_eq!(enemy_stats.score.score, Score::Heuristic(-2));
+    }
+
+    /* Root has a single move, and it loses on the spot. */
+    fn one_losing_move() -> SimpleBoard
+    {
+        SimpleBoard::Node(vec![
+            SimpleBoard::Leaf(Score::Lose),
+        ])
+    }
+
+    #[test]
+    fn test_narrow_window_single_losing_move()
+    {
+        let mut minimax = Minimax::new(NonZeroUsize::new(100).unwrap());
+        let alpha = TimedScore {
+            score: Score::Heuristic(5),
+            turns: 0,
+        };
+        let beta = TimedScore {
+            score: Score::Heuristic(6),
+            turns: 0,
+        };
+
+        let ally_stats = minimax.search_window(&one_losing_move(), Team::Ally, 2, alpha, beta, &mut SearchContext::new(&mut NoObserver));
+        assert_eq!(ally_stats.mv, Some(SimpleMove(0)));
+        assert_eq!(ally_stats.score.score, Score::Lose);
+        assert_eq!(ally_stats.mvs, vec![SimpleMove(0)]);
+
+        let enemy_stats = minimax.search_window(&one_losing_move(), Team::Enemy, 2, alpha, beta, &mut SearchContext::new(&mut NoObserver));
+        assert_eq!(enemy_stats.mv, Some(SimpleMove(0)));
+        assert_eq!(enemy_stats.score.score, Score::Lose);
+        assert_eq!(enemy_stats.mvs, vec![SimpleMove(0)]);
+
+        /* Same position again, now served from the tables */
+        let cached_stats = minimax.search_window(&one_losing_move(), Team::Ally, 2, alpha, beta, &mut SearchContext::new(&mut NoObserver));
+        assert_eq!(cached_stats.mv, Some(SimpleMove(0)));
+        assert_eq!(cached_stats.score.score, Score::Lose);
+    }
+
+    #[test]
+    fn test_empty_window()
+    {
+        let mut minimax = Minimax::new(NonZeroUsize::new(100).unwrap());
+        let win = TimedScore {
+            score: Score::Win,
+            turns: 0,
+        };
+
+        let ally_stats = minimax.search_window(&game1(), Team::Ally, 4, win, win, &mut SearchContext::new(&mut NoObserver));
+        assert!(ally_stats.mv.is_some());
+        assert_eq!(ally_stats.mvs.len(), 4);
+
+        let enemy_stats = minimax.search_window(&game1(), Team::Enemy, 4, win, win, &mut SearchContext::new(&mut NoObserver));
+        assert!(enemy_stats.mv.is_some());
+        assert_eq!(enemy_stats.mvs.len(), 4);
+    }
+
+    /* Exact value of a SimpleBoard for the ally, searched all the way down. */
+    fn exact_value(board: &SimpleBoard, turn: Team) -> Score
+    {
+        match *board
+        {
+            SimpleBoard::Leaf(s) => s,
+            SimpleBoard::Node(ref children) => {
+                let values = children.iter().map(|c| exact_value(c, turn.other_team()));
+                match turn
+                {
+                    Team::Ally => values.max().unwrap(),
+                    Team::Enemy => values.min().unwrap(),
+                }
+            }
+        }
+    }
+
+    /* SimpleBoard that sorts its moves best-first using the exact values. */
+    #[derive(Clone,PartialEq,Eq,Hash,Debug)]
+    struct OrderedBoard(SimpleBoard);
+
+    impl Board for OrderedBoard
+    {
+        type Move = SimpleMove;
+
+        fn gen_ally_moves(&self) -> Vec<Self::Move>
+        {
+            self.0.gen_ally_moves()
+        }
+
+        fn gen_enemy_moves(&self) -> Vec<Self::Move>
+        {
+            self.0.gen_enemy_moves()
+        }
+
+        fn do_move(&mut self, mv: &Self::Move)
+        {
+            self.0.do_move(mv);
+        }
+
+        fn score_for(&self, to_move: Team) -> Score
+        {
+            self.0.score_for(to_move)
+        }
+
+        fn score(&self) -> Score
+        {
+            self.score_for(Team::Ally)
+        }
+
+        fn is_game_over(&self) -> bool
+        {
+            self.0.is_game_over()
+        }
+
+        fn order_moves(&self, moves: &mut Vec<Self::Move>, turn: Team)
+        {
+            if let SimpleBoard::Node(ref children) = self.0
+            {
+                let other = turn.other_team();
+                match turn
+                {
+                    Team::Ally =>
+                        moves.sort_by_key(|mv| Reverse(exact_value(&children[mv.0], other))),
+                    Team::Enemy =>
+                        moves.sort_by_key(|mv| exact_value(&children[mv.0], other)),
+                }
+            }
+        }
+    }
+
+    #[test]
+    fn test_order_moves()
+    {
+        for game in [game1(), game2()]
+        {
+            let mut plain = Minimax::new(NonZeroUsize::new(100).unwrap());
+            let mut ordered = Minimax::new(NonZeroUsize::new(100).unwrap());
+
+            let plain_stats = plain.minimax(&game, Team::Ally, 4);
+            let ordered_stats = ordered.minimax(&OrderedBoard(game), Team::Ally, 4);
+
+            assert_eq!(plain_stats.mv, ordered_stats.mv);
+            assert_eq!(plain_stats.score.score, ordered_stats.score.score);
+            assert!(ordered_stats.nodes_visited < plain_stats.nodes_visited);
+        }
+    }
+
+    #[test]
+    fn test_table_move_first()
+    {
+        /* Best move comes last, so nothing gets pruned unless the
+         * table's suggestion is tried first.
+         */
+        let game = SimpleBoard::Node(vec![
+            SimpleBoard::Node(vec![
+                SimpleBoard::Leaf(Score::Heuristic(1)),
+                SimpleBoard::Leaf(Score::Heuristic(2)),
+            ]),
+            SimpleBoard::Node(vec![
+                SimpleBoard::Leaf(Score::Heuristic(3)),
+                SimpleBoard::Leaf(Score::Heuristic(4)),
+            ]),
+            SimpleBoard::Node(vec![
+                SimpleBoard::Leaf(Score::Heuristic(7)),
+                SimpleBoard::Leaf(Score::Heuristic(8)),
+            ]),
+        ]);
+
+        let mut cold = Minimax::new(NonZeroUsize::new(100).unwrap());
+        let cold_stats = cold.minimax(&game, Team::Ally, 2);
+        assert_eq!(cold_stats.mv, Some(SimpleMove(2)));
+        assert_eq!(cold_stats.nodes_visited, 10);
+
+        /* Pretend a depth 1 search already suggested the last move */
+        let mut warm = Minimax::new(NonZeroUsize::new(100).unwrap());
+        let hint = MoveStats {
+            mv: Some(SimpleMove(2)),
+            score: TimedScore {
+                score: Score::Heuristic(0),
+                turns: 1,
+            },
+            nodes_visited: 3,
+            mvs: vec![SimpleMove(2)],
+            depth_reached: 1,
+            extended_depth: 0,
+            seldepth: 0,
+            exhaustive: true,
+            completed: true,
+            solved: false,
+        };
+        warm.ttable.insert((game.clone(), Team::Ally), hint, 1, Bound::Exact);
+
+        let warm_stats = warm.minimax(&game, Team::Ally, 2);
+        assert_eq!(warm_stats.mv, Some(SimpleMove(2)));
+        assert_eq!(warm_stats.score.score, Score::Heuristic(7));
+        assert_eq!(warm_stats.nodes_visited, 8);
+    }
+
+    #[test]
+    fn test_principal_variation()
+    {
+        let mut minimax = Minimax::new(NonZeroUsize::new(100).unwrap());
+        let game = game1();
+        let pv = vec![SimpleMove(1), SimpleMove(0), SimpleMove(0), SimpleMove(0)];
+
+        let move_stats = minimax.minimax(&game, Team::Ally, 4);
+        assert_eq!(move_stats.mvs, pv);
+
+        /* Playing the line out lands on the reported score */
+        let mut board = game.clone();
+        for mv in move_stats.mvs.iter()
+        {
+            board.do_move(mv);
+        }
+        assert_eq!(board.score_for(Team::Ally), move_stats.score.score);
+
+        /* A cached root hands back the same line */
+        let cached_stats = minimax.minimax(&game, Team::Ally, 4);
+        assert_eq!(cached_stats.mvs, pv);
+
+        /* And so does a cached enemy position */
+        let mut after_best = game.clone();
+        after_best.do_move(&SimpleMove(1));
+        assert_eq!(minimax.minimax(&after_best, Team::Enemy, 3).mvs, pv[1..].to_vec());
+    }
+
+    /* Mate-distance pruning as the negamax search does it: the window a
+     * node below the root is left with, or the score it settles for if
+     * no mate there could fall inside it.
+     */
+    fn mate_window(ply: u32, alpha: TimedScore, beta: TimedScore) -> Result<(TimedScore, TimedScore), TimedScore>
+    {
+        let fastest_win = TimedScore { score: Score::Win, turns: 1 };
+        let fastest_loss = TimedScore { score: Score::Lose, turns: 1 };
+        if ply == 0 || alpha >= beta
+        {
+            Ok((alpha, beta))
+        }
+        else if alpha >= fastest_win.seen_from_root(ply)
+        {
+            Err(fastest_win)
+        }
+        else if beta <= fastest_loss.seen_from_root(ply)
+        {
+            Err(fastest_loss)
+        }
+        else
+        {
+            Ok((alpha.max(fastest_loss.seen_from_root(ply)), beta.min(fastest_win.seen_from_root(ply))))
+        }
+    }
+
+    /* The separate max/min search the crate used before negamax, kept
+     * around so the two can be checked against each other.
+     */
+    struct TwinMinimax
+    {
+        ally_ttable: TranspositionTable<SimpleBoard, MoveStats<SimpleMove>>,
+        enemy_ttable: TranspositionTable<SimpleBoard, MoveStats<SimpleMove>>,
+        /* The furthest from the root any node of the search was */
+        seldepth: u32,
+    }
+
+    impl TwinMinimax
+    {
+        fn new(ttable_size: NonZeroUsize) -> TwinMinimax
+        {
+            TwinMinimax
+            {
+                ally_ttable: TranspositionTable::new(ttable_size),
+                enemy_ttable: TranspositionTable::new(ttable_size),
+                seldepth: 0,
+            }
+        }
+
+        fn minimax(&mut self, board: &SimpleBoard, turn: Team, plies: u32) -> MoveStats<SimpleMove>
+        {
+            let lose = TimedScore {
+                score: Score::Lose,
+                turns: 0,
+            };
+            let win = TimedScore {
+                score: Score::Win,
+                turns: 0,
+            };
+
+            self.seldepth = 0;
+            let mut optimal_move = match turn
+            {
+                Team::Ally =>
+                    self.max(board, 0, plies, lose, win),
+                Team::Enemy =>
+                    self.min(board, 0, plies, lose, win),
+            };
+
+            optimal_move.nodes_visited += 1;
+            optimal_move.depth_reached = plies;
+            optimal_move.seldepth = self.seldepth;
+            optimal_move
+        }
+
+        fn max(&mut self, board: &SimpleBoard, ply: u32, plies: u32, alpha: TimedScore, beta: TimedScore) -> MoveStats<SimpleMove>
+        {
+            self.seldepth = self.seldepth.max(ply);
+            let mut moves = board.gen_ally_moves();
+
+            /* Fail state if you can't move */
+            if moves.is_empty()
+            {
+                return MoveStats
+                {
+                    mv: None,
+                    score: TimedScore {
+                        score: Score::Lose,
+                        turns: 0,
+                    },
+                    nodes_visited: 0,
+                    mvs: Vec::new(),
+                    depth_reached: 0,
+                    extended_depth: 0,
+                    seldepth: 0,
+                    exhaustive: true,
+                    completed: true,
+                    solved: true,
+                };
+            }
+
+            /* If you cannot proceed further */
+            if plies == 0 || board.is_game_over()
+            {
+                return MoveStats
+                {
+                    mv: None,
+                    score: TimedScore {
+                        score: board.score_for(Team::Ally),
+                        turns: 0,
+                    },
+                    nodes_visited: 0,
+                    mvs: Vec::new(),
+                    depth_reached: 0,
+                    extended_depth: 0,
+                    seldepth: 0,
+                    exhaustive: true,
+                    completed: true,
+                    solved: board.is_game_over(),
+                }
+            }
+
+            let mut best = MoveStats{
+                mv: None,
+                score: TimedScore {
+                    score: Score::Lose,
+                    turns: 0,
+                },
+                nodes_visited: 0,
+                mvs: Vec::new(),
+                depth_reached: 0,
+                extended_depth: 0,
+                seldepth: 0,
+                exhaustive: true,
+                completed: true,
+                solved: true,
+            };
+
+            if let Some((precomputed_move, bound)) = self.ally_ttable.get(board, plies)
+            {
+                if bound.cuts(precomputed_move.score.seen_from_root(ply), alpha, beta)
+                {
+                    return MoveStats {
+                        nodes_visited: 0,
+                        ..precomputed_move
+                    };
+                }
+            }
+            let (mut alpha, beta) = match mate_window(ply, alpha, beta)
+            {
+                Ok(window) => window,
+                Err(score) => return MoveStats { score, ..best },
+            };
+            let (alpha_orig, beta_orig) = (alpha, beta);
+
+            board.order_moves(&mut moves, Team::Ally);
+            if let Some((stats, _)) = self.ally_ttable.peek(board)
+            {
+                try_first(&mut moves, &stats.mv);
+            }
+
+            for mv in moves
+            {
+                /* Make a clone of the board so we don't break this one */
+                let mut board_clone = board.clone();
+                board_clone.do_move(&mv);
+
+                /* Find enemy's best move */
+                let enemy_move = self.min(&board_clone, ply + 1, plies - 1, alpha, beta);
+                best.nodes_visited += enemy_move.nodes_visited + 1;
+                best.extended_depth = best.extended_depth.max(enemy_move.extended_depth + 1);
+                best.solved &= enemy_move.solved;
+
+                let score = enemy_move.score.later();
+                if best.mv.is_none() || score > best.score
+                {
+                    best.mv = Some(mv);
+                    best.score = score;
+                    best.mvs = enemy_move.mvs;
+                }
+
+                /* Set α and break on β ≤ α */
+                if best.score.seen_from_root(ply) > alpha
+                {
+                    alpha = best.score.seen_from_root(ply);
+                }
+                if alpha >= beta
+                {
+                    break;
+                }
+            }
+
+            /* Nothing worth remembering if no move was settled on */
+            if let Some(ref mv) = best.mv
+            {
+                best.mvs.insert(0, mv.clone());
+                self.ally_ttable.insert(board.clone(), best.clone(), plies, Bound::of(best.score.seen_from_root(ply), alpha_orig, beta_orig));
+            }
+
+            best
+        }
+
+        fn min(&mut self, board: &SimpleBoard, ply: u32, plies: u32, alpha: TimedScore, beta: TimedScore) -> MoveStats<SimpleMove>
+        {
+            self.seldepth = self.seldepth.max(ply);
+            let mut moves = board.gen_enemy_moves();
+
+            /* Fail state if you can't move */
+            if moves.is_empty()
+            {
+                return MoveStats
+                {
+                    mv: None,
+                    /* If enemy can't move, we win. */
+                    score: TimedScore {
+                        score: Score::Win,
+                        turns: 0,
+                    },
+                    nodes_visited: 0,
+                    mvs: Vec::new(),
+                    depth_reached: 0,
+                    extended_depth: 0,
+                    seldepth: 0,
+                    exhaustive: true,
+                    completed: true,
+                    solved: true,
+                };
+            }
+
+            /* If you cannot proceed further */
+            if plies == 0 || board.is_game_over()
+            {
+                return MoveStats
+                {
+                    mv: None,
+                    score: TimedScore {
+                        score: board.score_for(Team::Enemy),
+                        turns: 0,
+                    },
+                    nodes_visited: 0,
+                    mvs: Vec::new(),
+                    depth_reached: 0,
+                    extended_depth: 0,
+                    seldepth: 0,
+                    exhaustive: true,
+                    completed: true,
+                    solved: board.is_game_over(),
+                }
+            }
+
+            let mut best = MoveStats {
+                mv: None,
+                /* Technically doesn't matter, but for consistancy's sake */
+                score: TimedScore {
+                    score: Score::Win,
+                    turns: 0,
+                },
+                nodes_visited: 0,
+                mvs: Vec::new(),
+                depth_reached: 0,
+                extended_depth: 0,
+                seldepth: 0,
+                exhaustive: true,
+                completed: true,
+                solved: true,
+            };
+
+            if let Some((precomputed_move, bound)) = self.enemy_ttable.get(board, plies)
+            {
+                if bound.cuts(precomputed_move.score.seen_from_root(ply), alpha, beta)
+                {
+                    return MoveStats {
+                        nodes_visited: 0,
+                        ..precomputed_move
+                    };
+                }
+            }
+            let (alpha, mut beta) = match mate_window(ply, alpha, beta)
+            {
+                Ok(window) => window,
+                Err(score) => return MoveStats { score, ..best },
+            };
+            let (alpha_orig, beta_orig) = (alpha, beta);
+
+            board.order_moves(&mut moves, Team::Enemy);
+            if let Some((stats, _)) = self.enemy_ttable.peek(board)
+            {
+                try_first(&mut moves, &stats.mv);
+            }
+
+            for mv in moves
+            {
+                /* Make a clone of the board so we don't break this one */
+                let mut board_clone = board.clone();
+                board_clone.do_move(&mv);
+
+                /* Find ally's best move */
+                let ally_move = self.max(&board_clone, ply + 1, plies - 1, alpha, beta);
+                best.nodes_visited += ally_move.nodes_visited + 1;
+                best.extended_depth = best.extended_depth.max(ally_move.extended_depth + 1);
+                best.solved &= ally_move.solved;
+
+                let score = ally_move.score.later();
+                if best.mv.is_none() || score < best.score
+                {
+                    best.mv = Some(mv);
+                    best.score = score;
+                    best.mvs = ally_move.mvs;
+                }
+
+                /* Set β and break on β ≤ α */
+                if best.score.seen_from_root(ply) < beta
+                {
+                    beta = best.score.seen_from_root(ply);
+                }
+                if beta <= alpha
+                {
+                    break;
+                }
+            }
+
+            /* Nothing worth remembering if no move was settled on */
+            if let Some(ref mv) = best.mv
+            {
+                best.mvs.insert(0, mv.clone());
+                self.enemy_ttable.insert(board.clone(), best.clone(), plies, Bound::of(best.score.seen_from_root(ply), alpha_orig, beta_orig));
+            }
+
+            best
+        }
+
+    }
+
+    /* Small xorshift generator so random tests are reproducible. */
+    struct XorShift(u64);
+
+    impl XorShift
+    {
+        fn below(&mut self, n: u64) -> u64
+        {
+            self.0 ^= self.0 << 13;
+            self.0 ^= self.0 >> 7;
+            self.0 ^= self.0 << 17;
+            self.0 % n
+        }
+    }
+
+    /* Random tree at most `depth` plies deep. Subtrees are sometimes reused
+     * so the tables see transpositions.
+     */
+    fn random_tree(rng: &mut XorShift, depth: u32, seen: &mut Vec<(u32, SimpleBoard)>) -> SimpleBoard
+    {
+        if depth == 0 || rng.below(8) == 0
+        {
+            return match rng.below(10)
+            {
+                0 => SimpleBoard::Leaf(Score::Win),
+                1 => SimpleBoard::Leaf(Score::Lose),
+                _ => SimpleBoard::Leaf(Score::Heuristic(rng.below(11) as i32 - 5)),
+            };
+        }
+
+        if rng.below(5) == 0
+        {
+            if let Some((_, tree)) = seen.iter().find(|&&(d, _)| d == depth)
+            {
+                return tree.clone();
+            }
+        }
+
+        let branches = 1 + rng.below(4);
+        let tree = SimpleBoard::Node((0..branches).map(|_| random_tree(rng, depth - 1, seen)).collect());
+        seen.push((depth, tree.clone()));
+        tree
+    }
+
+    #[test]
+    fn test_negamax_matches_twin_search()
+    {
+        let mut rng = XorShift(0x2545_f491_4f6c_dd1d);
+
+        for _ in 0..200
+        {
+            let depth = 1 + rng.below(5) as u32;
+            let tree = random_tree(&mut rng, depth, &mut Vec::new());
+
+            for &turn in [Team::Ally, Team::Enemy].iter()
+            {
+                let mut negamax = Minimax::new(NonZeroUsize::new(1000).unwrap());
+                let mut twin = TwinMinimax::new(NonZeroUsize::new(1000).unwrap());
+
+                /* Shallow first and then again, so later searches run
+                 * against warm tables too.
+                 */
+                for plies in (1..depth + 1).chain(1..depth + 1)
+                {
+                    assert_eq!(negamax.minimax(&tree, turn, plies), twin.minimax(&tree, turn, plies),
+                               "{:?} to move, {} plies, tree {:?}", turn, plies, tree);
+                }
+            }
+        }
+    }
+
+    #[test]
+    fn test_table_hits_for_both_sides()
+    {
+        let mut rng = XorShift(0x9b05_688c_2b3e_6c1f);
+        for _ in 0..100
+        {
+            let depth = 1 + rng.below(5) as u32;
+            let tree = random_tree(&mut rng, depth, &mut Vec::new());
+
+            /* Whichever side is to move, a hit hands back the whole line */
+            for &turn in [Team::Ally, Team::Enemy].iter()
+            {
+                let mut minimax = Minimax::new(NonZeroUsize::new(1000).unwrap());
+                let cold = minimax.minimax(&tree, turn, depth);
+                let hit = minimax.minimax(&tree, turn, depth);
+                if cold.mv.is_none()
+                {
+                    continue;
+                }
+                assert_eq!(minimax.search_stats().tt_hits, 1);
+                assert_eq!(hit, MoveStats { nodes_visited: 1, seldepth: 0, ..cold.clone() }, "{:?} to move, tree {:?}", turn, tree);
+
+                /* The two sides don't share entries */
+                minimax.minimax(&tree, turn.other_team(), depth);
+                assert!(minimax.ttable.peek(&(tree.clone(), turn.other_team())).is_some());
+                assert_eq!(minimax.minimax(&tree, turn, depth), hit);
+            }
+        }
+    }
+
+    #[test]
+    fn test_same_position_for_either_side_in_one_search()
+    {
+        let h = Score::Heuristic;
+
+        /* The shared subtree comes up with the enemy to move after the
+         * first root move and with the ally to move after the second, and
+         * is worth 1 to the enemy but 9 to the ally
+         */
+        let shared = GameTree::Node(vec![GameTree::Leaf(h(1)), GameTree::Leaf(h(9))]);
+        let tree = GameTree::Node(vec![shared.clone(), GameTree::Node(vec![shared.clone()])]);
+        for &plies in [3, 4].iter()
+        {
+            let mut minimax = Minimax::new(NonZeroUsize::new(1000).unwrap());
+            let stats = minimax.minimax(&tree, Team::Ally, plies);
+            assert_eq!((stats.mv, stats.score.score), (Some(Branch(1)), h(9)));
+            assert_eq!(minimax.probe(&shared, Team::Enemy).map(|entry| entry.score.score), Some(h(1)));
+            assert_eq!(minimax.probe(&shared, Team::Ally).map(|entry| entry.score.score), Some(h(9)));
+        }
+
+        /* Nor does it get mixed up on random trees that reuse subtrees */
+        for seed in 0..50
+        {
+            let tree = GameTree::random(seed, 6);
+            for &turn in [Team::Ally, Team::Enemy].iter()
+            {
+                let expected = reference::minimax(&tree, turn, 6);
+                let stats = Minimax::new(NonZeroUsize::new(1000).unwrap()).minimax(&tree, turn, 6);
+                assert_eq!(stats.score, expected.score, "seed {} for {:?}", seed, turn);
+            }
+        }
+    }
+
+    #[test]
+    fn test_table_stats()
+    {
+        let mut minimax = Minimax::new(NonZeroUsize::new(100).unwrap());
+
+        minimax.minimax(&game1(), Team::Ally, 4);
+        let first = minimax.table_stats();
+        assert!(first.insertions > 0);
+        assert_eq!(first.evictions, 0);
+        assert_eq!(first.capacity, 100);
+        assert!(first.len > 0);
+
+        minimax.minimax(&game1(), Team::Ally, 4);
+        let second = minimax.table_stats();
+        assert!(second.hits > first.hits);
+        assert_eq!(second.misses, first.misses);
+
+        minimax.reset_stats();
+        let reset = minimax.table_stats();
+        assert_eq!(reset.hits, 0);
+        assert_eq!(reset.misses, 0);
+        assert_eq!(reset.len, second.len);
+        assert_eq!(reset.insertions, 0);
+    }
+
+    #[test]
+    fn test_shared_table()
+    {
+        let mut minimax = Minimax::new(NonZeroUsize::new(100).unwrap());
+        let cold = minimax.minimax(&game1(), Team::Enemy, 4);
+
+        /* The root is stored for the side that moved there, not the other one */
+        let (stored, _) = minimax.ttable.get(&(game1(), Team::Enemy), 4).unwrap();
+        assert_eq!(stored.mv, cold.mv);
+        assert!(minimax.ttable.peek(&(game1(), Team::Ally)).is_none());
+
+        /* Both sides' positions share the one table */
+        let stats = minimax.table_stats();
+        assert_eq!(stats.capacity, 100);
+        assert_eq!(stats.len, minimax.ttable.len());
+
+        minimax.reset_stats();
+        let warm = minimax.minimax(&game1(), Team::Enemy, 4);
+        assert_eq!((warm.mv, warm.score, warm.mvs), (cold.mv, cold.score, cold.mvs));
+        assert_eq!(minimax.table_stats().hits, 1);
+    }
+
+    #[test]
+    fn test_table_evictions()
+    {
+        let mut minimax = Minimax::new(NonZeroUsize::new(2).unwrap());
+
+        minimax.minimax(&game1(), Team::Ally, 4);
+        let stats = minimax.table_stats();
+        assert_eq!(stats.len, 2);
+        assert_eq!(stats.evictions, stats.insertions - 2);
+    }
+
+    #[test]
+    fn test_draw_ord()
+    {
+        assert!(Score::Draw > Score::Lose);
+        assert!(Score::Draw > Score::Heuristic(-1));
+        assert!(Score::Draw < Score::Heuristic(1));
+        assert!(Score::Draw < Score::Win);
+        assert_eq!(Score::Draw, Score::Heuristic(0));
+        assert_eq!(-Score::Draw, Score::Draw);
+
+        let draw_now = TimedScore { score: Score::Draw, turns: 0 };
+        let draw_later = TimedScore { score: Score::Draw, turns: 10 };
+        let lose_late = TimedScore { score: Score::Lose, turns: 100 };
+        let win_late = TimedScore { score: Score::Win, turns: 100 };
+
+        assert!(draw_now > lose_late);
+        assert!(draw_later > lose_late);
+        assert!(win_late > draw_now);
+        assert!(win_late > draw_later);
+        assert_eq!(draw_now.cmp(&draw_later), ::std::cmp::Ordering::Equal);
+    }
+
+    #[test]
+    fn test_extreme_heuristics()
+    {
+        assert_eq!(-Score::Heuristic(i32::MIN), Score::Heuristic(i32::MAX));
+        assert_eq!(-Score::Heuristic(i32::MAX), Score::Heuristic(-i32::MAX));
+        assert_eq!(-Score::Heuristic(-i32::MAX), Score::Heuristic(i32::MAX));
+        assert_eq!(Score::heuristic(i32::MIN), Score::Heuristic(-i32::MAX));
+        assert_eq!(Score::heuristic(i32::MAX), Score::Heuristic(i32::MAX));
+        assert_eq!(Score::heuristic(-7), Score::Heuristic(-7));
+        assert!(Score::Heuristic(i32::MIN) > Score::Lose);
+        assert!(Score::Heuristic(i32::MAX) < Score::Win);
+
+        let late = TimedScore { score: Score::Win, turns: u32::MAX };
+        assert_eq!(late.later().turns, u32::MAX);
+        assert_eq!(late.seen_from_root(3).turns, u32::MAX);
+
+        let game = SimpleBoard::Node(vec![
+            SimpleBoard::Node(vec![
+                SimpleBoard::Leaf(Score::Heuristic(i32::MIN)),
+                SimpleBoard::Leaf(Score::Heuristic(i32::MIN)),
+            ]),
+            SimpleBoard::Node(vec![
+                SimpleBoard::Leaf(Score::Heuristic(i32::MAX)),
+                SimpleBoard::Leaf(Score::Heuristic(i32::MAX)),
+            ]),
+            SimpleBoard::Node(vec![
+                SimpleBoard::Leaf(Score::Heuristic(i32::MIN)),
+                SimpleBoard::Leaf(Score::Heuristic(i32::MAX)),
+            ]),
+        ]);
+
+        let mut minimax = Minimax::new(NonZeroUsize::new(100).unwrap());
+        let ally_stats = minimax.minimax(&game, Team::Ally, 2);
+        assert_eq!(ally_stats.mv, Some(SimpleMove(1)));
+        assert_eq!(ally_stats.score.score, Score::Heuristic(i32::MAX));
+
+        /* Flipped over to the enemy and back, i32::MIN comes out clamped */
+        let enemy_stats = minimax.minimax(&game, Team::Enemy, 2);
+        assert_eq!(enemy_stats.mv, Some(SimpleMove(0)));
+        assert_eq!(enemy_stats.score.score, Score::heuristic(i32::MIN));
+    }
+
+    #[test]
+    fn test_prefers_draw_over_loss()
+    {
+        let game = SimpleBoard::Node(vec![
+            SimpleBoard::Node(vec![
+                SimpleBoard::Leaf(Score::Heuristic(3)),
+                SimpleBoard::Leaf(Score::Lose),
+            ]),
+            SimpleBoard::Node(vec![
+                SimpleBoard::Leaf(Score::Draw),
+                SimpleBoard::Leaf(Score::Draw),
+            ]),
+        ]);
+
+        let mut minimax = Minimax::new(NonZeroUsize::new(100).unwrap());
+        let ally_stats = minimax.minimax(&game, Team::Ally, 2);
+        assert_eq!(ally_stats.mv, Some(SimpleMove(1)));
+        assert!(matches!(ally_stats.score.score, Score::Draw));
+
+        /* Moving first, the enemy settles for the draw as well rather
+         * than let the ally pick up the +3.
+         */
+        let enemy_stats = minimax.minimax(&game, Team::Enemy, 2);
+        assert_eq!(enemy_stats.mv, Some(SimpleMove(1)));
+        assert!(matches!(enemy_stats.score.score, Score::Draw));
+    }
+
+    /* Counting game where the side to move is worth `tempo` to it, and
+     * the two sides have different steps to pick from
+     */
+    #[derive(Clone,PartialEq,Eq,Hash,Debug)]
+    struct TempoBoard
+    {
+        lead: i32,
+        left: u32,
+        ally: [i32; 2],
+        enemy: [i32; 2],
+        tempo: i32,
+    }
+
+    impl TempoBoard
+    {
+        /* The same game with the sides swapped */
+        fn mirrored(&self) -> TempoBoard
+        {
+            TempoBoard {
+                lead: -self.lead,
+                left: self.left,
+                ally: [-self.enemy[0], -self.enemy[1]],
+                enemy: [-self.ally[0], -self.ally[1]],
+                tempo: self.tempo,
+            }
+        }
+    }
+
+    impl Board for TempoBoard
+    {
+        type Move = i32;
+
+        fn gen_ally_moves(&self) -> Vec<i32>
+        {
+            self.ally.to_vec()
+        }
+
+        fn gen_enemy_moves(&self) -> Vec<i32>
+        {
+            self.enemy.to_vec()
+        }
+
+        fn do_move(&mut self, mv: &i32)
+        {
+            self.lead += *mv;
+            self.left -= 1;
+        }
+
+        fn score_for(&self, to_move: Team) -> Score
+        {
+            Score::Heuristic(match to_move
+            {
+                Team::Ally => self.lead + self.tempo,
+                Team::Enemy => self.lead - self.tempo,
+            })
+        }
+
+        fn score(&self) -> Score
+        {
+            self.score_for(Team::Ally)
+        }
+
+        fn is_game_over(&self) -> bool
+        {
+            self.left == 0
+        }
+    }
+
+    #[test]
+    fn test_score_knows_side_to_move()
+    {
+        let board = TempoBoard { lead: 0, left: 6, ally: [1, 4], enemy: [-2, -3], tempo: 10 };
+
+        /* After one ply the enemy has the move */
+        let stats = Minimax::new(NonZeroUsize::new(100).unwrap()).minimax(&board, Team::Ally, 1);
+        assert_eq!((stats.mv, stats.score.score), (Some(4), Score::Heuristic(4 - 10)));
+        let stats = Minimax::new(NonZeroUsize::new(100).unwrap()).minimax(&board, Team::Ally, 2);
+        assert_eq!((stats.mv, stats.score.score), (Some(4), Score::Heuristic(4 - 3 + 10)));
+
+        /* Swapping the sides of the game swaps the results, which are
+         * always given from the ally's point of view
+         */
+        for plies in 0..7
+        {
+            for &turn in [Team::Ally, Team::Enemy].iter()
+            {
+                let stats = Minimax::new(NonZeroUsize::new(100).unwrap()).minimax(&board, turn, plies);
+                let mirror = Minimax::new(NonZeroUsize::new(100).unwrap()).minimax(&board.mirrored(), turn.other_team(), plies);
+                assert_eq!(stats.score, -mirror.score);
+                assert_eq!(stats.mv.map(|mv| -mv), mirror.mv);
+                assert_eq!(stats.mvs.iter().map(|mv| -mv).collect::<Vec<_>>(), mirror.mvs);
+            }
+        }
+    }
+
+    /* Board written before scores took the side to move: each side takes
+     * one or two from a pile, and whoever takes the last one wins
+     */
+    #[derive(Clone,PartialEq,Eq,Hash,Debug)]
+    struct Pile(u32);
+
+    impl Board for Pile
+    {
+        type Move = u32;
+
+        fn gen_ally_moves(&self) -> Vec<u32>
+        {
+            (1..3).filter(|&take| take <= self.0).collect()
+        }
+
+        fn gen_enemy_moves(&self) -> Vec<u32>
+        {
+            self.gen_ally_moves()
+        }
+
+        fn do_move(&mut self, take: &u32)
+        {
+            self.0 -= take;
+        }
+
+        fn score(&self) -> Score
+        {
+            Score::Heuristic(0)
+        }
+
+        /* An empty pile has no moves, and the side to move has lost */
+        fn is_game_over(&self) -> bool
+        {
+            false
+        }
+
+        fn on_no_moves(&self, _turn: Team) -> NoMovePolicy
+        {
+            NoMovePolicy::Lose
+        }
+    }
+
+    #[test]
+    fn test_score_only_board()
+    {
+        /* `score_for` falls back on `score` for either side */
+        let pile = Pile(4);
+        for &turn in [Team::Ally, Team::Enemy].iter()
+        {
+            #[allow(deprecated)]
+            let score = pile.score();
+            assert_eq!(pile.score_for(turn), score);
+        }
+
+        /* Piles of a multiple of three lose for the side to move */
+        let size = NonZeroUsize::new(1000).unwrap();
+        let stats = Minimax::new(size).minimax(&Pile(4), Team::Ally, 2);
+        assert_eq!(stats.mv, Some(1));
+        let stats = Minimax::new(size).minimax(&Pile(6), Team::Ally, 6);
+        assert_eq!(stats.score.score, Score::Lose);
+    }
+
+    #[test]
+    fn test_contempt()
+    {
+        let game = SimpleBoard::Node(vec![
+            SimpleBoard::Leaf(Score::Draw),
+            SimpleBoard::Leaf(Score::Heuristic(-2)),
+            SimpleBoard::Leaf(Score::Heuristic(3)),
+        ]);
+        let search = |turn, contempt|
+        {
+            let opts = SearchOptions::depth(1).contempt(contempt).build();
+            let stats = Minimax::new(NonZeroUsize::new(100).unwrap()).search(&game, turn, &opts);
+            (stats.mv, stats.score.score)
+        };
+
+        assert_eq!(search(Team::Ally, 0), (Some(SimpleMove(2)), Score::Heuristic(3)));
+        assert_eq!(search(Team::Enemy, 0), (Some(SimpleMove(1)), Score::Heuristic(-2)));
+
+        /* Draws look worse to the ally, and so better to the enemy */
+        assert_eq!(search(Team::Ally, 5), (Some(SimpleMove(2)), Score::Heuristic(3)));
+        assert_eq!(search(Team::Enemy, 5), (Some(SimpleMove(0)), Score::Heuristic(-5)));
+
+        /* Or the other way round */
+        assert_eq!(search(Team::Ally, -5), (Some(SimpleMove(0)), Score::Heuristic(5)));
+        assert_eq!(search(Team::Enemy, -5), (Some(SimpleMove(1)), Score::Heuristic(-2)));
+    }
+
+    #[test]
+    fn test_contempt_table()
+    {
+        /* A draw for sure, or a line the enemy holds to 1 */
+        let game = SimpleBoard::Node(vec![
+            SimpleBoard::Node(vec![SimpleBoard::Leaf(Score::Draw)]),
+            SimpleBoard::Node(vec![SimpleBoard::Leaf(Score::Heuristic(3)), SimpleBoard::Leaf(Score::Heuristic(1))]),
+        ]);
+        let mut minimax = Minimax::new(NonZeroUsize::new(100).unwrap());
+        let mut search = |contempt|
+        {
+            let opts = SearchOptions::depth(2).contempt(contempt).build();
+            let stats = minimax.search(&game, Team::Ally, &opts);
+            (stats.mv, stats.score.score, minimax.table_stats().insertions)
+        };
+
+        /* Only the search without contempt goes in the main table */
+        let (mv, score, stored) = search(0);
+        assert_eq!((mv, score), (Some(SimpleMove(1)), Score::Heuristic(1)));
+        assert!(stored > 0);
+        assert_eq!(search(-2), (Some(SimpleMove(0)), Score::Heuristic(2), stored));
+        assert_eq!(search(2), (Some(SimpleMove(1)), Score::Heuristic(1), stored));
+        assert_eq!(search(-2), (Some(SimpleMove(0)), Score::Heuristic(2), stored));
+        assert_eq!(search(0), (Some(SimpleMove(1)), Score::Heuristic(1), stored));
+    }
+
+    /* TempoBoard where a side may pass, using up its turn */
+    #[derive(Clone,PartialEq,Eq,Hash,Debug)]
+    struct PassingBoard
+    {
+        board: TempoBoard,
+        passes: bool,
+    }
+
+    impl Board for PassingBoard
+    {
+        type Move = i32;
+
+        fn gen_ally_moves(&self) -> Vec<i32>
+        {
+            self.board.gen_ally_moves()
+        }
+
+        fn gen_enemy_moves(&self) -> Vec<i32>
+        {
+            self.board.gen_enemy_moves()
+        }
+
+        fn do_move(&mut self, mv: &i32)
+        {
+            self.board.do_move(mv)
+        }
+
+        fn score_for(&self, to_move: Team) -> Score
+        {
+            self.board.score_for(to_move)
+        }
+
+        fn score(&self) -> Score
+        {
+            self.score_for(Team::Ally)
+        }
+
+        fn is_game_over(&self) -> bool
+        {
+            self.board.is_game_over()
+        }
+
+        fn do_null_move(&mut self) -> bool
+        {
+            if self.passes
+            {
+                self.board.left -= 1;
+            }
+            self.passes
+        }
+    }
+
+    #[test]
+    fn test_null_move_pruning()
+    {
+        /* Each side has a good step and a bad one, and passing is worth
+         * less than the good one
+         */
+        let tempo = TempoBoard { lead: 0, left: 14, ally: [3, -1], enemy: [-3, 1], tempo: 1 };
+        let board = PassingBoard { board: tempo.clone(), passes: true };
+        let budget = SearchOptions::depth(10).max_nodes(95).build();
+
+        for &turn in [Team::Ally, Team::Enemy].iter()
+        {
+            let mut plain = Minimax::new(NonZeroUsize::new(100_000).unwrap());
+            let expected = plain.minimax(&tempo, turn, 10);
+            assert_eq!(plain.search_stats().null_move_cutoffs, 0);
+            let mut pruned = Minimax::new(NonZeroUsize::new(100_000).unwrap());
+            let stats = pruned.minimax(&board, turn, 10);
+            assert_eq!((stats.mv, stats.score), (expected.mv, expected.score));
+            assert!(pruned.search_stats().null_move_cutoffs > 0);
+
+            /* Cutting off early leaves fewer nodes to search */
+            assert!(!Minimax::new(NonZeroUsize::new(100_000).unwrap()).search(&tempo, turn, &budget).completed);
+            let stats = Minimax::new(NonZeroUsize::new(100_000).unwrap()).search(&board, turn, &budget);
+            assert!(stats.completed);
+            assert_eq!(stats.mv, expected.mv);
+
+            /* Too shallow to leave room for the reduction */
+            let mut shallow = Minimax::new(NonZeroUsize::new(100_000).unwrap());
+            shallow.set_null_move_reduction(9);
+            assert_eq!(shallow.minimax(&board, turn, 10), expected);
+            assert_eq!(shallow.search_stats().null_move_cutoffs, 0);
+        }
+    }
+
+    /* Tree where passing at a node with a single move hands the ally an
+     * immediate loss
+     */
+    #[derive(Clone,PartialEq,Eq,Hash,Debug)]
+    struct LosingPassTree(GameTree);
+
+    impl Board for LosingPassTree
+    {
+        type Move = Branch;
+
+        fn gen_ally_moves(&self) -> Vec<Branch>
+        {
+            self.0.gen_ally_moves()
+        }
+
+        fn gen_enemy_moves(&self) -> Vec<Branch>
+        {
+            self.0.gen_enemy_moves()
+        }
+
+        fn do_move(&mut self, mv: &Branch)
+        {
+            self.0.do_move(mv);
+        }
+
+        fn score_for(&self, to_move: Team) -> Score
+        {
+            self.0.score_for(to_move)
+        }
+
+        fn score(&self) -> Score
+        {
+            self.score_for(Team::Ally)
+        }
+
+        fn is_game_over(&self) -> bool
+        {
+            self.0.is_game_over()
+        }
+
+        fn do_null_move(&mut self) -> bool
+        {
+            match self.0
+            {
+                GameTree::Node(ref children) if children.len() == 1 => (),
+                _ => return false,
+            }
+            self.0 = GameTree::Node(vec![GameTree::Leaf(Score::Lose)]);
+            true
+        }
+    }
+
+    #[test]
+    fn test_null_move_win_doesnt_cut()
+    {
+        /* Passing in the second root move wins for the enemy, but its only
+         * real move is the ally's best line
+         */
+        let tree = GameTree::Node(vec![
+            GameTree::Leaf(Score::Heuristic(0)),
+            GameTree::Node(vec![GameTree::Node(vec![GameTree::Node(vec![GameTree::Leaf(Score::Heuristic(5))])])]),
+        ]);
+        let board = LosingPassTree(tree.clone());
+
+        let expected = Minimax::new(NonZeroUsize::new(100).unwrap()).minimax(&tree, Team::Ally, 5);
+        let mut minimax = Minimax::new(NonZeroUsize::new(100).unwrap());
+        let stats = minimax.minimax(&board, Team::Ally, 5);
+        assert_eq!((stats.mv, stats.score), (expected.mv, expected.score));
+        assert_eq!(stats.mv, Some(Branch(1)));
+        assert_eq!(minimax.search_stats().null_move_cutoffs, 0);
+
+        /* The root holds the real score, not one the pass made up */
+        let probe = minimax.probe(&board, Team::Ally).unwrap();
+        assert_eq!((probe.score.score, probe.bound), (Score::Heuristic(5), Bound::Exact));
+    }
+
+    #[test]
+    fn test_null_move_refused()
+    {
+        let mut rng = XorShift(0x3c6e_f372_fe94_f82b);
+        for _ in 0..50
+        {
+            let steps = |rng: &mut XorShift, sign: i32| [sign * (1 + rng.below(5) as i32), sign * (1 + rng.below(5) as i32)];
+            let tempo = TempoBoard {
+                lead: rng.below(20) as i32 - 10,
+                left: 1 + rng.below(8) as u32,
+                ally: steps(&mut rng, 1),
+                enemy: steps(&mut rng, -1),
+                tempo: rng.below(5) as i32,
+            };
+            let board = PassingBoard { board: tempo.clone(), passes: false };
+            for plies in 0..9
+            {
+                for &turn in [Team::Ally, Team::Enemy].iter()
+                {
+                    let expected = Minimax::new(NonZeroUsize::new(1000).unwrap()).minimax(&tempo, turn, plies);
+                    let mut minimax = Minimax::new(NonZeroUsize::new(1000).unwrap());
+                    assert_eq!(minimax.minimax(&board, turn, plies), expected);
+                    assert_eq!(minimax.search_stats().null_move_cutoffs, 0);
+                }
+            }
+        }
+    }
+
+    /* SimpleBoard whose moves by `extended` are extended by a ply */
+    #[derive(Clone,PartialEq,Eq,Hash,Debug)]
+    struct ExtendedBoard
+    {
+        tree: SimpleBoard,
+        extended: Option<(Team, usize)>,
+    }
+
+    impl Board for ExtendedBoard
+    {
+        type Move = SimpleMove;
+
+        fn gen_ally_moves(&self) -> Vec<SimpleMove>
+        {
+            self.tree.gen_ally_moves()
+        }
+
+        fn gen_enemy_moves(&self) -> Vec<SimpleMove>
+        {
+            self.tree.gen_enemy_moves()
+        }
+
+        fn do_move(&mut self, mv: &SimpleMove)
+        {
+            self.tree.do_move(mv)
+        }
+
+        fn score_for(&self, to_move: Team) -> Score
+        {
+            self.tree.score_for(to_move)
+        }
+
+        fn score(&self) -> Score
+        {
+            self.score_for(Team::Ally)
+        }
+
+        fn is_game_over(&self) -> bool
+        {
+            self.tree.is_game_over()
+        }
+
+        /* Every move, if no particular one is extended */
+        fn move_extension(&self, mv: &SimpleMove, turn: Team) -> u32
+        {
+            match self.extended
+            {
+                Some(extended) if extended != (turn, mv.0) => 0,
+                _ => 1,
+            }
+        }
+    }
+
+    #[test]
+    fn test_move_extensions()
+    {
+        use self::SimpleBoard::{Node, Leaf};
+
+        /* The first move forces a reply that leaves a win one ply past
+         * the horizon
+         */
+        let board = ExtendedBoard {
+            tree: Node(vec![
+                Node(vec![
+                    Node(vec![Leaf(Score::Heuristic(-1)), Leaf(Score::Win)]),
+                ]),
+                Leaf(Score::Heuristic(1)),
+            ]),
+            extended: Some((Team::Ally, 0)),
+        };
+
+        let mut minimax = Minimax::new(NonZeroUsize::new(100).unwrap());
+        let stats = minimax.minimax(&board, Team::Ally, 2);
+        assert_eq!(stats.mv, Some(SimpleMove(0)));
+        assert_eq!(stats.score, TimedScore { score: Score::Win, turns: 3 });
+        assert_eq!(stats.mvs, vec![SimpleMove(0), SimpleMove(0), SimpleMove(1)]);
+        assert_eq!((stats.depth_reached, stats.extended_depth), (2, 3));
+
+        let mut minimax = Minimax::new(NonZeroUsize::new(100).unwrap());
+        minimax.set_max_extensions(0);
+        let stats = minimax.minimax(&board, Team::Ally, 2);
+        assert_eq!(stats.mv, Some(SimpleMove(1)));
+        assert_eq!(stats.score.score, Score::Heuristic(1));
+        assert_eq!(stats.extended_depth, 2);
+
+        /* Extending every move only goes as deep as the cap allows */
+        let mut rng = XorShift(0x9b05_688c_2b3e_6c1f);
+        let tree = full_tree(&mut rng, 8, 2);
+        let board = ExtendedBoard { tree: tree.clone(), extended: None };
+        for &turn in [Team::Ally, Team::Enemy].iter()
+        {
+            for cap in 0..4
+            {
+                let mut minimax = Minimax::new(NonZeroUsize::new(1000).unwrap());
+                minimax.set_max_extensions(cap);
+                let stats = minimax.minimax(&board, turn, 2);
+                let expected = Minimax::new(NonZeroUsize::new(1000).unwrap()).minimax(&tree, turn, 2 + cap);
+                assert_eq!((stats.mv, stats.score, stats.mvs), (expected.mv, expected.score, expected.mvs));
+                assert_eq!(stats.nodes_visited, expected.nodes_visited);
+                assert_eq!(stats.extended_depth, 2 + cap);
+                let parallel = minimax.minimax_parallel(&board, turn, 2, 2);
+                assert_eq!(parallel.extended_depth, 2 + cap);
+            }
+        }
+    }
+
+    #[test]
+    fn test_seldepth()
+    {
+        use self::SimpleBoard::{Node, Leaf};
+
+        /* A chain of forced moves, every one of them extended */
+        let mut chain = Leaf(Score::Heuristic(0));
+        for _ in 0..8
+        {
+            chain = Node(vec![chain]);
+        }
+        let board = ExtendedBoard { tree: chain, extended: None };
+        for cap in 0..4
+        {
+            let mut minimax = Minimax::new(NonZeroUsize::new(1000).unwrap());
+            minimax.set_max_extensions(cap);
+            let stats = minimax.minimax(&board, Team::Ally, 2);
+            assert_eq!((stats.depth_reached, stats.seldepth), (2, 2 + cap));
+
+            let mut parallel = Minimax::new(NonZeroUsize::new(1000).unwrap());
+            parallel.set_max_extensions(cap);
+            assert_eq!(parallel.minimax_parallel(&board, Team::Ally, 2, 2).seldepth, 2 + cap);
+            let mut multi = Minimax::new(NonZeroUsize::new(1000).unwrap());
+            multi.set_max_extensions(cap);
+            assert_eq!(multi.minimax_multi(&board, Team::Ally, 2, 2)[0].seldepth, 2 + cap);
+        }
+
+        /* Quiescence search goes a ply past the horizon for the recapture */
+        let board = tactical(0, vec![
+            (false, tactical(0, vec![
+                (true, tactical(-3, vec![
+                    (true, tactical(2, vec![
+                        (false, tactical(0, vec![])),
+                    ])),
+                ])),
+            ])),
+        ]);
+        let mut minimax = Minimax::new(NonZeroUsize::new(100).unwrap());
+        assert_eq!(minimax.minimax(&board, Team::Ally, 2).seldepth, 3);
+        minimax.set_quiescence_depth(0);
+        minimax.clear();
+        assert_eq!(minimax.minimax(&board, Team::Ally, 2).seldepth, 2);
+    }
+
+    /* SimpleBoard that extends every move by half a ply */
+    #[derive(Clone,PartialEq,Eq,Hash,Debug)]
+    struct HalfExtendedBoard(SimpleBoard);
+
+    impl Board for HalfExtendedBoard
+    {
+        type Move = SimpleMove;
+
+        fn gen_ally_moves(&self) -> Vec<SimpleMove>
+        {
+            self.0.gen_ally_moves()
+        }
+
+        fn gen_enemy_moves(&self) -> Vec<SimpleMove>
+        {
+            self.0.gen_enemy_moves()
+        }
+
+        fn do_move(&mut self, mv: &SimpleMove)
+        {
+            self.0.do_move(mv)
+        }
+
+        fn score_for(&self, to_move: Team) -> Score
+        {
+            self.0.score_for(to_move)
+        }
+
+        fn score(&self) -> Score
+        {
+            self.score_for(Team::Ally)
+        }
+
+        fn is_game_over(&self) -> bool
+        {
+            self.0.is_game_over()
+        }
+
+        fn fractional_extension(&self, _mv: &SimpleMove, _turn: Team) -> Depth
+        {
+            Depth::HALF_PLY
+        }
+    }
+
+    #[test]
+    fn test_fractional_extensions()
+    {
+        assert_eq!(Depth::from_plies(2).units(), 8);
+        assert_eq!(Depth::from_units(7).plies(), 1);
+        assert_eq!(Depth::HALF_PLY + Depth::HALF_PLY, Depth::ONE_PLY);
+        assert_eq!(Depth::from_plies(3).half(), Depth::from_units(6));
+
+        /* Two half plies make one, until the cap of three plies runs out */
+        let mut rng = XorShift(0x510e_527f_ade6_82d1);
+        let tree = full_tree(&mut rng, 8, 2);
+        let board = HalfExtendedBoard(tree.clone());
+        for &turn in [Team::Ally, Team::Enemy].iter()
+        {
+            for &(plies, reached) in [(1, 1), (2, 3), (4, 7)].iter()
+            {
+                let mut minimax = Minimax::new(NonZeroUsize::new(1000).unwrap());
+                let stats = minimax.minimax(&board, turn, plies);
+                let expected = Minimax::new(NonZeroUsize::new(1000).unwrap()).minimax(&tree, turn, reached);
+                assert_eq!((&stats.mv, stats.score, &stats.mvs), (&expected.mv, expected.score, &expected.mvs));
+                assert_eq!((stats.depth_reached, stats.reached_depth(), stats.extended_depth), (plies, Depth::from_plies(plies), reached));
+                assert_eq!(minimax.probe(&board, turn).unwrap().depth, plies);
+            }
+        }
+    }
+
+    /* SimpleBoard that searches its first moves a ply deeper and its
+     * second ones a ply shallower
+     */
+    #[derive(Clone,PartialEq,Eq,Hash,Debug)]
+    struct AdjustedBoard(SimpleBoard);
+
+    impl Board for AdjustedBoard
+    {
+        type Move = SimpleMove;
+
+        fn gen_ally_moves(&self) -> Vec<SimpleMove>
+        {
+            self.0.gen_ally_moves()
+        }
+
+        fn gen_enemy_moves(&self) -> Vec<SimpleMove>
+        {
+            self.0.gen_enemy_moves()
+        }
+
+        fn do_move(&mut self, mv: &SimpleMove)
+        {
+            self.0.do_move(mv)
+        }
+
+        fn score_for(&self, to_move: Team) -> Score
+        {
+            self.0.score_for(to_move)
+        }
+
+        fn score(&self) -> Score
+        {
+            self.score_for(Team::Ally)
+        }
+
+        fn is_game_over(&self) -> bool
+        {
+            self.0.is_game_over()
+        }
+
+        fn depth_adjustment(&self, mv: &SimpleMove, _turn: Team) -> i32
+        {
+            match mv.0
+            {
+                0 => 1,
+                1 => -1,
+                _ => 0,
+            }
+        }
+    }
+
+    #[test]
+    fn test_depth_adjustment()
+    {
+        assert_eq!(Depth::from_plies(2).add_plies(-1), Depth::ONE_PLY);
+        assert_eq!(Depth::ONE_PLY.add_plies(-3), Depth::ZERO);
+        assert_eq!(Depth::HALF_PLY.add_plies(1), Depth::from_units(6));
+
+        let size = NonZeroUsize::new(1000).unwrap();
+        let mut rng = XorShift(0x9b05_688c_2b3e_6c1f);
+        let tree = full_tree(&mut rng, 10, 2);
+        let board = AdjustedBoard(tree.clone());
+        let mut plain = Minimax::new(size);
+        plain.set_search_mode(SearchMode::PlainMinimax);
+        plain.minimax(&tree, Team::Ally, 3);
+        let stats = plain.search_stats();
+        assert_eq!((stats.shallowest_horizon, stats.deepest_horizon), (3, 3));
+
+        /* First moves all the way down gain three plies before the cap, and
+         * second moves run out of depth after two
+         */
+        for &(cap, shallowest, deepest) in [(3, 2, 6), (1, 2, 4), (0, 3, 3)].iter()
+        {
+            let mut minimax = Minimax::new(size);
+            minimax.set_search_mode(SearchMode::PlainMinimax);
+            minimax.set_max_depth_adjustment(cap);
+            minimax.minimax(&board, Team::Ally, 3);
+            let stats = minimax.search_stats();
+            assert_eq!((stats.shallowest_horizon, stats.deepest_horizon), (shallowest, deepest), "cap {}", cap);
+
+            for &turn in [Team::Ally, Team::Enemy].iter()
+            {
+                let mut minimax = Minimax::new(size);
+                minimax.set_max_depth_adjustment(cap);
+                let verification = minimax.verify(&board, turn, 4);
+                assert!(!verification.mismatch, "{:?}", verification);
+            }
+        }
+
+        /* Without adjustments it is the board it wraps */
+        let mut minimax = Minimax::new(size);
+        minimax.set_max_depth_adjustment(0);
+        let stats = minimax.minimax(&board, Team::Ally, 4);
+        let expected = Minimax::new(size).minimax(&tree, Team::Ally, 4);
+        assert_eq!((&stats.mv, stats.score, &stats.mvs), (&expected.mv, expected.score, &expected.mvs));
+
+        /* Every move in multi-PV and analysis is searched to the depth it
+         * gets as the only root move
+         */
+        let opts = SearchOptions::depth(4).build();
+        for &turn in [Team::Ally, Team::Enemy].iter()
+        {
+            let alone: Vec<_> = board.moves(turn).map(|mv| match Minimax::new(size).search_moves(&board, turn, &opts, &[mv])
+            {
+                SearchResult::BestMove(stats) => (stats.mv.unwrap(), stats.score),
+                other => panic!("{:?}", other),
+            }).collect();
+            let mut multi: Vec<_> = Minimax::new(size).minimax_multi(&board, turn, 4, 2).into_iter()
+                .map(|stats| (stats.mv.unwrap(), stats.score))
+                .collect();
+            let mut analyzed: Vec<_> = Minimax::new(size).analyze_root(&board, turn, 4).into_iter()
+                .map(|root| (root.mv, root.score))
+                .collect();
+            multi.sort_by_key(|entry| (entry.0).0);
+            analyzed.sort_by_key(|entry| (entry.0).0);
+            assert_eq!(multi, alone);
+            assert_eq!(analyzed, alone);
+        }
+    }
+
+    #[test]
+    fn test_ponder()
+    {
+        let size = NonZeroUsize::new(100_000).unwrap();
+        let board = TempoBoard { lead: 0, left: 12, ally: [1, 4], enemy: [-2, -3], tempo: 1 };
+        let mut minimax = Minimax::new(size);
+        let stats = minimax.minimax(&board, Team::Ally, 6);
+        let reply = *stats.expected_reply().unwrap();
+        assert_eq!(stats.mvs.len(), 6);
+        assert_eq!(Some(&reply), stats.mvs.get(1));
+
+        let mut played = board.clone();
+        played.do_move(&stats.mv.unwrap());
+        let mut expected = played.clone();
+        expected.do_move(&reply);
+
+        /* Think on the expected position until the opponent moves */
+        let stop = minimax.stop_handle();
+        thread::scope(|scope|
+        {
+            scope.spawn(||
+            {
+                thread::sleep(Duration::from_millis(50));
+                stop.store(true, atomic::Ordering::Relaxed);
+            });
+            minimax.ponder(&played, Team::Enemy, reply, &SearchOptions::depth(u32::MAX).build());
+        });
+        let pondered = match minimax.ponder_result(&reply)
+        {
+            PonderResult::Hit(pondered) => pondered,
+            PonderResult::Miss => panic!("the expected reply was played"),
+        };
+        assert!(pondered.depth_reached >= 6);
+        assert!(!stop.load(atomic::Ordering::Relaxed));
+        assert_eq!(minimax.ponder_result(&reply), PonderResult::Miss);
+
+        /* The opponent played it, and the table has it ready. What it has
+         * was searched deeper, to the end of the game.
+         */
+        let solved = Minimax::new(size).minimax(&expected, Team::Ally, 10);
+        assert_eq!(pondered.score, solved.score);
+        let opts = SearchOptions::depth(6).max_nodes(10).build();
+        let cold = Minimax::new(size).search(&expected, Team::Ally, &opts);
+        let hit = minimax.search(&expected, Team::Ally, &opts);
+        assert!(!cold.completed);
+        assert!(hit.completed);
+        assert_eq!(hit.score, solved.score);
+
+        /* Or the opponent plays something else, which is no worse off */
+        let other_reply = *board.enemy.iter().find(|&&mv| mv != reply).unwrap();
+        let mut other = played.clone();
+        other.do_move(&other_reply);
+        minimax.ponder(&played, Team::Enemy, reply, &SearchOptions::depth(6).build());
+        assert_eq!(minimax.ponder_result(&other_reply), PonderResult::Miss);
+        let solved = Minimax::new(size).minimax(&other, Team::Ally, 10);
+        let miss = minimax.minimax(&other, Team::Ally, 10);
+        assert_eq!(miss.score, solved.score);
+
+        /* Pondered to the depth the search then asks for, a hit is the
+         * same move for a fraction of the nodes
+         */
+        let mut pondering = Minimax::new(size);
+        let opts = SearchOptions::depth(8).build();
+        pondering.ponder(&played, Team::Enemy, reply, &opts);
+        let hit = match pondering.ponder_result(&reply)
+        {
+            PonderResult::Hit(hit) => hit,
+            PonderResult::Miss => panic!("the expected reply was played"),
+        };
+        let fresh = Minimax::new(size).iterative_deepening_with(&expected, Team::Ally, &opts);
+        assert_eq!((hit.mv, hit.score, hit.depth_reached), (fresh.mv, fresh.score, fresh.depth_reached));
+        let warmed = pondering.iterative_deepening_with(&expected, Team::Ally, &opts);
+        assert_eq!((warmed.mv, warmed.score), (fresh.mv, fresh.score));
+        assert!(warmed.nodes_visited * 10 < fresh.nodes_visited, "{} vs {}", warmed.nodes_visited, fresh.nodes_visited);
+    }
+
+    #[test]
+    fn test_ponder_in_background()
+    {
+        let size = NonZeroUsize::new(100_000).unwrap();
+        let board = TempoBoard { lead: 0, left: 12, ally: [1, 4], enemy: [-2, -3], tempo: 1 };
+        let mut minimax = Minimax::new(size);
+        let stats = minimax.minimax(&board, Team::Ally, 6);
+        let reply = *stats.expected_reply().unwrap();
+        let mut played = board.clone();
+        played.do_move(&stats.mv.unwrap());
+        let mut expected = played.clone();
+        expected.do_move(&reply);
+        let solved = Minimax::new(size).minimax(&expected, Team::Ally, 12);
+
+        /* Every reading of the clock is a millisecond later */
+        let ticks = Arc::new(AtomicU64::new(0));
+        let clock = {
+            let ticks = ticks.clone();
+            move || Duration::from_millis(ticks.fetch_add(1, atomic::Ordering::Relaxed))
+        };
+        let wait_for = |handle: &PonderHandle<TempoBoard>, depth: u32|
+        {
+            for _ in 0..1000
+            {
+                if handle.depth() >= depth
+                {
+                    return;
+                }
+                thread::sleep(Duration::from_millis(10));
+            }
+            panic!("pondering got no deeper than {}", handle.depth());
+        };
+
+        /* The opponent takes its time and plays the expected reply, by
+         * when pondering got to the end of the game. No time left is
+         * enough to answer it from there.
+         */
+        let handle = minimax.ponder_in_background(&played, Team::Enemy, reply, 12, clock.clone());
+        wait_for(&handle, 12);
+        let asked = ticks.load(atomic::Ordering::Relaxed);
+        let (minimax, hit) = handle.ponderhit(Duration::ZERO);
+        assert!(ticks.load(atomic::Ordering::Relaxed) <= asked + 1);
+        assert!(hit.completed);
+        assert_eq!(hit.depth_reached, 12);
+        assert_eq!((hit.mv, hit.score), (solved.mv, solved.score));
+
+        /* Told to go on forever, it deepens until the hit gives it a time
+         * limit, and the iterations it already completed still count
+         */
+        let handle = Minimax::new(size).ponder_in_background(&played, Team::Enemy, reply, u32::MAX, clock.clone());
+        wait_for(&handle, 12);
+        let (_, hit) = handle.ponderhit(Duration::from_millis(10));
+        assert!(hit.completed);
+        assert!(hit.depth_reached >= 12);
+        assert_eq!(hit.score, solved.score);
+
+        /* A miss hands back the search with the table pondering filled */
+        let handle = minimax.ponder_in_background(&played, Team::Enemy, reply, u32::MAX, clock);
+        wait_for(&handle, 8);
+        let mut minimax = handle.miss();
+        assert!(!minimax.stop_handle().load(atomic::Ordering::Relaxed));
+        let opts = SearchOptions::depth(8).max_nodes(10).build();
+        assert!(!Minimax::new(size).search(&expected, Team::Ally, &opts).completed);
+        let warmed = minimax.search(&expected, Team::Ally, &opts);
+        assert!(warmed.completed);
+        assert_eq!(warmed.score, solved.score);
+    }
+
+    /* SimpleBoard that only hands out moves through the lazy iterator. */
+    #[derive(Clone,PartialEq,Eq,Hash,Debug)]
+    struct LazyBoard(SimpleBoard);
+
+    impl Board for LazyBoard
+    {
+        type Move = SimpleMove;
+
+        fn gen_ally_moves(&self) -> Vec<Self::Move>
+        {
+            unreachable!()
+        }
+
+        fn gen_enemy_moves(&self) -> Vec<Self::Move>
+        {
+            unreachable!()
+        }
+
+        fn moves(&self, _turn: Team) -> impl Iterator<Item = Self::Move>
+        {
+            let count = match self.0
+            {
+                SimpleBoard::Node(ref v) => v.len(),
+                SimpleBoard::Leaf(_) => 1,
+            };
+            (0..count).map(SimpleMove)
+        }
+
+        fn do_move(&mut self, mv: &Self::Move)
+        {
+            self.0.do_move(mv);
+        }
+
+        fn score_for(&self, to_move: Team) -> Score
+        {
+            self.0.score_for(to_move)
+        }
+
+        fn score(&self) -> Score
+        {
+            self.score_for(Team::Ally)
+        }
+
+        fn is_game_over(&self) -> bool
+        {
+            self.0.is_game_over()
+        }
+    }
+
+    #[test]
+    fn test_lazy_moves()
+    {
+        for game in [game1(), game2()]
+        {
+            for &turn in [Team::Ally, Team::Enemy].iter()
+            {
+                let mut plain = Minimax::new(NonZeroUsize::new(100).unwrap());
+                let mut lazy = Minimax::new(NonZeroUsize::new(100).unwrap());
+
+                let plain_stats = plain.minimax(&game, turn, 4);
+                let lazy_stats = lazy.minimax(&LazyBoard(game.clone()), turn, 4);
+                assert_eq!(plain_stats, lazy_stats);
+            }
+        }
+    }
+
+    thread_local!(static STACK_BOARD_CLONES: Cell<u64> = const { Cell::new(0) });
+
+    /* SimpleBoard that takes moves back instead of being cloned, and
+     * counts how often it gets cloned anyway.
+     */
+    struct StackBoard
+    {
+        current: SimpleBoard,
+        history: Vec<SimpleBoard>,
+    }
+
+    impl Clone for StackBoard
+    {
+        fn clone(&self) -> StackBoard
+        {
+            STACK_BOARD_CLONES.with(|c| c.set(c.get() + 1));
+            StackBoard {
+                current: self.current.clone(),
+                history: self.history.clone(),
+            }
+        }
+    }
+
+    impl PartialEq for StackBoard
+    {
+        fn eq(&self, other: &StackBoard) -> bool
+        {
+            self.current == other.current
+        }
+    }
+
+    impl Eq for StackBoard {}
+
+    impl Hash for StackBoard
+    {
+        fn hash<H: Hasher>(&self, state: &mut H)
+        {
+            self.current.hash(state);
+        }
+    }
+
+    impl Board for StackBoard
+    {
+        type Move = SimpleMove;
+
+        const UNDO: Option<fn(&mut Self, &Self::Move)> = Some(UndoBoard::undo_move);
+
+        fn gen_ally_moves(&self) -> Vec<Self::Move>
+        {
+            self.current.gen_ally_moves()
+        }
+
+        fn gen_enemy_moves(&self) -> Vec<Self::Move>
+        {
+            self.current.gen_enemy_moves()
+        }
+
+        fn do_move(&mut self, mv: &Self::Move)
+        {
+            let mut next = self.current.clone();
+            next.do_move(mv);
+            self.history.push(::std::mem::replace(&mut self.current, next));
+        }
+
+        fn score_for(&self, to_move: Team) -> Score
+        {
+            self.current.score_for(to_move)
+        }
+
+        fn score(&self) -> Score
+        {
+            self.score_for(Team::Ally)
+        }
+
+        fn is_game_over(&self) -> bool
+        {
+            self.current.is_game_over()
+        }
+    }
+
+    impl UndoBoard for StackBoard
+    {
+        fn undo_move(&mut self, _mv: &SimpleMove)
+        {
+            self.current = self.history.pop().unwrap();
+        }
+    }
+
+    #[test]
+    fn test_undo_moves()
+    {
+        for game in [game1(), game2()]
+        {
+            for &turn in [Team::Ally, Team::Enemy].iter()
+            {
+                let mut plain = Minimax::new(NonZeroUsize::new(100).unwrap());
+                let mut undo = Minimax::new(NonZeroUsize::new(100).unwrap());
+                let board = StackBoard {
+                    current: game.clone(),
+                    history: Vec::new(),
+                };
+
+                STACK_BOARD_CLONES.with(|c| c.set(0));
+                let undo_stats = undo.minimax(&board, turn, 4);
+                let clones = STACK_BOARD_CLONES.with(|c| c.get());
+
+                assert_eq!(plain.minimax(&game, turn, 4), undo_stats);
+
+                /* One copy to search on, plus one table key per probe */
+                let probes = undo.table_stats().hits + undo.table_stats().misses;
+                assert_eq!(clones, probes + 1);
+            }
+        }
+    }
+
+    /// Board where some moves are captures. Positions without moves are
+    /// quiet leaves: they offer a single dummy move and are game over.
+    #[derive(Clone, PartialEq, Eq, Hash, Debug)]
+    struct TacticalBoard
+    {
+        score: i32,
+        moves: Vec<(bool, TacticalBoard)>,
+    }
+
+    impl Board for TacticalBoard
+    {
+        type Move = SimpleMove;
+
+        fn gen_ally_moves(&self) -> Vec<Self::Move>
+        {
+            if self.moves.is_empty()
+            {
+                return vec![SimpleMove(0)];
+            }
+            (0..self.moves.len()).map(SimpleMove).collect()
+        }
+
+        fn gen_enemy_moves(&self) -> Vec<Self::Move>
+        {
+            self.gen_ally_moves()
+        }
+
+        fn gen_quiescence_moves(&self, _turn: Team) -> Vec<Self::Move>
+        {
+            (0..self.moves.len())
+                .filter(|&idx| self.moves[idx].0)
+                .map(SimpleMove)
+                .collect()
+        }
+
+        fn do_move(&mut self, mv: &Self::Move)
+        {
+            if !self.moves.is_empty()
+            {
+                *self = self.moves[mv.0].1.clone();
+            }
+        }
+
+        fn score(&self) -> Score
+        {
+            Score::Heuristic(self.score)
+        }
+
+        fn is_game_over(&self) -> bool
+        {
+            self.moves.is_empty()
+        }
+    }
+
+    fn tactical(score: i32, moves: Vec<(bool, TacticalBoard)>) -> TacticalBoard
+    {
+        TacticalBoard {
+            score,
+            moves,
+        }
+    }
+
+    #[test]
+    fn test_quiescence()
+    {
+        /* The enemy capture under move 0 looks winning at the horizon, but
+         * the ally recaptures right after */
+        let board = tactical(0, vec![
+            (false, tactical(0, vec![
+                (false, tactical(1, vec![])),
+                (true, tactical(-3, vec![
+                    (true, tactical(2, vec![])),
+                ])),
+            ])),
+            (false, tactical(0, vec![
+                (false, tactical(0, vec![])),
+            ])),
+        ]);
+
+        let mut horizon = Minimax::new(NonZeroUsize::new(100).unwrap());
+        horizon.set_quiescence_depth(0);
+        let horizon_stats = horizon.minimax(&board, Team::Ally, 2);
+        assert_eq!(horizon_stats.mv, Some(SimpleMove(1)));
+        assert_eq!(horizon_stats.score.score, Score::Heuristic(0));
+
+        let mut quiet = Minimax::new(NonZeroUsize::new(100).unwrap());
+        let quiet_stats = quiet.minimax(&board, Team::Ally, 2);
+        assert_eq!(quiet_stats.mv, Some(SimpleMove(0)));
+        assert_eq!(quiet_stats.score.score, Score::Heuristic(1));
+        assert!(quiet_stats.nodes_visited > horizon_stats.nodes_visited);
+
+        /* Only the recapture is past the horizon */
+        assert_eq!(horizon.search_stats().quiescence_nodes, 0);
+        assert_eq!(quiet.search_stats().quiescence_nodes, 1);
+        assert_eq!(quiet_stats.nodes_visited - horizon_stats.nodes_visited, 1);
+
+        /* Boards without noisy moves search the same either way */
+        let mut plain = Minimax::new(NonZeroUsize::new(100).unwrap());
+        plain.set_quiescence_depth(0);
+        let mut default = Minimax::new(NonZeroUsize::new(100).unwrap());
+        assert_eq!(plain.minimax(&game1(), Team::Ally, 3), default.minimax(&game1(), Team::Ally, 3));
+    }
+
+    /* TacticalBoard whose noisy moves say how much they can gain */
+    #[derive(Clone, PartialEq, Eq, Hash, Debug)]
+    struct BoundedGains(TacticalBoard, Option<i32>);
+
+    impl Board for BoundedGains
+    {
+        type Move = SimpleMove;
+
+        fn gen_ally_moves(&self) -> Vec<Self::Move>
+        {
+            self.0.gen_ally_moves()
+        }
+
+        fn gen_enemy_moves(&self) -> Vec<Self::Move>
+        {
+            self.0.gen_enemy_moves()
+        }
+
+        fn gen_quiescence_moves(&self, turn: Team) -> Vec<Self::Move>
+        {
+            self.0.gen_quiescence_moves(turn)
+        }
+
+        fn move_gain_upper_bound(&self, _mv: &Self::Move) -> Option<i32>
+        {
+            self.1
+        }
+
+        fn do_move(&mut self, mv: &Self::Move)
+        {
+            self.0.do_move(mv)
+        }
+
+        fn score_for(&self, to_move: Team) -> Score
+        {
+            self.0.score_for(to_move)
+        }
+
+        fn score(&self) -> Score
+        {
+            self.score_for(Team::Ally)
+        }
+
+        fn is_game_over(&self) -> bool
+        {
+            self.0.is_game_over()
+        }
+    }
+
+    /* `plies` of captures, four each turn, each moving the score by one */
+    fn exchange(plies: u32, score: i32) -> TacticalBoard
+    {
+        if plies == 0
+        {
+            return tactical(score, vec![]);
+        }
+        let next = if plies.is_multiple_of(2) { score + 1 } else { score - 1 };
+        tactical(score, (0..4).map(|_| (true, exchange(plies - 1, next))).collect())
+    }
+
+    #[test]
+    fn test_delta_pruning()
+    {
+        /* Move 0 is worth 10, so under move 1 no capture of the exchange
+         * can lift the ally's 0 above alpha
+         */
+        let board = tactical(0, vec![
+            (false, tactical(0, vec![
+                (false, tactical(10, vec![])),
+            ])),
+            (false, tactical(0, vec![
+                (false, exchange(6, 0)),
+            ])),
+        ]);
+
+        let mut unknown = Minimax::new(NonZeroUsize::new(1000).unwrap());
+        let expected = unknown.minimax(&BoundedGains(board.clone(), None), Team::Ally, 2);
+        assert_eq!(expected.mv, Some(SimpleMove(0)));
+        assert_eq!(expected.score.score, Score::Heuristic(10));
+        assert_eq!(unknown.search_stats().delta_prunes, 0);
+        assert_eq!(unknown.search_stats().quiescence_nodes, 4);
+
+        let bounded = BoundedGains(board.clone(), Some(1));
+        let mut minimax = Minimax::new(NonZeroUsize::new(1000).unwrap());
+        let stats = minimax.minimax(&bounded, Team::Ally, 2);
+        assert_eq!((&stats.mv, stats.score), (&expected.mv, expected.score));
+        assert_eq!(minimax.search_stats().delta_prunes, 4);
+        assert_eq!(minimax.search_stats().quiescence_nodes, 0);
+        assert_eq!(expected.nodes_visited - stats.nodes_visited, 4);
+        assert!(!Minimax::new(NonZeroUsize::new(1000).unwrap()).verify(&bounded, Team::Ally, 2).mismatch);
+
+        /* A bound big enough to reach alpha searches the captures */
+        let mut minimax = Minimax::new(NonZeroUsize::new(1000).unwrap());
+        assert_eq!(minimax.minimax(&BoundedGains(board, Some(11)), Team::Ally, 2), expected);
+        assert_eq!(minimax.search_stats().delta_prunes, 0);
+    }
+
+    #[test]
+    fn test_minimax_parallel()
+    {
+        for game in [game1(), game2()]
+        {
+            for &turn in [Team::Ally, Team::Enemy].iter()
+            {
+                for plies in 1..5
+                {
+                    let mut serial = Minimax::new(NonZeroUsize::new(100).unwrap());
+                    let serial_stats = serial.minimax(&game, turn, plies);
+
+                    for threads in 1..4
+                    {
+                        let mut parallel = Minimax::new(NonZeroUsize::new(100).unwrap());
+                        let parallel_stats = parallel.minimax_parallel(&game, turn, plies, threads);
+                        assert_eq!(parallel_stats.mv, serial_stats.mv);
+                        assert_eq!(parallel_stats.score, serial_stats.score);
+                        assert_eq!(parallel_stats.depth_reached, plies);
+
+                        /* The merged tables answer the same search again */
+                        assert_eq!(parallel.minimax(&game, turn, plies).mv, serial_stats.mv);
+                    }
+                }
+            }
+        }
+    }
+
+    #[test]
+    fn test_bounds_not_served_as_exact()
+    {
+        use self::SimpleBoard::{Node, Leaf};
+
+        /* The same position shows up under different windows. Searched
+         * under a narrow one first, it only gets a bound, which used to be
+         * handed out as its score the next time around.
+         */
+        let shared = || Node(vec![
+            Node(vec![Leaf(Score::Win), Leaf(Score::Heuristic(3))]),
+            Leaf(Score::Win),
+            Leaf(Score::Lose),
+        ]);
+        let game = Node(vec![
+            Node(vec![
+                Node(vec![Leaf(Score::Win), shared()]),
+                Node(vec![shared()]),
+            ]),
+            Leaf(Score::Heuristic(-3)),
+        ]);
+
+        let mut minimax = Minimax::new(NonZeroUsize::new(100).unwrap());
+        for plies in [5, 5, 6]
+        {
+            let stats = minimax.minimax(&game, Team::Ally, plies);
+            assert_eq!(stats.mv, Some(SimpleMove(1)));
+            assert_eq!(stats.score.score, exact_value(&game, Team::Ally));
+        }
+    }
+
+    /// Records the root moves it is told about and asks to stop once it
+    /// has seen `stop_after` of them, or at its first poll past that.
+    struct StopAfter
+    {
+        stop_after: usize,
+        searched: Vec<(SimpleMove, TimedScore, u64)>,
+        polls: u32,
+    }
+
+    impl SearchObserver<SimpleBoard> for StopAfter
+    {
+        fn on_root_move_searched(&mut self, mv: &SimpleMove, score: TimedScore, nodes: u64)
+        {
+            self.searched.push((mv.clone(), score, nodes));
+        }
+
+        fn should_stop(&mut self) -> bool
+        {
+            self.polls += 1;
+            self.searched.len() >= self.stop_after
+        }
+    }
+
+    fn stop_after(stop_after: usize) -> StopAfter
+    {
+        StopAfter {
+            stop_after,
+            searched: Vec::new(),
+            polls: 0,
+        }
+    }
+
+    /* Every node has `branches` children, down to random leaves */
+    fn full_tree(rng: &mut XorShift, depth: u32, branches: u64) -> SimpleBoard
+    {
+        if depth == 0
+        {
+            return SimpleBoard::Leaf(Score::Heuristic(rng.below(1000) as i32 - 500));
+        }
+        SimpleBoard::Node((0..branches).map(|_| full_tree(rng, depth - 1, branches)).collect())
+    }
+
+    #[test]
+    fn test_observer()
+    {
+        for &turn in [Team::Ally, Team::Enemy].iter()
+        {
+            /* Left alone, the observer hears about every root move */
+            let mut minimax = Minimax::new(NonZeroUsize::new(100).unwrap());
+            let mut observer = stop_after(usize::MAX);
+            let stats = minimax.minimax_with_observer(&game1(), turn, 4, &mut observer);
+            assert_eq!(stats, Minimax::new(NonZeroUsize::new(100).unwrap()).minimax(&game1(), turn, 4));
+            assert!(stats.completed);
+            assert_eq!(observer.searched.len(), game1().gen_ally_moves().len());
+            assert_eq!(observer.polls as usize, observer.searched.len());
+
+            /* The first root move counts, the second never gets started */
+            let mut minimax = Minimax::new(NonZeroUsize::new(100).unwrap());
+            let mut observer = stop_after(1);
+            let stats = minimax.minimax_with_observer(&game1(), turn, 4, &mut observer);
+            assert!(!stats.completed);
+            assert_eq!(observer.searched.len(), 1);
+            assert_eq!(stats.mv, Some(observer.searched[0].0.clone()));
+            assert_eq!(stats.score.score, observer.searched[0].1.score);
+            assert_eq!(stats.nodes_visited, observer.searched[0].2 + 1);
+
+            /* Nothing half searched is kept, so a rerun finishes normally */
+            let rerun = minimax.minimax(&game1(), turn, 4);
+            assert!(rerun.completed);
+            assert_eq!(rerun.mv, Minimax::new(NonZeroUsize::new(100).unwrap()).minimax(&game1(), turn, 4).mv);
+        }
+
+        let tree = full_tree(&mut XorShift(0x9e37_79b9_7f4a_7c15), 7, 5);
+        let mut minimax = Minimax::new(NonZeroUsize::new(100_000).unwrap());
+        let full = minimax.minimax(&tree, Team::Ally, 7);
+
+        /* Stopping happens inside the first root move, which then can't be
+         * returned.
+         */
+        let mut minimax = Minimax::new(NonZeroUsize::new(100_000).unwrap());
+        let mut observer = stop_after(0);
+        let stats = minimax.minimax_with_observer(&tree, Team::Ally, 7, &mut observer);
+        assert!(!stats.completed);
+        assert_eq!(observer.polls, 1);
+        assert!(observer.searched.is_empty());
+        assert_eq!(stats.mv, None);
+        assert!(stats.nodes_visited <= NODES_PER_POLL + 1);
+        assert!(full.nodes_visited > NODES_PER_POLL);
+    }
+
+    #[test]
+    fn test_minimax_windowed()
+    {
+        let tree = full_tree(&mut XorShift(0x2545_f491_4f6c_dd1d), 6, 4);
+        for &turn in [Team::Ally, Team::Enemy].iter()
+        {
+            let full = Minimax::new(NonZeroUsize::new(10_000).unwrap()).minimax(&tree, turn, 6);
+            let guess = match full.score.score
+            {
+                Score::Heuristic(v) => v,
+                _ => unreachable!(),
+            };
+            let window = |v| TimedScore {
+                score: Score::Heuristic(v),
+                turns: 0,
+            };
+
+            /* A good guess gets the same answer for less */
+            let mut minimax = Minimax::new(NonZeroUsize::new(10_000).unwrap());
+            let result = minimax.minimax_windowed(&tree, turn, 6, window(guess - 10), window(guess + 10));
+            assert!(result.is_exact());
+            assert_eq!(result.stats.mv, full.mv);
+            assert_eq!(result.stats.score, full.score);
+            assert!(result.stats.nodes_visited < full.nodes_visited);
+
+            /* Bad guesses say which way they were off */
+            let mut minimax = Minimax::new(NonZeroUsize::new(10_000).unwrap());
+            let low = minimax.minimax_windowed(&tree, turn, 6, window(guess + 10), window(guess + 20));
+            assert!(low.failed_low());
+            assert!(low.stats.score <= window(guess + 10));
+
+            let mut minimax = Minimax::new(NonZeroUsize::new(10_000).unwrap());
+            let high = minimax.minimax_windowed(&tree, turn, 6, window(guess - 20), window(guess - 10));
+            assert!(high.failed_high());
+            assert!(high.stats.score >= window(guess - 10));
+            let entry = minimax.probe(&tree, turn).unwrap();
+            assert_eq!((entry.bound, entry.score, entry.depth), (Bound::LowerBound, high.stats.score, 6));
+
+            /* Bounds from the failed searches don't leak into a full one */
+            assert_eq!(minimax.minimax(&tree, turn, 6).score, full.score);
+        }
+    }
+
+    #[test]
+    fn test_zero_window_fail_low()
+    {
+        let tree = full_tree(&mut XorShift(0x2545_f491_4f6c_dd1d), 6, 4);
+        for &turn in [Team::Ally, Team::Enemy].iter()
+        {
+            let full = Minimax::new(NonZeroUsize::new(10_000).unwrap()).minimax(&tree, turn, 6);
+            let value = match full.score.score
+            {
+                Score::Heuristic(v) => v,
+                _ => unreachable!(),
+            };
+
+            /* Is the ally at least two better off than it is? */
+            let threshold = TimedScore { score: Score::Heuristic(value + 2), turns: 0 };
+            let mut minimax = Minimax::new(NonZeroUsize::new(10_000).unwrap());
+            let probe = minimax.minimax_windowed(&tree, turn, 6, threshold.just_below().unwrap(), threshold);
+            assert!(probe.failed_low());
+            assert!(probe.stats.nodes_visited < full.nodes_visited);
+
+            /* The root is stored as the bound it is */
+            let entry = minimax.probe(&tree, turn).unwrap();
+            assert_eq!((entry.bound, entry.depth), (Bound::UpperBound, 6));
+            assert!(entry.score < threshold);
+
+            /* So the full search at the same depth searches the root again
+             * instead of answering with the bound
+             */
+            minimax.reset_stats();
+            let stats = minimax.minimax(&tree, turn, 6);
+            assert_eq!((&stats.mv, stats.score), (&full.mv, full.score));
+            assert!(stats.nodes_visited > 1);
+            assert_eq!(minimax.probe(&tree, turn).unwrap().bound, Bound::Exact);
+        }
+    }
+
+    #[test]
+    fn test_warm_up_windows_keep_root_move()
+    {
+        let window = |v| TimedScore {
+            score: Score::Heuristic(v),
+            turns: 0,
+        };
+
+        /* Whatever windows the table was filled under, a full search after
+         * them finds what searching from cold does
+         */
+        for seed in 0..30
+        {
+            let tree = GameTree::random(seed, 6);
+            for &turn in [Team::Ally, Team::Enemy].iter()
+            {
+                let expected = reference::minimax(&tree, turn, 6);
+                for &(alpha, beta) in [(-5, -4), (-1, 1), (0, 1), (2, 5), (4, 5)].iter()
+                {
+                    let mut minimax = Minimax::new(NonZeroUsize::new(10_000).unwrap());
+                    minimax.minimax_windowed(&tree, turn, 6, window(alpha), window(beta));
+                    minimax.minimax_windowed(&tree, turn, 4, window(-beta), window(-alpha));
+                    let stats = minimax.minimax(&tree, turn, 6);
+                    assert_eq!(stats.score, expected.score, "seed {} for {:?} after ({}, {})", seed, turn, alpha, beta);
+                    assert!(stats.mv.as_ref().map_or(expected.mv.is_none(), |mv| expected.is_best(mv)),
+                            "seed {} for {:?} after ({}, {})", seed, turn, alpha, beta);
+                }
+            }
+        }
+    }
+
+    #[test]
+    fn test_bound()
+    {
+        use self::SimpleBoard::{Node, Leaf};
+
+        let tree = full_tree(&mut XorShift(0x2545_f491_4f6c_dd1d), 6, 4);
+        for &turn in [Team::Ally, Team::Enemy].iter()
+        {
+            let full = Minimax::new(NonZeroUsize::new(10_000).unwrap()).minimax(&tree, turn, 6);
+            let value = match turn
+            {
+                Team::Ally => full.score,
+                Team::Enemy => -full.score,
+            };
+
+            /* Exactly the score is at least itself, just above it isn't */
+            let mut minimax = Minimax::new(NonZeroUsize::new(10_000).unwrap());
+            assert_eq!(minimax.test_bound(&tree, turn, 6, value), BoundResult::AtLeast);
+            let entry = minimax.probe(&tree, turn).unwrap();
+            assert_eq!(entry.bound, if turn == Team::Ally { Bound::LowerBound } else { Bound::UpperBound });
+
+            /* The bound it left helps the full search along */
+            let warm = minimax.minimax(&tree, turn, 6);
+            assert_eq!((&warm.mv, warm.score), (&full.mv, full.score));
+            assert!(warm.nodes_visited < full.nodes_visited);
+
+            let mut minimax = Minimax::new(NonZeroUsize::new(10_000).unwrap());
+            assert_eq!(minimax.test_bound(&tree, turn, 6, value.just_above().unwrap()), BoundResult::Below);
+            assert_eq!(minimax.minimax(&tree, turn, 6).score, full.score);
+
+            /* Everything is worth at least a loss */
+            let lowest = TimedScore { score: Score::Lose, turns: 0 };
+            assert_eq!(minimax.test_bound(&tree, turn, 6, lowest), BoundResult::AtLeast);
+        }
+
+        /* Only the ally can force a win, by its second move */
+        let any_win = TimedScore { score: Score::Win, turns: u32::MAX };
+        let forced = Node(vec![
+            Leaf(Score::Heuristic(5)),
+            Node(vec![Leaf(Score::Win)]),
+        ]);
+        let mut minimax = Minimax::new(NonZeroUsize::new(100).unwrap());
+        assert_eq!(minimax.test_bound(&forced, Team::Ally, 2, any_win), BoundResult::AtLeast);
+        assert_eq!(minimax.test_bound(&forced, Team::Ally, 2, TimedScore { score: Score::Win, turns: 1 }), BoundResult::Below);
+        assert_eq!(minimax.test_bound(&forced, Team::Enemy, 2, any_win), BoundResult::Below);
+        let unforced = Node(vec![
+            Leaf(Score::Heuristic(5)),
+            Node(vec![Leaf(Score::Win), Leaf(Score::Heuristic(-5))]),
+        ]);
+        assert_eq!(minimax.test_bound(&unforced, Team::Ally, 2, any_win), BoundResult::Below);
+    }
+
+    #[test]
+    fn test_clear()
+    {
+        let mut minimax = Minimax::new(NonZeroUsize::new(100).unwrap());
+        minimax.minimax(&game1(), Team::Ally, 4);
+        minimax.minimax(&game1(), Team::Ally, 4);
+        assert!(minimax.table_stats().hits > 0);
+        assert!(!minimax.ttable.is_empty());
+
+        minimax.clear();
+        assert!(minimax.ttable.is_empty());
+        assert_eq!(minimax.generation(), 0);
+        assert_eq!(minimax.table_stats(), TableStats {
+            capacity: 100,
+            ..TableStats::default()
+        });
+
+        /* Starts from scratch, just like a new instance */
+        let stats = minimax.minimax(&game1(), Team::Ally, 4);
+        assert_eq!(stats, Minimax::new(NonZeroUsize::new(100).unwrap()).minimax(&game1(), Team::Ally, 4));
+        assert_eq!(minimax.table_stats().hits, 0);
+    }
+
+    #[test]
+    fn test_set_capacity()
+    {
+        let mut minimax = Minimax::new(NonZeroUsize::new(100).unwrap());
+        let cold = minimax.minimax(&game1(), Team::Ally, 4);
+        let len = minimax.ttable.len();
+
+        /* Growing keeps everything, the root is still answered from the table */
+        minimax.set_capacity(NonZeroUsize::new(200).unwrap());
+        assert_eq!(minimax.ttable.len(), len);
+        assert_eq!(minimax.table_stats().capacity, 200);
+        minimax.reset_stats();
+        assert_eq!(minimax.minimax(&game1(), Team::Ally, 4).mv, cold.mv);
+        assert_eq!(minimax.table_stats().hits, 1);
+
+        minimax.set_capacity(NonZeroUsize::new(1).unwrap());
+        assert_eq!(minimax.ttable.len(), 1);
+        assert_eq!(minimax.table_stats().evictions, len as u64 - 1);
+
+        /* Shrinking takes the memory the table reports down with it */
+        let mut minimax = Minimax::with_policy(NonZeroUsize::new(100).unwrap(), ReplacementPolicy::DepthPreferred);
+        minimax.minimax(&game1(), Team::Ally, 4);
+        let full = minimax.table_stats();
+        let entry = SearchTable::<SimpleBoard, Score, (SimpleBoard, Team)>::entry_bytes(ReplacementPolicy::DepthPreferred, 0, 0);
+        assert_eq!(full.bytes, full.len * entry);
+        minimax.resize_tables(NonZeroUsize::new(2).unwrap());
+        let shrunk = minimax.table_stats();
+        assert_eq!((shrunk.capacity, shrunk.len), (2, 2));
+        assert!(shrunk.len < full.len);
+        assert_eq!(shrunk.bytes, 2 * entry);
+        assert!(shrunk.bytes < full.bytes);
+
+        /* Clearing takes it to nothing */
+        minimax.clear_tables();
+        assert_eq!((minimax.table_stats().len, minimax.table_stats().bytes), (0, 0));
+    }
+
+    impl Encode for SimpleMove
+    {
+        fn encode<W: Write>(&self, w: &mut W) -> io::Result<()>
+        {
+            self.0.encode(w)
+        }
+    }
+
+    impl Decode for SimpleMove
+    {
+        fn decode<R: Read>(r: &mut R) -> io::Result<SimpleMove>
+        {
+            usize::decode(r).map(SimpleMove)
+        }
+    }
+
+    impl Encode for SimpleBoard
+    {
+        fn encode<W: Write>(&self, w: &mut W) -> io::Result<()>
+        {
             match *self
             {
-                SimpleBoard::Node(ref v) =>
-                    (0..v.len()).map(|u| SimpleMove(u)).collect(),
-                SimpleBoard::Leaf(_) =>
-                    /* Trick the algorithm into thinking
-                     * we still have moves left
-                     */
-                    vec![SimpleMove(1)],
+                SimpleBoard::Node(ref children) => {
+                    0u8.encode(w)?;
+                    children.encode(w)
+                }
+                SimpleBoard::Leaf(score) => {
+                    1u8.encode(w)?;
+                    score.encode(w)
+                }
+            }
+        }
+    }
+
+    impl Decode for SimpleBoard
+    {
+        fn decode<R: Read>(r: &mut R) -> io::Result<SimpleBoard>
+        {
+            match u8::decode(r)?
+            {
+                0 => Ok(SimpleBoard::Node(Vec::decode(r)?)),
+                _ => Ok(SimpleBoard::Leaf(Score::decode(r)?)),
+            }
+        }
+    }
+
+    #[test]
+    fn test_save_tables()
+    {
+        let mut minimax = Minimax::new(NonZeroUsize::new(100).unwrap());
+        let cold = minimax.minimax(&game2(), Team::Enemy, 4);
+        let mut saved = Vec::new();
+        minimax.save_tables(&mut saved).unwrap();
+
+        let mut loaded = Minimax::new(NonZeroUsize::new(100).unwrap());
+        loaded.load_tables(&mut &saved[..]).unwrap();
+        assert_eq!(loaded.ttable.to_entries(), minimax.ttable.to_entries());
+
+        /* The root comes straight out of the loaded table */
+        let warm = loaded.minimax(&game2(), Team::Enemy, 4);
+        assert_eq!(warm.mv, cold.mv);
+        assert_eq!(warm.score, cold.score);
+        assert_eq!(loaded.table_stats().hits, 1);
+
+        /* Cut off input is an error and leaves the table alone */
+        let mut truncated: Minimax<SimpleBoard> = Minimax::new(NonZeroUsize::new(100).unwrap());
+        assert!(truncated.load_tables(&mut &saved[..saved.len() - 1]).is_err());
+        assert!(truncated.ttable.is_empty());
+    }
+
+    #[test]
+    fn test_replacement_policies()
+    {
+        let leaf = |v| SimpleBoard::Leaf(Score::Heuristic(v));
+
+        /* Depth preferred turns away what's shallower than everything kept */
+        let mut table = TranspositionTable::with_policy(NonZeroUsize::new(2).unwrap(), ReplacementPolicy::DepthPreferred);
+        table.insert(leaf(0), 0, 3, Bound::Exact);
+        table.insert(leaf(1), 1, 1, Bound::Exact);
+        table.insert(leaf(2), 2, 0, Bound::Exact);
+        assert!(table.peek(&leaf(2)).is_none());
+        table.insert(leaf(3), 3, 2, Bound::Exact);
+        assert!(table.peek(&leaf(1)).is_none());
+        assert_eq!(table.peek(&leaf(0)), Some((&0, 3)));
+        assert_eq!(table.peek(&leaf(3)), Some((&3, 2)));
+        assert_eq!(table.stats().evictions, 1);
+
+        /* A shallower result for a stored position keeps the deeper one */
+        table.insert(leaf(0), 4, 1, Bound::LowerBound);
+        assert_eq!(table.peek(&leaf(0)), Some((&0, 3)));
+
+        /* LRU evicts the deep entry all the same */
+        let mut table = TranspositionTable::new(NonZeroUsize::new(2).unwrap());
+        table.insert(leaf(0), 0, 3, Bound::Exact);
+        table.insert(leaf(1), 1, 1, Bound::Exact);
+        table.insert(leaf(2), 2, 0, Bound::Exact);
+        assert!(table.peek(&leaf(0)).is_none());
+
+        /* Two tier writes every position to its always replaced tier, and
+         * only those deep enough to the depth preferred one
+         */
+        let mut table = TranspositionTable::with_policy(NonZeroUsize::new(8).unwrap(), ReplacementPolicy::TwoTier);
+        for v in 0..4
+        {
+            table.insert(leaf(v), v, 5, Bound::Exact);
+        }
+        table.insert(leaf(4), 4, 1, Bound::Exact);
+        assert_eq!(table.get(&leaf(4), 1), Some((4, Bound::Exact)));
+        assert_eq!(table.get(&leaf(0), 5), Some((0, Bound::Exact)));
+        for v in 5..9
+        {
+            table.insert(leaf(v), v, 1, Bound::Exact);
+        }
+        assert!(table.peek(&leaf(4)).is_none());
+        assert_eq!(table.stats(), TableStats {
+            hits: 2,
+            insertions: 9,
+            evictions: 5,
+            deep_hits: 1,
+            always_hits: 1,
+            len: 8,
+            capacity: 8,
+            bytes: 8 * TranspositionTable::<SimpleBoard, i32>::entry_bytes(ReplacementPolicy::TwoTier, 0, 0),
+            ..TableStats::default()
+        });
+
+        /* Deep enough, it replaces the shallowest in both tiers */
+        table.insert(leaf(9), 9, 6, Bound::Exact);
+        assert!(table.peek(&leaf(0)).is_none());
+        assert_eq!(table.peek(&leaf(9)), Some((&9, 6)));
+
+        /* Shrinking drops the shallowest, oldest first, from the depth
+         * preferred tier and the least recent from the other, and lists
+         * each position once
+         */
+        table.set_capacity(NonZeroUsize::new(5).unwrap());
+        let mut kept: Vec<_> = table.to_entries().into_iter().map(|(_, mv, depth, _)| (mv, depth)).collect();
+        kept.sort();
+        assert_eq!(kept, vec![(2, 5), (3, 5), (8, 1), (9, 6)]);
+    }
+
+    #[test]
+    fn test_table_bytes()
+    {
+        let bytes = 1 << 20;
+        let pv = PV_ESTIMATE * mem::size_of::<SimpleMove>();
+        let lru: Minimax<SimpleBoard> = Minimax::with_table_bytes(bytes, ReplacementPolicy::Lru);
+        let capacity = lru.table_stats().capacity;
+        assert_eq!(capacity / 2, Minimax::<SimpleBoard>::with_table_bytes(bytes / 2, ReplacementPolicy::Lru).table_stats().capacity);
+
+        /* Boards on the heap make for fewer entries */
+        let board = game1();
+        assert!(board.approx_heap_size() > 0);
+        let minimax = Minimax::with_table_bytes_for(bytes, ReplacementPolicy::Lru, &board);
+        assert!(minimax.table_stats().capacity < capacity);
+        let entry = SearchTable::<SimpleBoard, Score, (SimpleBoard, Team)>::entry_bytes(ReplacementPolicy::Lru, board.approx_heap_size(), pv);
+        assert_eq!(minimax.table_stats().capacity, bytes / entry);
+
+        /* Tables that prefer depth take more room an entry, and get the
+         * policy asked for
+         */
+        for &policy in [ReplacementPolicy::DepthPreferred, ReplacementPolicy::TwoTier].iter()
+        {
+            let minimax: Minimax<SimpleBoard> = Minimax::with_table_bytes(bytes, policy);
+            assert_eq!(minimax.ttable.policy(), policy);
+            assert!(minimax.table_stats().capacity < capacity);
+        }
+
+        /* So do keys holding a whole board, next to a hash */
+        let zobrist: Minimax<LightsBoard, Score, ZobristKey> = Minimax::with_zobrist_table_bytes(bytes, ReplacementPolicy::TwoTier);
+        let checked: Minimax<LightsBoard, Score, CheckedZobristKey> = Minimax::with_checked_zobrist_table_bytes(bytes, ReplacementPolicy::TwoTier);
+        let keyed: Minimax<LightsBoard> = Minimax::with_table_bytes(bytes, ReplacementPolicy::TwoTier);
+        assert_eq!(zobrist.ttable.policy(), ReplacementPolicy::TwoTier);
+        assert!(zobrist.table_stats().capacity >= checked.table_stats().capacity);
+        assert!(checked.table_stats().capacity > keyed.table_stats().capacity);
+
+        /* There is always one */
+        let minimax: Minimax<SimpleBoard> = Minimax::with_table_bytes(0, ReplacementPolicy::Lru);
+        assert_eq!(minimax.table_stats().capacity, 1);
+    }
+
+    #[test]
+    fn test_table_counters()
+    {
+        let leaf = |v| SimpleBoard::Leaf(Score::Heuristic(v));
+        let mut table = TranspositionTable::new(NonZeroUsize::new(2).unwrap());
+        table.insert(leaf(0), 0, 2, Bound::Exact);
+        table.insert(leaf(0), 1, 3, Bound::Exact);
+        assert_eq!(table.get(&leaf(0), 3), Some((1, Bound::Exact)));
+        assert_eq!(table.get(&leaf(0), 4), None);
+        assert_eq!(table.get(&leaf(1), 0), None);
+        let stats = table.stats();
+        assert_eq!((stats.probes(), stats.hits, stats.misses, stats.shallow_hits), (3, 1, 2, 1));
+        assert_eq!((stats.insertions, stats.overwrites, stats.evictions), (2, 1, 0));
+
+        /* The search counts what the table did for it */
+        let mut minimax = Minimax::new(NonZeroUsize::new(1000).unwrap());
+        minimax.minimax(&game1(), Team::Ally, 4);
+        let first = minimax.search_stats();
+        assert!(first.tt_stores > 0);
+        assert_eq!(first.tt_stores, minimax.table_stats().insertions);
+        assert_eq!(first.tt_probes, minimax.table_stats().probes());
+
+        /* The same search again is answered at the root */
+        minimax.minimax(&game1(), Team::Ally, 4);
+        let second = minimax.search_stats();
+        assert_eq!((second.tt_hits, second.tt_probes, second.tt_stores), (1, 1, 0));
+    }
+
+    #[test]
+    fn test_depth_preferred_ages_out_stale_entries()
+    {
+        let leaf = |v| SimpleBoard::Leaf(Score::Heuristic(v));
+        let mut table = TranspositionTable::with_policy(NonZeroUsize::new(2).unwrap(), ReplacementPolicy::DepthPreferred);
+        table.insert(leaf(0), 0, 10, Bound::Exact);
+        table.insert(leaf(1), 1, 5, Bound::Exact);
+
+        /* Still recent enough, so the shallow position is turned away */
+        table.set_generation(STALE_AFTER);
+        table.insert(leaf(2), 2, 1, Bound::Exact);
+        assert!(table.peek(&leaf(2)).is_none());
+
+        /* One generation later the oldest goes first, deepest or not, but
+         * until then it is still looked up
+         */
+        table.set_generation(STALE_AFTER + 1);
+        assert_eq!(table.get(&leaf(0), 10), Some((0, Bound::Exact)));
+        table.insert(leaf(2), 2, 1, Bound::Exact);
+        assert!(table.peek(&leaf(0)).is_none());
+        assert_eq!(table.peek(&leaf(1)), Some((&1, 5)));
+        assert_eq!(table.peek(&leaf(2)), Some((&2, 1)));
+
+        /* The other entry is as old, and once both are gone depth decides
+         * again
+         */
+        table.insert(leaf(3), 3, 0, Bound::Exact);
+        assert!(table.peek(&leaf(1)).is_none());
+        table.insert(leaf(4), 4, 1, Bound::Exact);
+        assert!(table.peek(&leaf(3)).is_none());
+        assert_eq!(table.peek(&leaf(2)), Some((&2, 1)));
+        assert_eq!(table.stats().evictions, 3);
+        assert_eq!(table.stats().stale_evictions, 2);
+    }
+
+    #[test]
+    fn test_generations_keep_the_current_search()
+    {
+        /* Searches of unrelated trees deeper than the one that matters,
+         * any one of which fills the table
+         */
+        let trees: Vec<_> = (0..6)
+            .map(|seed| full_tree(&mut XorShift(0x9e37_79b9_7f4a_7c15 ^ seed), 6, 4))
+            .collect();
+        let mut minimax = Minimax::with_policy(NonZeroUsize::new(64).unwrap(), ReplacementPolicy::DepthPreferred);
+        for tree in &trees[1..]
+        {
+            minimax.minimax(tree, Team::Ally, 6);
+        }
+        assert_eq!(minimax.generation(), trees.len() as u32 - 1);
+
+        /* A quick look first, then the search whose entries are wanted.
+         * The old trees make room before those, deep as they are.
+         */
+        minimax.minimax(&trees[0], Team::Ally, 1);
+        minimax.reset_stats();
+        minimax.minimax(&trees[0], Team::Ally, 3);
+        assert_eq!(minimax.generation(), trees.len() as u32 + 1);
+        assert!(minimax.table_stats().stale_evictions > 0);
+        assert_eq!(minimax.ttable.peek(&(trees[0].clone(), Team::Ally)).map(|(_, depth)| depth), Some(Depth::from_plies(3).units()));
+        for mv in trees[0].gen_ally_moves()
+        {
+            let mut child = trees[0].clone();
+            child.do_move(&mv);
+            assert!(minimax.ttable.peek(&(child, Team::Enemy)).is_some());
+        }
+
+        /* Deepening is still one search */
+        minimax.iterative_deepening_to(&trees[1], Team::Ally, 5);
+        assert_eq!(minimax.generation(), trees.len() as u32 + 2);
+    }
+
+    #[test]
+    fn test_depth_preferred_keeps_deep_entries()
+    {
+        /* Too small for the whole tree, so something has to give */
+        let tree = full_tree(&mut XorShift(0x5851_f42d_4c95_7f2d), 6, 4);
+        let capacity = NonZeroUsize::new(64).unwrap();
+        let deep_hits = |policy|
+        {
+            let mut minimax = Minimax::with_policy(capacity, policy);
+            minimax.minimax(&tree, Team::Ally, 6);
+            minimax.minimax(&tree, Team::Ally, 6);
+
+            /* Probe everything within two plies of the root, as a deeper
+             * search from here would
+             */
+            minimax.reset_stats();
+            let mut positions = vec![(tree.clone(), Team::Ally, 6)];
+            for mv in tree.gen_ally_moves()
+            {
+                let mut child = tree.clone();
+                child.do_move(&mv);
+                for reply in child.gen_enemy_moves()
+                {
+                    let mut grandchild = child.clone();
+                    grandchild.do_move(&reply);
+                    positions.push((grandchild, Team::Ally, 4));
+                }
+                positions.push((child, Team::Enemy, 5));
+            }
+            for (position, team, depth) in positions
+            {
+                minimax.ttable.get(&(position, team), depth);
+            }
+            minimax.table_stats().hits
+        };
+
+        let lru = deep_hits(ReplacementPolicy::Lru);
+        let depth_preferred = deep_hits(ReplacementPolicy::DepthPreferred);
+        let two_tier = deep_hits(ReplacementPolicy::TwoTier);
+        assert!(depth_preferred > lru, "{} vs {}", depth_preferred, lru);
+        assert!(two_tier > lru, "{} vs {}", two_tier, lru);
+    }
+
+    #[test]
+    fn test_from_entries_keeps_deepest()
+    {
+        let entries: Vec<_> = [1, 4, 2, 4, 3].iter()
+            .enumerate()
+            .map(|(idx, &depth)| (SimpleBoard::Leaf(Score::Heuristic(idx as i32)), idx, depth, Bound::Exact))
+            .collect();
+
+        let table = TranspositionTable::from_entries(NonZeroUsize::new(3).unwrap(), entries.clone());
+        let kept: Vec<_> = table.to_entries().into_iter().map(|(_, idx, depth, _)| (idx, depth)).collect();
+        assert_eq!(kept, vec![(1, 4), (3, 4), (4, 3)]);
+
+        let table = TranspositionTable::from_entries(NonZeroUsize::new(10).unwrap(), entries.clone());
+        assert_eq!(table.to_entries(), entries);
+        assert_eq!(table.stats().insertions, 0);
+    }
+
+    #[test]
+    fn test_tie_break()
+    {
+        use self::SimpleBoard::{Node, Leaf};
+
+        /* Moves 0 and 2 both come out at -2, move 1 at -3 */
+        let game = Node(vec![
+            Node(vec![Leaf(Score::Heuristic(-2)), Leaf(Score::Heuristic(5))]),
+            Node(vec![Leaf(Score::Heuristic(-3)), Leaf(Score::Heuristic(3))]),
+            Node(vec![Leaf(Score::Heuristic(4)), Leaf(Score::Heuristic(-2))]),
+        ]);
+
+        let first = Minimax::new(NonZeroUsize::new(100).unwrap()).minimax(&game, Team::Ally, 2);
+        assert_eq!(first.mv, Some(SimpleMove(0)));
+
+        let mut picked = Vec::new();
+        let mut nodes = None;
+        for seed in 0..16
+        {
+            let mut minimax = Minimax::new(NonZeroUsize::new(100).unwrap());
+            minimax.set_tie_break(TieBreak::Random(seed));
+            let stats = minimax.minimax(&game, Team::Ally, 2);
+            assert_eq!(stats.score, first.score);
+            assert_eq!(stats.mvs[0], stats.mv.clone().unwrap());
+
+            /* Same seed, same pick, even with the root in the table. The
+             * root moves come from the table this time.
+             */
+            let again = minimax.minimax(&game, Team::Ally, 2);
+            assert_eq!((&again.mv, again.score, &again.mvs), (&stats.mv, stats.score, &stats.mvs));
+            assert_eq!(again.nodes_visited, 4);
+
+            /* Which tie gets picked doesn't change the work done */
+            assert_eq!(*nodes.get_or_insert(stats.nodes_visited), stats.nodes_visited);
+
+            /* Nor does searching the root moves in parallel */
+            let mut parallel = Minimax::new(NonZeroUsize::new(100).unwrap());
+            parallel.set_tie_break(TieBreak::Random(seed));
+            let split = parallel.minimax_parallel(&game, Team::Ally, 2, 2);
+            assert_eq!((&split.mv, split.score), (&stats.mv, stats.score));
+            picked.push(stats.mv.unwrap());
+        }
+        assert!(picked.contains(&SimpleMove(0)));
+        assert!(picked.contains(&SimpleMove(2)));
+        assert!(!picked.contains(&SimpleMove(1)));
+
+        fn last(moves: &[SimpleMove]) -> usize
+        {
+            moves.len() - 1
+        }
+        let mut minimax = Minimax::new(NonZeroUsize::new(100).unwrap());
+        minimax.set_tie_break(TieBreak::Custom(last));
+        assert_eq!(minimax.minimax(&game, Team::Ally, 2).mv, Some(SimpleMove(2)));
+        assert_eq!(minimax.minimax_parallel(&game, Team::Ally, 2, 2).mv, Some(SimpleMove(2)));
+
+        /* Draws tie however long they take */
+        let drawn = Node(vec![
+            Node(vec![Leaf(Score::Draw)]),
+            Leaf(Score::Heuristic(-5)),
+            Leaf(Score::Draw),
+        ]);
+        let mut picked = Vec::new();
+        for seed in 0..16
+        {
+            let mut minimax = Minimax::new(NonZeroUsize::new(100).unwrap());
+            minimax.set_tie_break(TieBreak::Random(seed));
+            let stats = minimax.minimax(&drawn, Team::Ally, 2);
+            assert_eq!(stats.score.score, Score::Draw);
+            let mut parallel = Minimax::new(NonZeroUsize::new(100).unwrap());
+            parallel.set_tie_break(TieBreak::Random(seed));
+            assert_eq!(parallel.minimax_parallel(&drawn, Team::Ally, 2, 2).mv, stats.mv);
+            picked.push(stats.mv.unwrap());
+        }
+        assert!(picked.contains(&SimpleMove(0)));
+        assert!(picked.contains(&SimpleMove(2)));
+        assert!(!picked.contains(&SimpleMove(1)));
+    }
+
+    #[test]
+    fn test_all_best_moves()
+    {
+        use self::SimpleBoard::{Node, Leaf};
+
+        let size = NonZeroUsize::new(100).unwrap();
+        let h = |v| TimedScore { score: Score::Heuristic(v), turns: 2 };
+        let game = Node(vec![
+            Node(vec![Leaf(Score::Heuristic(-2)), Leaf(Score::Heuristic(5))]),
+            Node(vec![Leaf(Score::Heuristic(-3)), Leaf(Score::Heuristic(3))]),
+            Node(vec![Leaf(Score::Heuristic(4)), Leaf(Score::Heuristic(-2))]),
+        ]);
+        assert_eq!(Minimax::new(size).all_best_moves(&game, Team::Ally, 2), (h(-2), vec![SimpleMove(0), SimpleMove(2)]));
+        assert_eq!(Minimax::new(size).all_best_moves(&game, Team::Enemy, 2), (h(3), vec![SimpleMove(1)]));
+
+        /* The same whatever the tie break, and the table doesn't change it */
+        let mut minimax = Minimax::new(size);
+        minimax.set_tie_break(TieBreak::Random(3));
+        minimax.minimax(&game, Team::Ally, 2);
+        assert_eq!(minimax.all_best_moves(&game, Team::Ally, 2), (h(-2), vec![SimpleMove(0), SimpleMove(2)]));
+
+        /* Only the quickest wins tie */
+        let win_in = |plies| (1..plies).fold(GameTree::Leaf(Score::Win), |tree, _| GameTree::Node(vec![tree]));
+        let tree = GameTree::Node(vec![win_in(3), win_in(5), win_in(3)]);
+        let (score, moves) = Minimax::new(size).all_best_moves(&tree, Team::Ally, 6);
+        assert_eq!((score, moves), (TimedScore { score: Score::Win, turns: 3 }, vec![Branch(0), Branch(2)]));
+
+        assert_eq!(Minimax::new(size).all_best_moves(&Leaf(Score::Win), Team::Ally, 2).1, Vec::new());
+    }
+
+    /* Line where the side to move ends up winning for the ally in `plies`
+     * plies, without either side having a choice.
+     */
+    fn forced_win(plies: u32) -> SimpleBoard
+    {
+        if plies == 0
+        {
+            return SimpleBoard::Leaf(Score::Win);
+        }
+        SimpleBoard::Node(vec![forced_win(plies - 1)])
+    }
+
+    #[test]
+    fn test_shortest_win()
+    {
+        /* Move 0 wins in 5, move 1 in 3 through a position move 0 also
+         * passes through later on
+         */
+        let game = SimpleBoard::Node(vec![
+            forced_win(4),
+            forced_win(2),
+            SimpleBoard::Leaf(Score::Heuristic(5)),
+        ]);
+        let check = |stats: MoveStats<SimpleMove>|
+        {
+            assert_eq!(stats.mv, Some(SimpleMove(1)));
+            assert_eq!(stats.score, TimedScore {
+                score: Score::Win,
+                turns: 3,
+            });
+            assert_eq!(stats.mvs.len(), 3);
+        };
+
+        for plies in 3..8
+        {
+            check(Minimax::new(NonZeroUsize::new(100).unwrap()).minimax(&game, Team::Ally, plies));
+        }
+
+        /* Deepening on one table */
+        let mut minimax = Minimax::new(NonZeroUsize::new(100).unwrap());
+        for plies in 1..8
+        {
+            let stats = minimax.minimax(&game, Team::Ally, plies);
+            if plies >= 3
+            {
+                check(stats);
+            }
+        }
+
+        /* Tables warmed up on the longer line first, at other depths */
+        let mut minimax = Minimax::new(NonZeroUsize::new(100).unwrap());
+        minimax.minimax(&forced_win(4), Team::Enemy, 4);
+        minimax.minimax(&forced_win(2), Team::Enemy, 6);
+        check(minimax.minimax(&game, Team::Ally, 5));
+        check(minimax.minimax(&game, Team::Ally, 7));
+
+        /* The enemy puts off losing, so move 0 only wins in 3 */
+        let delaying = SimpleBoard::Node(vec![SimpleBoard::Leaf(Score::Win), forced_win(1)]);
+        let game = SimpleBoard::Node(vec![delaying.clone(), forced_win(1)]);
+        for plies in 3..6
+        {
+            let delayed = Minimax::new(NonZeroUsize::new(100).unwrap()).minimax(&delaying, Team::Enemy, plies - 1);
+            assert_eq!(delayed.mv, Some(SimpleMove(1)));
+            assert_eq!(delayed.score.turns, 2);
+
+            let stats = Minimax::new(NonZeroUsize::new(100).unwrap()).minimax(&game, Team::Ally, plies);
+            assert_eq!(stats.mv, Some(SimpleMove(1)));
+            assert_eq!(stats.score.turns, 2);
+        }
+    }
+
+    #[test]
+    fn test_search_options()
+    {
+        let tree = full_tree(&mut XorShift(0x0123_4567_89ab_cdef), 5, 4);
+
+        /* No limit is just minimax */
+        let full = Minimax::new(NonZeroUsize::new(10_000).unwrap()).minimax(&tree, Team::Ally, 5);
+        let opts = SearchOptions::depth(5).build();
+        assert_eq!(Minimax::new(NonZeroUsize::new(10_000).unwrap()).search(&tree, Team::Ally, &opts), full);
+        let opts = SearchOptions::depth(5).max_nodes(full.nodes_visited).build();
+        assert_eq!(Minimax::new(NonZeroUsize::new(10_000).unwrap()).search(&tree, Team::Ally, &opts), full);
+
+        let mut observer = stop_after(usize::MAX);
+        Minimax::new(NonZeroUsize::new(10_000).unwrap()).minimax_with_observer(&tree, Team::Ally, 5, &mut observer);
+        let (ref first_move, first_score, first_nodes) = observer.searched[0];
+
+        /* Running out in the second root move leaves the first one */
+        let opts = SearchOptions::depth(5).max_nodes(1 + first_nodes + 2).build();
+        let mut minimax = Minimax::new(NonZeroUsize::new(10_000).unwrap());
+        let capped = minimax.search(&tree, Team::Ally, &opts);
+        assert!(!capped.completed);
+        assert_eq!(capped.mv.as_ref(), Some(first_move));
+        assert_eq!(capped.score, first_score);
+        assert!(capped.nodes_visited <= opts.node_limit().unwrap() + 1);
+
+        /* The unfinished root wasn't stored, so it gets searched properly */
+        assert_eq!(minimax.minimax(&tree, Team::Ally, 5).mv, full.mv);
+
+        /* Running out in the first root move leaves nothing */
+        let opts = SearchOptions::depth(5).max_nodes(first_nodes / 2).build();
+        let capped = Minimax::new(NonZeroUsize::new(10_000).unwrap()).search(&tree, Team::Ally, &opts);
+        assert!(!capped.completed);
+        assert_eq!(capped.mv, None);
+
+        /* The same budget stops at the same node every time, table hits
+         * and all, whatever the heuristics learned by then
+         */
+        for budget in (1..full.nodes_visited).step_by(37)
+        {
+            let opts = SearchOptions::depth(5).max_nodes(budget).build();
+            let capped = |killers|
+            {
+                let mut minimax = Minimax::new(NonZeroUsize::new(10_000).unwrap());
+                minimax.set_killer_moves(killers);
+                minimax.minimax(&tree, Team::Ally, 3);
+                minimax.search(&tree, Team::Ally, &opts)
+            };
+            assert_eq!(capped(false), capped(false), "{} nodes", budget);
+            assert_eq!(capped(true), capped(true), "{} nodes", budget);
+        }
+    }
+
+    #[test]
+    fn test_opponent_error()
+    {
+        let size = NonZeroUsize::new(10_000).unwrap();
+        let leaves = |scores: &[i32]| GameTree::Node(scores.iter().map(|&score| GameTree::Leaf(Score::Heuristic(score))).collect());
+
+        /* The second move only loses 10 if the enemy finds its one good
+         * reply. Missing it half the time, it's worth 12 on average.
+         */
+        let tree = GameTree::Node(vec![leaves(&[0]), leaves(&[-10, 50, 60])]);
+        let normal = SearchOptions::depth(2).build();
+        let erring = SearchOptions::depth(2).opponent_error(0.5, 3).build();
+        let mut minimax = Minimax::new(size);
+        let stats = minimax.search(&tree, Team::Ally, &erring);
+        assert_eq!((stats.mv, stats.score.score, stats.mvs), (Some(Branch(1)), Score::Heuristic(12), vec![Branch(1), Branch(0)]));
+
+        /* The normal search doesn't see what the modelled one stored, nor
+         * the other way round
+         */
+        let stats = minimax.search(&tree, Team::Ally, &normal);
+        assert_eq!((stats.mv, stats.score.score), (Some(Branch(0)), Score::Heuristic(0)));
+        assert_eq!(minimax.search(&tree, Team::Ally, &erring).score.score, Score::Heuristic(12));
+
+        /* Only picking among its best reply, the enemy never errs */
+        let stats = Minimax::new(size).search(&tree, Team::Ally, &SearchOptions::depth(2).opponent_error(0.5, 1).build());
+        assert_eq!((stats.mv, stats.score.score), (Some(Branch(0)), Score::Heuristic(0)));
+
+        /* Searching for the enemy, the ally is the one erring */
+        let tree = GameTree::Node(vec![leaves(&[0]), leaves(&[10, -50, -60])]);
+        let stats = Minimax::new(size).search(&tree, Team::Enemy, &erring);
+        assert_eq!((stats.mv, stats.score.score), (Some(Branch(1)), Score::Heuristic(-12)));
+
+        /* Never erring is the normal search, node for node */
+        for seed in 0..20
+        {
+            let tree = GameTree::random(seed, 6);
+            for &turn in [Team::Ally, Team::Enemy].iter()
+            {
+                let normal = SearchOptions::depth(6).build();
+                let erring = SearchOptions::depth(6).opponent_error(0.0, 3).build();
+                assert_eq!(Minimax::new(size).search(&tree, turn, &erring), Minimax::new(size).search(&tree, turn, &normal));
+            }
+        }
+    }
+
+    #[test]
+    fn test_enemy_depth_penalty()
+    {
+        let size = NonZeroUsize::new(10_000).unwrap();
+        let leaf = |score: i32| GameTree::Leaf(Score::Heuristic(score));
+
+        /* The first move is safe. The second hopes the enemy takes the 3
+         * it gets at once, rather than the reply that only wins it 100
+         * four plies in, past what the weaker enemy sees.
+         */
+        let refutation = GameTree::Node(vec![GameTree::Node(vec![leaf(-100)])]);
+        let tree = GameTree::Node(vec![GameTree::Node(vec![leaf(-5)]), GameTree::Node(vec![refutation, leaf(-3)])]);
+        let normal = SearchOptions::depth(4).build();
+        let weaker = SearchOptions::depth(4).enemy_depth_penalty(1).build();
+        let mut minimax = Minimax::new(size);
+        let stats = minimax.search(&tree, Team::Ally, &weaker);
+        assert_eq!((stats.mv, stats.score.score, stats.mvs), (Some(Branch(1)), Score::Heuristic(-3), vec![Branch(1), Branch(1)]));
+
+        /* The normal search sees the refutation, and neither sees what the
+         * other stored
+         */
+        let stats = minimax.search(&tree, Team::Ally, &normal);
+        assert_eq!((stats.mv, stats.score.score), (Some(Branch(0)), Score::Heuristic(-5)));
+        assert_eq!(minimax.search(&tree, Team::Ally, &weaker).mv, Some(Branch(1)));
+
+        /* Searching for the enemy, the ally is the weaker one */
+        fn flip(tree: &GameTree) -> GameTree
+        {
+            match *tree
+            {
+                GameTree::Node(ref children) => GameTree::Node(children.iter().map(flip).collect()),
+                GameTree::Leaf(score) => GameTree::Leaf(-score),
+            }
+        }
+        let stats = Minimax::new(size).search(&flip(&tree), Team::Enemy, &weaker);
+        assert_eq!((stats.mv, stats.score.score), (Some(Branch(1)), Score::Heuristic(3)));
+
+        /* No penalty is the normal search, node for node */
+        for seed in 0..20
+        {
+            let tree = GameTree::random(seed, 6);
+            for &turn in [Team::Ally, Team::Enemy].iter()
+            {
+                let normal = SearchOptions::depth(6).build();
+                let even = SearchOptions::depth(6).enemy_depth_penalty(0).build();
+                assert_eq!(Minimax::new(size).search(&tree, turn, &even), Minimax::new(size).search(&tree, turn, &normal));
+            }
+        }
+    }
+
+    /* SimpleBoard scored in floats, at half the integer scores */
+    #[derive(Clone,PartialEq,Eq,Hash,Debug)]
+    struct FloatBoard(SimpleBoard);
+
+    impl Board<OrderedF32> for FloatBoard
+    {
+        type Move = SimpleMove;
+
+        fn gen_ally_moves(&self) -> Vec<Self::Move>
+        {
+            self.0.gen_ally_moves()
+        }
+
+        fn gen_enemy_moves(&self) -> Vec<Self::Move>
+        {
+            self.0.gen_enemy_moves()
+        }
+
+        fn do_move(&mut self, mv: &Self::Move)
+        {
+            self.0.do_move(mv)
+        }
+
+        fn score_for(&self, to_move: Team) -> OrderedF32
+        {
+            OrderedF32(match self.0.score_for(to_move)
+            {
+                Score::Win => f32::INFINITY,
+                Score::Lose => f32::NEG_INFINITY,
+                Score::Heuristic(v) => v as f32 / 2.0,
+                Score::Draw => 0.0,
+            })
+        }
+
+        fn score(&self) -> OrderedF32
+        {
+            self.score_for(Team::Ally)
+        }
+
+        fn is_game_over(&self) -> bool
+        {
+            self.0.is_game_over()
+        }
+    }
+
+    #[test]
+    fn test_float_scores()
+    {
+        let tree = full_tree(&mut XorShift(0x0bad_5eed_f00d_cafe), 5, 4);
+        for board in [game1(), game2(), tree].iter()
+        {
+            for &turn in [Team::Ally, Team::Enemy].iter()
+            {
+                let ints = Minimax::new(NonZeroUsize::new(10_000).unwrap()).minimax(board, turn, 5);
+                let mut minimax: Minimax<FloatBoard, OrderedF32> = Minimax::new(NonZeroUsize::new(10_000).unwrap());
+                let floats = minimax.minimax(&FloatBoard(board.clone()), turn, 5);
+
+                /* Halving every score changes none of the decisions */
+                assert_eq!(floats.mv, ints.mv);
+                assert_eq!(floats.mvs, ints.mvs);
+                assert_eq!(floats.nodes_visited, ints.nodes_visited);
+                assert_eq!(floats.score.turns, ints.score.turns);
+                assert_eq!(floats.score.score, FloatBoard(SimpleBoard::Leaf(ints.score.score)).score_for(Team::Ally));
+            }
+        }
+
+        assert!(OrderedF32(-0.0) == OrderedF32(0.0));
+        assert!(-OrderedF32(f32::INFINITY) < OrderedF32(-1e30));
+    }
+
+    #[test]
+    fn test_minimax_multi()
+    {
+        let top = |board: &SimpleBoard, turn, k|
+        {
+            let results = Minimax::new(NonZeroUsize::new(1000).unwrap()).minimax_multi(board, turn, 4, k);
+            let found: Vec<_> = results.iter()
+                .map(|stats| (stats.mv.clone().unwrap().0, stats.score.score, stats.score.turns))
+                .collect();
+            (results, found)
+        };
+        let h = Score::Heuristic;
+
+        /* The ally takes 6 from move 1, 5 from move 2 and 3 from move 0 */
+        let (results, found) = top(&game1(), Team::Ally, 3);
+        assert_eq!(found, vec![(1, h(6), 4), (2, h(5), 4), (0, h(3), 4)]);
+        assert_eq!(results[0], Minimax::new(NonZeroUsize::new(1000).unwrap()).minimax(&game1(), Team::Ally, 4));
+        assert_eq!(results[1].mvs, vec![SimpleMove(2), SimpleMove(0), SimpleMove(0), SimpleMove(0)]);
+        assert_eq!(results[2].mvs, vec![SimpleMove(0), SimpleMove(1), SimpleMove(0), SimpleMove(0)]);
+        assert_eq!(top(&game1(), Team::Ally, 2).1, found[..2].to_vec());
+
+        /* The enemy ties moves 0 and 2 at 6, move 1 gives up 7 */
+        let (results, found) = top(&game1(), Team::Enemy, 3);
+        assert_eq!(found, vec![(0, h(6), 4), (2, h(6), 4), (1, h(7), 4)]);
+        assert_eq!(results[0], Minimax::new(NonZeroUsize::new(1000).unwrap()).minimax(&game1(), Team::Enemy, 4));
+
+        /* Asking for more than there are gives them all */
+        assert_eq!(top(&game2(), Team::Ally, 3).1, vec![(0, h(-3), 4), (1, h(-6), 4)]);
+        assert_eq!(top(&game2(), Team::Enemy, 5).1, vec![(0, h(6), 4), (1, h(6), 4)]);
+        assert!(top(&game2(), Team::Enemy, 0).0.is_empty());
+
+        /* Each score is what searching just that move gives */
+        let tree = full_tree(&mut XorShift(0x3c6e_f372_fe94_f82b), 5, 5);
+        let mut minimax = Minimax::new(NonZeroUsize::new(10_000).unwrap());
+        let results = minimax.minimax_multi(&tree, Team::Ally, 5, 3);
+        assert_eq!(results.len(), 3);
+        let mut exact: Vec<_> = tree.gen_ally_moves().into_iter()
+            .map(|mv|
+            {
+                let mut child = tree.clone();
+                child.do_move(&mv);
+                let reply = Minimax::new(NonZeroUsize::new(10_000).unwrap()).minimax(&child, Team::Enemy, 4);
+                (Reverse(reply.score.score), mv.0)
+            })
+            .collect();
+        exact.sort();
+        let found: Vec<_> = results.iter().map(|stats| (Reverse(stats.score.score), stats.mv.clone().unwrap().0)).collect();
+        assert_eq!(found, exact[..3].to_vec());
+
+        /* Two moves into the same position are still told apart, the
+         * second getting its score from the table
+         */
+        let same = || GameTree::Node(vec![GameTree::Leaf(h(4)), GameTree::Leaf(h(2))]);
+        let tree = GameTree::Node(vec![same(), GameTree::Leaf(h(1)), same()]);
+        let mut minimax = Minimax::new(NonZeroUsize::new(1000).unwrap());
+        let results = minimax.minimax_multi(&tree, Team::Ally, 2, 5);
+        let found: Vec<_> = results.iter().map(|stats| (stats.mvs[0], stats.score.score)).collect();
+        assert_eq!(found, vec![(Branch(0), h(2)), (Branch(2), h(2)), (Branch(1), h(1))]);
+        assert!(minimax.table_stats().hits > 0);
+    }
+
+    #[test]
+    fn test_best_move_ranked()
+    {
+        let size = NonZeroUsize::new(1000).unwrap();
+        let h = Score::Heuristic;
+        let picked = |tree: &GameTree, turn, pick|
+        {
+            let ranked = Minimax::new(size).best_move_ranked(tree, turn, 2, pick);
+            ranked.chosen().map(|stats| (stats.mv.unwrap(), stats.score.score))
+        };
+
+        /* Ranked 10, 7, 4, and a loss last */
+        let tree = GameTree::Node(vec![GameTree::Leaf(h(10)), GameTree::Leaf(h(4)), GameTree::Leaf(Score::Lose), GameTree::Leaf(h(7))]);
+        let ranked = Minimax::new(size).best_move_ranked(&tree, Team::Ally, 2, MovePick::Rank(1));
+        let found: Vec<_> = ranked.ranked.iter().map(|stats| (stats.mv.unwrap(), stats.score.score)).collect();
+        assert_eq!(found, vec![(Branch(0), h(10)), (Branch(3), h(7)), (Branch(1), h(4)), (Branch(2), Score::Lose)]);
+        assert_eq!(ranked.ranked[0], Minimax::new(size).minimax(&tree, Team::Ally, 2));
+        assert_eq!(ranked.rank, Some(1));
+        assert_eq!(picked(&tree, Team::Ally, MovePick::Rank(0)), Some((Branch(0), h(10))));
+        assert_eq!(picked(&tree, Team::Ally, MovePick::Rank(2)), Some((Branch(1), h(4))));
+        assert_eq!(picked(&tree, Team::Ally, MovePick::Margin(3)), Some((Branch(3), h(7))));
+        assert_eq!(picked(&tree, Team::Ally, MovePick::Margin(2)), Some((Branch(0), h(10))));
+
+        /* The loss is never picked while something else is left */
+        assert_eq!(picked(&tree, Team::Ally, MovePick::Rank(3)), Some((Branch(1), h(4))));
+        assert_eq!(picked(&tree, Team::Ally, MovePick::Rank(100)), Some((Branch(1), h(4))));
+        assert_eq!(picked(&tree, Team::Ally, MovePick::Margin(1000)), Some((Branch(1), h(4))));
+
+        /* The same for the enemy, ranking by its own scores */
+        let tree = GameTree::Node(vec![GameTree::Leaf(h(-10)), GameTree::Leaf(Score::Win), GameTree::Leaf(h(-4))]);
+        assert_eq!(picked(&tree, Team::Enemy, MovePick::Rank(1)), Some((Branch(2), h(-4))));
+        assert_eq!(picked(&tree, Team::Enemy, MovePick::Rank(2)), Some((Branch(2), h(-4))));
+
+        /* A win and a loss are only within a margin of their like, and with
+         * nothing but losses left one of them has to be played
+         */
+        let tree = GameTree::Node(vec![GameTree::Leaf(Score::Win), GameTree::Leaf(h(0)), GameTree::Leaf(Score::Win)]);
+        assert_eq!(picked(&tree, Team::Ally, MovePick::Margin(5)), Some((Branch(2), Score::Win)));
+        let tree = GameTree::Node(vec![GameTree::Leaf(Score::Lose), GameTree::Leaf(Score::Lose)]);
+        assert_eq!(picked(&tree, Team::Ally, MovePick::Rank(1)), Some((Branch(1), Score::Lose)));
+
+        /* Nothing to pick once the game is over */
+        let ranked = Minimax::new(size).best_move_ranked(&GameTree::Leaf(h(3)), Team::Ally, 2, MovePick::Rank(0));
+        assert!(ranked.ranked.is_empty());
+        assert_eq!(ranked.chosen(), None);
+    }
+
+    #[test]
+    fn test_analyze_root()
+    {
+        let size = NonZeroUsize::new(10_000).unwrap();
+        let found = |scores: &[RootScore<SimpleMove>]| -> Vec<_>
+        {
+            scores.iter().map(|root| (root.mv.0, root.score.score, root.bound)).collect()
+        };
+        let h = Score::Heuristic;
+
+        /* The same as asking `minimax_multi` for every move */
+        for &turn in [Team::Ally, Team::Enemy].iter()
+        {
+            let scores = Minimax::new(size).analyze_root(&game1(), turn, 4);
+            let multi: Vec<_> = Minimax::new(size).minimax_multi(&game1(), turn, 4, 3).into_iter()
+                .map(|stats| (stats.mv.unwrap().0, stats.score.score, Bound::Exact))
+                .collect();
+            assert_eq!(found(&scores), multi);
+        }
+        assert_eq!(found(&Minimax::new(size).analyze_root(&game1(), Team::Ally, 4)), vec![(1, h(6), Bound::Exact), (2, h(5), Bound::Exact), (0, h(3), Bound::Exact)]);
+
+        /* Each score is what searching just that move gives */
+        let tree = full_tree(&mut XorShift(0x3c6e_f372_fe94_f82b), 5, 5);
+        for &turn in [Team::Ally, Team::Enemy].iter()
+        {
+            let mut minimax = Minimax::new(size);
+            let scores = minimax.analyze_root(&tree, turn, 5);
+            assert_eq!(scores.len(), 5);
+            assert!(scores.windows(2).all(|pair| match turn
+            {
+                Team::Ally => pair[0].score >= pair[1].score,
+                Team::Enemy => pair[0].score <= pair[1].score,
+            }));
+            assert_eq!(Some(scores[0].mv.clone()), minimax.minimax(&tree, turn, 5).mv);
+            for root in &scores
+            {
+                let mut child = tree.clone();
+                child.do_move(&root.mv);
+                let reply = Minimax::new(size).minimax(&child, turn.other_team(), 4);
+                assert_eq!((root.score.score, root.bound), (reply.score.score, Bound::Exact));
+            }
+
+            /* Within a margin, the moves behind by more are only bounded,
+             * and by bounds that hold
+             */
+            let within = Minimax::new(size).analyze_root_within(&tree, turn, 5, 20);
+            assert_eq!(within[0], scores[0]);
+            let mut bounded = 0;
+            for root in &within
+            {
+                let exact = scores.iter().find(|exact| exact.mv == root.mv).unwrap();
+                match root.bound
+                {
+                    Bound::Exact => assert_eq!(root.score, exact.score),
+                    Bound::UpperBound => assert!(turn == Team::Ally && exact.score <= root.score),
+                    Bound::LowerBound => assert!(turn == Team::Enemy && exact.score >= root.score),
+                }
+                if root.bound != Bound::Exact
+                {
+                    bounded += 1;
+                }
+            }
+            assert!(bounded > 0);
+        }
+
+        /* Nothing to report once the game is over */
+        assert!(Minimax::new(size).analyze_root(&GameTree::Leaf(h(3)), Team::Ally, 2).is_empty());
+    }
+
+    #[test]
+    fn test_evaluation_noise()
+    {
+        let size = NonZeroUsize::new(10_000).unwrap();
+        let lcg = |seed: u64|
+        {
+            let mut state = seed;
+            move ||
+            {
+                state = state.wrapping_mul(6364136223846793005).wrapping_add(1442695040888963407);
+                state >> 33
+            }
+        };
+        let tree = GameTree::full(7, 3, 4);
+        let best = Minimax::new(size).minimax(&tree, Team::Ally, 3);
+
+        /* More noise misses the best move more often */
+        let misses = |noise: u32| (0..200)
+            .filter(|&seed|
+            {
+                let mut minimax = Minimax::new(size);
+                minimax.set_evaluation_noise(noise, lcg(seed));
+                minimax.minimax(&tree, Team::Ally, 3).mv != best.mv
+            })
+            .count();
+        let (none, some, lots) = (misses(0), misses(30), misses(300));
+        assert_eq!(none, 0);
+        assert!(0 < some && some < lots, "{} vs {}", some, lots);
+        assert!(lots > 50, "{}", lots);
+
+        /* The same seed plays the same */
+        let noisy = |seed|
+        {
+            let mut minimax = Minimax::new(size);
+            minimax.set_evaluation_noise(300, lcg(seed));
+            minimax.minimax(&tree, Team::Ally, 3)
+        };
+        assert_eq!(noisy(5), noisy(5));
+
+        /* Noisy searches leave the normal table alone */
+        let mut minimax = Minimax::new(size);
+        minimax.minimax(&tree, Team::Ally, 3);
+        minimax.set_evaluation_noise(300, lcg(1));
+        for _ in 0..5
+        {
+            minimax.minimax(&tree, Team::Ally, 3);
+            minimax.minimax_multi(&tree, Team::Ally, 3, 4);
+        }
+        minimax.set_evaluation_noise(0, lcg(1));
+        let mut warm = Minimax::new(size);
+        warm.minimax(&tree, Team::Ally, 3);
+        assert_eq!(minimax.minimax(&tree, Team::Ally, 3), warm.minimax(&tree, Team::Ally, 3));
+
+        /* Wins and losses get none */
+        let tree = GameTree::Node(vec![GameTree::Leaf(Score::Heuristic(0)), GameTree::Leaf(Score::Win), GameTree::Leaf(Score::Lose)]);
+        for seed in 0..20
+        {
+            let mut minimax = Minimax::new(size);
+            minimax.set_evaluation_noise(1_000_000, lcg(seed));
+            let stats = minimax.minimax(&tree, Team::Ally, 1);
+            assert_eq!((stats.mv, stats.score.score), (Some(Branch(1)), Score::Win));
+        }
+    }
+
+    /* Two squares to shuffle between forever. Either side may stay put or
+     * switch, and the heuristic likes the left one for the ally.
+     */
+    #[derive(Clone,PartialEq,Eq,Hash,Debug)]
+    struct ShuffleBoard
+    {
+        left: bool,
+    }
+
+    impl Board for ShuffleBoard
+    {
+        type Move = bool;
+
+        fn gen_ally_moves(&self) -> Vec<Self::Move>
+        {
+            vec![false, true]
+        }
+
+        fn gen_enemy_moves(&self) -> Vec<Self::Move>
+        {
+            vec![false, true]
+        }
+
+        fn do_move(&mut self, switch: &Self::Move)
+        {
+            self.left ^= *switch;
+        }
+
+        fn score(&self) -> Score
+        {
+            Score::Heuristic(if self.left { 40 } else { -40 })
+        }
+
+        fn is_game_over(&self) -> bool
+        {
+            false
+        }
+    }
+
+    #[test]
+    fn test_solve()
+    {
+        let size = NonZeroUsize::new(10_000).unwrap();
+
+        /* Every line of these ends within four plies */
+        for &turn in [Team::Ally, Team::Enemy].iter()
+        {
+            let expected = Minimax::new(size).minimax(&game1(), turn, 4);
+            assert!(expected.solved);
+            let solved = Minimax::new(size).solve(&game1(), turn, 100).unwrap();
+            assert_eq!((&solved.mv, solved.score, &solved.mvs), (&expected.mv, expected.score, &expected.mvs));
+            assert!(solved.solved);
+        }
+
+        /* One ply short and the score is a guess */
+        assert!(!Minimax::new(size).minimax(&game1(), Team::Ally, 3).solved);
+        match Minimax::new(size).solve(&game1(), Team::Ally, 3)
+        {
+            Err(SolveError::TooDeep(stats)) => assert!(stats.completed && !stats.solved),
+            other => panic!("{:?}", other),
+        }
+
+        let win_in = |plies| (1..plies).fold(GameTree::Leaf(Score::Win), |tree, _| GameTree::Node(vec![tree]));
+        let tree = GameTree::Node(vec![win_in(9), win_in(5), GameTree::Node(vec![GameTree::Leaf(Score::Heuristic(3))])]);
+        let solved = Minimax::new(size).solve(&tree, Team::Ally, u32::MAX).unwrap();
+        assert_eq!((solved.mv, solved.score), (Some(Branch(1)), TimedScore { score: Score::Win, turns: 5 }));
+
+        /* Shuffling back and forth never ends, unless repeating does */
+        let board = ShuffleBoard { left: true };
+        let mut minimax = Minimax::new(size);
+        assert!(matches!(minimax.solve(&board, Team::Ally, 20), Err(SolveError::TooDeep(_))));
+        let mut minimax = Minimax::new(size);
+        minimax.set_repetition_score(Some(Score::Draw));
+        let solved = minimax.solve(&board, Team::Ally, 20).unwrap();
+        assert_eq!(solved.score.score, Score::Draw);
+
+        /* Stopped, it says so */
+        let mut minimax = Minimax::new(size);
+        minimax.stop_handle().store(true, atomic::Ordering::SeqCst);
+        assert!(matches!(minimax.solve(&tree, Team::Ally, 100), Err(SolveError::Stopped(_))));
+    }
+
+    #[derive(Clone,Debug,PartialEq,Eq,Hash)]
+    enum PlayerTree
+    {
+        Node(Vec<PlayerTree>),
+        Leaf([i32; 3]),
+    }
+
+    /* Three players taking turns down a `PlayerTree`, whose values add up
+     * to `total` if it is set
+     */
+    #[derive(Clone,Debug,PartialEq,Eq,Hash)]
+    struct ThreePlayers
+    {
+        tree: PlayerTree,
+        player: usize,
+        total: Option<i32>,
+    }
+
+    impl MultiPlayerBoard for ThreePlayers
+    {
+        type Move = Branch;
+
+        fn current_player(&self) -> usize
+        {
+            self.player
+        }
+
+        fn moves(&self) -> Vec<Branch>
+        {
+            match self.tree
+            {
+                PlayerTree::Node(ref children) => (0..children.len()).map(Branch).collect(),
+                PlayerTree::Leaf(_) => Vec::new(),
+            }
+        }
+
+        fn do_move(&mut self, mv: &Branch)
+        {
+            let child = match self.tree
+            {
+                PlayerTree::Node(ref mut children) => children.swap_remove(mv.0),
+                PlayerTree::Leaf(_) => panic!("no moves past a leaf"),
+            };
+            self.tree = child;
+            self.player = (self.player + 1) % 3;
+        }
+
+        fn score(&self) -> Vec<i32>
+        {
+            match self.tree
+            {
+                PlayerTree::Node(_) => vec![3, 3, 4],
+                PlayerTree::Leaf(values) => values.to_vec(),
+            }
+        }
+
+        fn is_game_over(&self) -> bool
+        {
+            matches!(self.tree, PlayerTree::Leaf(_))
+        }
+
+        fn max_total(&self) -> Option<i32>
+        {
+            self.total
+        }
+    }
+
+    fn player_tree(rng: &mut XorShift, depth: u32) -> PlayerTree
+    {
+        if depth == 0 || rng.below(6) == 0
+        {
+            let first = rng.below(11) as i32;
+            let second = rng.below(11 - first as u64) as i32;
+            return PlayerTree::Leaf([first, second, 10 - first - second]);
+        }
+        PlayerTree::Node((0..1 + rng.below(4)).map(|_| player_tree(rng, depth - 1)).collect())
+    }
+
+    /* Max^n without a table or pruning, giving the values and line */
+    fn plain_maxn(board: &ThreePlayers, plies: u32) -> (Vec<i32>, Vec<Branch>)
+    {
+        if plies == 0 || board.is_game_over()
+        {
+            return (board.score(), Vec::new());
+        }
+
+        let mut best: Option<(Vec<i32>, Vec<Branch>)> = None;
+        for mv in board.moves()
+        {
+            let mut child = board.clone();
+            child.do_move(&mv);
+            let (values, mut mvs) = plain_maxn(&child, plies - 1);
+            if best.as_ref().is_none_or(|best| values[board.player] > best.0[board.player])
+            {
+                mvs.insert(0, mv);
+                best = Some((values, mvs));
+            }
+        }
+        best.unwrap()
+    }
+
+    #[test]
+    fn test_maxn()
+    {
+        let size = NonZeroUsize::new(10_000).unwrap();
+        let leaf = |values| PlayerTree::Leaf(values);
+
+        /* The second player takes 6 over 3 in the first subtree, leaving
+         * the first player 1, so they do better with the second subtree
+         */
+        let board = ThreePlayers {
+            tree: PlayerTree::Node(vec![
+                PlayerTree::Node(vec![leaf([5, 3, 2]), leaf([1, 6, 3])]),
+                PlayerTree::Node(vec![leaf([3, 4, 3]), leaf([4, 1, 5])]),
+            ]),
+            player: 0,
+            total: None,
+        };
+        let stats = MaxN::new(size).maxn(&board, 2);
+        assert_eq!((stats.mv, stats.values, stats.mvs), (Some(Branch(1)), vec![3, 4, 3], vec![Branch(1), Branch(0)]));
+        assert_eq!(stats.nodes_visited, 7);
+
+        /* One ply in, the subtrees are worth what unfinished games are */
+        let stats = MaxN::new(size).maxn(&board, 1);
+        assert_eq!((stats.mv, stats.values), (Some(Branch(0)), vec![3, 3, 4]));
+
+        /* Once the second player is sure of 7 in the second subtree, the
+         * first player can't get more than the 3 they have
+         */
+        let board = ThreePlayers {
+            tree: PlayerTree::Node(vec![
+                PlayerTree::Node(vec![leaf([3, 4, 3]), leaf([4, 1, 5])]),
+                PlayerTree::Node(vec![leaf([2, 7, 1]), leaf([9, 0, 1])]),
+            ]),
+            player: 0,
+            total: Some(10),
+        };
+        let stats = MaxN::new(size).maxn(&board, 2);
+        assert_eq!((stats.mv, stats.values), (Some(Branch(0)), vec![3, 4, 3]));
+        assert_eq!(stats.nodes_visited, 6);
+
+        /* Pruned or not, and with the table or without, the same moves */
+        let mut rng = XorShift(7);
+        for _ in 0..50
+        {
+            let tree = player_tree(&mut rng, 6);
+            let player = rng.below(3) as usize;
+            let board = ThreePlayers { tree, player, total: None };
+            let pruned = ThreePlayers { total: Some(10), ..board.clone() };
+            for plies in 1..7
+            {
+                let expected = plain_maxn(&board, plies);
+                let full = MaxN::new(size).maxn(&board, plies);
+                let cut = MaxN::new(size).maxn(&pruned, plies);
+                assert_eq!((&full.values, &full.mvs), (&expected.0, &expected.1));
+                assert_eq!((&cut.values, &cut.mvs), (&expected.0, &expected.1));
+                assert!(cut.nodes_visited <= full.nodes_visited);
+            }
+        }
+    }
+
+    #[test]
+    fn test_mcts()
+    {
+        let leaf = GameTree::Leaf;
+        let tree = GameTree::Node(vec![leaf(Score::Lose), leaf(Score::Win), leaf(Score::Draw)]);
+        let stats = Mcts::new(1).search(&tree, Team::Ally, Budget::Iterations(200));
+        assert_eq!((stats.mv, stats.win_rate, stats.visits), (Some(Branch(1)), 1.0, 200));
+        assert_eq!(stats.root_moves.len(), 3);
+        let stats = Mcts::new(1).search(&tree, Team::Enemy, Budget::Iterations(200));
+        assert_eq!((stats.mv, stats.win_rate), (Some(Branch(0)), 1.0));
+
+        /* The first move only wins if the enemy lets it. Drawing is safer. */
+        let tree = GameTree::Node(vec![
+            GameTree::Node(vec![leaf(Score::Win), leaf(Score::Lose)]),
+            GameTree::Node(vec![leaf(Score::Draw), leaf(Score::Draw)]),
+        ]);
+        let mut mcts = Mcts::new(2);
+        let stats = mcts.search(&tree, Team::Ally, Budget::Iterations(2000));
+        assert_eq!((stats.mv, stats.mvs.len()), (Some(Branch(1)), 2));
+        assert!((stats.win_rate - 0.5).abs() < 1e-9);
+        let risky = stats.root_moves.iter().find(|root| root.mv == Branch(0)).unwrap();
+        assert!(risky.win_rate < 0.5 && risky.visits < 1000);
+
+        /* The same source gives the same search, a closure included */
+        let mut state = 5u64;
+        let rng = move ||
+        {
+            state = state.wrapping_mul(6_364_136_223_846_793_005).wrapping_add(1);
+            state >> 33
+        };
+        let first = Mcts::new(3).search(&tree, Team::Ally, Budget::Iterations(300));
+        assert_eq!(Mcts::new(3).search(&tree, Team::Ally, Budget::Iterations(300)), first);
+        let closure = Mcts::with_rng(rng).search(&tree, Team::Ally, Budget::Iterations(300));
+        assert_eq!(closure.mv, Some(Branch(1)));
+
+        /* Moving on keeps what was learned below the move, and searching
+         * the same position again adds to it
+         */
+        let visited = stats.root_moves.iter().find(|root| root.mv == Branch(1)).unwrap().visits;
+        mcts.advance(&Branch(1));
+        let mut child = tree.clone();
+        child.do_move(&Branch(1));
+        assert_eq!(mcts.search(&child, Team::Enemy, Budget::Iterations(0)).visits, visited);
+        assert_eq!(mcts.search(&child, Team::Enemy, Budget::Iterations(10)).visits, visited + 10);
+        assert_eq!(mcts.search(&tree, Team::Ally, Budget::Iterations(10)).visits, 10);
+
+        /* Out of time, the search stops */
+        let ticks = Cell::new(0);
+        let clock = || { ticks.set(ticks.get() + 1); Duration::from_millis(ticks.get()) };
+        let stats = Mcts::new(4).search(&tree, Team::Ally, Budget::Time(Duration::from_millis(50), &clock));
+        assert_eq!(stats.visits, 50);
+
+        /* Games that never end need a cap on the playouts */
+        let mut mcts = Mcts::new(5);
+        mcts.set_playout_depth(Some(10));
+        let stats = mcts.search(&ShuffleBoard { left: true }, Team::Ally, Budget::Iterations(100));
+        assert_eq!(stats.visits, 100);
+        assert!(stats.win_rate > 0.0 && stats.win_rate < 1.0);
+    }
+
+    #[test]
+    fn test_proof_number_search()
+    {
+        let leaf = GameTree::Leaf;
+
+        /* Both replies to the second move lose for the enemy */
+        let tree = GameTree::Node(vec![
+            GameTree::Node(vec![leaf(Score::Win), leaf(Score::Draw)]),
+            GameTree::Node(vec![leaf(Score::Win), leaf(Score::Win)]),
+        ]);
+        assert_eq!(pns::prove(&tree, Team::Ally, 100), ProofResult::Proven(Some(Branch(1))));
+        assert_eq!(pns::prove(&tree, Team::Enemy, 100), ProofResult::Disproven);
+
+        let tree = GameTree::Node(vec![
+            GameTree::Node(vec![leaf(Score::Win)]),
+            GameTree::Node(vec![leaf(Score::Draw), leaf(Score::Lose)]),
+            GameTree::Node(vec![leaf(Score::Lose), leaf(Score::Lose)]),
+        ]);
+        assert_eq!(pns::prove(&tree, Team::Enemy, 100), ProofResult::Proven(Some(Branch(2))));
+
+        /* A draw isn't a win, and a game already won needs no move */
+        let tree = GameTree::Node(vec![GameTree::Node(vec![leaf(Score::Draw)])]);
+        assert_eq!(pns::prove(&tree, Team::Ally, 100), ProofResult::Disproven);
+        assert_eq!(pns::prove(&leaf(Score::Win), Team::Ally, 100), ProofResult::Proven(None));
+        assert_eq!(pns::prove(&leaf(Score::Win), Team::Enemy, 100), ProofResult::Disproven);
+
+        /* Too few nodes to tell */
+        assert_eq!(pns::prove(&GameTree::full(1, 4, 3), Team::Ally, 5), ProofResult::Unknown);
+        assert_eq!(pns::prove(&GameTree::full(1, 4, 3), Team::Ally, 1000), ProofResult::Disproven);
+
+        /* Proven exactly where the whole search finds a win, and the move
+         * given keeps it
+         */
+        let size = NonZeroUsize::new(10_000).unwrap();
+        let mut prover = ProofSearch::new(size);
+        for seed in 0..40
+        {
+            let tree = GameTree::random(seed, 6);
+            for &turn in [Team::Ally, Team::Enemy].iter()
+            {
+                let win = match turn
+                {
+                    Team::Ally => Score::Win,
+                    Team::Enemy => Score::Lose,
+                };
+                let proof = prover.prove(&tree, turn, 100_000);
+                assert_eq!(proof, pns::prove(&tree, turn, 100_000));
+                let expected = Minimax::new(size).minimax(&tree, turn, 7);
+                match proof
+                {
+                    ProofResult::Proven(Some(mv)) => {
+                        let mut child = tree.clone();
+                        child.do_move(&mv);
+                        assert_eq!(Minimax::new(size).minimax(&child, turn.other_team(), 7).score.score, win);
+                    }
+                    ProofResult::Proven(None) => assert!(tree.is_game_over()),
+                    ProofResult::Disproven => assert!(expected.score.score != win),
+                    ProofResult::Unknown => panic!("{} nodes weren't enough", 100_000),
+                }
+                assert_eq!(expected.score.score == win, matches!(proof, ProofResult::Proven(_)));
+            }
+        }
+    }
+
+    #[test]
+    fn test_verify()
+    {
+        let size = NonZeroUsize::new(10_000).unwrap();
+        fn check<M: std::fmt::Debug, S: std::fmt::Debug>(verification: Verification<M, S>)
+        {
+            assert!(!verification.mismatch, "{:?}", verification);
+        }
+
+        for seed in 0..30
+        {
+            let tree = GameTree::random(seed, 6);
+            for &turn in [Team::Ally, Team::Enemy].iter()
+            {
+                for plies in 1..7
+                {
+                    check(Minimax::new(size).verify(&tree, turn, plies));
+                    let mut pvs = Minimax::new(size);
+                    pvs.set_search_mode(SearchMode::PrincipalVariation);
+                    check(pvs.verify(&tree, turn, plies));
+                }
+            }
+        }
+
+        /* One table for every depth, and boards with more to them */
+        let mut minimax = Minimax::new(size);
+        let tree = RandomTree::new(3, 5, 4);
+        for plies in 1..6
+        {
+            check(minimax.verify(&tree, Team::Ally, plies));
+        }
+        check(Minimax::new(size).verify(&game1(), Team::Enemy, 4));
+        check(Minimax::new(size).verify(&HalfExtendedBoard(game1()), Team::Ally, 3));
+        let mut repeating = Minimax::new(size);
+        repeating.set_repetition_score(Some(Score::Draw));
+        check(repeating.verify(&ShuffleBoard { left: true }, Team::Ally, 6));
+
+        /* The reference on its own is minimax with the default settings */
+        let plain = reference::minimax(&game1(), Team::Ally, 4);
+        let expected = Minimax::new(size).minimax(&game1(), Team::Ally, 4);
+        assert_eq!((&plain.mv, plain.score, &plain.mvs), (&expected.mv, expected.score, &expected.mvs));
+        assert_eq!(plain.root_moves.len(), game1().moves(Team::Ally).count());
+
+        /* Razoring on a board whose static scores mean nothing prunes
+         * the better move, which the reference still finds
+         */
+        let tree = GameTree::Node(vec![
+            GameTree::Leaf(Score::Heuristic(3)),
+            GameTree::Node(vec![GameTree::Node(vec![GameTree::Leaf(Score::Heuristic(10))])]),
+        ]);
+        let mut razored = Minimax::new(size);
+        razored.set_razoring_margins(vec![0]);
+        let verification = razored.verify(&tree, Team::Ally, 3);
+        assert!(verification.mismatch);
+        assert_eq!((verification.search.mv, verification.search.score.score), (Some(Branch(0)), Score::Heuristic(3)));
+        assert_eq!((verification.reference.mv, verification.reference.score.score), (Some(Branch(1)), Score::Heuristic(10)));
+    }
+
+    #[test]
+    fn test_repetition()
+    {
+        let board = ShuffleBoard { left: true };
+
+        /* Left alone, whoever moves last gets the square they like */
+        let mut plain = Minimax::new(NonZeroUsize::new(1000).unwrap());
+        let shuffled = plain.minimax(&board, Team::Ally, 20);
+        assert_eq!(shuffled.score.score, Score::Heuristic(-40));
+        let odd = Minimax::new(NonZeroUsize::new(1000).unwrap()).minimax(&board, Team::Ally, 21);
+        assert_eq!(odd.score.score, Score::Heuristic(40));
+
+        /* Every line comes back around within a few plies */
+        let mut nodes = Vec::new();
+        for &turn in [Team::Ally, Team::Enemy].iter()
+        {
+            for &plies in [8, 20, 40].iter()
+            {
+                let mut minimax = Minimax::new(NonZeroUsize::new(1000).unwrap());
+                minimax.set_repetition_score(Some(Score::Draw));
+                let stats = minimax.minimax(&board, turn, plies);
+                assert_eq!(stats.score.score, Score::Draw);
+                assert!(stats.mvs.len() <= 4);
+                nodes.push(stats.nodes_visited);
+
+                /* None of it holds off this path, so none of it is kept */
+                assert!(minimax.ttable.is_empty());
+            }
+        }
+        assert!(nodes.iter().all(|&n| n == nodes[0]));
+        assert!(nodes[0] < shuffled.nodes_visited);
+
+        /* Without repeats nothing changes, transpositions included */
+        let tree = full_tree(&mut XorShift(0x6a09_e667_f3bc_c908), 5, 3);
+        for board in [game1(), game2(), tree].iter()
+        {
+            let mut minimax = Minimax::new(NonZeroUsize::new(1000).unwrap());
+            minimax.set_repetition_score(Some(Score::Draw));
+            let stats = minimax.minimax(board, Team::Ally, 5);
+            assert_eq!(stats, Minimax::new(NonZeroUsize::new(1000).unwrap()).minimax(board, Team::Ally, 5));
+            assert!(!minimax.ttable.is_empty());
+        }
+    }
+
+    /* A pile of tokens. The ally takes one or two, the enemy only ever two,
+     * so one token left means the enemy is stuck, like a player in Othello
+     * with nowhere to go. Whoever took more is ahead.
+     */
+    #[derive(Clone,PartialEq,Eq,Hash,Debug)]
+    struct PileBoard
+    {
+        pile: u32,
+        lead: i32,
+        stuck: NoMovePolicy,
+    }
+
+    impl Board for PileBoard
+    {
+        /* Whether the ally took them, and how many */
+        type Move = (bool, u32);
+
+        fn gen_ally_moves(&self) -> Vec<Self::Move>
+        {
+            (1..3).filter(|&take| take <= self.pile).map(|take| (true, take)).collect()
+        }
+
+        fn gen_enemy_moves(&self) -> Vec<Self::Move>
+        {
+            if self.pile >= 2 { vec![(false, 2)] } else { Vec::new() }
+        }
+
+        fn do_move(&mut self, &(ally, take): &Self::Move)
+        {
+            self.pile -= take;
+            self.lead += if ally { take as i32 } else { -(take as i32) };
+        }
+
+        fn score(&self) -> Score
+        {
+            Score::Heuristic(self.lead)
+        }
+
+        fn is_game_over(&self) -> bool
+        {
+            self.pile == 0
+        }
+
+        fn on_no_moves(&self, _turn: Team) -> NoMovePolicy
+        {
+            self.stuck
+        }
+    }
+
+    /* Plain minimax over the rules above, with scores for the ally */
+    fn pile_reference(board: &PileBoard, turn: Team, plies: u32) -> Score
+    {
+        if board.is_game_over()
+        {
+            return board.score_for(turn);
+        }
+
+        let moves = board.moves(turn).collect::<Vec<_>>();
+        if moves.is_empty()
+        {
+            return match (board.stuck, turn)
+            {
+                (NoMovePolicy::Lose, Team::Ally) => Score::Lose,
+                (NoMovePolicy::Lose, Team::Enemy) => Score::Win,
+                (NoMovePolicy::Pass, _) if plies > 0 => pile_reference(board, turn.other_team(), plies - 1),
+                _ => board.score_for(turn),
+            };
+        }
+        if plies == 0
+        {
+            return board.score_for(turn);
+        }
+
+        let scores = moves.iter().map(|mv|
+        {
+            let mut child = board.clone();
+            child.do_move(mv);
+            pile_reference(&child, turn.other_team(), plies - 1)
+        });
+        match turn
+        {
+            Team::Ally => scores.max().unwrap(),
+            Team::Enemy => scores.min().unwrap(),
+        }
+    }
+
+    #[test]
+    fn test_no_move_policy()
+    {
+        let pile = |pile, stuck| PileBoard { pile, lead: 0, stuck };
+
+        /* An empty pile is game over, whatever the moves say */
+        for &stuck in [NoMovePolicy::Lose, NoMovePolicy::Pass, NoMovePolicy::ScoreBoard].iter()
+        {
+            let done = PileBoard { lead: 3, ..pile(0, stuck) };
+            let stats = Minimax::new(NonZeroUsize::new(100).unwrap()).minimax(&done, Team::Enemy, 4);
+            assert_eq!(stats.score.score, Score::Heuristic(3));
+        }
+
+        /* Stuck with one token: lost, passed or scored as it is */
+        let mut minimax = Minimax::new(NonZeroUsize::new(100).unwrap());
+        assert_eq!(minimax.minimax(&pile(1, NoMovePolicy::Lose), Team::Enemy, 3).score.score, Score::Win);
+        let passed = minimax.minimax(&pile(1, NoMovePolicy::Pass), Team::Enemy, 3);
+        assert_eq!(passed.mv, None);
+        assert_eq!(passed.score, TimedScore {
+            score: Score::Heuristic(1),
+            turns: 2,
+        });
+        assert_eq!(passed.nodes_visited, 3);
+        assert_eq!(minimax.minimax(&pile(1, NoMovePolicy::ScoreBoard), Team::Enemy, 3).score.score, Score::Heuristic(0));
+
+        for &stuck in [NoMovePolicy::Lose, NoMovePolicy::Pass, NoMovePolicy::ScoreBoard].iter()
+        {
+            for start in 1..8
+            {
+                for &turn in [Team::Ally, Team::Enemy].iter()
+                {
+                    for plies in 1..8
+                    {
+                        let board = pile(start, stuck);
+                        let stats = Minimax::new(NonZeroUsize::new(1000).unwrap()).minimax(&board, turn, plies);
+                        assert_eq!(stats.score.score, pile_reference(&board, turn, plies), "{:?} {} {:?} {}", stuck, start, turn, plies);
+                    }
+                }
+            }
+        }
+    }
+
+    #[test]
+    fn test_plain_minimax_mode()
+    {
+        let mut rng = XorShift(0x510e_527f_ade6_82d1);
+        for _ in 0..200
+        {
+            let depth = 1 + rng.below(6) as u32;
+            let tree = random_tree(&mut rng, depth, &mut Vec::new());
+
+            for &turn in [Team::Ally, Team::Enemy].iter()
+            {
+                let mut pruned = Minimax::new(NonZeroUsize::new(1000).unwrap());
+                let mut plain = Minimax::new(NonZeroUsize::new(1000).unwrap());
+                plain.set_search_mode(SearchMode::PlainMinimax);
+
+                let expected = pruned.minimax(&tree, turn, depth);
+                let stats = plain.minimax(&tree, turn, depth);
+                assert_eq!((&stats.mv, stats.score), (&expected.mv, expected.score), "{:?} to move, tree {:?}", turn, tree);
+                /* Exact entries answer more lookups, so only without the
+                 * table are the nodes comparable
+                 */
+                if plain.search_stats().tt_hits == 0
+                {
+                    assert!(stats.nodes_visited >= expected.nodes_visited);
+                }
+
+                /* Nothing is ever cut short without pruning */
+                assert_eq!(plain.search_stats().beta_cutoffs, 0);
+                assert_eq!(plain.search_stats().alpha_improvements, 0);
+            }
+        }
+
+        let tree = full_tree(&mut XorShift(0x9b05_688c_2b3e_6c1f), 6, 4);
+        let mut pruned = Minimax::new(NonZeroUsize::new(10_000).unwrap());
+        let mut plain = Minimax::new(NonZeroUsize::new(10_000).unwrap());
+        plain.set_search_mode(SearchMode::PlainMinimax);
+        let expected = pruned.minimax(&tree, Team::Ally, 6);
+        let stats = plain.minimax(&tree, Team::Ally, 6);
+        assert_eq!((stats.mv, stats.score, stats.mvs), (expected.mv, expected.score, expected.mvs));
+
+        /* Without transpositions plain minimax visits every node */
+        assert_eq!(stats.nodes_visited, (0..7).map(|d| 4u64.pow(d)).sum());
+        assert!(expected.nodes_visited < stats.nodes_visited / 4);
+        assert!(pruned.search_stats().beta_cutoffs > 0);
+        assert!(pruned.search_stats().alpha_improvements > 0);
+        assert_eq!(pruned.search_stats().tt_hits, 0);
+
+        /* The same search again comes straight from the table */
+        pruned.minimax(&tree, Team::Ally, 6);
+        assert_eq!(pruned.search_stats(), SearchStats {
+            tt_hits: 1,
+            tt_probes: 1,
+            ..SearchStats::default()
+        });
+
+        /* Quiescence search is left unpruned too */
+        let board = tactical(0, vec![
+            (false, tactical(0, vec![
+                (true, tactical(-3, vec![
+                    (true, tactical(2, vec![])),
+                    (true, tactical(-1, vec![])),
+                ])),
+            ])),
+            (false, tactical(0, vec![])),
+        ]);
+        let mut plain = Minimax::new(NonZeroUsize::new(1000).unwrap());
+        plain.set_search_mode(SearchMode::PlainMinimax);
+        let expected = Minimax::new(NonZeroUsize::new(1000).unwrap()).minimax(&board, Team::Ally, 2);
+        let stats = plain.minimax(&board, Team::Ally, 2);
+        assert_eq!((stats.mv, stats.score), (expected.mv, expected.score));
+        assert_eq!(plain.search_stats().beta_cutoffs, 0);
+    }
+
+    /* Six lights to switch on and off, each worth something to the ally.
+     * The hash is kept up to date move by move, the way Zobrist hashing is
+     * meant to be used.
+     */
+    #[derive(Clone,PartialEq,Eq,Hash,Debug)]
+    struct LightsBoard
+    {
+        lit: u8,
+        hash: u64,
+    }
+
+    const LIGHT_WORTH: [i32; 6] = [3, -2, 5, -4, 1, -1];
+
+    /* A fixed random key per light */
+    fn light_key(light: usize) -> u64
+    {
+        let mut x = (light as u64 + 1).wrapping_mul(0x9e37_79b9_7f4a_7c15);
+        x ^= x >> 31;
+        x.wrapping_mul(0xbf58_476d_1ce4_e5b9)
+    }
+
+    impl LightsBoard
+    {
+        fn new(lit: u8) -> LightsBoard
+        {
+            let hash = (0..6).filter(|&light| lit & (1 << light) != 0).fold(0, |hash, light| hash ^ light_key(light));
+            LightsBoard {
+                lit,
+                hash,
+            }
+        }
+    }
+
+    impl Board for LightsBoard
+    {
+        type Move = usize;
+
+        fn gen_ally_moves(&self) -> Vec<Self::Move>
+        {
+            (0..6).collect()
+        }
+
+        fn gen_enemy_moves(&self) -> Vec<Self::Move>
+        {
+            (0..6).collect()
+        }
+
+        fn do_move(&mut self, &light: &Self::Move)
+        {
+            self.lit ^= 1 << light;
+            self.hash ^= light_key(light);
+        }
+
+        fn score(&self) -> Score
+        {
+            Score::Heuristic((0..6).filter(|&light| self.lit & (1 << light) != 0).map(|light| LIGHT_WORTH[light]).sum())
+        }
+
+        fn is_game_over(&self) -> bool
+        {
+            false
+        }
+    }
+
+    impl ZobristBoard for LightsBoard
+    {
+        fn zobrist(&self) -> u64
+        {
+            self.hash
+        }
+    }
+
+    #[test]
+    fn test_zobrist_tables()
+    {
+        /* Switching lights in any order lands on the hash from scratch */
+        let mut rng = XorShift(0x1f83_d9ab_fb41_bd6b);
+        let mut board = LightsBoard::new(0);
+        for _ in 0..100
+        {
+            board.do_move(&(rng.below(6) as usize));
+            assert_eq!(board.zobrist(), LightsBoard::new(board.lit).zobrist());
+        }
+
+        /* Without collisions it searches exactly like board keys */
+        for &turn in [Team::Ally, Team::Enemy].iter()
+        {
+            for plies in 1..6
+            {
+                let board = LightsBoard::new(0b010110);
+                let mut keyed = Minimax::new(NonZeroUsize::new(10_000).unwrap());
+                let mut hashed = Minimax::with_zobrist_tables(NonZeroUsize::new(10_000).unwrap(), ReplacementPolicy::Lru);
+                let expected = keyed.minimax(&board, turn, plies);
+                assert_eq!(hashed.minimax(&board, turn, plies), expected);
+                /* Only the keys take less room */
+                assert!(hashed.table_stats().bytes <= keyed.table_stats().bytes);
+                assert_eq!(TableStats { bytes: keyed.table_stats().bytes, ..hashed.table_stats() }, keyed.table_stats());
+                assert_eq!(hashed.minimax(&board, turn, plies).mv, expected.mv);
+            }
+        }
+
+        /* The side to move is part of the key */
+        let board = LightsBoard::new(0b000101);
+        assert!(<ZobristKey as PositionKey<LightsBoard, Score>>::of(&board, Team::Ally) !=
+                <ZobristKey as PositionKey<LightsBoard, Score>>::of(&board, Team::Enemy));
+
+        let mut hashed = Minimax::with_zobrist_tables(NonZeroUsize::new(10_000).unwrap(), ReplacementPolicy::DepthPreferred);
+        hashed.minimax(&board, Team::Ally, 3);
+        let mut saved = Vec::new();
+        hashed.save_tables(&mut saved).unwrap();
+        let mut loaded: Minimax<LightsBoard, Score, ZobristKey> = Minimax::with_zobrist_tables(NonZeroUsize::new(10_000).unwrap(), ReplacementPolicy::DepthPreferred);
+        loaded.load_tables(&mut &saved[..]).unwrap();
+        assert_eq!(loaded.ttable.to_entries(), hashed.ttable.to_entries());
+    }
+
+    #[test]
+    fn test_hash_only_collisions()
+    {
+        let stats = |v| MoveStats {
+            mv: Some(SimpleMove(v)),
+            score: TimedScore {
+                score: Score::Heuristic(v as i32),
+                turns: 1,
+            },
+            nodes_visited: 1,
+            mvs: vec![SimpleMove(v)],
+            depth_reached: 1,
+            extended_depth: 0,
+            seldepth: 0,
+            exhaustive: true,
+            completed: true,
+            solved: false,
+        };
+
+        /* Every board with its own hash keeps its own entry */
+        let mut table = HashOnlyTable::new(NonZeroUsize::new(64).unwrap());
+        let boards: Vec<_> = (0..64).map(LightsBoard::new).collect();
+        for (idx, board) in boards.iter().enumerate()
+        {
+            let key = <ZobristKey as PositionKey<LightsBoard, Score>>::of(board, Team::Ally);
+            table.insert(key, stats(idx), 1, Bound::Exact);
+        }
+        for (idx, board) in boards.iter().enumerate()
+        {
+            let key = <ZobristKey as PositionKey<LightsBoard, Score>>::of(board, Team::Ally);
+            assert_eq!(table.get(&key, 1), Some((stats(idx), Bound::Exact)));
+            let other_side = <ZobristKey as PositionKey<LightsBoard, Score>>::of(board, Team::Enemy);
+            assert_eq!(table.get(&other_side, 0), None);
+        }
+
+        /* Two boards after the same search don't see each other's results */
+        let mut shared = Minimax::with_zobrist_tables(NonZeroUsize::new(10_000).unwrap(), ReplacementPolicy::Lru);
+        for lit in [0b000000, 0b101010, 0b110011, 0b000000].iter()
+        {
+            let board = LightsBoard::new(*lit);
+            let fresh = Minimax::new(NonZeroUsize::new(10_000).unwrap()).minimax(&board, Team::Ally, 4);
+            let stats = shared.minimax(&board, Team::Ally, 4);
+            assert_eq!((stats.mv, stats.score, stats.mvs), (fresh.mv, fresh.score, fresh.mvs));
+        }
+
+        /* What the hash can't tell apart is one position to the table */
+        let mut colliding = LightsBoard::new(0b000001);
+        colliding.hash = LightsBoard::new(0b000010).hash;
+        let mut minimax = Minimax::with_zobrist_tables(NonZeroUsize::new(10_000).unwrap(), ReplacementPolicy::Lru);
+        let first = minimax.minimax(&LightsBoard::new(0b000010), Team::Ally, 2);
+        assert_eq!(minimax.minimax(&colliding, Team::Ally, 2).score, first.score);
+
+        /* Unless the check word tells them apart */
+        let mut checked = Minimax::with_checked_zobrist_tables(NonZeroUsize::new(10_000).unwrap(), ReplacementPolicy::Lru);
+        checked.minimax(&LightsBoard::new(0b000010), Team::Ally, 2);
+        assert!(checked.probe(&LightsBoard::new(0b000010), Team::Ally).is_some());
+        assert!(checked.probe(&colliding, Team::Ally).is_none());
+        let fresh = Minimax::new(NonZeroUsize::new(10_000).unwrap()).minimax(&colliding, Team::Ally, 2);
+        let stats = checked.minimax(&colliding, Team::Ally, 2);
+        assert_eq!((stats.mv, stats.score, stats.nodes_visited), (fresh.mv, fresh.score, fresh.nodes_visited));
+        assert!(fresh.score != first.score);
+
+        /* Checked keys save and load like the others */
+        let mut saved = Vec::new();
+        checked.save_tables(&mut saved).unwrap();
+        let mut loaded: Minimax<LightsBoard, Score, CheckedZobristKey> = Minimax::with_checked_zobrist_tables(NonZeroUsize::new(10_000).unwrap(), ReplacementPolicy::Lru);
+        loaded.load_tables(&mut &saved[..]).unwrap();
+        assert_eq!(loaded.ttable.to_entries(), checked.ttable.to_entries());
+    }
+
+    /* Ten moves everywhere, two of which are good for whoever plays them,
+     * though the board lists them last. Leaves are scored by who played
+     * the good moves, plus some noise from the path that led there.
+     */
+    #[derive(Clone,PartialEq,Eq,Hash,Debug)]
+    struct WideBoard
+    {
+        path: u64,
+        worth: i32,
+        ally_to_move: bool,
+    }
+
+    const WIDE_WORTH: [i32; 10] = [0, 1, 0, 2, 1, 0, 3, 1, 40, 25];
+
+    impl WideBoard
+    {
+        fn new(seed: u64, turn: Team) -> WideBoard
+        {
+            WideBoard {
+                path: seed,
+                worth: 0,
+                ally_to_move: turn == Team::Ally,
+            }
+        }
+    }
+
+    impl Board for WideBoard
+    {
+        type Move = usize;
+
+        fn gen_ally_moves(&self) -> Vec<Self::Move>
+        {
+            (0..10).collect()
+        }
+
+        fn gen_enemy_moves(&self) -> Vec<Self::Move>
+        {
+            (0..10).collect()
+        }
+
+        fn do_move(&mut self, &mv: &Self::Move)
+        {
+            self.path = (self.path ^ mv as u64).wrapping_mul(0x2545_f491_4f6c_dd1d).rotate_left(29);
+            self.worth += if self.ally_to_move { WIDE_WORTH[mv] } else { -WIDE_WORTH[mv] };
+            self.ally_to_move = !self.ally_to_move;
+        }
+
+        fn score(&self) -> Score
+        {
+            Score::Heuristic(self.worth * 100 + (self.path % 97) as i32)
+        }
+
+        fn is_game_over(&self) -> bool
+        {
+            false
+        }
+    }
+
+    #[test]
+    fn test_killers_and_history()
+    {
+        let mut total_plain = 0;
+        let mut total_heuristics = 0;
+        for seed in 0..4
+        {
+            for &turn in [Team::Ally, Team::Enemy].iter()
+            {
+                let board = WideBoard::new(seed, turn);
+                let mut plain = Minimax::new(NonZeroUsize::new(100_000).unwrap());
+                let mut heuristics = Minimax::with_heuristics(NonZeroUsize::new(100_000).unwrap());
+                let expected = plain.minimax(&board, turn, 4);
+                let stats = heuristics.minimax(&board, turn, 4);
+                assert_eq!((stats.mv, stats.score), (expected.mv, expected.score));
+                assert!(stats.nodes_visited < expected.nodes_visited);
+                total_plain += expected.nodes_visited;
+                total_heuristics += stats.nodes_visited;
+            }
+        }
+        assert!(total_heuristics * 2 < total_plain, "{} vs {}", total_heuristics, total_plain);
+
+        /* The history learns which move to try first */
+        let board = WideBoard::new(5, Team::Ally);
+        let mut plain = Minimax::new(NonZeroUsize::new(100_000).unwrap());
+        let mut history = Minimax::new(NonZeroUsize::new(100_000).unwrap());
+        history.set_history_heuristic(true);
+        plain.minimax(&board, Team::Ally, 4);
+        history.minimax(&board, Team::Ally, 4);
+        let before = plain.search_stats().first_move_cutoff_rate();
+        let after = history.search_stats().first_move_cutoff_rate();
+        assert!(after > before, "{} vs {}", after, before);
+        assert!(after <= 1.0);
+
+        /* Decaying halves what it learned, and clearing forgets it */
+        let counts = |minimax: &Minimax<WideBoard>| board.gen_ally_moves().iter().map(|mv| minimax.history.as_ref().unwrap().count(mv)).collect::<Vec<_>>();
+        let learned = counts(&history);
+        assert!(learned.iter().any(|&count| count > 1));
+        history.decay_history();
+        assert_eq!(counts(&history), learned.iter().map(|&count| count / 2).collect::<Vec<_>>());
+        history.clear();
+        assert!(counts(&history).iter().all(|&count| count == 0));
+        assert_eq!(SearchStats::default().first_move_cutoff_rate(), 0.0);
+
+        /* Each on its own still finds the same move */
+        let board = WideBoard::new(7, Team::Ally);
+        let expected = Minimax::new(NonZeroUsize::new(100_000).unwrap()).minimax(&board, Team::Ally, 4);
+        for &(killers, history) in [(true, false), (false, true)].iter()
+        {
+            let mut minimax = Minimax::new(NonZeroUsize::new(100_000).unwrap());
+            minimax.set_killer_moves(killers);
+            minimax.set_history_heuristic(history);
+            let stats = minimax.minimax(&board, Team::Ally, 4);
+            assert_eq!((stats.mv, stats.score), (expected.mv, expected.score));
+            assert!(stats.nodes_visited < expected.nodes_visited);
+        }
+
+        /* And so do the other drivers */
+        let mut heuristics = Minimax::with_heuristics(NonZeroUsize::new(100_000).unwrap());
+        let stats = heuristics.minimax_parallel(&board, Team::Ally, 4, 3);
+        assert_eq!((stats.mv, stats.score), (expected.mv, expected.score));
+        heuristics.clear();
+        let best = heuristics.minimax_multi(&board, Team::Ally, 3, 3);
+        let reference = Minimax::new(NonZeroUsize::new(100_000).unwrap()).minimax_multi(&board, Team::Ally, 3, 3);
+        let moves = |results: &[MoveStats<usize>]| results.iter().map(|stats| (stats.mv, stats.score)).collect::<Vec<_>>();
+        assert_eq!(moves(&best), moves(&reference));
+    }
+
+    #[test]
+    fn test_countermoves()
+    {
+        let mut total_plain = 0;
+        let mut total_counters = 0;
+        for seed in 0..4
+        {
+            let board = WideBoard::new(seed, Team::Ally);
+            let expected = Minimax::new(NonZeroUsize::new(100_000).unwrap()).minimax(&board, Team::Ally, 4);
+            let mut counters = Minimax::new(NonZeroUsize::new(100_000).unwrap());
+            counters.set_countermoves(true);
+            let stats = counters.minimax(&board, Team::Ally, 4);
+            assert_eq!((stats.mv, stats.score), (expected.mv, expected.score));
+            total_plain += expected.nodes_visited;
+            total_counters += stats.nodes_visited;
+
+            /* Every refutation is filed under the move it answered */
+            let learned = |minimax: &Minimax<WideBoard>, turn| board.gen_ally_moves().iter()
+                .filter(|mv| minimax.countermoves.as_ref().unwrap().get(turn, mv).is_some())
+                .count();
+            assert!(learned(&counters, Team::Enemy) > 0);
+            counters.clear();
+            assert_eq!((learned(&counters, Team::Ally), learned(&counters, Team::Enemy)), (0, 0));
+        }
+        assert!(total_counters < total_plain, "{} vs {}", total_counters, total_plain);
+
+        /* Along with the other heuristics too */
+        let board = WideBoard::new(7, Team::Ally);
+        let expected = Minimax::new(NonZeroUsize::new(100_000).unwrap()).minimax(&board, Team::Ally, 4);
+        let mut all = Minimax::with_heuristics(NonZeroUsize::new(100_000).unwrap());
+        all.set_countermoves(true);
+        let stats = all.minimax(&board, Team::Ally, 4);
+        assert_eq!((stats.mv, stats.score), (expected.mv, expected.score));
+        let stats = all.minimax_parallel(&board, Team::Ally, 4, 3);
+        assert_eq!((stats.mv, stats.score), (expected.mv, expected.score));
+    }
+
+    #[test]
+    fn test_refutation_table()
+    {
+        let mut total_plain = 0;
+        let mut total_refuted = 0;
+        for seed in 0..4
+        {
+            let board = WideBoard::new(seed, Team::Ally);
+            let expected = Minimax::new(NonZeroUsize::new(100_000).unwrap()).minimax(&board, Team::Ally, 4);
+            let mut refuted = Minimax::new(NonZeroUsize::new(100_000).unwrap());
+            refuted.set_refutation_table(NonZeroUsize::new(1000));
+            let stats = refuted.minimax(&board, Team::Ally, 4);
+            assert_eq!((stats.mv, stats.score), (expected.mv, expected.score));
+            total_plain += expected.nodes_visited;
+            total_refuted += stats.nodes_visited;
+
+            /* It is right often enough to measure */
+            let search_stats = refuted.search_stats();
+            assert!(search_stats.refutation_hits > 0);
+            assert!(search_stats.refutation_hits <= search_stats.refutation_probes);
+
+            let learned = |minimax: &Minimax<WideBoard>| board.gen_ally_moves().iter()
+                .filter(|mv| minimax.refutations.as_ref().unwrap().get(Team::Ally, mv).is_some())
+                .count();
+            assert!(learned(&refuted) > 0);
+            refuted.clear();
+            assert_eq!(learned(&refuted), 0);
+        }
+        assert!(total_refuted < total_plain, "{} vs {}", total_refuted, total_plain);
+
+        /* Full, it forgets the move filed the longest ago */
+        let mut table = RefutationTable::new(2);
+        table.record(Team::Ally, &1, &10);
+        table.record(Team::Enemy, &1, &11);
+        table.record(Team::Ally, &1, &12);
+        table.record(Team::Ally, &2, &20);
+        assert_eq!((table.get(Team::Ally, &1), table.get(Team::Enemy, &1), table.get(Team::Ally, &2)), (None, Some(&11), Some(&20)));
+    }
+
+    #[test]
+    fn test_move_hint()
+    {
+        /* A hint stored one ply deep is still tried first three plies deep */
+        let board = WideBoard::new(2, Team::Ally);
+        let mut minimax = Minimax::new(NonZeroUsize::new(100_000).unwrap());
+        assert_eq!(minimax.move_hint(&board, Team::Ally), None);
+        minimax.seed(board.clone(), Team::Ally, 5, TimedScore { score: Score::Heuristic(0), turns: 1 }, 1);
+        assert_eq!(minimax.move_hint(&board, Team::Ally), Some(5));
+        assert_eq!(minimax.move_hint(&board, Team::Enemy), None);
+        assert_eq!(minimax.table_stats().hits + minimax.table_stats().misses, 0);
+
+        let (stats, trace) = minimax.search_with_trace(&board, Team::Ally, &SearchOptions::depth(3).build(), 1);
+        assert_eq!(trace.root_moves().next().map(|root| *root.mv), Some(5));
+        assert_eq!(minimax.move_hint(&board, Team::Ally), stats.mv);
+
+        /* Hints don't keep an entry from being the next one evicted */
+        let mut minimax = Minimax::new(NonZeroUsize::new(2).unwrap());
+        let boards: Vec<_> = (10..13).map(|seed| WideBoard::new(seed, Team::Ally)).collect();
+        let score = TimedScore { score: Score::Heuristic(0), turns: 1 };
+        minimax.seed(boards[0].clone(), Team::Ally, 1, score, 1);
+        minimax.seed(boards[1].clone(), Team::Ally, 2, score, 1);
+        assert_eq!(minimax.move_hint(&boards[0], Team::Ally), Some(1));
+        minimax.seed(boards[2].clone(), Team::Ally, 3, score, 1);
+        assert_eq!(minimax.move_hint(&boards[0], Team::Ally), None);
+        assert_eq!(minimax.move_hint(&boards[1], Team::Ally), Some(2));
+    }
+
+    /* A `WideBoard` whose moves are all forcing */
+    #[derive(Clone,PartialEq,Eq,Hash)]
+    struct ForcingBoard(WideBoard);
+
+    impl Board for ForcingBoard
+    {
+        type Move = usize;
+
+        fn gen_ally_moves(&self) -> Vec<usize>
+        {
+            self.0.gen_ally_moves()
+        }
+
+        fn gen_enemy_moves(&self) -> Vec<usize>
+        {
+            self.0.gen_enemy_moves()
+        }
+
+        fn do_move(&mut self, mv: &usize)
+        {
+            self.0.do_move(mv);
+        }
+
+        fn score_for(&self, to_move: Team) -> Score
+        {
+            self.0.score_for(to_move)
+        }
+
+        fn score(&self) -> Score
+        {
+            self.score_for(Team::Ally)
+        }
+
+        fn is_game_over(&self) -> bool
+        {
+            false
+        }
+
+        fn is_tactical(&self, _mv: &usize, _turn: Team) -> bool
+        {
+            true
+        }
+    }
+
+    #[test]
+    fn test_late_move_reductions()
+    {
+        let size = NonZeroUsize::new(100_000).unwrap();
+        let board = WideBoard::new(4, Team::Ally);
+        let full = SearchOptions::depth(5).build();
+        let reduced = SearchOptions::depth(5).late_move_reductions(LateMoveReductions::default()).build();
+
+        let mut plain = Minimax::with_heuristics(size);
+        let expected = plain.search(&board, Team::Ally, &full);
+        assert_eq!(plain.search_stats().reduced_searches, 0);
+
+        /* Late moves are searched less deep, and some of them again */
+        let mut lmr = Minimax::with_heuristics(size);
+        let stats = lmr.search(&board, Team::Ally, &reduced);
+        let counters = lmr.search_stats();
+        assert!(stats.completed);
+        assert!(stats.nodes_visited < expected.nodes_visited, "{} vs {}", stats.nodes_visited, expected.nodes_visited);
+        assert!(counters.reduced_searches > 0);
+        assert!(counters.reduction_re_searches > 0);
+        assert!(counters.reduction_re_searches < counters.reduced_searches);
+        assert_eq!(stats.mv, expected.mv);
+
+        /* As with principal variation search */
+        let mut both = Minimax::with_heuristics(size);
+        both.set_search_mode(SearchMode::PrincipalVariation);
+        let stats = both.search(&board, Team::Ally, &reduced);
+        assert!(stats.nodes_visited < expected.nodes_visited);
+        assert!(both.search_stats().reduced_searches > 0);
+
+        /* Not below the minimum depth, and never tactical moves */
+        let shallow = SearchOptions::depth(2).late_move_reductions(LateMoveReductions::default()).build();
+        lmr.clear();
+        lmr.search(&board, Team::Ally, &shallow);
+        assert_eq!(lmr.search_stats().reduced_searches, 0);
+
+        let forcing = ForcingBoard(board);
+        let expected = Minimax::with_heuristics(size).search(&forcing, Team::Ally, &full);
+        let mut lmr = Minimax::with_heuristics(size);
+        let stats = lmr.search(&forcing, Team::Ally, &reduced);
+        assert_eq!(lmr.search_stats().reduced_searches, 0);
+        assert_eq!((stats.mv, stats.score, stats.nodes_visited), (expected.mv, expected.score, expected.nodes_visited));
+    }
+
+    #[test]
+    fn test_root_ordering()
+    {
+        let size = NonZeroUsize::new(100_000).unwrap();
+        for &turn in [Team::Ally, Team::Enemy].iter()
+        {
+            let board = RandomTree::new(1, 10, 8);
+            let mut minimax = Minimax::new(size);
+            minimax.set_root_ordering(true);
+            let mut plain = Minimax::new(size);
+
+            /* Each search goes through the root moves best first by the
+             * one before it, and finds what it would have anyway
+             */
+            let mut previous: Vec<(u32, TimedScore)> = Vec::new();
+            for &plies in [2, 4, 6].iter()
+            {
+                let stats = minimax.minimax(&board, turn, plies);
+                let expected = plain.minimax(&board, turn, plies);
+                assert_eq!(stats.score, expected.score);
+
+                let order: Vec<u32> = minimax.root_order().iter().map(|&(mv, _)| mv).collect();
+                assert_eq!(order.len(), 8);
+                if !previous.is_empty()
+                {
+                    previous.sort_by_key(|&(_, score)| Reverse(match turn
+                    {
+                        Team::Ally => score,
+                        Team::Enemy => -score,
+                    }));
+                    assert_eq!(order, previous.iter().map(|&(mv, _)| mv).collect::<Vec<_>>());
+                }
+                previous = minimax.root_order().to_vec();
+            }
+
+            /* Another position starts from the board's order */
+            let other = RandomTree::new(2, 10, 8);
+            minimax.minimax(&other, turn, 2);
+            assert_eq!(minimax.root_order().iter().map(|&(mv, _)| mv).collect::<Vec<_>>(), (0..8).collect::<Vec<_>>());
+
+            minimax.clear();
+            assert!(minimax.root_order().is_empty());
+        }
+    }
+
+    /* WideBoard where every position is too sharp to prune on */
+    #[derive(Clone,PartialEq,Eq,Hash,Debug)]
+    struct SharpBoard(WideBoard);
+
+    impl Board for SharpBoard
+    {
+        type Move = usize;
+
+        fn gen_ally_moves(&self) -> Vec<usize>
+        {
+            self.0.gen_ally_moves()
+        }
+
+        fn gen_enemy_moves(&self) -> Vec<usize>
+        {
+            self.0.gen_enemy_moves()
+        }
+
+        fn do_move(&mut self, mv: &usize)
+        {
+            self.0.do_move(mv)
+        }
+
+        fn score_for(&self, to_move: Team) -> Score
+        {
+            self.0.score_for(to_move)
+        }
+
+        fn score(&self) -> Score
+        {
+            self.score_for(Team::Ally)
+        }
+
+        fn is_game_over(&self) -> bool
+        {
+            self.0.is_game_over()
+        }
+
+        fn is_tactical_position(&self, _turn: Team) -> bool
+        {
+            true
+        }
+    }
+
+    #[test]
+    fn test_reverse_futility()
+    {
+        let size = NonZeroUsize::new(100_000).unwrap();
+        let board = WideBoard::new(6, Team::Ally);
+        let plain_opts = SearchOptions::depth(4).build();
+        let mut plain = Minimax::new(size);
+        let expected = plain.search(&board, Team::Ally, &plain_opts);
+        assert_eq!(plain.search_stats().reverse_futility_prunes, 0);
+
+        /* Nodes well above beta near the horizon are left failing high */
+        let opts = SearchOptions::depth(4).reverse_futility(ReverseFutility::default()).build();
+        let mut pruning = Minimax::new(size);
+        let stats = pruning.search(&board, Team::Ally, &opts);
+        assert!(pruning.search_stats().reverse_futility_prunes > 0);
+        assert!(stats.nodes_visited < expected.nodes_visited, "{} vs {}", stats.nodes_visited, expected.nodes_visited);
+        assert_eq!((stats.mv, stats.score), (expected.mv, expected.score));
+
+        /* Margins too wide to ever prune, and positions flagged as sharp,
+         * search exactly as without it
+         */
+        let wide = SearchOptions::depth(4).reverse_futility(ReverseFutility { margin: i32::MAX, max_depth: 4 }).build();
+        let mut minimax = Minimax::new(size);
+        assert_eq!(minimax.search(&board, Team::Ally, &wide), expected);
+        assert_eq!(minimax.search_stats().reverse_futility_prunes, 0);
+        let sharp = SharpBoard(board.clone());
+        let mut minimax = Minimax::new(size);
+        assert_eq!(minimax.search(&sharp, Team::Ally, &opts), Minimax::new(size).search(&sharp, Team::Ally, &plain_opts));
+        assert_eq!(minimax.search_stats().reverse_futility_prunes, 0);
+
+        /* A margin that lets anything through prunes where beta is a heuristic
+         * score, and not where it is a win or a loss
+         */
+        let reckless = SearchOptions::depth(3).reverse_futility(ReverseFutility { margin: -1000, max_depth: 3 }).build();
+        let deep = GameTree::Node(vec![GameTree::Node(vec![GameTree::Leaf(Score::Lose)])]);
+        for &(first, prunes) in [(Score::Heuristic(5), 1), (Score::Win, 0)].iter()
+        {
+            let tree = GameTree::Node(vec![GameTree::Leaf(first), deep.clone()]);
+            let mut minimax = Minimax::new(size);
+            minimax.search(&tree, Team::Ally, &reckless);
+            assert_eq!(minimax.search_stats().reverse_futility_prunes, prunes, "{:?} first", first);
+        }
+    }
+
+    #[test]
+    fn test_beam_search()
+    {
+        let size = NonZeroUsize::new(100_000).unwrap();
+        let mut rng = XorShift(0x2545_f491_4f6c_dd1d);
+        let tree = full_tree(&mut rng, 4, 3);
+        let exact = Minimax::new(size).search(&tree, Team::Ally, &SearchOptions::depth(4).build());
+        assert!(exact.exhaustive);
+
+        /* A beam as wide as the tree leaves nothing out */
+        let opts = SearchOptions::depth(4).max_branching(3).max_root_branching(3).build();
+        let mut minimax = Minimax::new(size);
+        let stats = minimax.search(&tree, Team::Ally, &opts);
+        assert_eq!(stats, exact);
+        assert_eq!(minimax.search_stats().beam_discarded_moves, 0);
+
+        /* A beam of one follows the first move all the way down */
+        let opts = SearchOptions::depth(4).max_branching(1).max_root_branching(1).build();
+        let mut minimax = Minimax::new(size);
+        let stats = minimax.search(&tree, Team::Ally, &opts);
+        let mut leaf = &tree;
+        while let SimpleBoard::Node(ref children) = *leaf
+        {
+            leaf = &children[0];
+        }
+        assert_eq!(stats.mvs, vec![SimpleMove(0); 4]);
+        assert_eq!(stats.score, TimedScore { score: leaf.score_for(Team::Ally), turns: 4 });
+        assert_eq!(stats.nodes_visited, 5);
+        assert!(!stats.exhaustive && !stats.solved);
+        assert_eq!(minimax.search_stats().beam_discarded_moves, 2 * 4);
+
+        /* Unless asked to, the root keeps every move, each followed greedily */
+        let opts = SearchOptions::depth(4).max_branching(1).build();
+        let mut minimax = Minimax::new(size);
+        let stats = minimax.search(&tree, Team::Ally, &opts);
+        assert_eq!(stats.nodes_visited, 1 + 3 * 4);
+        assert!(!stats.exhaustive);
+        assert_eq!(minimax.search_stats().beam_discarded_moves, 3 * 2 * 3);
+
+        /* The beam's scores stay out of the normal search's table */
+        assert_eq!(minimax.search(&tree, Team::Ally, &SearchOptions::depth(4).build()), exact);
+    }
+
+    #[test]
+    fn test_razoring()
+    {
+        let size = NonZeroUsize::new(100_000).unwrap();
+        let board = WideBoard::new(6, Team::Ally);
+        let mut plain = Minimax::new(size);
+        let expected = plain.minimax(&board, Team::Ally, 4);
+        assert_eq!(plain.search_stats().razored_nodes, 0);
+
+        /* Hopeless nodes near the horizon are left after standing pat */
+        let mut razoring = Minimax::new(size);
+        razoring.set_razoring_margins(vec![300, 600]);
+        let stats = razoring.minimax(&board, Team::Ally, 4);
+        assert!(razoring.search_stats().razored_nodes > 0);
+        assert!(stats.nodes_visited < expected.nodes_visited, "{} vs {}", stats.nodes_visited, expected.nodes_visited);
+        assert_eq!((stats.mv, stats.score), (expected.mv, expected.score));
+
+        /* Margins too wide to ever razor change nothing */
+        let mut wide = Minimax::new(size);
+        wide.set_razoring_margins(vec![i32::MAX; 4]);
+        let stats = wide.minimax(&board, Team::Ally, 4);
+        assert_eq!(wide.search_stats().razored_nodes, 0);
+        assert_eq!((stats.mv, stats.score, stats.nodes_visited), (expected.mv, expected.score, expected.nodes_visited));
+
+        /* A win in five in hand doesn't razor away a win in three, even
+         * though the static score of the node it's found at is well below
+         */
+        let win_in = |plies| (1..plies).fold(GameTree::Leaf(Score::Win), |tree, _| GameTree::Node(vec![tree]));
+        let tree = GameTree::Node(vec![win_in(5), win_in(3)]);
+        let expected = Minimax::new(size).minimax(&tree, Team::Ally, 6);
+        assert_eq!((expected.mv, expected.score), (Some(Branch(1)), TimedScore { score: Score::Win, turns: 3 }));
+        let mut razoring = Minimax::new(size);
+        razoring.set_razoring_margins(vec![1; 6]);
+        let stats = razoring.minimax(&tree, Team::Ally, 6);
+        assert_eq!((stats.mv, stats.score), (expected.mv, expected.score));
+    }
+
+    #[test]
+    fn test_singular_extensions()
+    {
+        let size = NonZeroUsize::new(100_000).unwrap();
+        let board = WideBoard::new(8, Team::Ally);
+        let schedule = SingularExtensions { margin: 100, min_depth: 3 };
+        let plain = SearchOptions::depth(4).build();
+        let singular = SearchOptions::depth(4).singular_extensions(schedule).build();
+
+        /* The move worth 40 is far ahead of the rest wherever it's played,
+         * so once the table knows that, it gets extended
+         */
+        let mut minimax = Minimax::with_heuristics(size);
+        let expected = minimax.iterative_deepening_with(&board, Team::Ally, &plain);
+        assert_eq!(minimax.search_stats().singular_extensions, 0);
+        assert_eq!(expected.extended_depth, 4);
+
+        let mut minimax = Minimax::with_heuristics(size);
+        let stats = minimax.iterative_deepening_with(&board, Team::Ally, &singular);
+        assert!(stats.completed);
+        assert_eq!(stats.mv, Some(8));
+        assert_eq!(stats.mv, expected.mv);
+        assert!(minimax.search_stats().singular_extensions > 0);
+        assert_eq!(stats.depth_reached, 4);
+        assert!(stats.extended_depth > 4 && stats.extended_depth <= 4 + 3, "{}", stats.extended_depth);
+
+        /* Extensions still keep to the cap */
+        let mut capped = Minimax::with_heuristics(size);
+        capped.set_max_extensions(0);
+        let stats = capped.iterative_deepening_with(&board, Team::Ally, &singular);
+        assert_eq!(capped.search_stats().singular_extensions, 0);
+        assert_eq!((stats.mv, stats.extended_depth), (expected.mv, 4));
+
+        /* And the check leaves no trace */
+        let mut traced = Minimax::with_heuristics(size);
+        traced.iterative_deepening_with(&board, Team::Ally, &SearchOptions::depth(3).build());
+        let (_, trace) = traced.search_with_trace(&board, Team::Ally, &singular, 1);
+        assert_eq!(trace.root.children.len(), board.gen_ally_moves().len());
+    }
+
+    #[test]
+    fn test_singular_root_check()
+    {
+        let size = NonZeroUsize::new(100_000).unwrap();
+        let tree = full_tree(&mut XorShift(0x510e_527f_ade6_82d1), 6, 4);
+        /* With a margin this wide nothing is singular, the check only runs */
+        let schedule = SingularExtensions { margin: 10_000, min_depth: 2 };
+        for &turn in [Team::Ally, Team::Enemy].iter()
+        {
+            let heard = |opts: &SearchOptions|
+            {
+                let mut minimax = Minimax::new(size);
+                minimax.minimax(&tree, turn, 3);
+                let mut observer = stop_after(usize::MAX);
+                let mut ctx = SearchContext::new(&mut observer);
+                ctx.limit(opts);
+                let stats = minimax.search_full(&tree, turn, 5, &mut ctx);
+                let order: Vec<_> = minimax.root_order().iter().map(|(mv, _)| mv.clone()).collect();
+                let searched: Vec<_> = observer.searched.iter().map(|(mv, _, _)| mv.clone()).collect();
+                (stats.mv, searched, order)
+            };
+
+            /* The check at the root leaves the root's books to the search */
+            let plain = heard(&SearchOptions::depth(5).build());
+            let checked = heard(&SearchOptions::depth(5).singular_extensions(schedule).build());
+            assert_eq!(plain.1.len(), 4);
+            assert_eq!(checked, plain);
+
+            /* And keeps to the moves the root was given */
+            let mut minimax = Minimax::new(size);
+            minimax.minimax(&tree, turn, 3);
+            let allowed = [SimpleMove(1), SimpleMove(3)];
+            let opts = SearchOptions::depth(5).singular_extensions(schedule).build();
+            match minimax.search_moves(&tree, turn, &opts, &allowed)
+            {
+                SearchResult::BestMove(stats) => assert!(allowed.contains(&stats.mv.unwrap())),
+                other => panic!("{:?}", other),
+            }
+            assert_eq!(minimax.root_order().iter().map(|(mv, _)| mv.clone()).collect::<Vec<_>>(), allowed);
+        }
+
+        /* The best move is only singular among the moves kept to */
+        let h = Score::Heuristic;
+        let game = SimpleBoard::Node(vec![SimpleBoard::Leaf(h(100)), SimpleBoard::Leaf(h(95)), SimpleBoard::Leaf(h(-100))]);
+        let opts = SearchOptions::depth(5).singular_extensions(SingularExtensions { margin: 50, min_depth: 2 }).build();
+        for &(allowed, singular) in [(&[SimpleMove(0), SimpleMove(2)][..], true), (&[SimpleMove(0), SimpleMove(1), SimpleMove(2)][..], false)].iter()
+        {
+            let mut minimax = Minimax::new(size);
+            minimax.minimax(&game, Team::Ally, 3);
+            match minimax.search_moves(&game, Team::Ally, &opts, allowed)
+            {
+                SearchResult::BestMove(stats) => assert_eq!(stats.mv, Some(SimpleMove(0))),
+                other => panic!("{:?}", other),
+            }
+            assert_eq!(minimax.search_stats().singular_extensions > 0, singular, "{:?}", allowed);
+        }
+
+    }
+
+    #[test]
+    fn test_internal_deepening()
+    {
+        let size = NonZeroUsize::new(1 << 16).unwrap();
+        let plain = SearchOptions::depth(6).build();
+        let deepening = SearchOptions::depth(6).internal_deepening(InternalDeepening::default()).build();
+        let mut total_plain = 0;
+        let mut total_deepening = 0;
+        for seed in 0..6
+        {
+            let tree = RandomTree::new(seed, 6, 6);
+            for &turn in [Team::Ally, Team::Enemy].iter()
+            {
+                let mut minimax = Minimax::new(size);
+                let expected = minimax.search(&tree, turn, &plain);
+                assert_eq!(minimax.search_stats().internal_deepenings, 0);
+
+                /* A cold table has no moves, so at least the root is
+                 * deepened, and again inside that
+                 */
+                let mut minimax = Minimax::new(size);
+                let stats = minimax.search(&tree, turn, &deepening);
+                assert!(minimax.search_stats().internal_deepenings >= 2);
+                assert_eq!((stats.mv, stats.score), (expected.mv, expected.score));
+                assert_ne!(stats.nodes_visited, expected.nodes_visited);
+                total_plain += expected.nodes_visited;
+                total_deepening += stats.nodes_visited;
+            }
+        }
+        assert!(total_deepening < total_plain, "{} vs {}", total_deepening, total_plain);
+    }
+
+    #[test]
+    fn test_internal_deepening_root()
+    {
+        let size = NonZeroUsize::new(1 << 16).unwrap();
+        let deepening = SearchOptions::depth(6).internal_deepening(InternalDeepening::default()).build();
+        let tree = full_tree(&mut XorShift(0x9b05_688c_2b3e_6c1f), 6, 4);
+        for &turn in [Team::Ally, Team::Enemy].iter()
+        {
+            /* The shallow search of the root is left out of its books */
+            let mut minimax = Minimax::new(size);
+            let mut observer = stop_after(usize::MAX);
+            let mut ctx = SearchContext::new(&mut observer);
+            ctx.limit(&deepening);
+            let stats = minimax.search_full(&tree, turn, 6, &mut ctx);
+            assert!(ctx.stats.internal_deepenings > 0);
+            let searched: Vec<_> = observer.searched.iter().map(|(mv, _, _)| mv.clone()).collect();
+            let order: Vec<_> = minimax.root_order().iter().map(|(mv, _)| mv.clone()).collect();
+            assert_eq!(searched.len(), 4);
+            assert_eq!(order, searched);
+            assert_eq!(stats.mv, Minimax::new(size).minimax(&tree, turn, 6).mv);
+
+            /* And keeps to the root moves, whatever the table says */
+            let best = [stats.mv.unwrap()];
+            match minimax.search_without(&tree, turn, &deepening, &best)
+            {
+                SearchResult::BestMove(stats) => assert!(!best.contains(&stats.mv.unwrap())),
+                other => panic!("{:?}", other),
+            }
+            assert!(minimax.root_order().iter().all(|(mv, _)| !best.contains(mv)));
+            assert_eq!(minimax.root_order().len(), 3);
+        }
+    }
+
+    /* Random tree whose positions score what they are worth, with every
+     * side's moves worst first
+     */
+    fn adversarial(rng: &mut XorShift, depth: u32, turn: Team) -> TacticalBoard
+    {
+        if depth == 0
+        {
+            return tactical(rng.below(1000) as i32 - 500, vec![]);
+        }
+        let mut children: Vec<_> = (0..4).map(|_| adversarial(rng, depth - 1, turn.other_team())).collect();
+        match turn
+        {
+            Team::Ally => children.sort_by_key(|child| child.score),
+            Team::Enemy => children.sort_by_key(|child| -child.score),
+        }
+        let score = match turn
+        {
+            Team::Ally => children.last().unwrap().score,
+            Team::Enemy => children.first().unwrap().score,
+        };
+        tactical(score, children.into_iter().map(|child| (false, child)).collect())
+    }
+
+    #[test]
+    fn test_pre_ordering()
+    {
+        let size = NonZeroUsize::new(1 << 16).unwrap();
+        let mut rng = XorShift(0x9b05_688c_2b3e_6c1f);
+        let tree = adversarial(&mut rng, 6, Team::Ally);
+        let mut minimax = Minimax::new(size);
+        let expected = minimax.search(&tree, Team::Ally, &SearchOptions::depth(6).build());
+        assert_eq!(minimax.search_stats().pre_ordering_nodes, 0);
+
+        let mut minimax = Minimax::new(size);
+        let stats = minimax.search(&tree, Team::Ally, &SearchOptions::depth(6).pre_ordering(2).build());
+        assert_eq!((&stats.mv, stats.score), (&expected.mv, expected.score));
+        assert!(minimax.search_stats().pre_ordering_nodes > 0);
+        assert!(stats.nodes_visited * 2 < expected.nodes_visited, "{} vs {}", stats.nodes_visited, expected.nodes_visited);
+
+        /* Nodes too close to the horizon keep the board's order */
+        let mut minimax = Minimax::new(size);
+        assert_eq!(minimax.search(&tree, Team::Ally, &SearchOptions::depth(6).pre_ordering(7).build()), expected);
+        assert_eq!(minimax.search_stats().pre_ordering_nodes, 0);
+    }
+
+    /* Tries the moves worth the most first, counting the nodes it orders
+     * over all of its clones
+     */
+    #[derive(Clone)]
+    struct MostWorthFirst(Arc<AtomicUsize>);
+
+    impl MoveOrderer<WideBoard> for MostWorthFirst
+    {
+        fn order(&mut self, _board: &WideBoard, _turn: Team, moves: &mut Vec<usize>, depth: u32, _tt_move: Option<&usize>)
+        {
+            assert!(depth > 0);
+            self.0.fetch_add(1, atomic::Ordering::Relaxed);
+            moves.sort_by_key(|&mv| Reverse(WIDE_WORTH[mv]));
+        }
+    }
+
+    #[test]
+    fn test_move_orderer()
+    {
+        let size = NonZeroUsize::new(100_000).unwrap();
+        let board = WideBoard::new(3, Team::Ally);
+        let expected = Minimax::new(size).minimax(&board, Team::Ally, 4);
+
+        /* Leaving out the table move costs nodes once the table knows
+         * something, a good order saves them
+         */
+        let mut table_first = Minimax::new(size);
+        let mut identity = Minimax::new(size);
+        identity.set_move_orderer(Identity);
+        table_first.minimax(&board, Team::Ally, 3);
+        identity.minimax(&board, Team::Ally, 3);
+        let deepened = table_first.minimax(&board, Team::Ally, 4);
+        let unordered = identity.minimax(&board, Team::Ally, 4);
+        assert_eq!((unordered.mv, unordered.score), (expected.mv, expected.score));
+        assert!(unordered.nodes_visited > deepened.nodes_visited, "{} vs {}", unordered.nodes_visited, deepened.nodes_visited);
+
+        let calls = Arc::new(AtomicUsize::new(0));
+        let stats = Minimax::with_orderer(size, MostWorthFirst(calls.clone())).minimax(&board, Team::Ally, 4);
+        assert_eq!((stats.mv, stats.score), (expected.mv, expected.score));
+        assert!(stats.nodes_visited < expected.nodes_visited, "{} vs {}", stats.nodes_visited, expected.nodes_visited);
+        assert!(calls.load(atomic::Ordering::Relaxed) > 0);
+
+        /* Tupled, the table move goes ahead of the good order */
+        let mut both = Minimax::new(size);
+        both.set_move_orderer((MostWorthFirst(Arc::new(AtomicUsize::new(0))), TableMoveFirst));
+        let paired = both.minimax(&board, Team::Ally, 4);
+        assert_eq!((paired.mv, paired.score), (expected.mv, expected.score));
+        let mut moves = vec![0, 1, 2, 8, 9];
+        (Identity, TableMoveFirst).order(&board, Team::Ally, &mut moves, 1, Some(&8));
+        assert_eq!(moves, vec![8, 0, 1, 2, 9]);
+        (MostWorthFirst(calls.clone()), Identity).order(&board, Team::Ally, &mut moves, 1, Some(&2));
+        assert_eq!(moves, vec![8, 9, 1, 0, 2]);
+
+        /* Every worker orders with its own clone */
+        let calls = Arc::new(AtomicUsize::new(0));
+        let mut parallel = Minimax::with_orderer(size, MostWorthFirst(calls.clone()));
+        let stats = parallel.minimax_parallel(&board, Team::Ally, 4, 3);
+        assert_eq!((stats.mv, stats.score), (expected.mv, expected.score));
+        assert!(calls.load(atomic::Ordering::Relaxed) > 1);
+    }
+
+    /* Moves on by a millisecond every time it is read */
+    struct TickingClock(Cell<u64>);
+
+    impl Clock for TickingClock
+    {
+        fn elapsed(&self) -> Duration
+        {
+            let now = self.0.get();
+            self.0.set(now + 1);
+            Duration::from_millis(now)
+        }
+    }
+
+    #[test]
+    fn test_time_limit()
+    {
+        let tree = full_tree(&mut XorShift(0x0123_4567_89ab_cdef), 5, 4);
+        let full = Minimax::new(NonZeroUsize::new(10_000).unwrap()).minimax(&tree, Team::Ally, 5);
+
+        let mut observer = stop_after(usize::MAX);
+        Minimax::new(NonZeroUsize::new(10_000).unwrap()).minimax_with_observer(&tree, Team::Ally, 5, &mut observer);
+        let (ref first_move, first_score, first_nodes) = observer.searched[0];
+
+        /* Read at every node, the clock runs out in the second root move */
+        let clock = TickingClock(Cell::new(0));
+        let limit = Duration::from_millis(first_nodes + 3);
+        let opts = SearchOptions::depth(5).time_limit(limit, &clock).clock_interval(1).build();
+        let timed = Minimax::new(NonZeroUsize::new(10_000).unwrap()).search(&tree, Team::Ally, &opts);
+        assert!(!timed.completed);
+        assert_eq!(timed.mv.as_ref(), Some(first_move));
+        assert_eq!(timed.score, first_score);
+
+        /* It stops exactly where the same node limit does */
+        let opts = SearchOptions::depth(5).max_nodes(first_nodes + 3).build();
+        assert_eq!(Minimax::new(NonZeroUsize::new(10_000).unwrap()).search(&tree, Team::Ally, &opts), timed);
+
+        /* Read every 8 nodes, it only notices at the next reading */
+        let clock = TickingClock(Cell::new(0));
+        let readings = (first_nodes + 3) / 8;
+        let opts = SearchOptions::depth(5).time_limit(Duration::from_millis(readings), &clock).clock_interval(8).build();
+        let timed = Minimax::new(NonZeroUsize::new(10_000).unwrap()).search(&tree, Team::Ally, &opts);
+        assert!(!timed.completed);
+        assert_eq!(timed.mv.as_ref(), Some(first_move));
+        let opts = SearchOptions::depth(5).max_nodes(8 * (readings + 1) - 1).build();
+        assert_eq!(Minimax::new(NonZeroUsize::new(10_000).unwrap()).search(&tree, Team::Ally, &opts), timed);
+
+        /* Plenty of time, or a function for a clock, is just minimax */
+        let opts = SearchOptions::depth(5).time_limit(Duration::from_secs(1), &|| Duration::from_secs(0)).build();
+        assert_eq!(opts.clock_interval(), 4096);
+        assert_eq!(Minimax::new(NonZeroUsize::new(10_000).unwrap()).search(&tree, Team::Ally, &opts), full);
+    }
+
+    /* Moves leading to every pruned node of a trace */
+    fn pruned_paths(node: &TraceNode<SimpleMove>, path: &mut Vec<usize>, found: &mut Vec<Vec<usize>>)
+    {
+        for child in &node.children
+        {
+            path.push(child.mv.as_ref().unwrap().0);
+            if child.pruned
+            {
+                assert_eq!((child.score, child.window, child.children.len()), (None, None, 0));
+                found.push(path.clone());
             }
+            pruned_paths(child, path, found);
+            path.pop();
+        }
+    }
+
+    fn count_nodes(node: &TraceNode<SimpleMove>) -> usize
+    {
+        1 + node.children.iter().map(count_nodes).sum::<usize>()
+    }
+
+    #[test]
+    fn test_search_trace()
+    {
+        let opts = SearchOptions::depth(4).build();
+        let mut minimax = Minimax::new(NonZeroUsize::new(100).unwrap());
+        let (stats, trace) = minimax.search_with_trace(&game1(), Team::Ally, &opts, 4);
+        assert_eq!(stats, Minimax::new(NonZeroUsize::new(100).unwrap()).minimax(&game1(), Team::Ally, 4));
+        assert_eq!(trace.root.mv, None);
+        assert_eq!(trace.root.score, Some(stats.score));
+        assert_eq!(trace.root.nodes_visited, stats.nodes_visited);
+        assert!(!trace.root.tt_hit);
+
+        /* Every root move gets searched, with the scores minimax gives them */
+        let heuristic = |value, turns| Some(TimedScore { score: Score::Heuristic(value), turns });
+        let root_moves: Vec<RootMove<SimpleMove>> = trace.root_moves().collect();
+        assert_eq!(root_moves.iter().map(|root| (root.mv.0, root.score, root.pruned)).collect::<Vec<_>>(),
+                   vec![(0, heuristic(3, 3), false), (1, heuristic(6, 3), false), (2, heuristic(5, 3), false)]);
+        assert_eq!(root_moves.iter().map(|root| root.nodes_visited).sum::<u64>() + 1, stats.nodes_visited);
+
+        /* The rest of the windows follow from the first line searched */
+        let lose = TimedScore { score: Score::Lose, turns: 0 };
+        let win = TimedScore { score: Score::Win, turns: 0 };
+        let windows: Vec<_> = trace.root.children.iter().map(|child| child.window).collect();
+        assert_eq!(windows, vec![
+            Some((lose, win)),
+            Some((heuristic(3, 4).unwrap(), win)),
+            Some((heuristic(6, 4).unwrap(), win)),
+        ]);
+
+        /* The textbook cutoffs of this tree */
+        let mut found = Vec::new();
+        pruned_paths(&trace.root, &mut Vec::new(), &mut found);
+        assert_eq!(found, vec![vec![0, 0, 1, 2], vec![1, 0, 1, 1], vec![2, 1]]);
+
+        let dot = trace.to_dot();
+        assert!(dot.starts_with("digraph search {"));
+        assert_eq!(dot.matches(" -> ").count(), count_nodes(&trace.root) - 1);
+        assert_eq!(dot.matches("style=dashed").count(), 3);
+
+        /* Searching it again, the table has the answer */
+        let (again, trace) = minimax.search_with_trace(&game1(), Team::Ally, &opts, 4);
+        assert_eq!(again.mv, stats.mv);
+        assert!(trace.root.tt_hit);
+        assert!(trace.root.children.is_empty());
+    }
+
+    #[test]
+    fn test_search_trace_depth()
+    {
+        let opts = SearchOptions::depth(4).build();
+        let full = Minimax::new(NonZeroUsize::new(100).unwrap()).search_with_trace(&game1(), Team::Ally, &opts, 4).1;
+
+        /* Only the root moves, and what pruning happened among them */
+        let (stats, trace) = Minimax::new(NonZeroUsize::new(100).unwrap()).search_with_trace(&game1(), Team::Ally, &opts, 1);
+        assert_eq!(stats.mv, Some(SimpleMove(1)));
+        assert_eq!(trace.root.children.len(), 3);
+        for (child, full_child) in trace.root.children.iter().zip(&full.root.children)
+        {
+            assert!(child.children.is_empty());
+            assert_eq!((child.score, child.window, child.nodes_visited), (full_child.score, full_child.window, full_child.nodes_visited));
         }
 
-        fn gen_enemy_moves(&self) -> Vec<Self::Move>
+        let (_, trace): (_, SearchTrace<SimpleMove>) = Minimax::new(NonZeroUsize::new(100).unwrap()).search_with_trace(&game1(), Team::Ally, &opts, 0);
+        assert!(trace.root.children.is_empty());
+        assert_eq!(trace.root_moves().count(), 0);
+    }
+
+    #[test]
+    fn test_perft()
+    {
+        let tree = GameTree::full(1, 3, 4);
+        assert_eq!(testing::perft(&tree, 0, Team::Ally), 1);
+        assert_eq!(testing::perft(&tree, 2, Team::Ally), 16);
+        assert_eq!(testing::perft(&tree, 3, Team::Enemy), 64);
+        /* Past the leaves there is nothing more to count */
+        assert_eq!(testing::perft(&tree, 5, Team::Ally), 64);
+
+        let tree = RandomTree::new(7, 4, 8);
+        assert_eq!(testing::perft(&tree, 4, Team::Ally), 8u64.pow(4));
+
+        let uneven = GameTree::Node(vec![
+            GameTree::Leaf(Score::Win),
+            GameTree::Node(vec![GameTree::Leaf(Score::Draw), GameTree::Leaf(Score::Lose), GameTree::Leaf(Score::Heuristic(1))]),
+        ]);
+        assert_eq!(testing::perft(&uneven, 1, Team::Ally), 2);
+        assert_eq!(testing::perft(&uneven, 2, Team::Ally), 4);
+        assert_eq!(uneven.gen_enemy_moves(), vec![Branch(0), Branch(1)]);
+    }
+
+    #[test]
+    fn test_verify_against_plain_minimax()
+    {
+        for seed in 0..100
         {
-            match *self
-            {
-                SimpleBoard::Node(ref v) =>
-                    (0..v.len()).map(|u| SimpleMove(u)).collect(),
-                SimpleBoard::Leaf(_) =>
-                    /* Trick the algorithm into thinking
-                     * we still have moves left
-                     */
-                    vec![SimpleMove(1)],
-            }
+            let depth = 1 + (seed % 6) as u32;
+            let tree = GameTree::random(seed, depth);
+            assert_eq!(tree, GameTree::random(seed, depth));
+            testing::verify_against_plain_minimax(&tree, depth);
         }
 
-        fn do_move(&mut self, mv: &Self::Move)
+        testing::verify_against_plain_minimax(&GameTree::full(3, 5, 4), 5);
+        testing::verify_against_plain_minimax(&RandomTree::new(11, 4, 6), 4);
+        /* Heuristics of the inner nodes matter once the search stops short */
+        testing::verify_against_plain_minimax(&RandomTree::new(12, 6, 4), 3);
+    }
+
+    #[test]
+    fn test_iterative_deepening_to()
+    {
+        let tree = RandomTree::new(5, 6, 6);
+        for &size in [10_000, 1].iter()
         {
-            let child = match *self
+            let mut minimax = Minimax::new(NonZeroUsize::new(size).unwrap());
+            let results = minimax.iterative_deepening_to(&tree, Team::Ally, 6);
+            assert_eq!(results.iter().map(|stats| stats.depth_reached).collect::<Vec<_>>(), vec![1, 2, 3, 4, 5, 6]);
+
+            let cold = Minimax::new(NonZeroUsize::new(size).unwrap()).minimax(&tree, Team::Ally, 6);
+            let deepest = results.last().unwrap();
+            assert_eq!((&deepest.mv, deepest.score), (&cold.mv, cold.score));
+            /* With a table too small to remember anything, following the
+             * last principal variation still pays off
+             */
+            assert!(deepest.nodes_visited < cold.nodes_visited, "{} {}", deepest.nodes_visited, cold.nodes_visited);
+        }
+
+        let enemy = Minimax::new(NonZeroUsize::new(1000).unwrap()).iterative_deepening_to(&tree, Team::Enemy, 4);
+        let cold = Minimax::new(NonZeroUsize::new(1000).unwrap()).minimax(&tree, Team::Enemy, 4);
+        assert_eq!((&enemy[3].mv, enemy[3].score), (&cold.mv, cold.score));
+
+        /* Nothing beats a win, so the deepening stops there */
+        let won = GameTree::Node(vec![
+            GameTree::Node(vec![GameTree::Leaf(Score::Heuristic(1))]),
+            GameTree::Leaf(Score::Win),
+        ]);
+        let results = Minimax::new(NonZeroUsize::new(100).unwrap()).iterative_deepening_to(&won, Team::Ally, 5);
+        assert_eq!(results.len(), 1);
+        assert_eq!(results[0].mv, Some(Branch(1)));
+
+        assert_eq!(Minimax::new(NonZeroUsize::new(100).unwrap()).iterative_deepening_to(&tree, Team::Ally, 0).len(), 1);
+    }
+
+    #[test]
+    fn test_iterative_deepening_with()
+    {
+        let tree = RandomTree::new(9, 6, 5);
+        let results = Minimax::new(NonZeroUsize::new(10_000).unwrap()).iterative_deepening_to(&tree, Team::Ally, 6);
+        let deepening = |opts: &SearchOptions| Minimax::new(NonZeroUsize::new(10_000).unwrap()).iterative_deepening_with(&tree, Team::Ally, opts);
+
+        let stats = deepening(&SearchOptions::depth(6).max_nodes(1_000_000).build());
+        assert_eq!(&stats, results.last().unwrap());
+
+        /* Enough for the fourth iteration alone, but the budget is shared
+         * and the first three already used up most of it
+         */
+        let budget = results[..4].iter().map(|stats| stats.nodes_visited).sum::<u64>();
+        let stats = deepening(&SearchOptions::depth(6).max_nodes(budget).build());
+        assert_eq!(stats, results[2]);
+        assert!(stats.completed);
+
+        /* Not even one root move is searched, but there is a move */
+        let stats = deepening(&SearchOptions::depth(6).max_nodes(1).build());
+        assert!(!stats.completed);
+        assert_eq!((stats.mv, stats.mvs, stats.depth_reached, stats.score.score), (Some(0), vec![0], 0, Score::Heuristic(0)));
+
+        /* Part of the first iteration is better than nothing */
+        let clock = TickingClock(Cell::new(0));
+        let opts = SearchOptions::depth(6).time_limit(Duration::from_millis(results[0].nodes_visited - 2), &clock).clock_interval(1).build();
+        let stats = deepening(&opts);
+        assert!(!stats.completed);
+        assert!(stats.mv.is_some());
+        assert_eq!(stats.depth_reached, 1);
+    }
+
+    #[test]
+    #[cfg(feature = "std-clock")]
+    fn test_minimax_timed()
+    {
+        let tree = RandomTree::new(9, 5, 5);
+        let expected = Minimax::new(NonZeroUsize::new(10_000).unwrap()).iterative_deepening_to(&tree, Team::Enemy, 5);
+        let stats = Minimax::new(NonZeroUsize::new(10_000).unwrap()).minimax_timed(&tree, Team::Enemy, 5, Duration::from_secs(60));
+        assert_eq!(&stats, expected.last().unwrap());
+
+        let stats = Minimax::new(NonZeroUsize::new(10_000).unwrap()).minimax_timed(&tree, Team::Enemy, 5, Duration::from_secs(0));
+        assert!(stats.mv.is_some());
+    }
+
+    #[test]
+    fn test_advance()
+    {
+        let size = NonZeroUsize::new(100_000).unwrap();
+        let mut board = RandomTree::new(5, 20, 6);
+        let mut minimax = Minimax::with_heuristics(size);
+
+        /* A game where the engine plays the ally and the enemy sticks to
+         * a script, off the expected line now and then
+         */
+        let script = [None, Some(3), None, Some(0)];
+        for (turn, &scripted) in script.iter().enumerate()
+        {
+            let stats = minimax.minimax(&board, Team::Ally, 6);
+            let cold = Minimax::with_heuristics(size).minimax(&board, Team::Ally, 6);
+            assert_eq!(stats.score, cold.score);
+            /* Far fewer on the line the last search expected, and still
+             * fewer off it
+             */
+            if turn == 1
             {
-                SimpleBoard::Node(ref v) =>
-                    Some(v[mv.0].clone()),
-                SimpleBoard::Leaf(_) =>
-                    None,
-            };
+                assert!(stats.nodes_visited * 2 < cold.nodes_visited, "{} vs {}", stats.nodes_visited, cold.nodes_visited);
+            }
+            if turn > 0
+            {
+                assert!(stats.nodes_visited < cold.nodes_visited, "{} vs {}", stats.nodes_visited, cold.nodes_visited);
+            }
 
-            if let Some(c) = child
+            let mv = stats.mv.unwrap();
+            let reply = scripted.unwrap_or(*stats.expected_reply().unwrap());
+            for mv in &[mv, reply]
             {
-                *self = c;
+                board.do_move(mv);
+                minimax.advance(mv);
             }
         }
+        assert_eq!(minimax.played_moves().len(), 2 * script.len());
 
-        fn score(&self) -> Score
+        minimax.clear();
+        assert!(minimax.played_moves().is_empty());
+    }
+
+    #[test]
+    fn test_stop_handle()
+    {
+        let tree = RandomTree::new(3, 5, 6);
+        let expected = Minimax::new(NonZeroUsize::new(10_000).unwrap()).minimax(&tree, Team::Ally, 5);
+
+        /* Set before the search, only the first root move gets done */
+        let mut minimax = Minimax::new(NonZeroUsize::new(10_000).unwrap());
+        let stop = minimax.stop_handle();
+        stop.store(true, atomic::Ordering::Relaxed);
+        let stopped = minimax.minimax(&tree, Team::Ally, 5);
+        assert!(!stopped.completed);
+        assert_eq!(stopped.mv, Some(0));
+        assert!(stopped.nodes_visited < expected.nodes_visited);
+        assert!(minimax.probe(&tree, Team::Ally).is_none());
+
+        let deepening = minimax.iterative_deepening_to(&tree, Team::Ally, 5);
+        assert_eq!(deepening.len(), 1);
+        assert!(!deepening[0].completed);
+        assert!(!minimax.minimax_parallel(&tree, Team::Ally, 5, 3).completed);
+        assert!(minimax.probe(&tree, Team::Ally).is_none());
+        assert_eq!(minimax.minimax_multi(&tree, Team::Ally, 5, 3).len(), 1);
+
+        /* Once cleared, what was left in the table doesn't show */
+        stop.store(false, atomic::Ordering::Relaxed);
+        let again = minimax.minimax(&tree, Team::Ally, 5);
+        assert!(again.completed);
+        assert_eq!((again.mv, again.score, again.mvs), (expected.mv, expected.score, expected.mvs));
+
+        /* From another thread, in a search that would take far too long */
+        let huge = RandomTree::new(3, 30, 8);
+        let mut minimax = Minimax::new(NonZeroUsize::new(10_000).unwrap());
+        let stop = minimax.stop_handle();
+        let stopped = thread::scope(|scope|
         {
-            match *self
+            scope.spawn(||
             {
-                SimpleBoard::Node(_) =>
-                    unreachable!(),
-                SimpleBoard::Leaf(s) =>
-                    s,
+                thread::sleep(Duration::from_millis(50));
+                stop.store(true, atomic::Ordering::Relaxed);
+            });
+            minimax.minimax(&huge, Team::Ally, 30)
+        });
+        assert!(!stopped.completed);
+    }
+
+    #[test]
+    fn test_just_above()
+    {
+        let scores = [Score::Lose, Score::Heuristic(i32::MIN), Score::Heuristic(-3), Score::Draw, Score::Heuristic(1), Score::Heuristic(i32::MAX), Score::Win];
+        let mut timed = Vec::new();
+        for &score in scores.iter()
+        {
+            for &turns in [0, 1, 2, u32::MAX - 1, u32::MAX].iter()
+            {
+                timed.push(TimedScore { score, turns });
             }
         }
-
-        fn is_game_over(&self) -> bool
+        for &low in timed.iter()
         {
-            match *self
+            match low.just_above()
             {
-                SimpleBoard::Node(_) =>
-                    false,
-                SimpleBoard::Leaf(_) =>
-                    true,
+                Some(high) => {
+                    assert!(high > low, "{:?} {:?}", low, high);
+                    assert!(!timed.iter().any(|&between| low < between && between < high), "{:?} {:?}", low, high);
+                }
+                None => assert_eq!((low.score, low.turns), (Score::Win, 0)),
             }
         }
+
+        assert_eq!(OrderedF32(1.0).above().unwrap().0, 1.0f32.next_up());
+        assert!(OrderedF32::MAX.above().is_none());
     }
 
     #[test]
-    fn test_score_ord()
+    fn test_principal_variation_search()
     {
-        assert!(Score::Win > Score::Heuristic(0));
-        assert!(Score::Heuristic(0) > Score::Lose);
-        assert!(Score::Win > Score::Lose);
-        assert!(Score::Heuristic(100) > Score::Heuristic(0));
-        assert!(Score::Heuristic(0) > Score::Heuristic(-100));
-        assert!(Score::Win == Score::Win);
-        assert!(Score::Lose == Score::Lose);
-        assert!(Score::Heuristic(0) == Score::Heuristic(0));
+        let mut rng = XorShift(0x1f83_d9ab_fb41_bd6b);
+        let mut searches = SearchStats::default();
+        for _ in 0..200
+        {
+            let depth = 1 + rng.below(6) as u32;
+            let tree = random_tree(&mut rng, depth, &mut Vec::new());
+
+            for &turn in [Team::Ally, Team::Enemy].iter()
+            {
+                let expected = Minimax::new(NonZeroUsize::new(1000).unwrap()).minimax(&tree, turn, depth);
+                let mut pvs = Minimax::new(NonZeroUsize::new(1000).unwrap());
+                pvs.set_search_mode(SearchMode::PrincipalVariation);
+                let stats = pvs.minimax(&tree, turn, depth);
+                assert_eq!((&stats.mv, stats.score), (&expected.mv, expected.score), "{:?} to move, tree {:?}", turn, tree);
+                searches.add(pvs.search_stats());
+            }
+        }
+        assert!(searches.zero_window_searches > 0);
+        assert!(searches.re_searches > 0);
+        assert!(searches.re_searches < searches.zero_window_searches);
+
+        let tree = RandomTree::new(17, 6, 6);
+        for plies in 1..7
+        {
+            let expected = Minimax::new(NonZeroUsize::new(100_000).unwrap()).minimax(&tree, Team::Ally, plies);
+            let mut pvs = Minimax::new(NonZeroUsize::new(100_000).unwrap());
+            pvs.set_search_mode(SearchMode::PrincipalVariation);
+            let stats = pvs.minimax(&tree, Team::Ally, plies);
+            assert_eq!((stats.mv, stats.score), (expected.mv, expected.score));
+        }
+
+        /* Floats have neighbours too */
+        let board = FloatBoard(full_tree(&mut XorShift(0x5be0_cd19_137e_2179), 5, 4));
+        let expected: MoveStats<SimpleMove, OrderedF32> = Minimax::new(NonZeroUsize::new(10_000).unwrap()).minimax(&board, Team::Ally, 5);
+        let mut pvs: Minimax<FloatBoard, OrderedF32> = Minimax::new(NonZeroUsize::new(10_000).unwrap());
+        pvs.set_search_mode(SearchMode::PrincipalVariation);
+        assert_eq!(pvs.minimax(&board, Team::Ally, 5).score, expected.score);
+        assert!(pvs.search_stats().zero_window_searches > 0);
     }
 
     #[test]
-    fn test_move_stats_ord()
+    fn test_mtdf()
     {
-        assrt!()
+        let mut rng = XorShift(0x6a09_e667_f3bc_c908);
+        let mut trees = vec![game1(), game2()];
+        trees.extend((0..100).map(|_| random_tree(&mut rng, 5, &mut Vec::new())));
+        let guesses = [Score::Lose, Score::Heuristic(-2), Score::Heuristic(0), Score::Heuristic(3), Score::Win];
+
+        for tree in trees.iter()
+        {
+            for &turn in [Team::Ally, Team::Enemy].iter()
+            {
+                for plies in 1..6
+                {
+                    let expected = Minimax::new(NonZeroUsize::new(1000).unwrap()).minimax(tree, turn, plies);
+                    for &guess in guesses.iter()
+                    {
+                        let mut mtdf = Minimax::new(NonZeroUsize::new(1000).unwrap());
+                        let stats = mtdf.mtdf(tree, turn, plies, TimedScore { score: guess, turns: 0 });
+                        assert_eq!(stats.score.cmp(&expected.score), Ordering::Equal, "{:?} to move, {} plies, guess {:?}, tree {:?}", turn, plies, guess, tree);
+                        assert!(stats.completed);
+                        assert_eq!(stats.depth_reached, plies);
+
+                        /* The move it picked gets the score it found */
+                        if let (Some(mv), true) = (stats.mv.as_ref(), plies > 1)
+                        {
+                            let mut child = tree.clone();
+                            child.do_move(mv);
+                            let reply = Minimax::new(NonZeroUsize::new(1000).unwrap()).minimax(&child, turn.other_team(), plies - 1);
+                            assert_eq!(reply.score.later().cmp(&stats.score), Ordering::Equal, "{:?} {:?} {}", mv, turn, plies);
+                        }
+                    }
+                }
+            }
+        }
+
+        /* A good guess takes fewer passes than a bad one */
+        let tree = full_tree(&mut XorShift(0x1f83_d9ab_fb41_bd6b), 6, 4);
+        let exact = Minimax::new(NonZeroUsize::new(10_000).unwrap()).minimax(&tree, Team::Ally, 6);
+        let good = Minimax::new(NonZeroUsize::new(10_000).unwrap()).mtdf(&tree, Team::Ally, 6, exact.score);
+        let bad = Minimax::new(NonZeroUsize::new(10_000).unwrap()).mtdf(&tree, Team::Ally, 6, TimedScore { score: Score::Lose, turns: 0 });
+        assert_eq!((good.score, bad.score), (exact.score, exact.score));
+        assert!(good.nodes_visited < bad.nodes_visited);
     }
 
     #[test]
-    fn test_minimax()
+    fn test_mate_distance_pruning()
     {
-        let mut minimax = Minimax::new(NonZeroUsize::new(100).unwrap());
-        let game1 =
-        SimpleBoard::Node(vec![
-            SimpleBoard::Node(vec![
-                SimpleBoard::Node(vec![
-                    SimpleBoard::Node(vec![
-                        SimpleBoard::Leaf(Score::Heuristic(5)),
-                        SimpleBoard::Leaf(Score::Heuristic(6)),
-                    ]),
-                    SimpleBoard::Node(vec![
-                        SimpleBoard::Leaf(Score::Heuristic(7)),
-                        SimpleBoard::Leaf(Score::Heuristic(4)),
-                        SimpleBoard::Leaf(Score::Heuristic(5)),
-                    ]),
-                ]),
-                SimpleBoard::Node(vec![
-                    SimpleBoard::Node(vec![
-                        SimpleBoard::Leaf(Score::Heuristic(3)),
-                    ]),
-                ]),
-            ]),
-            SimpleBoard::Node(vec![
-                SimpleBoard::Node(vec![
-                    SimpleBoard::Node(vec![
-                        SimpleBoard::Leaf(Score::Heuristic(6)),
-                    ]),
-                    SimpleBoard::Node(vec![
-                        SimpleBoard::Leaf(Score::Heuristic(6)),
-                        SimpleBoard::Leaf(Score::Heuristic(9)),
-                    ]),
-                ]),
-                SimpleBoard::Node(vec![
-                    SimpleBoard::Node(vec![
-                        SimpleBoard::Leaf(Score::Heuristic(7)),
-                    ]),
-                ]),
-            ]),
-            SimpleBoard::Node(vec![
-                SimpleBoard::Node(vec![
-                    SimpleBoard::Node(vec![
-                        SimpleBoard::Leaf(Score::Heuristic(5)),
-                    ]),
-                ]),
-                SimpleBoard::Node(vec![
-                    SimpleBoard::Node(vec![
-                        SimpleBoard::Leaf(Score::Heuristic(9)),
-                        SimpleBoard::Leaf(Score::Heuristic(8)),
-                    ]),
-                    SimpleBoard::Node(vec![
-                        SimpleBoard::Leaf(Score::Heuristic(6)),
-                    ]),
-                ]),
-            ]),
-        ]);
-        let game2 =
-        SimpleBoard::Node(vec![
-            SimpleBoard::Node(vec![
-                SimpleBoard::Node(vec![
-                    SimpleBoard::Node(vec![
-                        SimpleBoard::Leaf(Score::Heuristic(12)),
-                        SimpleBoard::Leaf(Score::Heuristic(10)),
-                    ]),
-                    SimpleBoard::Node(vec![
-                        SimpleBoard::Leaf(Score::Heuristic(-18)),
-                        SimpleBoard::Leaf(Score::Heuristic(-7)),
-                    ]),
-                ]),
-                SimpleBoard::Node(vec![
-                    SimpleBoard::Node(vec![
-                        SimpleBoard::Leaf(Score::Heuristic(6)),
-                        SimpleBoard::Leaf(Score::Heuristic(-17)),
-                    ]),
-                    SimpleBoard::Node(vec![
-                        SimpleBoard::Leaf(Score::Heuristic(-3)),
-                        SimpleBoard::Leaf(Score::Heuristic(6)),
-                    ]),
-                ]),
-            ]),
-            SimpleBoard::Node(vec![
-                SimpleBoard::Node(vec![
-                    SimpleBoard::Node(vec![
-                        SimpleBoard::Leaf(Score::Heuristic(-19)),
-                        SimpleBoard::Leaf(Score::Heuristic(-16)),
-                    ]),
-                    SimpleBoard::Node(vec![
-                        SimpleBoard::Leaf(Score::Heuristic(-4)),
-                        SimpleBoard::Leaf(Score::Heuristic(-6)),
-                    ]),
-                ]),
-                SimpleBoard::Node(vec![
-                    SimpleBoard::Node(vec![
-                        SimpleBoard::Leaf(Score::Heuristic(-1)),
-                        SimpleBoard::Leaf(Score::Heuristic(6)),
-                    ]),
-                    SimpleBoard::Node(vec![
-                        SimpleBoard::Leaf(Score::Heuristic(9)),
-                        SimpleBoard::Leaf(Score::Heuristic(15)),
-                    ]),
-                ]),
-            ]),
-        ]);
-
-        println!();
-        println!("Game 1");
-        assert_eq!(game1.gen_ally_moves(), vec![SimpleMove(0), SimpleMove(1), SimpleMove(2)]);
-        let move_stats1 = minimax.minimax(&game1, Team::Ally, 4);
-        let optimal_move1 = MoveStats {
-            mv: Some(SimpleMove(1)),
-            score: Score::Heuristic(6),
-            turns: 4,
-            nodes_visited: 25,
-        };
-        assert_eq!(move_stats1, optimal_move1);
+        let size = NonZeroUsize::new(10_000).unwrap();
+        let win_in = |plies| (1..plies).fold(GameTree::Leaf(Score::Win), |tree, _| GameTree::Node(vec![tree]));
+        let tree = GameTree::Node(vec![win_in(7), win_in(3), win_in(5)]);
 
-        println!();
-        println!("Game 2");
-        assert_eq!(game2.gen_ally_moves(), vec![SimpleMove(0), SimpleMove(1)]);
-        let move_stats2 = minimax.minimax(&game2, Team::Ally, 4);
-        let optimal_move2 = MoveStats {
-            mv: Some(SimpleMove(0)),
-            score: Score::Heuristic(-3),
-            turns: 4,
-            nodes_visited: 21,
-        };
-        assert_eq!(move_stats2, optimal_move2);
+        /* The winner takes the shortest mate, the loser the longest, and
+         * once the win in three is in hand the longer lines stop short
+         */
+        for &(turn, mv, turns) in [(Team::Ally, Branch(1), 3), (Team::Enemy, Branch(0), 7)].iter()
+        {
+            let mut plain = Minimax::new(size);
+            plain.set_search_mode(SearchMode::PlainMinimax);
+            let expected = plain.minimax(&tree, turn, 8);
+            assert_eq!(plain.search_stats().mate_distance_cutoffs, 0);
+            assert_eq!((expected.mv, expected.score), (Some(mv), TimedScore { score: Score::Win, turns }));
 
-        println!();
-        println!("Testing caching");
-        assert_eq!(game2.gen_ally_moves(), vec![SimpleMove(0), SimpleMove(1)]);
-        let move_stats2 = minimax.minimax(&game2, Team::Ally, 4);
-        let optimal_move2 = MoveStats {
-            mv: Some(SimpleMove(0)),
-            score: Score::Heuristic(-3),
-            turns: 4,
-            nodes_visited: 21,
-        };
-        assert_eq!(move_stats2, optimal_move2);
+            let mut minimax = Minimax::new(size);
+            let stats = minimax.minimax(&tree, turn, 8);
+            assert_eq!((stats.mv, stats.score), (expected.mv, expected.score));
+            if turn == Team::Ally
+            {
+                assert!(minimax.search_stats().mate_distance_cutoffs > 0);
+                assert!(stats.nodes_visited < expected.nodes_visited);
+            }
+        }
     }
 }
@@ -0,0 +1,163 @@
+//! Max^n search, for games of three or more players where `Team` and the
+//! two-player engine don't fit.
+//!
+//! Every position is worth something to each player, and the player to
+//! move picks the move that is worth the most to them, whatever it does to
+//! the others.
+
+use transposition_table::{TranspositionTable, Bound};
+
+use std::hash::Hash;
+use std::num::NonZeroUsize;
+
+/// A position in a game of any number of players, numbered from zero.
+pub trait MultiPlayerBoard: Clone
+{
+    type Move: Clone + Eq;
+
+    /// The player about to move.
+    fn current_player(&self) -> usize;
+    fn moves(&self) -> Vec<Self::Move>;
+    fn do_move(&mut self, mv: &Self::Move);
+
+    /// What the position is worth to each player, one value per player.
+    fn score(&self) -> Vec<i32>;
+    fn is_game_over(&self) -> bool;
+
+    /// The most the values of `score` can add up to, if they are never
+    /// negative and never add up to more than that. This turns on shallow
+    /// pruning. `None`, the default, searches every move.
+    fn max_total(&self) -> Option<i32>
+    {
+        None
+    }
+}
+
+/// Result of a max^n search.
+#[derive(Clone,Debug,PartialEq,Eq)]
+pub struct MaxnStats<M>
+{
+    pub mv: Option<M>,
+    /// What the position is worth to each player if everyone plays `mvs`.
+    pub values: Vec<i32>,
+    /// Nodes this search went through, counting the root.
+    pub nodes_visited: u64,
+    /// Principal variation, starting with `mv`.
+    pub mvs: Vec<M>,
+}
+
+/// Max^n search with its own transposition table, keyed by position and
+/// player to move.
+pub struct MaxN<B>
+    where B: MultiPlayerBoard + Eq + Hash
+{
+    ttable: TranspositionTable<(B, usize), MaxnStats<B::Move>>,
+}
+
+impl<B> MaxN<B>
+    where B: MultiPlayerBoard + Eq + Hash
+{
+    pub fn new(capacity: NonZeroUsize) -> MaxN<B>
+    {
+        MaxN {
+            ttable: TranspositionTable::new(capacity),
+        }
+    }
+
+    pub fn ttable(&self) -> &TranspositionTable<(B, usize), MaxnStats<B::Move>>
+    {
+        &self.ttable
+    }
+
+    /// Searches `plies` plies deep, each player picking the move worth the
+    /// most to them. Ties go to the first move.
+    pub fn maxn(&mut self, board: &B, plies: u32) -> MaxnStats<B::Move>
+    {
+        let mut nodes = 0;
+        self.search(board, plies, None, &mut nodes)
+            .expect("the root is never cut")
+    }
+
+    /* `parent` is the best value the player who moved into `board` already
+     * has elsewhere. Once the player here is sure of so much that the rest
+     * of the total is no better, the parent won't come here and the node
+     * is cut.
+     */
+    fn search(&mut self, board: &B, depth: u32, parent: Option<i32>, nodes: &mut u64) -> Option<MaxnStats<B::Move>>
+    {
+        *nodes += 1;
+        let player = board.current_player();
+        let key = (board.clone(), player);
+        if let Some((mut stored, _)) = self.ttable.get(&key, depth)
+        {
+            stored.nodes_visited = 1;
+            return Some(stored);
+        }
+
+        let moves = if depth == 0 || board.is_game_over() { Vec::new() } else { board.moves() };
+        if moves.is_empty()
+        {
+            return Some(MaxnStats {
+                mv: None,
+                values: board.score(),
+                nodes_visited: 1,
+                mvs: Vec::new(),
+            });
+        }
+
+        let total = board.max_total();
+        let mut best: Option<MaxnStats<B::Move>> = None;
+        let mut visited = 1;
+        let mut cut = false;
+        for mv in moves
+        {
+            let mut child = board.clone();
+            child.do_move(&mv);
+
+            let here = best.as_ref().filter(|_| total.is_some()).map(|best| best.values[player]);
+            let before = *nodes;
+            let reply = self.search(&child, depth - 1, here, nodes);
+            visited += *nodes - before;
+            let reply = match reply
+            {
+                Some(reply) => reply,
+                None => continue,
+            };
+
+            if best.as_ref().is_none_or(|best| reply.values[player] > best.values[player])
+            {
+                let mut mvs = Vec::with_capacity(reply.mvs.len() + 1);
+                mvs.push(mv.clone());
+                mvs.extend(reply.mvs);
+                best = Some(MaxnStats {
+                    mv: Some(mv),
+                    values: reply.values,
+                    nodes_visited: 0,
+                    mvs,
+                });
+            }
+
+            let mine = best.as_ref().unwrap().values[player];
+            if let (Some(total), Some(theirs)) = (total, parent)
+            {
+                if mine >= total - theirs
+                {
+                    cut = true;
+                    break;
+                }
+            }
+        }
+
+        if cut
+        {
+            return None;
+        }
+
+        /* Replies are only cut once there is a best one to beat them */
+        let mut best = best.expect("the first reply is never cut");
+        best.nodes_visited = visited;
+
+        self.ttable.insert(key, best.clone(), depth, Bound::Exact);
+        Some(best)
+    }
+}
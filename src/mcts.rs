@@ -0,0 +1,389 @@
+//! Monte Carlo tree search, a second engine over the same `Board`, for
+//! games without a good `score_for`.
+//!
+//! Instead of looking at every move to a fixed depth, it plays the game
+//! out at random many times and spends more of them on the moves that do
+//! well, with UCT to still try the others now and then.
+
+use board::{Board, NoMovePolicy};
+use clock::Clock;
+use rng::XorShift;
+use ::{Score, Team};
+
+use std::time::Duration;
+
+/// Where `Mcts` gets its random numbers, so the same source gives the same
+/// search. Functions returning a `u64` are sources too.
+pub trait RandomSource
+{
+    fn next_u64(&mut self) -> u64;
+}
+
+impl<F> RandomSource for F
+    where F: FnMut() -> u64
+{
+    fn next_u64(&mut self) -> u64
+    {
+        self()
+    }
+}
+
+impl RandomSource for XorShift
+{
+    fn next_u64(&mut self) -> u64
+    {
+        XorShift::next_u64(self)
+    }
+}
+
+/// How long `Mcts::search` goes on for.
+#[derive(Copy,Clone)]
+pub enum Budget<'a>
+{
+    /// This many playouts.
+    Iterations(u64),
+    /// Playouts until more than the duration has passed on the clock.
+    Time(Duration, &'a dyn Clock),
+}
+
+/// A root move and how it did.
+#[derive(Clone,Debug,PartialEq)]
+pub struct MctsMove<M>
+{
+    pub mv: M,
+    pub visits: u64,
+    /// Share of the playouts through the move the side playing it won,
+    /// with draws as half.
+    pub win_rate: f64,
+}
+
+/// Result of `Mcts::search`, laid out like `MoveStats`.
+#[derive(Clone,Debug,PartialEq)]
+pub struct MctsStats<M>
+{
+    /// The most visited root move.
+    pub mv: Option<M>,
+    /// Win rate of `mv` for the side to move.
+    pub win_rate: f64,
+    /// Playouts through the root, counting those from earlier searches of
+    /// a tree that was kept.
+    pub visits: u64,
+    /// Line of most visited moves, starting with `mv`.
+    pub mvs: Vec<M>,
+    /// Every root move the tree has tried, in the order they were tried.
+    pub root_moves: Vec<MctsMove<M>>,
+}
+
+struct Node<M>
+{
+    mv: Option<M>,
+    turn: Team,
+    children: Vec<usize>,
+    untried: Vec<M>,
+    visits: u64,
+    /* Playouts the side that moved here won, draws counting as half */
+    wins: f64,
+}
+
+/// Monte Carlo tree search with UCT, keeping its tree from one search to
+/// the next while the game follows it, see `advance`.
+pub struct Mcts<B>
+    where B: Board + Eq
+{
+    /* Arena of the tree, the root first */
+    nodes: Vec<Node<B::Move>>,
+    root: Option<(B, Team)>,
+    rng: Box<dyn RandomSource>,
+    exploration: f64,
+    playout_depth: Option<u32>,
+    heuristic_scale: f64,
+}
+
+impl<B> Mcts<B>
+    where B: Board + Eq
+{
+    /// Makes a search with its own generator, started from `seed`.
+    pub fn new(seed: u64) -> Mcts<B>
+    {
+        Mcts::with_rng(XorShift::new(seed))
+    }
+
+    /// Makes a search that takes its random numbers from `rng`.
+    pub fn with_rng<R>(rng: R) -> Mcts<B>
+        where R: RandomSource + 'static
+    {
+        Mcts {
+            nodes: Vec::new(),
+            root: None,
+            rng: Box::new(rng),
+            exploration: 2f64.sqrt(),
+            playout_depth: None,
+            heuristic_scale: 100.0,
+        }
+    }
+
+    /// Sets the UCT exploration constant, √2 by default. Higher tries the
+    /// weaker moves more often.
+    pub fn set_exploration(&mut self, exploration: f64)
+    {
+        self.exploration = exploration;
+    }
+
+    /// Stops playouts after `plies` plies and scores them with
+    /// `score_for`. Playouts go on until the game is over by default,
+    /// which never happens in games that can go on forever.
+    pub fn set_playout_depth(&mut self, plies: Option<u32>)
+    {
+        self.playout_depth = plies;
+    }
+
+    /// Sets how far a heuristic score is from even to be worth about three
+    /// wins in four, 100 by default. Only playouts cut short by
+    /// `set_playout_depth` see heuristic scores.
+    pub fn set_heuristic_scale(&mut self, scale: f64)
+    {
+        self.heuristic_scale = scale;
+    }
+
+    /// Runs playouts from `board`, `turn` to move, for as long as `budget`
+    /// says. The tree of an earlier search is kept if it was of the same
+    /// position, and thrown away otherwise.
+    ///
+    /// Boards that pass when out of moves are scored as they stand there,
+    /// like `NoMovePolicy::ScoreBoard`.
+    pub fn search(&mut self, board: &B, turn: Team, budget: Budget) -> MctsStats<B::Move>
+    {
+        let kept = self.root.as_ref().is_some_and(|root| root.0 == *board && root.1 == turn);
+        if !kept
+        {
+            self.nodes.clear();
+            self.nodes.push(self.leaf(board, turn, None));
+            self.root = Some((board.clone(), turn));
+        }
+
+        let deadline = match budget
+        {
+            Budget::Time(limit, clock) => Some((clock, clock.elapsed() + limit)),
+            Budget::Iterations(_) => None,
+        };
+        let mut iterations = 0;
+        loop
+        {
+            match (budget, deadline)
+            {
+                (Budget::Iterations(most), _) if iterations >= most => break,
+                (_, Some((clock, deadline))) if clock.elapsed() > deadline => break,
+                _ => {}
+            }
+            self.iterate(board);
+            iterations += 1;
+        }
+
+        self.stats()
+    }
+
+    /// Moves the root down to the position after `mv`, keeping what the
+    /// tree learned below it for the next search. Forgets the tree if it
+    /// hasn't tried `mv`.
+    pub fn advance(&mut self, mv: &B::Move)
+    {
+        let (mut board, turn) = match self.root.take()
+        {
+            Some(root) => root,
+            None => return,
+        };
+        board.do_move(mv);
+
+        let child = self.nodes.first()
+            .and_then(|root| root.children.iter().find(|&&child| self.nodes[child].mv.as_ref() == Some(mv)))
+            .cloned();
+        match child
+        {
+            Some(child) => {
+                /* Copy the subtree out, parents before children */
+                let mut nodes = Vec::new();
+                let mut pending = vec![child];
+                let mut moved = Vec::new();
+                while let Some(old) = pending.pop()
+                {
+                    moved.push(old);
+                    pending.extend(self.nodes[old].children.iter().rev());
+                }
+                let mut index = vec![usize::MAX; self.nodes.len()];
+                for (new, &old) in moved.iter().enumerate()
+                {
+                    index[old] = new;
+                }
+                let mut old_nodes: Vec<_> = self.nodes.drain(..).map(Some).collect();
+                for &old in &moved
+                {
+                    let mut node = old_nodes[old].take().unwrap();
+                    node.children = node.children.iter().map(|&child| index[child]).collect();
+                    nodes.push(node);
+                }
+                nodes[0].mv = None;
+                self.nodes = nodes;
+                self.root = Some((board, turn.other_team()));
+            }
+            None => self.nodes.clear(),
+        }
+    }
+
+    fn leaf(&self, board: &B, turn: Team, mv: Option<B::Move>) -> Node<B::Move>
+    {
+        let untried = if board.is_game_over() { Vec::new() } else { board.moves(turn).collect() };
+        Node {
+            mv,
+            turn,
+            children: Vec::new(),
+            untried,
+            visits: 0,
+            wins: 0.0,
+        }
+    }
+
+    /* One playout: down the tree by UCT, one new node, at random to the
+     * end, and the result back up the way it came
+     */
+    fn iterate(&mut self, root: &B)
+    {
+        let mut board = root.clone();
+        let mut path = vec![0];
+        let mut node = 0;
+        while self.nodes[node].untried.is_empty() && !self.nodes[node].children.is_empty()
+        {
+            node = self.select(node);
+            board.do_move(self.nodes[node].mv.as_ref().unwrap());
+            path.push(node);
+        }
+
+        if !self.nodes[node].untried.is_empty()
+        {
+            let untried = &mut self.nodes[node].untried;
+            let pick = (self.rng.next_u64() % untried.len() as u64) as usize;
+            let mv = untried.swap_remove(pick);
+            let turn = self.nodes[node].turn;
+            board.do_move(&mv);
+            let child = self.leaf(&board, turn.other_team(), Some(mv));
+            self.nodes.push(child);
+            let child = self.nodes.len() - 1;
+            self.nodes[node].children.push(child);
+            path.push(child);
+            node = child;
+        }
+
+        let value = self.playout(&mut board, self.nodes[node].turn);
+        for &node in &path
+        {
+            let node = &mut self.nodes[node];
+            node.visits += 1;
+            node.wins += match node.turn
+            {
+                Team::Ally => 1.0 - value,
+                Team::Enemy => value,
+            };
+        }
+    }
+
+    fn select(&self, node: usize) -> usize
+    {
+        let parent = &self.nodes[node];
+        let log_visits = (parent.visits as f64).ln();
+        let uct = |child: usize|
+        {
+            let child = &self.nodes[child];
+            child.wins / child.visits as f64 + self.exploration * (log_visits / child.visits as f64).sqrt()
+        };
+
+        let mut best = parent.children[0];
+        for &child in &parent.children[1..]
+        {
+            if uct(child) > uct(best)
+            {
+                best = child;
+            }
+        }
+        best
+    }
+
+    /* Plays at random from `board` and returns what the end is worth to
+     * the ally, from 0 for a loss to 1 for a win
+     */
+    fn playout(&mut self, board: &mut B, mut turn: Team) -> f64
+    {
+        let mut plies = 0;
+        loop
+        {
+            if board.is_game_over() || self.playout_depth.is_some_and(|depth| plies >= depth)
+            {
+                return self.value(board.score_for(turn));
+            }
+
+            let moves: Vec<_> = board.moves(turn).collect();
+            if moves.is_empty()
+            {
+                return match board.on_no_moves(turn)
+                {
+                    NoMovePolicy::Lose if turn == Team::Ally => 0.0,
+                    NoMovePolicy::Lose => 1.0,
+                    _ => self.value(board.score_for(turn)),
+                };
+            }
+
+            let pick = (self.rng.next_u64() % moves.len() as u64) as usize;
+            board.do_move(&moves[pick]);
+            turn = turn.other_team();
+            plies += 1;
+        }
+    }
+
+    fn value(&self, score: Score) -> f64
+    {
+        match score
+        {
+            Score::Win => 1.0,
+            Score::Lose => 0.0,
+            Score::Draw => 0.5,
+            Score::Heuristic(val) => 1.0 / (1.0 + (-f64::from(val) * 3f64.ln() / self.heuristic_scale).exp()),
+        }
+    }
+
+    fn stats(&self) -> MctsStats<B::Move>
+    {
+        /* The first of the most visited, since ties keep the last */
+        let most_visited = |node: &Node<B::Move>| node.children.iter()
+            .rev()
+            .cloned()
+            .max_by_key(|&child| self.nodes[child].visits)
+            .filter(|&child| self.nodes[child].visits > 0);
+        let win_rate = |node: &Node<B::Move>| if node.visits == 0 { 0.0 } else { node.wins / node.visits as f64 };
+
+        let root = &self.nodes[0];
+        let mut mvs = Vec::new();
+        let mut node = root;
+        while let Some(child) = most_visited(node)
+        {
+            node = &self.nodes[child];
+            mvs.push(node.mv.clone().unwrap());
+        }
+        let best = most_visited(root).map(|child| &self.nodes[child]);
+
+        MctsStats {
+            mv: best.and_then(|best| best.mv.clone()),
+            win_rate: best.map_or(0.0, win_rate),
+            visits: root.visits,
+            mvs,
+            root_moves: root.children.iter()
+                .map(|&child|
+                {
+                    let child = &self.nodes[child];
+                    MctsMove {
+                        mv: child.mv.clone().unwrap(),
+                        visits: child.visits,
+                        win_rate: win_rate(child),
+                    }
+                })
+                .collect(),
+        }
+    }
+}
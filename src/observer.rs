@@ -0,0 +1,47 @@
+use board::Board;
+use ::{Score, TimedScore};
+
+use std::sync::atomic::{AtomicBool, Ordering};
+
+/// Hooks into a running search, for reporting progress and stopping it
+/// early. Every method does nothing by default.
+pub trait SearchObserver<B, S = Score>
+    where B: Board<S>
+{
+    /// Called after each root move has been searched, with its score from
+    /// the ally's point of view and the nodes its subtree took.
+    fn on_root_move_searched(&mut self, _mv: &B::Move, _score: TimedScore<S>, _nodes: u64)
+    {
+    }
+
+    /// Polled after every root move and every `NODES_PER_POLL` nodes
+    /// deeper in the tree. Once it returns true the search unwinds.
+    fn should_stop(&mut self) -> bool
+    {
+        false
+    }
+}
+
+/// How many nodes may be searched between two `should_stop` polls.
+pub const NODES_PER_POLL: u64 = 1024;
+
+/// Observer that never stops the search.
+pub struct NoObserver;
+
+impl<B, S> SearchObserver<B, S> for NoObserver
+    where B: Board<S>
+{
+}
+
+/// Observer that stops the search once the flag is set, which can be done
+/// from another thread.
+pub struct StopFlag<'a>(pub &'a AtomicBool);
+
+impl<'a, B, S> SearchObserver<B, S> for StopFlag<'a>
+    where B: Board<S>
+{
+    fn should_stop(&mut self) -> bool
+    {
+        self.0.load(Ordering::Relaxed)
+    }
+}
@@ -0,0 +1,431 @@
+use clock::Clock;
+
+use std::fmt;
+use std::time::Duration;
+
+/// How many nodes may be searched between two readings of the clock, unless
+/// `SearchOptionsBuilder::clock_interval` says otherwise.
+pub const NODES_PER_CLOCK_READING: u64 = 4096;
+
+/// When late move reductions search a move less deep, from
+/// `SearchOptionsBuilder::late_move_reductions`.
+///
+/// At nodes with at least `min_depth` plies left, the moves after the
+/// first `full_moves` are searched `reduction` plies shallower with a
+/// zero-width window, and only searched again at full depth if they beat
+/// the best move so far. The table move, killers, countermoves, the
+/// principal variation, extended moves and those `Board::is_tactical`
+/// flags are always searched in full, and so are the root moves.
+#[derive(Copy,Clone,Debug,PartialEq,Eq)]
+pub struct LateMoveReductions
+{
+    pub reduction: u32,
+    pub full_moves: usize,
+    pub min_depth: u32,
+}
+
+impl Default for LateMoveReductions
+{
+    /// One ply off the moves after the third, with three plies or more
+    /// left.
+    fn default() -> LateMoveReductions
+    {
+        LateMoveReductions {
+            reduction: 1,
+            full_moves: 3,
+            min_depth: 3,
+        }
+    }
+}
+
+/// When singular extensions search the table move a ply deeper, from
+/// `SearchOptionsBuilder::singular_extensions`.
+///
+/// At nodes with at least `min_depth` plies left, whose table entry is at
+/// most three plies shallower and scores at least as well as it says, the
+/// other moves are searched half as deep with a window `margin` below that
+/// score. If none of them reaches it the table move is singular, the only
+/// good move, and gets an extra ply, within `Minimax::set_max_extensions`.
+#[derive(Copy,Clone,Debug,PartialEq,Eq)]
+pub struct SingularExtensions
+{
+    pub margin: i32,
+    pub min_depth: u32,
+}
+
+impl Default for SingularExtensions
+{
+    /// A margin of 50 with six plies or more left.
+    fn default() -> SingularExtensions
+    {
+        SingularExtensions {
+            margin: 50,
+            min_depth: 6,
+        }
+    }
+}
+
+/// When internal iterative deepening searches a node less deep first, from
+/// `SearchOptionsBuilder::internal_deepening`.
+///
+/// A node with at least `min_depth` plies left and a window wider than
+/// zero, that the transposition table has no move for, is first searched
+/// `reduction` plies shallower to find one. The real search then tries
+/// that move first. Only the node count changes.
+#[derive(Copy,Clone,Debug,PartialEq,Eq)]
+pub struct InternalDeepening
+{
+    pub min_depth: u32,
+    pub reduction: u32,
+}
+
+impl Default for InternalDeepening
+{
+    /// Two plies shallower, with four plies or more left.
+    fn default() -> InternalDeepening
+    {
+        InternalDeepening {
+            min_depth: 4,
+            reduction: 2,
+        }
+    }
+}
+
+/// When reverse futility pruning leaves a node failing high, from
+/// `SearchOptionsBuilder::reverse_futility`.
+///
+/// A node with at most `max_depth` plies left, whose static score is still
+/// at or above beta after taking `margin` off for each of them, is taken to
+/// fail high without looking at its moves, and is worth its static score.
+/// Not the root or the principal variation, nor nodes whose beta is a win
+/// or a loss, nor positions `Board::is_tactical_position` flags.
+#[derive(Copy,Clone,Debug,PartialEq,Eq)]
+pub struct ReverseFutility
+{
+    pub margin: i32,
+    pub max_depth: u32,
+}
+
+impl Default for ReverseFutility
+{
+    /// A margin of 100 a ply, with three plies or fewer left.
+    fn default() -> ReverseFutility
+    {
+        ReverseFutility {
+            margin: 100,
+            max_depth: 3,
+        }
+    }
+}
+
+/// How the opponent is expected to play under
+/// `SearchOptionsBuilder::opponent_error`.
+///
+/// Wherever the opponent moves, it plays its best reply, except with
+/// `probability` it picks one of its `replies` best replies at random.
+/// The opponent's nodes are then worth that blend of their replies,
+/// through `GameScore::blend`, rather than the best one.
+///
+/// The search no longer treats the game as zero-sum: the opponent is
+/// scored as playing worse than it could, so a move can look good only
+/// because it hopes for a mistake, and an opponent that doesn't make one
+/// gets more than the score says. Those nodes see every reply with the
+/// full window, so there is far less pruning.
+#[derive(Copy,Clone,Debug,PartialEq)]
+pub struct OpponentError
+{
+    pub probability: f64,
+    pub replies: usize,
+}
+
+/// Settings for one call to `Minimax::search`, such as
+/// `SearchOptions::depth(6).max_nodes(2_000_000).build()`.
+#[derive(Copy,Clone)]
+pub struct SearchOptions<'a>
+{
+    plies: u32,
+    max_nodes: Option<u64>,
+    time_limit: Option<(Duration, &'a dyn Clock)>,
+    clock_interval: u64,
+    contempt: i32,
+    late_move_reductions: Option<LateMoveReductions>,
+    singular_extensions: Option<SingularExtensions>,
+    internal_deepening: Option<InternalDeepening>,
+    reverse_futility: Option<ReverseFutility>,
+    pre_ordering: Option<u32>,
+    max_branching: Option<usize>,
+    max_root_branching: Option<usize>,
+    opponent_error: Option<OpponentError>,
+    enemy_depth_penalty: u32,
+}
+
+impl<'a> SearchOptions<'a>
+{
+    /// Starts a set of options that searches `plies` plies deep.
+    pub fn depth(plies: u32) -> SearchOptionsBuilder<'a>
+    {
+        SearchOptionsBuilder {
+            opts: SearchOptions {
+                plies,
+                max_nodes: None,
+                time_limit: None,
+                clock_interval: NODES_PER_CLOCK_READING,
+                contempt: 0,
+                late_move_reductions: None,
+                singular_extensions: None,
+                internal_deepening: None,
+                reverse_futility: None,
+                pre_ordering: None,
+                max_branching: None,
+                max_root_branching: None,
+                opponent_error: None,
+                enemy_depth_penalty: 0,
+            },
+        }
+    }
+
+    pub fn plies(&self) -> u32
+    {
+        self.plies
+    }
+
+    pub fn node_limit(&self) -> Option<u64>
+    {
+        self.max_nodes
+    }
+
+    /// The time limit and the clock it is measured on.
+    pub fn time_limit(&self) -> Option<(Duration, &'a dyn Clock)>
+    {
+        self.time_limit
+    }
+
+    pub fn clock_interval(&self) -> u64
+    {
+        self.clock_interval
+    }
+
+    pub fn contempt(&self) -> i32
+    {
+        self.contempt
+    }
+
+    pub fn late_move_reductions(&self) -> Option<LateMoveReductions>
+    {
+        self.late_move_reductions
+    }
+
+    pub fn singular_extensions(&self) -> Option<SingularExtensions>
+    {
+        self.singular_extensions
+    }
+
+    pub fn internal_deepening(&self) -> Option<InternalDeepening>
+    {
+        self.internal_deepening
+    }
+
+    pub fn reverse_futility(&self) -> Option<ReverseFutility>
+    {
+        self.reverse_futility
+    }
+
+    /// Plies a node needs left for `SearchOptionsBuilder::pre_ordering`
+    /// to sort its moves.
+    pub fn pre_ordering(&self) -> Option<u32>
+    {
+        self.pre_ordering
+    }
+
+    pub fn max_branching(&self) -> Option<usize>
+    {
+        self.max_branching
+    }
+
+    pub fn max_root_branching(&self) -> Option<usize>
+    {
+        self.max_root_branching
+    }
+
+    pub fn opponent_error(&self) -> Option<OpponentError>
+    {
+        self.opponent_error
+    }
+
+    pub fn enemy_depth_penalty(&self) -> u32
+    {
+        self.enemy_depth_penalty
+    }
+}
+
+impl<'a> fmt::Debug for SearchOptions<'a>
+{
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result
+    {
+        f.debug_struct("SearchOptions")
+            .field("plies", &self.plies)
+            .field("max_nodes", &self.max_nodes)
+            .field("time_limit", &self.time_limit.map(|(limit, _)| limit))
+            .field("clock_interval", &self.clock_interval)
+            .field("contempt", &self.contempt)
+            .field("late_move_reductions", &self.late_move_reductions)
+            .field("singular_extensions", &self.singular_extensions)
+            .field("internal_deepening", &self.internal_deepening)
+            .field("reverse_futility", &self.reverse_futility)
+            .field("pre_ordering", &self.pre_ordering)
+            .field("max_branching", &self.max_branching)
+            .field("max_root_branching", &self.max_root_branching)
+            .field("opponent_error", &self.opponent_error)
+            .field("enemy_depth_penalty", &self.enemy_depth_penalty)
+            .finish()
+    }
+}
+
+/// Builder for `SearchOptions`, from `SearchOptions::depth`.
+#[derive(Copy,Clone,Debug)]
+pub struct SearchOptionsBuilder<'a>
+{
+    opts: SearchOptions<'a>,
+}
+
+impl<'a> SearchOptionsBuilder<'a>
+{
+    /// Stops the search once it has visited about `nodes` nodes. Like a
+    /// cancelled search, it then returns the best root move it searched to
+    /// the end. Nodes answered from the transposition table count too, so
+    /// the same budget on the same tables stops at the same node.
+    pub fn max_nodes(mut self, nodes: u64) -> SearchOptionsBuilder<'a>
+    {
+        self.opts.max_nodes = Some(nodes);
+        self
+    }
+
+    /// Stops the search once more than `limit` has passed on `clock` since
+    /// it started, the same way `max_nodes` does.
+    pub fn time_limit(mut self, limit: Duration, clock: &'a dyn Clock) -> SearchOptionsBuilder<'a>
+    {
+        self.opts.time_limit = Some((limit, clock));
+        self
+    }
+
+    /// Reads the clock every `nodes` nodes instead of every
+    /// `NODES_PER_CLOCK_READING`. Zero is taken as one.
+    pub fn clock_interval(mut self, nodes: u64) -> SearchOptionsBuilder<'a>
+    {
+        self.opts.clock_interval = nodes.max(1);
+        self
+    }
+
+    /// Scores draws and even positions as `contempt` worse than even for
+    /// the ally, so a positive contempt steers away from draws and a
+    /// negative one towards them. Searches with contempt keep their
+    /// results in a transposition table of their own, so the normal
+    /// search never sees their scores. That table only holds one contempt
+    /// at a time.
+    pub fn contempt(mut self, contempt: i32) -> SearchOptionsBuilder<'a>
+    {
+        self.opts.contempt = contempt;
+        self
+    }
+
+    /// Searches late moves less deep, see `LateMoveReductions`. Off by
+    /// default. Unlike pruning, reductions can change the move and score
+    /// found, in exchange for searching deeper in the same time.
+    pub fn late_move_reductions(mut self, schedule: LateMoveReductions) -> SearchOptionsBuilder<'a>
+    {
+        self.opts.late_move_reductions = Some(schedule);
+        self
+    }
+
+    /// Searches singular table moves a ply deeper, see
+    /// `SingularExtensions`. Off by default. Scores only get extended if
+    /// they implement `GameScore::add_margin` and `GameScore::above`.
+    pub fn singular_extensions(mut self, schedule: SingularExtensions) -> SearchOptionsBuilder<'a>
+    {
+        self.opts.singular_extensions = Some(schedule);
+        self
+    }
+
+    /// Finds a move to try first at nodes the table knows nothing about,
+    /// see `InternalDeepening`. Off by default.
+    pub fn internal_deepening(mut self, schedule: InternalDeepening) -> SearchOptionsBuilder<'a>
+    {
+        self.opts.internal_deepening = Some(schedule);
+        self
+    }
+
+    /// Leaves nodes near the horizon whose static score is well above beta
+    /// failing high, see `ReverseFutility`. Off by default. Like razoring
+    /// it can change the move and score found, and scores only prune if
+    /// they implement `GameScore::add_margin`.
+    pub fn reverse_futility(mut self, schedule: ReverseFutility) -> SearchOptionsBuilder<'a>
+    {
+        self.opts.reverse_futility = Some(schedule);
+        self
+    }
+
+    /// Sorts the moves of nodes with at least `min_depth` plies left, that
+    /// the transposition table has no move for, by the static score of
+    /// the position after each before searching them, best first. Off by
+    /// default. The score found stays the same, though another move as
+    /// good may come first. The positions scored are counted in
+    /// `SearchStats::pre_ordering_nodes` rather than as nodes.
+    pub fn pre_ordering(mut self, min_depth: u32) -> SearchOptionsBuilder<'a>
+    {
+        self.opts.pre_ordering = Some(min_depth);
+        self
+    }
+
+    /// Beam search: only the first `moves` moves of every node below the
+    /// root, in the order the search would try them, are searched, and
+    /// the rest are left out. Off by default. Results that left moves out
+    /// aren't `MoveStats::exhaustive`, and are kept apart from the normal
+    /// search's like those of `contempt`. Zero is taken as one.
+    ///
+    /// The root keeps all its moves, unless `max_root_branching` says
+    /// otherwise, so the best move there can't be left out by accident.
+    pub fn max_branching(mut self, moves: usize) -> SearchOptionsBuilder<'a>
+    {
+        self.opts.max_branching = Some(moves.max(1));
+        self
+    }
+
+    /// Only searches the first `moves` root moves, like `max_branching`
+    /// does below the root. Off by default. Zero is taken as one.
+    pub fn max_root_branching(mut self, moves: usize) -> SearchOptionsBuilder<'a>
+    {
+        self.opts.max_root_branching = Some(moves.max(1));
+        self
+    }
+
+    /// Expects the opponent to pick one of its `replies` best replies at
+    /// random with `probability`, see `OpponentError`. Off by default, and
+    /// a probability of zero searches as if it were. The opponent is the
+    /// side not moving at the root. Results are kept apart from the normal
+    /// search's like those of `contempt`.
+    pub fn opponent_error(mut self, probability: f64, replies: usize) -> SearchOptionsBuilder<'a>
+    {
+        self.opts.opponent_error = Some(OpponentError {
+            probability: probability.clamp(0.0, 1.0),
+            replies: replies.max(1),
+        });
+        self
+    }
+
+    /// Searches the opponent's replies `plies` plies less deep than it
+    /// would, every time it is to move, but never below its horizon: the
+    /// opponent plays as if it looked less far ahead, which is how a
+    /// weaker player would. Zero, the default, searches both sides alike.
+    /// The opponent is the side not moving at the root. Results are kept
+    /// apart from the normal search's like those of `contempt`.
+    pub fn enemy_depth_penalty(mut self, plies: u32) -> SearchOptionsBuilder<'a>
+    {
+        self.opts.enemy_depth_penalty = plies;
+        self
+    }
+
+    pub fn build(self) -> SearchOptions<'a>
+    {
+        self.opts
+    }
+}
@@ -0,0 +1,78 @@
+use board::Board;
+use ::{Score, Team};
+
+/// Decides the order the moves of a node are searched in, for games that
+/// know better than any generic heuristic which moves to try first.
+///
+/// The search calls it at every node it expands, once the moves have been
+/// generated and put in order by `Board::order_moves` and any heuristics
+/// that are turned on. Install one with `Minimax::set_move_orderer`.
+/// Two orderers in a tuple run one after the other, so `(mine,
+/// TableMoveFirst)` puts the table move ahead of whatever `mine` picked.
+pub trait MoveOrderer<B, S = Score>
+    where B: Board<S>
+{
+    /// Reorders `moves`, the moves of `turn` on `board` with `depth` plies
+    /// left to search. `tt_move` is the best move the transposition table
+    /// has for the position, if any. Does nothing by default.
+    fn order(&mut self, _board: &B, _turn: Team, _moves: &mut Vec<B::Move>, _depth: u32, _tt_move: Option<&B::Move>)
+    {
+    }
+}
+
+/// Orderer that leaves the moves as they are.
+#[derive(Copy,Clone,Debug,Default,PartialEq,Eq)]
+pub struct Identity;
+
+impl<B, S> MoveOrderer<B, S> for Identity
+    where B: Board<S>
+{
+}
+
+/// Orderer that tries the table move first and leaves the rest as they
+/// are. The default.
+#[derive(Copy,Clone,Debug,Default,PartialEq,Eq)]
+pub struct TableMoveFirst;
+
+impl<B, S> MoveOrderer<B, S> for TableMoveFirst
+    where B: Board<S>
+{
+    fn order(&mut self, _board: &B, _turn: Team, moves: &mut Vec<B::Move>, _depth: u32, tt_move: Option<&B::Move>)
+    {
+        if let Some(pos) = tt_move.and_then(|first| moves.iter().position(|mv| mv == first))
+        {
+            moves[..pos + 1].rotate_right(1);
+        }
+    }
+}
+
+impl<B, S, A, C> MoveOrderer<B, S> for (A, C)
+    where B: Board<S>,
+          A: MoveOrderer<B, S>,
+          C: MoveOrderer<B, S>
+{
+    fn order(&mut self, board: &B, turn: Team, moves: &mut Vec<B::Move>, depth: u32, tt_move: Option<&B::Move>)
+    {
+        self.0.order(board, turn, moves, depth, tt_move);
+        self.1.order(board, turn, moves, depth, tt_move);
+    }
+}
+
+/* An installed orderer, which each worker of a parallel search gets its
+ * own copy of
+ */
+pub(crate) trait SharedOrderer<B, S>: MoveOrderer<B, S> + Send
+    where B: Board<S>
+{
+    fn boxed_clone(&self) -> Box<dyn SharedOrderer<B, S>>;
+}
+
+impl<B, S, O> SharedOrderer<B, S> for O
+    where B: Board<S>,
+          O: MoveOrderer<B, S> + Clone + Send + 'static
+{
+    fn boxed_clone(&self) -> Box<dyn SharedOrderer<B, S>>
+    {
+        Box::new(self.clone())
+    }
+}
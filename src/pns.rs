@@ -0,0 +1,262 @@
+//! Proof-number search, for finding out whether a side can force a win
+//! much faster than alpha-beta would.
+//!
+//! Only wins count: a position is proven if the side the question is
+//! about can force one, and disproven if the other side can hold it off,
+//! by winning itself or by drawing. Heuristic scores say nothing.
+
+use board::{Board, NoMovePolicy};
+use transposition_table::{TranspositionTable, Bound};
+use ::{Score, Team};
+
+use std::hash::Hash;
+use std::num::NonZeroUsize;
+
+/* Proof and disproof numbers of a settled node */
+const INFINITE: u64 = u64::MAX;
+
+/// The answer `prove` came to.
+#[derive(Clone,Debug,PartialEq,Eq)]
+pub enum ProofResult<M>
+{
+    /// The side to move can force a win, starting with the move, or has
+    /// already won if there is none.
+    Proven(Option<M>),
+    /// It can't.
+    Disproven,
+    /// The node limit ran out first.
+    Unknown,
+}
+
+struct Node<B, M>
+{
+    board: B,
+    turn: Team,
+    mv: Option<M>,
+    parent: Option<usize>,
+    children: Vec<usize>,
+    expanded: bool,
+    /* Nodes below that would have to be proven, or disproven, to settle
+     * this one
+     */
+    proof: u64,
+    disproof: u64,
+}
+
+/// Proof-number search with a table of the positions it settled, which
+/// stands in for searching transpositions again and lasts from one
+/// question to the next.
+pub struct ProofSearch<B>
+    where B: Board + Eq + Hash
+{
+    /* Settled positions with the side to move, the side the question was
+     * about and whether it wins
+     */
+    ttable: TranspositionTable<(B, Team), (Team, bool)>,
+    nodes: Vec<Node<B, B::Move>>,
+}
+
+/// Whether `turn` can force a win on `board`, within `node_limit` nodes.
+pub fn prove<B>(board: &B, turn: Team, node_limit: usize) -> ProofResult<B::Move>
+    where B: Board + Eq + Hash
+{
+    ProofSearch::new(NonZeroUsize::new(node_limit.max(1)).unwrap()).prove(board, turn, node_limit)
+}
+
+impl<B> ProofSearch<B>
+    where B: Board + Eq + Hash
+{
+    /// Makes a search whose table holds up to `ttable_size` settled
+    /// positions.
+    pub fn new(ttable_size: NonZeroUsize) -> ProofSearch<B>
+    {
+        ProofSearch {
+            ttable: TranspositionTable::new(ttable_size),
+            nodes: Vec::new(),
+        }
+    }
+
+    /// Whether `turn` can force a win on `board`, giving up once the tree
+    /// has `node_limit` nodes.
+    ///
+    /// Sides that pass when out of moves are scored as they stand there,
+    /// like `NoMovePolicy::ScoreBoard`.
+    pub fn prove(&mut self, board: &B, turn: Team, node_limit: usize) -> ProofResult<B::Move>
+    {
+        self.nodes.clear();
+        let root = self.leaf(board.clone(), turn, None, None, turn);
+        self.nodes.push(root);
+
+        while self.nodes[0].proof != 0 && self.nodes[0].disproof != 0 && self.nodes.len() < node_limit
+        {
+            let node = self.most_proving(turn);
+            self.expand(node, turn);
+            self.update(node, turn);
+        }
+
+        let root = &self.nodes[0];
+        if root.proof == 0
+        {
+            let winner = root.children.iter().find(|&&child| self.nodes[child].proof == 0);
+            ProofResult::Proven(winner.and_then(|&child| self.nodes[child].mv.clone()))
+        }
+        else if root.disproof == 0
+        {
+            ProofResult::Disproven
+        }
+        else
+        {
+            ProofResult::Unknown
+        }
+    }
+
+    /* A new node, settled at once if the game is over there or the table
+     * knows it
+     */
+    fn leaf(&mut self, board: B, turn: Team, mv: Option<B::Move>, parent: Option<usize>, attacker: Team) -> Node<B, B::Move>
+    {
+        let won = match self.ttable.get(&(board.clone(), turn), 0)
+        {
+            Some(((asked, won), _)) if asked == attacker => Some(won),
+            _ => outcome(&board, turn, attacker),
+        };
+        let (proof, disproof) = match won
+        {
+            Some(true) => (0, INFINITE),
+            Some(false) => (INFINITE, 0),
+            None => (1, 1),
+        };
+
+        Node {
+            board,
+            turn,
+            mv,
+            parent,
+            children: Vec::new(),
+            expanded: false,
+            proof,
+            disproof,
+        }
+    }
+
+    /* Down from the root, the attacker tries its most provable move and
+     * the defender its least disprovable
+     */
+    fn most_proving(&self, attacker: Team) -> usize
+    {
+        let mut node = 0;
+        while self.nodes[node].expanded
+        {
+            let children = &self.nodes[node].children;
+            node = if self.nodes[node].turn == attacker
+            {
+                *children.iter().min_by_key(|&&child| self.nodes[child].proof).unwrap()
+            }
+            else
+            {
+                *children.iter().min_by_key(|&&child| self.nodes[child].disproof).unwrap()
+            };
+        }
+        node
+    }
+
+    fn expand(&mut self, node: usize, attacker: Team)
+    {
+        let turn = self.nodes[node].turn;
+        let moves: Vec<_> = self.nodes[node].board.moves(turn).collect();
+        for mv in moves
+        {
+            let mut board = self.nodes[node].board.clone();
+            board.do_move(&mv);
+            let child = self.leaf(board, turn.other_team(), Some(mv), Some(node), attacker);
+            self.nodes.push(child);
+            let child = self.nodes.len() - 1;
+            self.nodes[node].children.push(child);
+        }
+        self.nodes[node].expanded = true;
+    }
+
+    /* Recomputes the numbers from `node` up to the root, storing the
+     * nodes that got settled
+     */
+    fn update(&mut self, mut node: usize, attacker: Team)
+    {
+        loop
+        {
+            let children = &self.nodes[node].children;
+            let proofs = children.iter().map(|&child| self.nodes[child].proof);
+            let disproofs = children.iter().map(|&child| self.nodes[child].disproof);
+            let (proof, disproof) = if self.nodes[node].turn == attacker
+            {
+                (proofs.min().unwrap_or(INFINITE), disproofs.fold(0, u64::saturating_add))
+            }
+            else
+            {
+                (proofs.fold(0, u64::saturating_add), disproofs.min().unwrap_or(INFINITE))
+            };
+
+            /* A side without moves has lost, or the board says otherwise */
+            let (proof, disproof) = match outcome_without_moves(&self.nodes[node], attacker)
+            {
+                Some(true) => (0, INFINITE),
+                Some(false) => (INFINITE, 0),
+                None => (proof, disproof),
+            };
+
+            let current = &mut self.nodes[node];
+            current.proof = proof;
+            current.disproof = disproof;
+            if proof == 0 || disproof == 0
+            {
+                let key = (current.board.clone(), current.turn);
+                self.ttable.insert(key, (attacker, proof == 0), 0, Bound::Exact);
+            }
+
+            match self.nodes[node].parent
+            {
+                Some(parent) => node = parent,
+                None => break,
+            }
+        }
+    }
+}
+
+/* Whether `attacker` has won, if the game is over */
+fn outcome<B>(board: &B, turn: Team, attacker: Team) -> Option<bool>
+    where B: Board
+{
+    if board.is_game_over()
+    {
+        Some(wins(board.score_for(turn), attacker))
+    }
+    else
+    {
+        None
+    }
+}
+
+/* Whether an expanded node without children is won by `attacker` */
+fn outcome_without_moves<B>(node: &Node<B, B::Move>, attacker: Team) -> Option<bool>
+    where B: Board
+{
+    if !node.expanded || !node.children.is_empty()
+    {
+        return None;
+    }
+
+    match node.board.on_no_moves(node.turn)
+    {
+        NoMovePolicy::Lose => Some(node.turn != attacker),
+        _ => Some(wins(node.board.score_for(node.turn), attacker)),
+    }
+}
+
+/* Whether `score`, the ally's, is a win for `attacker` */
+fn wins(score: Score, attacker: Team) -> bool
+{
+    match attacker
+    {
+        Team::Ally => score == Score::Win,
+        Team::Enemy => score == Score::Lose,
+    }
+}
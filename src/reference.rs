@@ -0,0 +1,288 @@
+//! Plain minimax without pruning, tables or move ordering, slow enough to
+//! be obviously right. `Minimax::verify` checks the real search against
+//! it, and boards can be checked against it on their own.
+//!
+//! It scores the game the way `Minimax` does with its default settings:
+//! the same passes, extensions, depth adjustments and quiescence search,
+//! and a win sooner beats the same win later.
+
+use board::{Board, NoMovePolicy};
+use depth::Depth;
+use ::{GameScore, Score, Team, TimedScore};
+
+use std::cmp::Ordering;
+
+/// Result of a reference search.
+#[derive(Clone,Debug,PartialEq,Eq)]
+pub struct ReferenceResult<M, S = Score>
+{
+    /// The first of the best root moves.
+    pub mv: Option<M>,
+    /// From the ally's point of view, like `MoveStats::score`.
+    pub score: TimedScore<S>,
+    /// Principal variation, starting with `mv`.
+    pub mvs: Vec<M>,
+    /// Every root move with its score, from the ally's point of view.
+    pub root_moves: Vec<(M, TimedScore<S>)>,
+    /// Nodes searched, counting the root.
+    pub nodes_visited: u64,
+}
+
+impl<M, S> ReferenceResult<M, S>
+    where M: PartialEq,
+          S: GameScore
+{
+    /// Whether `mv` scores as well as the best root move.
+    pub fn is_best(&self, mv: &M) -> bool
+    {
+        self.root_moves.iter().any(|(root, score)| root == mv && score.cmp(&self.score) == Ordering::Equal)
+    }
+}
+
+/// The settings of `Minimax` the game's score depends on, as the reference
+/// search plays them.
+#[derive(Copy,Clone,Debug,PartialEq,Eq)]
+pub struct Reference<S = Score>
+{
+    pub quiescence_depth: u32,
+    pub max_extensions: u32,
+    pub max_depth_adjustment: u32,
+    pub repetition_score: Option<S>,
+}
+
+impl<S> Default for Reference<S>
+{
+    /// What a new `Minimax` uses.
+    fn default() -> Reference<S>
+    {
+        Reference {
+            quiescence_depth: 8,
+            max_extensions: 3,
+            max_depth_adjustment: 3,
+            repetition_score: None,
+        }
+    }
+}
+
+/* Scores and lines, from the side to move */
+struct Line<M, S>
+{
+    score: TimedScore<S>,
+    mvs: Vec<M>,
+}
+
+/* Positions from the root down to the node being searched, the plies
+ * the moves there were adjusted by, and the nodes searched so far
+ */
+struct Walk<B>
+{
+    path: Vec<(B, Team)>,
+    adjustment: i32,
+    nodes: u64,
+}
+
+/// Searches `board` `plies` plies deep for `turn` with the default
+/// settings.
+pub fn minimax<B, S>(board: &B, turn: Team, plies: u32) -> ReferenceResult<B::Move, S>
+    where B: Board<S> + PartialEq,
+          S: GameScore
+{
+    Reference::default().search(board, turn, plies)
+}
+
+impl<S> Reference<S>
+    where S: GameScore
+{
+    /// Searches `board` `plies` plies deep for `turn`, at least one.
+    pub fn search<B>(&self, board: &B, turn: Team, plies: u32) -> ReferenceResult<B::Move, S>
+        where B: Board<S> + PartialEq
+    {
+        let ally = |score: TimedScore<S>| match turn
+        {
+            Team::Ally => score,
+            Team::Enemy => -score,
+        };
+
+        let depth = Depth::from_plies(plies.max(1));
+        let mut walk = Walk { path: vec![(board.clone(), turn)], adjustment: 0, nodes: 1 };
+        let mut root_moves = Vec::new();
+        let mut best: Option<Line<B::Move, S>> = None;
+        if !board.is_game_over()
+        {
+            for mv in self.ordered(board, turn)
+            {
+                let reply = self.child(&mut walk, board, turn, mv.clone(), depth, Depth::ZERO);
+                root_moves.push((mv, ally(reply.score)));
+                if best.as_ref().is_none_or(|best| reply.score > best.score)
+                {
+                    best = Some(reply);
+                }
+            }
+        }
+
+        /* Out of moves, the root is scored like any other node */
+        let line = match best
+        {
+            Some(line) => line,
+            None => {
+                walk.path.clear();
+                self.node(&mut walk, board, turn, depth, Depth::ZERO)
+            }
+        };
+        ReferenceResult {
+            mv: line.mvs.first().cloned(),
+            score: ally(line.score),
+            mvs: line.mvs,
+            root_moves,
+            nodes_visited: walk.nodes,
+        }
+    }
+
+    fn ordered<B>(&self, board: &B, turn: Team) -> Vec<B::Move>
+        where B: Board<S>
+    {
+        let mut moves: Vec<_> = board.moves(turn).collect();
+        board.order_moves(&mut moves, turn);
+        moves
+    }
+
+    /* The line starting with `mv`, seen from `turn` */
+    fn child<B>(&self, walk: &mut Walk<B>, board: &B, turn: Team, mv: B::Move, depth: Depth, extended: Depth) -> Line<B::Move, S>
+        where B: Board<S> + PartialEq
+    {
+        let extension = board.fractional_extension(&mv, turn).min(Depth::from_plies(self.max_extensions).saturating_sub(extended));
+        let cap = self.max_depth_adjustment.min(i32::MAX as u32) as i32;
+        let adjustment = walk.adjustment.saturating_add(board.depth_adjustment(&mv, turn)).clamp(-cap, cap) - walk.adjustment;
+        let mut child = board.clone();
+        child.do_move(&mv);
+        walk.adjustment += adjustment;
+        let reply = self.node(walk, &child, turn.other_team(), (depth - Depth::ONE_PLY + extension).add_plies(adjustment), extended + extension);
+        walk.adjustment -= adjustment;
+        let mut mvs = reply.mvs;
+        mvs.insert(0, mv);
+        Line {
+            score: (-reply.score).later(),
+            mvs,
+        }
+    }
+
+    fn node<B>(&self, walk: &mut Walk<B>, board: &B, turn: Team, depth: Depth, extended: Depth) -> Line<B::Move, S>
+        where B: Board<S> + PartialEq
+    {
+        walk.nodes += 1;
+        if board.is_game_over()
+        {
+            return stand(board, turn);
+        }
+
+        let moves = self.ordered(board, turn);
+        if moves.is_empty()
+        {
+            return match board.on_no_moves(turn)
+            {
+                NoMovePolicy::Lose => line(S::MIN),
+                NoMovePolicy::Pass if depth >= Depth::ONE_PLY => {
+                    walk.path.push((board.clone(), turn));
+                    let reply = self.node(walk, board, turn.other_team(), depth - Depth::ONE_PLY, extended);
+                    walk.path.pop();
+                    Line { score: (-reply.score).later(), mvs: Vec::new() }
+                }
+                _ => stand(board, turn),
+            };
+        }
+        if depth < Depth::ONE_PLY
+        {
+            return self.quiesce(walk, board, turn, self.quiescence_depth);
+        }
+
+        /* Positions further up with the same side to move */
+        if let Some(score) = self.repetition_score
+        {
+            if walk.path.iter().any(|(seen, to_move)| seen == board && *to_move == turn)
+            {
+                return line(match turn
+                {
+                    Team::Ally => score,
+                    Team::Enemy => -score,
+                });
+            }
+        }
+
+        let mut best: Option<Line<B::Move, S>> = None;
+        walk.path.push((board.clone(), turn));
+        for mv in moves
+        {
+            let reply = self.child(walk, board, turn, mv, depth, extended);
+            if best.as_ref().is_none_or(|best| reply.score > best.score)
+            {
+                best = Some(reply);
+            }
+        }
+        walk.path.pop();
+        best.unwrap()
+    }
+
+    fn quiesce<B>(&self, walk: &mut Walk<B>, board: &B, turn: Team, depth: u32) -> Line<B::Move, S>
+        where B: Board<S>
+    {
+        let mut best = stand(board, turn);
+        if depth == 0
+        {
+            return best;
+        }
+
+        let other = turn.other_team();
+        for mv in board.gen_quiescence_moves(turn)
+        {
+            let mut child = board.clone();
+            child.do_move(&mv);
+            walk.nodes += 1;
+            let reply = if child.is_game_over()
+            {
+                stand(&child, other)
+            }
+            else if child.moves(other).next().is_none()
+            {
+                match child.on_no_moves(other)
+                {
+                    NoMovePolicy::Lose => line(S::MIN),
+                    _ => stand(&child, other),
+                }
+            }
+            else
+            {
+                self.quiesce(walk, &child, other, depth - 1)
+            };
+
+            let score = (-reply.score).later();
+            if score > best.score
+            {
+                let mut mvs = reply.mvs;
+                mvs.insert(0, mv);
+                best = Line { score, mvs };
+            }
+        }
+        best
+    }
+}
+
+fn line<M, S>(score: S) -> Line<M, S>
+{
+    Line {
+        score: TimedScore { score, turns: 0 },
+        mvs: Vec::new(),
+    }
+}
+
+/* The static score, from the side to move */
+fn stand<B, S>(board: &B, turn: Team) -> Line<B::Move, S>
+    where B: Board<S>,
+          S: GameScore
+{
+    let score = board.score_for(turn);
+    line(match turn
+    {
+        Team::Ally => score,
+        Team::Enemy => -score,
+    })
+}
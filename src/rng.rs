@@ -0,0 +1,28 @@
+/// Small xorshift generator, for when the search needs to pick at random
+/// but has to stay reproducible.
+#[derive(Copy,Clone,Debug)]
+pub struct XorShift(u64);
+
+impl XorShift
+{
+    pub fn new(seed: u64) -> XorShift
+    {
+        /* Xorshift gets stuck on zero */
+        let state = seed ^ 0x9e37_79b9_7f4a_7c15;
+        XorShift(if state == 0 { 1 } else { state })
+    }
+
+    pub fn next_u64(&mut self) -> u64
+    {
+        self.0 ^= self.0 << 13;
+        self.0 ^= self.0 >> 7;
+        self.0 ^= self.0 << 17;
+        self.0
+    }
+
+    /// A number in `0..n`. `n` must not be zero.
+    pub fn below(&mut self, n: u64) -> u64
+    {
+        self.next_u64() % n
+    }
+}
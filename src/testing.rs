@@ -0,0 +1,263 @@
+//! Helpers for checking a search or a board: counting the raw game tree,
+//! comparing the search against plain minimax, and random game trees to
+//! run them on.
+//!
+//! Needs the `testing` feature.
+
+use board::Board;
+use rng::XorShift;
+use ::{Minimax, GameScore, SearchMode, Score, Team};
+
+use std::fmt::Debug;
+use std::hash::Hash;
+use std::num::NonZeroUsize;
+
+/* Table size of the searches `verify_against_plain_minimax` compares */
+const VERIFY_TABLE_SIZE: usize = 1 << 16;
+
+/// Counts the leaves of the game tree `depth` plies below `board`, `turn`
+/// moving first, without any pruning.
+///
+/// Positions `depth` plies down are leaves, and so are the ones where the
+/// game's over or the side to move has no moves. A change to a board's
+/// move generation that changes this has changed the game.
+pub fn perft<B, S>(board: &B, depth: u32, turn: Team) -> u64
+    where B: Board<S>
+{
+    if depth == 0 || board.is_game_over()
+    {
+        return 1;
+    }
+
+    let mut leaves = 0;
+    let mut moved = false;
+    for mv in board.moves(turn)
+    {
+        let mut child = board.clone();
+        child.do_move(&mv);
+        leaves += perft(&child, depth - 1, turn.other_team());
+        moved = true;
+    }
+
+    if moved { leaves } else { 1 }
+}
+
+/// Searches `board` `depth` plies deep for both sides with the default
+/// alpha-beta search and with `SearchMode::PlainMinimax`, and panics if
+/// they pick different moves or scores, or if `Minimax::verify` finds the
+/// search disagreeing with the reference.
+pub fn verify_against_plain_minimax<B, S>(board: &B, depth: u32)
+    where B: Board<S> + Eq + Hash + Debug,
+          B::Move: Debug,
+          S: GameScore + Debug
+{
+    let size = NonZeroUsize::new(VERIFY_TABLE_SIZE).unwrap();
+    for &turn in [Team::Ally, Team::Enemy].iter()
+    {
+        let pruned = Minimax::new(size).minimax(board, turn, depth);
+        let mut plain = Minimax::new(size);
+        plain.set_search_mode(SearchMode::PlainMinimax);
+        let expected = plain.minimax(board, turn, depth);
+
+        assert_eq!((&pruned.mv, pruned.score), (&expected.mv, expected.score),
+                   "alpha-beta and plain minimax differ, {:?} to move {} plies deep on {:?}", turn, depth, board);
+
+        let verification = Minimax::new(size).verify(board, turn, depth);
+        assert!(!verification.mismatch, "the search and the reference differ, {:?} to move {} plies deep on {:?}: {:?}",
+                turn, depth, board, verification);
+    }
+}
+
+/// Move of a `GameTree`, the index of the subtree to go down.
+#[derive(Copy,Clone,Debug,PartialEq,Eq,Hash)]
+pub struct Branch(pub usize);
+
+/// A game tree spelled out node by node. Either side picks any subtree of
+/// a node, and leaves are the end of the game.
+///
+/// Nodes score as even, so searches shallower than the tree only see
+/// what's below them through the leaves they reach.
+#[derive(Clone,Debug,PartialEq,Eq,Hash)]
+pub enum GameTree
+{
+    Node(Vec<GameTree>),
+    Leaf(Score),
+}
+
+impl GameTree
+{
+    /// Random tree of at most `depth` plies. Subtrees end early now and
+    /// then, some leaves are wins and losses, and some subtrees are reused
+    /// so transposition tables see the same position more than once. The
+    /// same seed gives the same tree.
+    pub fn random(seed: u64, depth: u32) -> GameTree
+    {
+        GameTree::grow(&mut XorShift::new(seed), depth, &mut Vec::new())
+    }
+
+    /// Tree with `branches` subtrees at every node, exactly `depth` plies
+    /// deep, with random heuristic leaves.
+    pub fn full(seed: u64, depth: u32, branches: usize) -> GameTree
+    {
+        GameTree::grow_full(&mut XorShift::new(seed), depth, branches)
+    }
+
+    fn grow(rng: &mut XorShift, depth: u32, seen: &mut Vec<(u32, GameTree)>) -> GameTree
+    {
+        if depth == 0 || rng.below(8) == 0
+        {
+            return GameTree::Leaf(match rng.below(10)
+            {
+                0 => Score::Win,
+                1 => Score::Lose,
+                _ => Score::Heuristic(rng.below(11) as i32 - 5),
+            });
+        }
+
+        if rng.below(5) == 0
+        {
+            if let Some((_, tree)) = seen.iter().find(|&&(d, _)| d == depth)
+            {
+                return tree.clone();
+            }
+        }
+
+        let branches = 1 + rng.below(4);
+        let tree = GameTree::Node((0..branches).map(|_| GameTree::grow(rng, depth - 1, seen)).collect());
+        seen.push((depth, tree.clone()));
+        tree
+    }
+
+    fn grow_full(rng: &mut XorShift, depth: u32, branches: usize) -> GameTree
+    {
+        if depth == 0
+        {
+            return GameTree::Leaf(Score::Heuristic(rng.below(1000) as i32 - 500));
+        }
+        GameTree::Node((0..branches).map(|_| GameTree::grow_full(rng, depth - 1, branches)).collect())
+    }
+
+    fn branches(&self) -> Vec<Branch>
+    {
+        match *self
+        {
+            GameTree::Node(ref children) => (0..children.len()).map(Branch).collect(),
+            GameTree::Leaf(_) => Vec::new(),
+        }
+    }
+}
+
+impl Board for GameTree
+{
+    type Move = Branch;
+
+    fn gen_ally_moves(&self) -> Vec<Branch>
+    {
+        self.branches()
+    }
+
+    fn gen_enemy_moves(&self) -> Vec<Branch>
+    {
+        self.branches()
+    }
+
+    fn do_move(&mut self, mv: &Branch)
+    {
+        let child = match *self
+        {
+            GameTree::Node(ref mut children) => children.swap_remove(mv.0),
+            GameTree::Leaf(_) => panic!("no moves past a leaf"),
+        };
+        *self = child;
+    }
+
+    fn score(&self) -> Score
+    {
+        match *self
+        {
+            GameTree::Node(_) => Score::Heuristic(0),
+            GameTree::Leaf(score) => score,
+        }
+    }
+
+    fn is_game_over(&self) -> bool
+    {
+        match *self
+        {
+            GameTree::Node(_) => false,
+            GameTree::Leaf(_) => true,
+        }
+    }
+}
+
+/// A random game tree that is never built, too big to spell out as a
+/// `GameTree`. Each position is known by the path to it.
+///
+/// Every move is worth a random amount to the ally, found by hashing the
+/// path, and a position scores as the sum of the moves that led to it. So
+/// like a real heuristic, a shallow score says something about the scores
+/// below it.
+#[derive(Copy,Clone,Debug,PartialEq,Eq,Hash)]
+pub struct RandomTree
+{
+    seed: u64,
+    path: u64,
+    left: u32,
+    branches: u32,
+    total: i32,
+}
+
+impl RandomTree
+{
+    /// Root of a tree `depth` plies deep with `branches` moves everywhere.
+    pub fn new(seed: u64, depth: u32, branches: u32) -> RandomTree
+    {
+        RandomTree {
+            seed,
+            path: 0,
+            left: depth,
+            branches,
+            total: 0,
+        }
+    }
+}
+
+impl Board for RandomTree
+{
+    type Move = u32;
+
+    fn gen_ally_moves(&self) -> Vec<u32>
+    {
+        self.moves(Team::Ally).collect()
+    }
+
+    fn gen_enemy_moves(&self) -> Vec<u32>
+    {
+        self.moves(Team::Enemy).collect()
+    }
+
+    fn moves(&self, _turn: Team) -> impl Iterator<Item = u32>
+    {
+        0..self.branches
+    }
+
+    fn do_move(&mut self, mv: &u32)
+    {
+        self.path = self.path.wrapping_mul(u64::from(self.branches) + 1).wrapping_add(u64::from(*mv) + 1);
+        self.left -= 1;
+
+        /* Spread neighbouring paths apart before they seed the generator */
+        let mut rng = XorShift::new((self.seed ^ self.path).wrapping_mul(0x2545_f491_4f6c_dd1d));
+        self.total += rng.below(201) as i32 - 100;
+    }
+
+    fn score(&self) -> Score
+    {
+        Score::Heuristic(self.total)
+    }
+
+    fn is_game_over(&self) -> bool
+    {
+        self.left == 0
+    }
+}
@@ -0,0 +1,122 @@
+use ::{Team, Score, TimedScore};
+
+use std::fmt::{Debug, Write};
+
+/// One node of a `SearchTrace`.
+///
+/// Scores and windows are from the ally's point of view. Scores count
+/// turns from this node, windows count them from the root, the way the
+/// search keeps them.
+#[derive(Clone,Debug,PartialEq,Eq)]
+pub struct TraceNode<M, S = Score>
+{
+    /// The move that led here. `None` at the root and after a pass.
+    pub mv: Option<M>,
+    /// Who is to move here.
+    pub turn: Team,
+    /// The window the node was searched with. `None` if it was pruned.
+    pub window: Option<(TimedScore<S>, TimedScore<S>)>,
+    /// What the node returned. `None` if it was pruned.
+    pub score: Option<TimedScore<S>>,
+    /// Whether the transposition table settled the node.
+    pub tt_hit: bool,
+    /// Whether the node was skipped after a sibling caused a cutoff.
+    pub pruned: bool,
+    pub nodes_visited: u64,
+    /// The nodes below, in search order. Empty past the trace depth.
+    pub children: Vec<TraceNode<M, S>>,
+}
+
+/// What a search did near the root, from `Minimax::search_with_trace`.
+#[derive(Clone,Debug,PartialEq,Eq)]
+pub struct SearchTrace<M, S = Score>
+{
+    pub root: TraceNode<M, S>,
+}
+
+/// Summary of one root move of a `SearchTrace`.
+#[derive(Copy,Clone,Debug,PartialEq,Eq)]
+pub struct RootMove<'a, M, S = Score>
+    where M: 'a
+{
+    pub mv: &'a M,
+    pub score: Option<TimedScore<S>>,
+    pub nodes_visited: u64,
+    pub tt_hit: bool,
+    pub pruned: bool,
+}
+
+impl<M, S> SearchTrace<M, S>
+{
+    /// The root moves in search order. Moves passed to the other side
+    /// aren't listed.
+    pub fn root_moves(&self) -> impl Iterator<Item = RootMove<'_, M, S>>
+        where S: Copy
+    {
+        self.root.children.iter()
+            .filter_map(|node| node.mv.as_ref().map(|mv| RootMove {
+                mv,
+                score: node.score,
+                nodes_visited: node.nodes_visited,
+                tt_hit: node.tt_hit,
+                pruned: node.pruned,
+            }))
+    }
+
+    /// The trace as a Graphviz digraph, with edges labelled by move.
+    /// Pruned nodes are drawn dashed and table hits filled in.
+    pub fn to_dot(&self) -> String
+        where M: Debug,
+              S: Debug
+    {
+        let mut out = String::from("digraph search {\n    node [shape=box];\n");
+        let mut next = 0;
+        dot_node(&self.root, &mut next, &mut out);
+        out.push_str("}\n");
+        out
+    }
+}
+
+/* Writes `node` and everything below it, returning the id it was given */
+fn dot_node<M, S>(node: &TraceNode<M, S>, next: &mut usize, out: &mut String) -> usize
+    where M: Debug,
+          S: Debug
+{
+    let id = *next;
+    *next += 1;
+
+    let mut label = format!("{:?} to move", node.turn);
+    if let Some(ref score) = node.score
+    {
+        write!(label, "\\nscore {:?} in {}", score.score, score.turns).unwrap();
+    }
+    if let Some((ref alpha, ref beta)) = node.window
+    {
+        write!(label, "\\nwindow {:?} in {} .. {:?} in {}", alpha.score, alpha.turns, beta.score, beta.turns).unwrap();
+    }
+    let style = if node.pruned
+    {
+        ", style=dashed"
+    }
+    else if node.tt_hit
+    {
+        ", style=filled"
+    }
+    else
+    {
+        ""
+    };
+    writeln!(out, "    n{} [label=\"{}\"{}];", id, label.replace('"', "\\\""), style).unwrap();
+
+    for child in &node.children
+    {
+        let child_id = dot_node(child, next, out);
+        let mv = match child.mv
+        {
+            Some(ref mv) => format!("{:?}", mv),
+            None => String::from("pass"),
+        };
+        writeln!(out, "    n{} -> n{} [label=\"{}\"];", id, child_id, mv.replace('"', "\\\"")).unwrap();
+    }
+    id
+}
@@ -1,40 +1,656 @@
 use lru::LruCache;
 
+use board::ZobristBoard;
+use ::Team;
+
+use std::cmp::Reverse;
+use std::collections::BTreeMap;
 use std::hash::Hash;
+use std::mem;
 use std::num::NonZeroUsize;
 
+/// Usage counters for a `TranspositionTable`.
+#[derive(Copy,Clone,Debug,Default,PartialEq,Eq)]
+pub struct TableStats
+{
+    /// Lookups that found an entry searched deep enough.
+    pub hits: u64,
+    /// Lookups that found nothing usable.
+    pub misses: u64,
+    /// Of the misses, those that found the position searched less deep
+    /// than asked for.
+    pub shallow_hits: u64,
+    pub insertions: u64,
+    /// Entries pushed out to make room for new ones.
+    pub evictions: u64,
+    /// Entries replaced by a new one for the same position. A `TwoTier`
+    /// table counts each tier.
+    pub overwrites: u64,
+    /// Of the hits, those a `TwoTier` table found in its depth preferred
+    /// tier. Other tables leave this at zero.
+    pub deep_hits: u64,
+    /// Of the hits, those a `TwoTier` table found in its always replaced
+    /// tier. Other tables leave this at zero.
+    pub always_hits: u64,
+    /// Of the evictions, those of depth preferred entries let go for being
+    /// from an old generation.
+    pub stale_evictions: u64,
+    pub len: usize,
+    pub capacity: usize,
+    /// About how many bytes the entries take, `len` of what `entry_bytes`
+    /// gives one. Keys and values are taken to keep nothing on the heap,
+    /// unless the table was made by `with_bytes`.
+    pub bytes: usize,
+}
+
+impl TableStats
+{
+    /// Lookups made, hits or not.
+    pub fn probes(&self) -> u64
+    {
+        self.hits + self.misses
+    }
+}
+
+/// How a stored score relates to the true score of its position.
+#[derive(Copy,Clone,Debug,PartialEq,Eq)]
+pub enum Bound
+{
+    /// The score is exact.
+    Exact,
+    /// The search failed high, the true score is at least this.
+    LowerBound,
+    /// The search failed low, the true score is at most this.
+    UpperBound,
+}
+
+impl Bound
+{
+    /// Which bound `score` is, given the window it was searched with.
+    pub fn of<S>(score: S, alpha: S, beta: S) -> Bound
+        where S: Ord
+    {
+        if score <= alpha
+        {
+            Bound::UpperBound
+        }
+        else if score >= beta
+        {
+            Bound::LowerBound
+        }
+        else
+        {
+            Bound::Exact
+        }
+    }
+
+    /// Whether a stored `score` settles a search with the window `alpha`,
+    /// `beta` without looking any further.
+    pub fn cuts<S>(self, score: S, alpha: S, beta: S) -> bool
+        where S: Ord
+    {
+        match self
+        {
+            Bound::Exact => true,
+            Bound::LowerBound => score >= beta,
+            Bound::UpperBound => score <= alpha,
+        }
+    }
+}
+
+/// What a transposition table is keyed by: a position together with the
+/// side to move.
+pub trait PositionKey<B, S>: Clone + Eq + Hash
+{
+    fn of(board: &B, turn: Team) -> Self;
+}
+
+/// The default key, a copy of the whole board.
+impl<B, S> PositionKey<B, S> for (B, Team)
+    where B: Clone + Eq + Hash
+{
+    fn of(board: &B, turn: Team) -> (B, Team)
+    {
+        (board.clone(), turn)
+    }
+}
+
+/// Key made of nothing but the board's Zobrist hash, with the side to move
+/// mixed in. Boards with the same hash share an entry, so a collision
+/// returns another position's result. See `CheckedZobristKey` for keys
+/// that guard against that.
+#[derive(Copy,Clone,Debug,PartialEq,Eq,Hash)]
+pub struct ZobristKey(pub u64);
+
+/* Xored into the hash when the enemy is to move */
+const ENEMY_TO_MOVE: u64 = 0xf39c_c060_5ced_c834;
+
+impl<B, S> PositionKey<B, S> for ZobristKey
+    where B: ZobristBoard<S>
+{
+    fn of(board: &B, turn: Team) -> ZobristKey
+    {
+        match turn
+        {
+            Team::Ally => ZobristKey(board.zobrist()),
+            Team::Enemy => ZobristKey(board.zobrist() ^ ENEMY_TO_MOVE),
+        }
+    }
+}
+
+/// `ZobristKey` along with `ZobristBoard::zobrist_check`. Two boards only
+/// share an entry if both hashes collide, so a board whose Zobrist hash
+/// collides with another one's finds nothing in the table rather than the
+/// other's result, short of odds of about one in four billion. Keys take
+/// twice the room, 16 bytes rather than 8.
+#[derive(Copy,Clone,Debug,PartialEq,Eq,Hash)]
+pub struct CheckedZobristKey
+{
+    pub hash: u64,
+    pub check: u32,
+}
+
+impl<B, S> PositionKey<B, S> for CheckedZobristKey
+    where B: ZobristBoard<S> + Hash
+{
+    fn of(board: &B, turn: Team) -> CheckedZobristKey
+    {
+        CheckedZobristKey {
+            hash: <ZobristKey as PositionKey<B, S>>::of(board, turn).0,
+            check: board.zobrist_check(),
+        }
+    }
+}
+
+/// A table keyed by Zobrist hash alone, trading a small risk of collisions
+/// for never hashing, comparing or cloning whole boards.
+pub type HashOnlyTable<M> = TranspositionTable<ZobristKey, M>;
+
+/// Which entry makes room when a full table is handed a new position.
+#[derive(Copy,Clone,Debug,PartialEq,Eq)]
+pub enum ReplacementPolicy
+{
+    /// Evict the least recently used entry.
+    Lru,
+    /// Evict the shallowest entry, the oldest one if several are as
+    /// shallow. A position shallower than everything stored isn't kept.
+    /// Entries more than `STALE_AFTER` generations old go first, however
+    /// deep, so old deep results don't stay forever.
+    DepthPreferred,
+    /// Two tiers of the same size: a depth preferred one, and one every
+    /// position is written to, where the least recently used one makes
+    /// room. Lookups try the depth preferred tier first.
+    TwoTier,
+}
+
+/* A `TwoTier` table gives one in this many entries to its always replaced
+ * tier
+ */
+const RECENT_SHARE: usize = 2;
+
+/// Generations a depth preferred entry lasts before anything newer is
+/// preferred to it, see `TranspositionTable::set_generation`.
+pub const STALE_AFTER: u32 = 1;
+
+/* What the LRU order adds to every entry: the links of its node and the
+ * allocator's header for it, and a hash map slot with room to spare
+ */
+const NODE_OVERHEAD: usize = 8 * mem::size_of::<usize>();
+
+struct Entry<M>
+{
+    mv: M,
+    depth: u32,
+    bound: Bound,
+    /* When it was stored, for evicting the oldest of the shallowest */
+    stamp: u64,
+    /* The generation of the table when it was stored */
+    generation: u32,
+}
+
 pub struct TranspositionTable<B, M>
     where B: Eq + Hash
 {
-    cache: LruCache<B, (M, u32)>,
+    cache: LruCache<B, Entry<M>>,
+    policy: ReplacementPolicy,
+    /* Keys of `cache` by depth and age, only kept when depth decides */
+    by_depth: BTreeMap<(u32, u64), B>,
+    /* The same entries by age, with their depths to find them above */
+    by_age: BTreeMap<u64, u32>,
+    next_stamp: u64,
+    generation: u32,
+    /* The always replaced tier of a `TwoTier` table, if it is big enough
+     * for one
+     */
+    recent: Option<LruCache<B, Entry<M>>>,
+    /* What `entry_bytes` gives an entry of this table */
+    entry_size: usize,
+    stats: TableStats,
+}
+
+/* Capacities of the main table and the always replaced tier */
+fn split(policy: ReplacementPolicy, capacity: NonZeroUsize) -> (NonZeroUsize, Option<NonZeroUsize>)
+{
+    match policy
+    {
+        ReplacementPolicy::TwoTier => {
+            let recent = capacity.get() / RECENT_SHARE;
+            (NonZeroUsize::new(capacity.get() - recent).unwrap(), NonZeroUsize::new(recent))
+        }
+        _ => (capacity, None),
+    }
+}
+
+/* An entry that was searched at least `depth` deep */
+fn usable<M>(entry: Option<&Entry<M>>, depth: u32) -> Option<(M, Bound)>
+    where M: Clone
+{
+    match entry
+    {
+        Some(entry) if entry.depth >= depth => Some((entry.mv.clone(), entry.bound)),
+        _ => None,
+    }
 }
 
 impl<B, M> TranspositionTable<B, M>
     where B: Eq + Hash,
           M: Clone
 {
+    /// Makes a table that evicts the least recently used entry.
     pub fn new(capacity: NonZeroUsize) -> TranspositionTable<B, M>
     {
+        TranspositionTable::with_policy(capacity, ReplacementPolicy::Lru)
+    }
+
+    /// Makes a table of as many entries as `entry_bytes` says fit in
+    /// `bytes`, at least one.
+    pub fn with_bytes(bytes: usize, policy: ReplacementPolicy, key_heap: usize, value_heap: usize) -> TranspositionTable<B, M>
+    {
+        let entry_size = Self::entry_bytes(policy, key_heap, value_heap);
+        let entries = bytes / entry_size;
         TranspositionTable {
-            cache: LruCache::new(capacity),
+            entry_size,
+            ..TranspositionTable::with_policy(NonZeroUsize::new(entries.max(1)).unwrap(), policy)
         }
     }
 
-    pub fn get(&mut self, board: &B, depth: u32) -> Option<M>
+    /// About how many bytes an entry takes, for keys and values that keep
+    /// `key_heap` and `value_heap` more bytes on the heap. Tables that
+    /// prefer depth keep a second copy of every key, to find the shallowest
+    /// and the oldest by.
+    pub fn entry_bytes(policy: ReplacementPolicy, key_heap: usize, value_heap: usize) -> usize
     {
-        if let Some(precomputed_move) = self.cache.get(board)
+        let key = mem::size_of::<B>() + key_heap;
+        let entry = key + mem::size_of::<Entry<M>>() + value_heap + NODE_OVERHEAD;
+        match policy
         {
-            if precomputed_move.1 >= depth
+            ReplacementPolicy::Lru => entry,
+            /* The copy, and the depth and age orders with the slack in
+             * their nodes
+             */
+            _ => entry + key + 2 * mem::size_of::<((u32, u64), (u64, u32))>(),
+        }
+    }
+
+    /// Makes a table holding up to `capacity` entries, replaced according
+    /// to `policy`.
+    pub fn with_policy(capacity: NonZeroUsize, policy: ReplacementPolicy) -> TranspositionTable<B, M>
+    {
+        let (main, recent) = split(policy, capacity);
+        TranspositionTable {
+            cache: LruCache::new(main),
+            policy,
+            by_depth: BTreeMap::new(),
+            by_age: BTreeMap::new(),
+            next_stamp: 0,
+            generation: 0,
+            recent: recent.map(LruCache::new),
+            entry_size: Self::entry_bytes(policy, 0, 0),
+            stats: TableStats::default(),
+        }
+    }
+
+    pub fn policy(&self) -> ReplacementPolicy
+    {
+        self.policy
+    }
+
+    /// Tags the entries stored from now on with `generation`. Depth
+    /// preferred entries more than `STALE_AFTER` generations older make
+    /// room before any newer entry, though they are still looked up.
+    pub fn set_generation(&mut self, generation: u32)
+    {
+        self.generation = generation;
+    }
+
+    pub fn generation(&self) -> u32
+    {
+        self.generation
+    }
+
+    /// Looks up `board` if it was searched at least `depth` deep. Returns
+    /// the entry and which bound its score is.
+    pub fn get(&mut self, board: &B, depth: u32) -> Option<(M, Bound)>
+    {
+        let two_tier = self.policy == ReplacementPolicy::TwoTier;
+        let entry = self.cache.get(board);
+        let mut stored = entry.is_some();
+        let mut found = usable(entry, depth);
+        if found.is_some() && two_tier
+        {
+            self.stats.deep_hits += 1;
+        }
+        if found.is_none()
+        {
+            if let Some(ref mut recent) = self.recent
             {
-                return Some(precomputed_move.0.clone());
+                let entry = recent.get(board);
+                stored |= entry.is_some();
+                found = usable(entry, depth);
+                if found.is_some()
+                {
+                    self.stats.always_hits += 1;
+                }
             }
         }
 
-        None
+        match found
+        {
+            Some(_) => self.stats.hits += 1,
+            None => {
+                self.stats.misses += 1;
+                if stored
+                {
+                    self.stats.shallow_hits += 1;
+                }
+            },
+        }
+        found
+    }
+
+    /// Looks up `board` regardless of the depth it was stored at, without
+    /// touching the LRU order. Returns the entry and its depth.
+    pub fn peek(&self, board: &B) -> Option<(&M, u32)>
+    {
+        self.peek_with_bound(board).map(|(mv, depth, _)| (mv, depth))
+    }
+
+    /// Like `peek`, also returning which bound the entry's score is.
+    pub fn peek_with_bound(&self, board: &B) -> Option<(&M, u32, Bound)>
+    {
+        self.cache.peek(board)
+            .or_else(|| self.recent.as_ref().and_then(|recent| recent.peek(board)))
+            .map(|entry| (&entry.mv, entry.depth, entry.bound))
+    }
+
+    pub fn insert(&mut self, board: B, mv: M, depth: u32, bound: Bound)
+        where B: Clone
+    {
+        self.stats.insertions += 1;
+        let mut entry = Entry {
+            mv,
+            depth,
+            bound,
+            stamp: 0,
+            generation: self.generation,
+        };
+
+        if let ReplacementPolicy::Lru = self.policy
+        {
+            Self::put_lru(&mut self.cache, &mut self.stats, board, entry);
+            return;
+        }
+
+        if let Some(ref mut recent) = self.recent
+        {
+            let always = Entry {
+                mv: entry.mv.clone(),
+                ..entry
+            };
+            Self::put_lru(recent, &mut self.stats, board.clone(), always);
+        }
+        if !self.make_room(&board, depth)
+        {
+            return;
+        }
+
+        entry.stamp = self.next_stamp;
+        self.next_stamp += 1;
+        self.by_depth.insert((depth, entry.stamp), board.clone());
+        self.by_age.insert(entry.stamp, depth);
+        self.cache.put(board, entry);
+    }
+
+    fn put_lru(cache: &mut LruCache<B, Entry<M>>, stats: &mut TableStats, board: B, entry: Entry<M>)
+    {
+        if cache.contains(&board)
+        {
+            stats.overwrites += 1;
+        }
+        else if cache.len() == cache.cap().get()
+        {
+            stats.evictions += 1;
+        }
+        cache.put(board, entry);
+    }
+
+    /* Frees a depth preferred slot for `board`, unless everything stored
+     * there is deeper.
+     */
+    fn make_room(&mut self, board: &B, depth: u32) -> bool
+    {
+        if let Some(&Entry { depth: stored, stamp, .. }) = self.cache.peek(board)
+        {
+            if stored > depth
+            {
+                return false;
+            }
+            self.cache.pop(board);
+            self.by_depth.remove(&(stored, stamp));
+            self.by_age.remove(&stamp);
+            self.stats.overwrites += 1;
+            return true;
+        }
+
+        if self.cache.len() < self.cache.cap().get()
+        {
+            return true;
+        }
+
+        if let Some((&stamp, &stored)) = self.by_age.iter().next()
+        {
+            if self.is_stale(&self.by_depth[&(stored, stamp)])
+            {
+                self.evict((stored, stamp));
+                self.stats.stale_evictions += 1;
+                return true;
+            }
+        }
+
+        match self.by_depth.keys().next()
+        {
+            Some(&(shallowest, _)) if shallowest <= depth => {
+                self.evict_shallowest();
+                true
+            },
+            _ => false,
+        }
+    }
+
+    /* Whether the entry for `board` is too many generations old */
+    fn is_stale(&self, board: &B) -> bool
+    {
+        self.cache.peek(board).is_some_and(|entry| self.generation.wrapping_sub(entry.generation) > STALE_AFTER)
+    }
+
+    fn evict_shallowest(&mut self)
+    {
+        if let Some(&slot) = self.by_depth.keys().next()
+        {
+            self.evict(slot);
+        }
+    }
+
+    fn evict(&mut self, slot: (u32, u64))
+    {
+        if let Some(board) = self.by_depth.remove(&slot)
+        {
+            self.by_age.remove(&slot.1);
+            self.cache.pop(&board);
+            self.stats.evictions += 1;
+        }
+    }
+
+    /// Builds a table from entries as returned by `to_entries`. If there
+    /// are more than fit, the deepest ones are kept. Counters start at
+    /// zero.
+    pub fn from_entries(capacity: NonZeroUsize, entries: Vec<(B, M, u32, Bound)>) -> TranspositionTable<B, M>
+        where B: Clone
+    {
+        TranspositionTable::from_entries_with_policy(capacity, ReplacementPolicy::Lru, entries)
+    }
+
+    /// Like `from_entries`, for a table replaced according to `policy`.
+    pub fn from_entries_with_policy(capacity: NonZeroUsize, policy: ReplacementPolicy, mut entries: Vec<(B, M, u32, Bound)>) -> TranspositionTable<B, M>
+        where B: Clone
+    {
+        if entries.len() > capacity.get()
+        {
+            /* Deepest first, then back into the order they came in */
+            let mut keep: Vec<usize> = (0..entries.len()).collect();
+            keep.sort_by_key(|&idx| Reverse(entries[idx].2));
+            keep.truncate(capacity.get());
+            keep.sort_unstable();
+
+            let mut idx = 0;
+            let mut kept = keep.iter().peekable();
+            entries.retain(|_|
+            {
+                let wanted = kept.peek() == Some(&&idx);
+                if wanted
+                {
+                    kept.next();
+                }
+                idx += 1;
+                wanted
+            });
+        }
+
+        let mut table = TranspositionTable::with_policy(capacity, policy);
+        for (board, mv, depth, bound) in entries
+        {
+            table.insert(board, mv, depth, bound);
+        }
+        table.reset_stats();
+        table
+    }
+
+    /// Every entry with its depth and bound, from least to most recently
+    /// used. A `TwoTier` table lists its always replaced tier first,
+    /// leaving out the positions its depth preferred tier also holds.
+    pub fn to_entries(&self) -> Vec<(B, M, u32, Bound)>
+        where B: Clone
+    {
+        self.recent.iter()
+            .flat_map(|recent| recent.iter().rev())
+            .filter(|&(board, _)| !self.cache.contains(board))
+            .chain(self.cache.iter().rev())
+            .map(|(board, entry)| (board.clone(), entry.mv.clone(), entry.depth, entry.bound))
+            .collect()
+    }
+
+    pub fn len(&self) -> usize
+    {
+        self.cache.len() + self.recent.as_ref().map_or(0, |recent| recent.len())
+    }
+
+    pub fn is_empty(&self) -> bool
+    {
+        self.len() == 0
+    }
+
+    /// Drops every entry and zeroes the counters, leaving the table as it
+    /// was made.
+    pub fn clear(&mut self)
+    {
+        self.cache.clear();
+        self.by_depth.clear();
+        self.by_age.clear();
+        if let Some(ref mut recent) = self.recent
+        {
+            recent.clear();
+        }
+        self.reset_stats();
+    }
+
+    /// Changes how many entries the table holds. When shrinking, the
+    /// entries the policy would replace first are evicted.
+    pub fn set_capacity(&mut self, capacity: NonZeroUsize)
+    {
+        let (main, recent) = split(self.policy, capacity);
+
+        if self.cache.len() > main.get()
+        {
+            match self.policy
+            {
+                ReplacementPolicy::Lru =>
+                    self.stats.evictions += (self.cache.len() - main.get()) as u64,
+                _ => while self.cache.len() > main.get()
+                {
+                    self.evict_shallowest();
+                },
+            }
+        }
+        self.cache.resize(main);
+
+        self.recent = match (self.recent.take(), recent)
+        {
+            (Some(mut cache), Some(capacity)) => {
+                if cache.len() > capacity.get()
+                {
+                    self.stats.evictions += (cache.len() - capacity.get()) as u64;
+                }
+                cache.resize(capacity);
+                Some(cache)
+            },
+            (Some(cache), None) => {
+                self.stats.evictions += cache.len() as u64;
+                None
+            },
+            (None, recent) => recent.map(LruCache::new),
+        };
+    }
+
+    /// Copies the entries of `other` into this table. Where both tables
+    /// hold a position, the deeper entry is kept.
+    pub fn merge(&mut self, other: &TranspositionTable<B, M>)
+        where B: Clone
+    {
+        /* Oldest first, so the most recent entries end up most recent here */
+        for (board, mv, depth, bound) in other.to_entries()
+        {
+            match self.peek(&board)
+            {
+                Some((_, ours)) if ours >= depth => {},
+                _ => self.insert(board, mv, depth, bound),
+            }
+        }
+    }
+
+    /// Counters accumulated since the table was made or last reset.
+    pub fn stats(&self) -> TableStats
+    {
+        TableStats {
+            len: self.len(),
+            capacity: self.cache.cap().get() + self.recent.as_ref().map_or(0, |recent| recent.cap().get()),
+            bytes: self.len() * self.entry_size,
+            ..self.stats
+        }
     }
 
-    pub fn insert(&mut self, board: B, mv: M, depth: u32)
+    /// Zeroes the counters. Entries are kept.
+    pub fn reset_stats(&mut self)
     {
-        self.cache.put(board, (mv, depth));
+        self.stats = TableStats::default();
     }
 }
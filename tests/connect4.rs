@@ -0,0 +1,75 @@
+//! Facts about connect four positions, checked against the board in
+//! `examples/connect4.rs`.
+
+extern crate minimax;
+
+#[path = "../examples/connect4.rs"]
+#[allow(dead_code)]
+mod connect4;
+
+use connect4::{ConnectFour, Drop};
+use minimax::{Minimax, Score, Team};
+use minimax::board::Board;
+
+use std::num::NonZeroUsize;
+
+fn engine() -> Minimax<ConnectFour>
+{
+    Minimax::new(NonZeroUsize::new(1_000_000).unwrap())
+}
+
+#[test]
+fn takes_an_immediate_win()
+{
+    /* X has three on the bottom row, O three stacked in column 7 */
+    let board = ConnectFour::from_columns("172737", Team::Ally);
+    let stats = engine().minimax(&board, Team::Ally, 4);
+    assert_eq!(stats.mv, Some(Drop { column: 3, team: Team::Ally }));
+    assert_eq!(stats.score.score, Score::Win);
+    assert_eq!(stats.score.turns, 1);
+}
+
+#[test]
+fn blocks_an_immediate_threat()
+{
+    /* O has three on the bottom row, X nothing to win with */
+    let board = ConnectFour::from_columns("717263", Team::Ally);
+    let stats = engine().minimax(&board, Team::Ally, 4);
+    assert_eq!(stats.mv, Some(Drop { column: 3, team: Team::Ally }));
+    assert!(stats.score.score != Score::Lose);
+}
+
+#[test]
+fn finds_a_forced_win_in_seven_plies()
+{
+    /* .......
+     * .......
+     * X......
+     * O......
+     * X..X..O
+     * X.OX.OO
+     */
+    let board = ConnectFour::from_columns("1713414617", Team::Ally);
+
+    /* Too deep to see at five plies */
+    let shallow = engine().minimax(&board, Team::Ally, 5);
+    assert!(shallow.score.score != Score::Win);
+
+    let stats = engine().minimax(&board, Team::Ally, 7);
+    assert_eq!(stats.score.score, Score::Win);
+    assert_eq!(stats.score.turns, 7);
+    assert_eq!(stats.mvs.len(), 7);
+
+    /* Whatever O answers, X still wins within five more plies */
+    let mv = stats.mv.unwrap();
+    let mut after = board;
+    after.do_move(&mv);
+    for reply in after.gen_enemy_moves()
+    {
+        let mut next = after;
+        next.do_move(&reply);
+        let stats = engine().minimax(&next, Team::Ally, 5);
+        assert_eq!(stats.score.score, Score::Win, "no win after {:?}", reply);
+        assert!(stats.score.turns <= 5);
+    }
+}
@@ -0,0 +1,167 @@
+//! Expectimax on a tiny dice game: on its turn a side either takes two
+//! points, or rolls a die for as many as it shows, except that a one
+//! costs it five. Rolling is worth more on average, but it is a gamble,
+//! and whoever has more points at the end wins.
+
+extern crate minimax;
+
+use minimax::{Score, Team};
+use minimax::board::Board;
+use minimax::expectimax::{self, ChanceBoard, WIN_VALUE};
+
+#[derive(Copy,Clone,Debug,PartialEq,Eq)]
+enum Play
+{
+    Take,
+    Roll,
+}
+
+#[derive(Copy,Clone,Debug,PartialEq,Eq)]
+struct Turn
+{
+    team: Team,
+    play: Play,
+}
+
+#[derive(Clone,Debug)]
+struct Dice
+{
+    /* Points of the ally and the enemy, and the turns they have left */
+    points: [i32; 2],
+    turns: [u32; 2],
+    /* The side whose die is about to be rolled */
+    rolling: Option<Team>,
+}
+
+fn side(team: Team) -> usize
+{
+    match team
+    {
+        Team::Ally => 0,
+        Team::Enemy => 1,
+    }
+}
+
+impl Dice
+{
+    fn new(ally: i32, enemy: i32, ally_turns: u32, enemy_turns: u32) -> Dice
+    {
+        Dice {
+            points: [ally, enemy],
+            turns: [ally_turns, enemy_turns],
+            rolling: None,
+        }
+    }
+
+    fn plays(&self, team: Team) -> Vec<Turn>
+    {
+        if self.turns[side(team)] == 0
+        {
+            return Vec::new();
+        }
+        vec![Turn { team, play: Play::Take }, Turn { team, play: Play::Roll }]
+    }
+}
+
+impl Board for Dice
+{
+    type Move = Turn;
+
+    fn gen_ally_moves(&self) -> Vec<Turn>
+    {
+        self.plays(Team::Ally)
+    }
+
+    fn gen_enemy_moves(&self) -> Vec<Turn>
+    {
+        self.plays(Team::Enemy)
+    }
+
+    fn do_move(&mut self, mv: &Turn)
+    {
+        self.turns[side(mv.team)] -= 1;
+        match mv.play
+        {
+            Play::Take => self.points[side(mv.team)] += 2,
+            Play::Roll => self.rolling = Some(mv.team),
+        }
+    }
+
+    fn score(&self) -> Score
+    {
+        match self.points[0] - self.points[1]
+        {
+            0 => Score::Draw,
+            lead if lead > 0 => Score::Win,
+            _ => Score::Lose,
+        }
+    }
+
+    fn is_game_over(&self) -> bool
+    {
+        self.rolling.is_none() && self.turns == [0, 0]
+    }
+}
+
+impl ChanceBoard for Dice
+{
+    fn chance_outcomes(&self) -> Option<Vec<(Dice, f64)>>
+    {
+        let team = self.rolling?;
+        Some((1..=6)
+            .map(|face|
+            {
+                let mut rolled = self.clone();
+                rolled.points[side(team)] += if face == 1 { -5 } else { face };
+                rolled.rolling = None;
+                (rolled, 1.0 / 6.0)
+            })
+            .collect())
+    }
+}
+
+fn assert_value(value: f64, expected: f64)
+{
+    assert!((value - expected).abs() < WIN_VALUE * 1e-9, "{} vs {}", value, expected);
+}
+
+#[test]
+fn plays_safe_when_ahead()
+{
+    let stats = expectimax::expectimax(&Dice::new(3, 0, 1, 0), Team::Ally, 1);
+    assert_eq!(stats.mv, Some(Turn { team: Team::Ally, play: Play::Take }));
+    assert_value(stats.value, WIN_VALUE);
+}
+
+#[test]
+fn gambles_when_behind()
+{
+    /* Four or more wins, three draws, and two or one loses */
+    let stats = expectimax::expectimax(&Dice::new(0, 3, 1, 0), Team::Ally, 1);
+    assert_eq!(stats.mv, Some(Turn { team: Team::Ally, play: Play::Roll }));
+    assert_value(stats.value, WIN_VALUE / 6.0);
+
+    /* The enemy sees it the same way */
+    let stats = expectimax::expectimax(&Dice::new(3, 0, 0, 1), Team::Enemy, 1);
+    assert_eq!(stats.mv, Some(Turn { team: Team::Enemy, play: Play::Roll }));
+    assert_value(stats.value, -WIN_VALUE / 6.0);
+}
+
+#[test]
+fn moving_last_is_an_edge()
+{
+    /* Taking two points lets the enemy win half the time by rolling. A
+     * roll does slightly better, averaged over what the enemy does after
+     * each face.
+     */
+    let stats = expectimax::expectimax(&Dice::new(0, 0, 1, 1), Team::Ally, 2);
+    assert_eq!(stats.mv, Some(Turn { team: Team::Ally, play: Play::Roll }));
+    assert_value(stats.value, -WIN_VALUE / 36.0);
+
+    /* A ply short, the enemy's answer is past the horizon and the two
+     * points look like a sure win
+     */
+    let stats = expectimax::expectimax(&Dice::new(0, 0, 1, 1), Team::Ally, 1);
+    assert_eq!(stats.mv, Some(Turn { team: Team::Ally, play: Play::Take }));
+    assert_value(stats.value, WIN_VALUE);
+}
@@ -0,0 +1,66 @@
+//! How close `Minimax::with_table_bytes` comes to the memory it is given,
+//! counted by an allocator that keeps track of what is live. The search
+//! fills the table with tic-tac-toe positions from `examples/tictactoe.rs`.
+
+extern crate minimax;
+
+#[path = "../examples/tictactoe.rs"]
+#[allow(dead_code)]
+mod tictactoe;
+
+use tictactoe::TicTacToe;
+use minimax::{Minimax, Team};
+use minimax::transposition_table::ReplacementPolicy;
+
+use std::alloc::{GlobalAlloc, Layout, System};
+use std::sync::atomic::{AtomicUsize, Ordering};
+
+/* Counts the bytes allocated and not freed yet. This is the only test
+ * here, so nothing else allocates while it measures.
+ */
+struct Counting;
+
+static LIVE: AtomicUsize = AtomicUsize::new(0);
+
+unsafe impl GlobalAlloc for Counting
+{
+    unsafe fn alloc(&self, layout: Layout) -> *mut u8
+    {
+        LIVE.fetch_add(layout.size(), Ordering::Relaxed);
+        System.alloc(layout)
+    }
+
+    unsafe fn dealloc(&self, ptr: *mut u8, layout: Layout)
+    {
+        LIVE.fetch_sub(layout.size(), Ordering::Relaxed);
+        System.dealloc(ptr, layout)
+    }
+
+    unsafe fn realloc(&self, ptr: *mut u8, layout: Layout, new_size: usize) -> *mut u8
+    {
+        LIVE.fetch_add(new_size, Ordering::Relaxed);
+        LIVE.fetch_sub(layout.size(), Ordering::Relaxed);
+        System.realloc(ptr, layout, new_size)
+    }
+}
+
+#[global_allocator]
+static ALLOCATOR: Counting = Counting;
+
+#[test]
+fn full_table_takes_about_its_bytes()
+{
+    let bytes = 1 << 18;
+    for &policy in [ReplacementPolicy::Lru, ReplacementPolicy::DepthPreferred, ReplacementPolicy::TwoTier].iter()
+    {
+        let before = LIVE.load(Ordering::Relaxed);
+        let mut minimax: Minimax<TicTacToe> = Minimax::with_table_bytes(bytes, policy);
+        minimax.minimax(&TicTacToe::new(), Team::Ally, 9);
+        let used = LIVE.load(Ordering::Relaxed) - before;
+
+        /* Full, and it errs on the side of taking less */
+        let stats = minimax.table_stats();
+        assert_eq!(stats.len, stats.capacity, "{:?}", policy);
+        assert!(used <= bytes && used >= bytes / 2, "{:?} took {} bytes", policy, used);
+    }
+}
@@ -0,0 +1,151 @@
+//! Perfect play facts about tic-tac-toe, checked against the board in
+//! `examples/tictactoe.rs`.
+
+extern crate minimax;
+
+#[path = "../examples/tictactoe.rs"]
+#[allow(dead_code)]
+mod tictactoe;
+
+use tictactoe::{Mark, TicTacToe};
+use minimax::{Minimax, Score, Team};
+use minimax::board::Board;
+use minimax::mcts::{Mcts, Budget};
+use minimax::pns::{self, ProofResult};
+
+use std::num::NonZeroUsize;
+
+fn engine() -> Minimax<TicTacToe>
+{
+    Minimax::new(NonZeroUsize::new(100_000).unwrap())
+}
+
+#[test]
+fn empty_board_is_a_draw()
+{
+    for &turn in [Team::Ally, Team::Enemy].iter()
+    {
+        let stats = engine().minimax(&TicTacToe::new(), turn, 9);
+        assert_eq!(stats.score.score, Score::Draw);
+        assert!(stats.completed);
+        assert_eq!(stats.mvs.len(), 9);
+    }
+}
+
+#[test]
+fn solves_the_empty_board()
+{
+    for &turn in [Team::Ally, Team::Enemy].iter()
+    {
+        let stats = engine().solve(&TicTacToe::new(), turn, 9).unwrap();
+        assert_eq!(stats.score.score, Score::Draw);
+        assert!(stats.solved);
+    }
+}
+
+#[test]
+fn blocks_three_in_a_row()
+{
+    let board = TicTacToe::parse("OO.
+                                  .X.
+                                  ...");
+    let stats = engine().minimax(&board, Team::Ally, 7);
+    assert_eq!(stats.mv, Some(Mark { cell: 2, team: Team::Ally }));
+
+    /* The same from the other side */
+    let board = TicTacToe::parse("X..
+                                  XO.
+                                  ...");
+    let stats = engine().minimax(&board, Team::Enemy, 7);
+    assert_eq!(stats.mv, Some(Mark { cell: 6, team: Team::Enemy }));
+}
+
+#[test]
+fn wins_rather_than_blocks()
+{
+    let board = TicTacToe::parse("XX.
+                                  OO.
+                                  ...");
+    let stats = engine().minimax(&board, Team::Ally, 5);
+    assert_eq!(stats.mv, Some(Mark { cell: 2, team: Team::Ally }));
+    assert_eq!(stats.score.score, Score::Win);
+    assert_eq!(stats.score.turns, 1);
+}
+
+#[test]
+fn playouts_find_the_win_and_the_block()
+{
+    let board = TicTacToe::parse("XX.
+                                  OO.
+                                  ...");
+    let stats = Mcts::new(1).search(&board, Team::Ally, Budget::Iterations(2000));
+    assert_eq!(stats.mv, Some(Mark { cell: 2, team: Team::Ally }));
+    assert_eq!(stats.win_rate, 1.0);
+
+    let board = TicTacToe::parse("OO.
+                                  .X.
+                                  ...");
+    let stats = Mcts::new(1).search(&board, Team::Ally, Budget::Iterations(5000));
+    assert_eq!(stats.mv, Some(Mark { cell: 2, team: Team::Ally }));
+}
+
+#[test]
+fn proves_forced_wins()
+{
+    /* Taking the last cell of the top row is the only move that wins */
+    let board = TicTacToe::parse("XX.
+                                  OO.
+                                  ...");
+    assert_eq!(pns::prove(&board, Team::Ally, 10_000), ProofResult::Proven(Some(Mark { cell: 2, team: Team::Ally })));
+
+    /* An edge next to the first corner loses for O, though not at once */
+    let board = TicTacToe::parse("XO.
+                                  ...
+                                  ...");
+    let mv = match pns::prove(&board, Team::Ally, 100_000)
+    {
+        ProofResult::Proven(Some(mv)) => mv,
+        other => panic!("{:?}", other),
+    };
+    let mut next = board;
+    next.do_move(&mv);
+    assert_eq!(engine().minimax(&next, Team::Enemy, 9).score.score, Score::Win);
+
+    assert_eq!(pns::prove(&TicTacToe::new(), Team::Ally, 100_000), ProofResult::Disproven);
+}
+
+/* Tries every way for the enemy to play against the engine */
+fn never_loses(minimax: &mut Minimax<TicTacToe>, board: TicTacToe, turn: Team)
+{
+    if board.is_game_over()
+    {
+        assert!(board.winner() != Some(Team::Enemy), "lost on\n{}", board.draw());
+        return;
+    }
+
+    match turn
+    {
+        Team::Ally => {
+            let mv = minimax.minimax(&board, Team::Ally, 9).mv.unwrap();
+            let mut next = board;
+            next.do_move(&mv);
+            never_loses(minimax, next, Team::Enemy);
+        }
+        Team::Enemy => {
+            for mv in board.gen_enemy_moves()
+            {
+                let mut next = board;
+                next.do_move(&mv);
+                never_loses(minimax, next, Team::Ally);
+            }
+        }
+    }
+}
+
+#[test]
+fn engine_never_loses()
+{
+    let mut minimax = engine();
+    never_loses(&mut minimax, TicTacToe::new(), Team::Ally);
+    never_loses(&mut minimax, TicTacToe::new(), Team::Enemy);
+}